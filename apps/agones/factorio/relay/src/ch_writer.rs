@@ -1,11 +1,12 @@
 use std::time::Instant;
 
 use anyhow::Result;
+use jedi::entity::pipe_clickhouse::{ClickHouseInserter, InserterConfig};
 use jedi::state::sidecar::ClickHouseConfig;
 use serde_json::json;
 use tokio::sync::broadcast::Receiver;
 use tokio::sync::broadcast::error::RecvError;
-use tracing::{debug, info, warn};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::config::Config;
@@ -16,7 +17,7 @@ const PLAYER_EVENTS_TABLE: &str = "gameops.factorio_player_events_raw";
 const CHAT_LOG_TABLE: &str = "gameops.factorio_chat_log_raw";
 
 struct Producer {
-    ch: ClickHouseConfig,
+    ch: ClickHouseInserter,
     cfg: Config,
     rotation_id: String,
     started: Instant,
@@ -102,14 +103,11 @@ impl Producer {
         self.insert(CHAT_LOG_TABLE, row).await;
     }
 
+    /// Queue the row on the shared inserter, which batches, retries and
+    /// logs failed batches itself.
     async fn insert(&self, table: &str, row: serde_json::Value) {
-        match self
-            .ch
-            .execute_insert(table, std::slice::from_ref(&row))
-            .await
-        {
-            Ok(()) => debug!(table, "inserted row"),
-            Err(e) => warn!(table, error = %e, "clickhouse insert failed"),
+        if let Err(e) = self.ch.write(table, &row).await {
+            warn!(table, error = %e, "clickhouse row not queued");
         }
     }
 
@@ -150,6 +148,7 @@ pub async fn run(cfg: Config, mut rx: Receiver<GameEvent>) -> Result<()> {
         password: cfg.clickhouse_password.clone().unwrap_or_default(),
         database: cfg.clickhouse_database.clone(),
     };
+    let (ch, flusher) = ClickHouseInserter::spawn(ch, InserterConfig::default());
 
     let mut producer = Producer {
         ch,
@@ -172,5 +171,8 @@ pub async fn run(cfg: Config, mut rx: Receiver<GameEvent>) -> Result<()> {
             Err(RecvError::Closed) => break,
         }
     }
+    // Dropping the last handle lets the flusher send the tail and exit.
+    drop(producer);
+    let _ = flusher.await;
     Ok(())
 }
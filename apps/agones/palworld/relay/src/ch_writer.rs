@@ -1,11 +1,12 @@
 use std::time::Instant;
 
 use anyhow::Result;
+use jedi::entity::pipe_clickhouse::{ClickHouseInserter, InserterConfig};
 use jedi::state::sidecar::ClickHouseConfig;
 use serde_json::json;
 use tokio::sync::broadcast::Receiver;
 use tokio::sync::broadcast::error::RecvError;
-use tracing::{debug, error, info, warn};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::config::Config;
@@ -14,14 +15,11 @@ use crate::event::{GameEvent, GameEventKind};
 const SNAPSHOTS_TABLE: &str = "gameops.palworld_snapshots_raw";
 const PLAYER_EVENTS_TABLE: &str = "gameops.palworld_player_events_raw";
 
-const LOUD_FAILURE_THRESHOLD: u32 = 3;
-
 struct Producer {
-    ch: ClickHouseConfig,
+    ch: ClickHouseInserter,
     cfg: Config,
     rotation_id: String,
     started: Instant,
-    consecutive_failures: u32,
 }
 
 impl Producer {
@@ -56,32 +54,11 @@ impl Producer {
         self.insert(PLAYER_EVENTS_TABLE, row).await;
     }
 
+    /// Queue the row on the shared inserter, which batches, retries and
+    /// logs failed or rejected (missing table?) batches itself.
     async fn insert(&mut self, table: &str, row: serde_json::Value) {
-        match self
-            .ch
-            .execute_insert(table, std::slice::from_ref(&row))
-            .await
-        {
-            Ok(()) => {
-                if self.consecutive_failures >= LOUD_FAILURE_THRESHOLD {
-                    info!(table, "clickhouse inserts recovered");
-                }
-                self.consecutive_failures = 0;
-                debug!(table, "inserted row");
-            }
-            Err(e) => {
-                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
-                if self.consecutive_failures >= LOUD_FAILURE_THRESHOLD {
-                    error!(
-                        table,
-                        error = %e,
-                        consecutive = self.consecutive_failures,
-                        "clickhouse inserts failing — telemetry is being dropped (missing table?)"
-                    );
-                } else {
-                    warn!(table, error = %e, "clickhouse insert failed");
-                }
-            }
+        if let Err(e) = self.ch.write(table, &row).await {
+            warn!(table, error = %e, "clickhouse row not queued");
         }
     }
 
@@ -118,12 +95,12 @@ pub async fn run(cfg: Config, mut rx: Receiver<GameEvent>) -> Result<()> {
         password: cfg.clickhouse_password.clone().unwrap_or_default(),
         database: cfg.clickhouse_database.clone(),
     };
+    let (ch, flusher) = ClickHouseInserter::spawn(ch, InserterConfig::default());
 
     let mut producer = Producer {
         ch,
         rotation_id: Uuid::new_v4().to_string(),
         started: Instant::now(),
-        consecutive_failures: 0,
         cfg,
     };
 
@@ -140,5 +117,8 @@ pub async fn run(cfg: Config, mut rx: Receiver<GameEvent>) -> Result<()> {
             Err(RecvError::Closed) => break,
         }
     }
+    // Dropping the last handle lets the flusher send the tail and exit.
+    drop(producer);
+    let _ = flusher.await;
     Ok(())
 }
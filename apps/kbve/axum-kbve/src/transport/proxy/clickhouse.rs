@@ -13,6 +13,7 @@ use super::core::*;
 use jedi::entity::pipe_clickhouse::alerts as ch_alerts;
 use jedi::entity::pipe_clickhouse::factorio as ch_factorio;
 use jedi::entity::pipe_clickhouse::logs as ch_logs;
+use jedi::entity::pipe_clickhouse::query as ch_query;
 use jedi::state::sidecar::ClickHouseConfig;
 
 static CLICKHOUSE_DIRECT: OnceLock<ClickHouseConfig> = OnceLock::new();
@@ -80,6 +81,10 @@ pub struct ClickHouseLogsRequest {
     /// by log/alert commands.
     #[serde(default)]
     pub server_id: Option<String>,
+    /// Opaque `next_cursor` from a previous `"query"` response; resumes
+    /// the newest-first listing after the last row of that page.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// Response shape for the ClickHouse logs route. `rows` is the raw
@@ -95,6 +100,10 @@ pub struct ClickHouseLogsResponse {
     #[schema(value_type = Vec<serde_json::Value>)]
     pub rows: Vec<serde_json::Value>,
     pub count: usize,
+    /// Present on `"query"` responses that filled a whole page — send it
+    /// back as `cursor` for the next one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[utoipa::path(
@@ -141,6 +150,17 @@ pub async fn clickhouse_logs_proxy_handler(headers: HeaderMap, body: Bytes) -> R
 
     let result = match req.command.as_str() {
         "query" => {
+            let cursor = match req.cursor.as_deref().map(ch_query::Cursor::decode) {
+                None => None,
+                Some(Ok(cursor)) => Some(cursor),
+                Some(Err(e)) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        axum::Json(json!({"error": e.to_string()})),
+                    )
+                        .into_response();
+                }
+            };
             let params = ch_logs::LogsQueryParams {
                 pod_namespace: req.pod_namespace,
                 pod_name: req.pod_name,
@@ -149,6 +169,7 @@ pub async fn clickhouse_logs_proxy_handler(headers: HeaderMap, body: Bytes) -> R
                 search: req.search,
                 minutes: req.minutes,
                 limit: req.limit,
+                cursor,
             };
             ch_logs::run_query(config, &params).await
         }
@@ -256,6 +277,7 @@ pub async fn clickhouse_logs_proxy_handler(headers: HeaderMap, body: Bytes) -> R
         Ok(out) => axum::Json(ClickHouseLogsResponse {
             rows: out.rows,
             count: out.count,
+            next_cursor: out.next_cursor.map(|c| c.encode()),
         })
         .into_response(),
         Err(e) => {
@@ -58,6 +58,9 @@ export const MultiMessageKind = {
     XREAD:    MessageKind.REDIS | MessageKind.STREAM | MessageKind.READ,
     WATCH:    MessageKind.REDIS | MessageKind.HEARTBEAT | MessageKind.READ | MessageKind.INFO,
     UNWATCH:  MessageKind.REDIS | MessageKind.HEARTBEAT | MessageKind.DEL | MessageKind.INFO,
+    PWATCH:   MessageKind.REDIS | MessageKind.HEARTBEAT | MessageKind.READ | MessageKind.LIST,
+    PUNWATCH: MessageKind.REDIS | MessageKind.HEARTBEAT | MessageKind.DEL | MessageKind.LIST,
+    WRESUME:  MessageKind.REDIS | MessageKind.HEARTBEAT | MessageKind.READ | MessageKind.ACTION,
     PUBLISH:  MessageKind.REDIS | MessageKind.MESSAGE | MessageKind.ACTION,
     SUBSCRIBE: MessageKind.REDIS | MessageKind.MESSAGE | MessageKind.READ,
 } as const;
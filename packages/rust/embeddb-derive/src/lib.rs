@@ -1,26 +1,37 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Token};
+
+fn named_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> Result<&'a Punctuated<Field, Token![,]>, TokenStream> {
+    let name = &input.ident;
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(named) => Ok(&named.named),
+            _ => Err(syn::Error::new_spanned(name, format!("{derive} requires named fields"))
+                .to_compile_error()
+                .into()),
+        },
+        _ => Err(syn::Error::new_spanned(
+            name,
+            format!("{derive} can only be derived for structs"),
+        )
+            .to_compile_error()
+            .into()),
+    }
+}
 
 #[proc_macro_derive(FromEmbedRow)]
 pub fn derive_from_embed_row(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
-    let fields = match &input.data {
-        Data::Struct(s) => match &s.fields {
-            Fields::Named(named) => &named.named,
-            _ => {
-                return syn::Error::new_spanned(name, "FromEmbedRow requires named fields")
-                    .to_compile_error()
-                    .into();
-            }
-        },
-        _ => {
-            return syn::Error::new_spanned(name, "FromEmbedRow can only be derived for structs")
-                .to_compile_error()
-                .into();
-        }
+    let fields = match named_fields(&input, "FromEmbedRow") {
+        Ok(fields) => fields,
+        Err(err) => return err,
     };
 
     let inits = fields.iter().map(|field| {
@@ -49,3 +60,128 @@ pub fn derive_from_embed_row(input: TokenStream) -> TokenStream {
     };
     expanded.into()
 }
+
+fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (i, ch) in ident.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(ch.to_lowercase());
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn table_name(input: &DeriveInput) -> syn::Result<String> {
+    let mut table = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("embeddb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                table = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `table = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(table.unwrap_or_else(|| snake_case(&input.ident.to_string())))
+}
+
+fn is_primary_key(field: &Field) -> syn::Result<bool> {
+    let mut pk = false;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("embeddb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("primary_key") {
+                pk = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `primary_key`"))
+            }
+        })?;
+    }
+    Ok(pk)
+}
+
+#[proc_macro_derive(EmbedTable, attributes(embeddb))]
+pub fn derive_embed_table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let fields = match named_fields(&input, "EmbedTable") {
+        Ok(fields) => fields,
+        Err(err) => return err,
+    };
+    let table = match table_name(&input) {
+        Ok(table) => table,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    let mut keys = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let col = ident.to_string();
+        let ty = &field.ty;
+        let primary_key = match is_primary_key(field) {
+            Ok(pk) => pk,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        columns.push(quote! {
+            ::embeddb::ColumnDef {
+                name: #col,
+                sql_type: <#ty as ::embeddb::ToEmbedValue>::SQL_TYPE,
+                nullable: <#ty as ::embeddb::ToEmbedValue>::NULLABLE,
+                primary_key: #primary_key,
+            }
+        });
+        values.push(quote! { ::embeddb::ToEmbedValue::to_embed_value(&self.#ident) });
+        if primary_key {
+            keys.push(ty);
+        }
+    }
+
+    let (key_ty, key_values) = match keys.as_slice() {
+        [] => {
+            return syn::Error::new_spanned(
+                name,
+                "EmbedTable requires at least one #[embeddb(primary_key)] field",
+            )
+                .to_compile_error()
+                .into();
+        }
+        [ty] => (
+            quote! { #ty },
+            quote! { vec![::embeddb::ToEmbedValue::to_embed_value(__key)] },
+        ),
+        tys => {
+            let idx = (0..tys.len()).map(syn::Index::from);
+            (
+                quote! { (#(#tys),*) },
+                quote! { vec![#(::embeddb::ToEmbedValue::to_embed_value(&__key.#idx)),*] },
+            )
+        }
+    };
+
+    let expanded = quote! {
+        impl ::embeddb::EmbedTable for #name {
+            type Key = #key_ty;
+            const TABLE: &'static str = #table;
+            const COLUMNS: &'static [::embeddb::ColumnDef] = &[#(#columns),*];
+
+            fn values(&self) -> Vec<::embeddb::EmbedValue> {
+                vec![#(#values),*]
+            }
+
+            fn key_values(__key: &Self::Key) -> Vec<::embeddb::EmbedValue> {
+                #key_values
+            }
+        }
+    };
+    expanded.into()
+}
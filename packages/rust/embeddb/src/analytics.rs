@@ -72,6 +72,76 @@ pub fn for_each(
     Ok(count)
 }
 
+#[cfg(feature = "transfer")]
+pub fn export(
+    conn: &duckdb::Connection,
+    path: &Path,
+    sql: &str,
+    format: crate::DataFormat,
+    dest: &Path,
+) -> Result<u64> {
+    attach_fresh(conn, path)?;
+    let copy = format!(
+        "COPY ({sql}) TO '{}' ({})",
+        sql_quote_str(crate::db::path_str(dest)?),
+        format.copy_options()
+    );
+    Ok(conn.execute(&copy, [])? as u64)
+}
+
+#[cfg(feature = "transfer")]
+pub fn describe_file(
+    conn: &duckdb::Connection,
+    file: &Path,
+    format: crate::DataFormat,
+) -> Result<Vec<(String, String)>> {
+    let source = format.reader_sql(&sql_quote_str(crate::db::path_str(file)?));
+    let mut stmt = conn.prepare(&format!("DESCRIBE SELECT * FROM {source}"))?;
+    let mut rows = stmt.query([])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push((row.get::<_, String>(0)?, row.get::<_, String>(1)?));
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "transfer")]
+pub fn scan_file(
+    conn: &duckdb::Connection,
+    file: &Path,
+    format: crate::DataFormat,
+    batch_size: usize,
+    f: &mut dyn FnMut(Vec<crate::EmbedRow>) -> bool,
+) -> Result<u64> {
+    let source = format.reader_sql(&sql_quote_str(crate::db::path_str(file)?));
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {source}"))?;
+    let mut rows = stmt.query([])?;
+    let ncols = rows
+        .as_ref()
+        .map(|s| s.column_names().len())
+        .unwrap_or_default();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut count = 0_u64;
+    while let Some(row) = rows.next()? {
+        let mut vals = Vec::with_capacity(ncols);
+        for i in 0..ncols {
+            vals.push(value_from_ref(row.get_ref(i)?)?);
+        }
+        batch.push(crate::EmbedRow(vals));
+        count += 1;
+        if batch.len() >= batch_size {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+            if !f(full) {
+                return Ok(count);
+            }
+        }
+    }
+    if !batch.is_empty() {
+        f(batch);
+    }
+    Ok(count)
+}
+
 fn value_from_ref(v: duckdb::types::ValueRef<'_>) -> Result<crate::EmbedValue> {
     use duckdb::types::ValueRef as V;
     Ok(match v {
@@ -0,0 +1,851 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::vector::{check_dim, TopK};
+use crate::{
+    dot, normalize, pack, unpack, EmbedDb, EmbedError, EmbedValue, Result, VectorFilter,
+    VectorHit, VectorSpace,
+};
+
+pub const ANN_META_TABLE: &str = "_embeddb_ann_meta";
+
+const CREATE_META: &str = "CREATE TABLE IF NOT EXISTS _embeddb_ann_meta (\
+ model TEXT PRIMARY KEY,\
+ dim INTEGER NOT NULL,\
+ lists INTEGER NOT NULL,\
+ probes INTEGER NOT NULL,\
+ generation INTEGER NOT NULL,\
+ built_at INTEGER NOT NULL)";
+
+const CREATE_CENTROIDS: &str = "CREATE TABLE IF NOT EXISTS _embeddb_ann_centroids (\
+ model TEXT NOT NULL,\
+ list_id INTEGER NOT NULL,\
+ centroid BLOB NOT NULL,\
+ PRIMARY KEY (model, list_id))";
+
+const CREATE_ASSIGN: &str = "CREATE TABLE IF NOT EXISTS _embeddb_ann_assign (\
+ model TEXT NOT NULL,\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ list_id INTEGER NOT NULL,\
+ PRIMARY KEY (model, ref_kind, ref_id))";
+
+const CREATE_ASSIGN_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_ann_assign_list \
+ON _embeddb_ann_assign (model, list_id)";
+
+pub(crate) const ASSIGN_SQL: &str = "INSERT INTO _embeddb_ann_assign \
+(model, ref_kind, ref_id, list_id) VALUES (?, ?, ?, ?) \
+ON CONFLICT (model, ref_kind, ref_id) DO UPDATE SET list_id = excluded.list_id";
+
+const META_UPSERT_SQL: &str = "INSERT INTO _embeddb_ann_meta \
+(model, dim, lists, probes, generation, built_at) \
+VALUES (?, ?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (model) DO UPDATE SET dim = excluded.dim, lists = excluded.lists, \
+probes = excluded.probes, generation = excluded.generation, built_at = excluded.built_at";
+
+const MAX_AUTO_LISTS: usize = 4096;
+
+pub(crate) async fn init(db: &EmbedDb) -> Result<()> {
+    db.execute(CREATE_META, ()).await?;
+    db.execute(CREATE_CENTROIDS, ()).await?;
+    db.execute(CREATE_ASSIGN, ()).await?;
+    db.execute(CREATE_ASSIGN_INDEX, ()).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnConfig {
+    pub lists: Option<usize>,
+    pub probes: usize,
+    pub train_iters: usize,
+    pub max_train_rows: usize,
+}
+
+impl Default for AnnConfig {
+    fn default() -> Self {
+        AnnConfig { lists: None, probes: 8, train_iters: 10, max_train_rows: 16_384 }
+    }
+}
+
+impl AnnConfig {
+    pub fn with_lists(mut self, lists: usize) -> Self {
+        self.lists = Some(lists);
+        self
+    }
+
+    pub fn with_probes(mut self, probes: usize) -> Self {
+        self.probes = probes;
+        self
+    }
+
+    pub fn with_train_iters(mut self, iters: usize) -> Self {
+        self.train_iters = iters;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.lists == Some(0) {
+            return Err(EmbedError::AnnIndex("lists must be >= 1".into()));
+        }
+        if self.probes == 0 {
+            return Err(EmbedError::AnnIndex("probes must be >= 1".into()));
+        }
+        if self.max_train_rows == 0 {
+            return Err(EmbedError::AnnIndex("max_train_rows must be >= 1".into()));
+        }
+        Ok(())
+    }
+
+    fn lists_for(&self, rows: usize) -> usize {
+        let lists = self
+            .lists
+            .unwrap_or_else(|| ((rows as f64).sqrt().round() as usize).clamp(1, MAX_AUTO_LISTS));
+        lists.min(rows).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    #[default]
+    Auto,
+    Exact,
+    Probes(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnStatus {
+    pub model: String,
+    pub dim: usize,
+    pub lists: usize,
+    pub probes: usize,
+    pub generation: i64,
+    pub built_at: i64,
+    pub assigned: i64,
+}
+
+#[derive(Debug)]
+pub(crate) struct IvfIndex {
+    dim: usize,
+    probes: usize,
+    centroids: Vec<Vec<f32>>,
+}
+
+impl IvfIndex {
+    pub(crate) fn nearest(&self, unit: &[f32]) -> usize {
+        nearest(&self.centroids, unit).0
+    }
+
+    /// Every list, nearest centroid first.
+    fn ranked_lists(&self, unit: &[f32]) -> Vec<usize> {
+        let mut scored: Vec<(usize, f32)> =
+            self.centroids.iter().enumerate().map(|(i, c)| (i, dot(unit, c))).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().map(|(i, _)| i).collect()
+    }
+}
+
+// Each cached index carries the meta row's `generation`, and a lookup
+// checks it against the row before using the index, so a rebuild or
+// probe change by another handle or process evicts it. "No index" is
+// never cached: another handle or process may build one, and rows
+// upserted without an assignment would never show up in ANN results.
+#[derive(Debug, Default)]
+pub(crate) struct AnnCache {
+    inner: Mutex<HashMap<String, (i64, Arc<IvfIndex>)>>,
+}
+
+impl AnnCache {
+    fn get(&self, model: &str) -> Option<(i64, Arc<IvfIndex>)> {
+        let guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.get(model).cloned()
+    }
+
+    fn put(&self, model: &str, generation: i64, index: Arc<IvfIndex>) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.insert(model.to_string(), (generation, index));
+    }
+
+    pub(crate) fn evict(&self, model: &str) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.remove(model);
+    }
+}
+
+fn nearest(centroids: &[Vec<f32>], unit: &[f32]) -> (usize, f32) {
+    let mut best = (0, f32::NEG_INFINITY);
+    for (i, c) in centroids.iter().enumerate() {
+        let score = dot(unit, c);
+        if score > best.1 {
+            best = (i, score);
+        }
+    }
+    best
+}
+
+fn train(sample: &[Vec<f32>], lists: usize, iters: usize) -> Vec<Vec<f32>> {
+    let n = sample.len();
+    let lists = lists.min(n);
+    if lists == 0 {
+        return Vec::new();
+    }
+    let dim = sample[0].len();
+    let mut centroids: Vec<Vec<f32>> = (0..lists).map(|i| sample[i * n / lists].clone()).collect();
+    let mut assign = vec![usize::MAX; n];
+    for _ in 0..iters {
+        let mut changed = false;
+        let mut scores = Vec::with_capacity(n);
+        for (i, v) in sample.iter().enumerate() {
+            let (list, score) = nearest(&centroids, v);
+            if assign[i] != list {
+                assign[i] = list;
+                changed = true;
+            }
+            scores.push(score);
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![vec![0.0_f32; dim]; lists];
+        let mut counts = vec![0_usize; lists];
+        for (v, &list) in sample.iter().zip(&assign) {
+            counts[list] += 1;
+            for (s, x) in sums[list].iter_mut().zip(v) {
+                *s += x;
+            }
+        }
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|a, b| scores[*a].total_cmp(&scores[*b]));
+        let mut reseed = order.into_iter();
+        for (list, sum) in sums.into_iter().enumerate() {
+            if counts[list] == 0 {
+                if let Some(far) = reseed.next() {
+                    centroids[list] = sample[far].clone();
+                }
+                continue;
+            }
+            if let Ok((unit, _)) = normalize(&sum) {
+                centroids[list] = unit;
+            }
+        }
+    }
+    centroids
+}
+
+fn blob(value: Option<&EmbedValue>) -> Result<Vec<f32>> {
+    match value {
+        Some(EmbedValue::Blob(b)) => unpack(b),
+        other => Err(EmbedError::VectorBlob(format!("expected blob column, got {:?}", other))),
+    }
+}
+
+fn int(value: Option<&EmbedValue>) -> i64 {
+    match value {
+        Some(EmbedValue::Int(n)) => *n,
+        _ => 0,
+    }
+}
+
+pub(crate) async fn drop_index(tx: &crate::EmbedTx<'_>, model: &str) -> Result<()> {
+    tx.execute("DELETE FROM _embeddb_ann_assign WHERE model = ?", (model,)).await?;
+    tx.execute("DELETE FROM _embeddb_ann_centroids WHERE model = ?", (model,)).await?;
+    tx.execute("DELETE FROM _embeddb_ann_meta WHERE model = ?", (model,)).await?;
+    Ok(())
+}
+
+impl EmbedDb {
+    pub(crate) async fn ann_index(&self, space: &VectorSpace) -> Result<Option<Arc<IvfIndex>>> {
+        let meta = self
+            .query_one(
+                "SELECT dim, probes, generation FROM _embeddb_ann_meta WHERE model = ?",
+                (space.model.as_str(),),
+            )
+            .await?;
+        let Some(meta) = meta else {
+            self.ann_cache().evict(&space.model);
+            return Ok(None);
+        };
+        let generation = int(meta.get(2));
+        match self.ann_cache().get(&space.model) {
+            Some((cached, index)) if cached == generation => {
+                return Ok(Some(index).filter(|index| index.dim == space.dim));
+            }
+            Some(_) => self.ann_cache().evict(&space.model),
+            None => {}
+        }
+        let dim = int(meta.get(0)) as usize;
+        let rows = self
+            .query_rows(
+                "SELECT centroid FROM _embeddb_ann_centroids WHERE model = ? ORDER BY list_id",
+                (space.model.as_str(),),
+            )
+            .await?;
+        let mut centroids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let c = blob(row.get(0))?;
+            if c.len() != dim {
+                return Err(EmbedError::VectorDim { expected: dim, actual: c.len() });
+            }
+            centroids.push(c);
+        }
+        if centroids.is_empty() {
+            return Ok(None);
+        }
+        let index =
+            Arc::new(IvfIndex { dim, probes: int(meta.get(1)).max(1) as usize, centroids });
+        self.ann_cache().put(&space.model, generation, index.clone());
+        Ok(Some(index).filter(|index| index.dim == space.dim))
+    }
+
+    pub async fn vector_ann_build(
+        &self,
+        space: &VectorSpace,
+        config: &AnnConfig,
+    ) -> Result<AnnStatus> {
+        config.validate()?;
+        let model = space.model.as_str();
+        // Scans and rewrites share one transaction, and only the scanned
+        // rows are reassigned, so nothing written outside the snapshot loses
+        // its list.
+        let tx = self.begin().await?;
+        if let Err(e) = crate::quant::ensure_f32_copies(&tx, model).await {
+            tx.rollback().await?;
+            return Err(e);
+        }
+        let total = tx
+            .query_one("SELECT count(*) FROM _embeddb_vectors WHERE model = ?", (model,))
+            .await?
+            .map(|r| int(r.get(0)))
+            .unwrap_or(0);
+        if total <= 0 {
+            tx.rollback().await?;
+            return Err(EmbedError::AnnIndex(format!(
+                "model '{}' has no vectors to train on",
+                space.model
+            )));
+        }
+        let stride = (total as usize).div_ceil(config.max_train_rows).max(1);
+
+        let mut sampled = Vec::new();
+        let mut seen = 0_usize;
+        tx.query_for_each(
+            "SELECT vec FROM _embeddb_vectors WHERE model = ? ORDER BY id",
+            (model,),
+            |row| {
+                if seen.is_multiple_of(stride) {
+                    sampled.push(row.get(0).cloned());
+                }
+                seen += 1;
+            },
+        )
+            .await?;
+        let mut sample = Vec::with_capacity(sampled.len());
+        for value in &sampled {
+            let v = blob(value.as_ref())?;
+            check_dim(space, &v)?;
+            sample.push(v);
+        }
+        drop(sampled);
+
+        let lists = config.lists_for(sample.len());
+        let centroids = train(&sample, lists, config.train_iters);
+        drop(sample);
+
+        let mut assigned = Vec::with_capacity(total as usize);
+        let mut bad = None;
+        tx.query_for_each(
+            "SELECT ref_kind, ref_id, vec FROM _embeddb_vectors WHERE model = ?",
+            (model,),
+            |row| {
+                if bad.is_some() {
+                    return;
+                }
+                match blob(row.get(2)) {
+                    Ok(v) if v.len() == space.dim => {
+                        let list = nearest(&centroids, &v).0 as i64;
+                        let kind = row.as_str(0).unwrap_or_default().to_string();
+                        let id = row.as_str(1).unwrap_or_default().to_string();
+                        assigned.push((kind, id, list));
+                    }
+                    Ok(v) => {
+                        bad = Some(EmbedError::VectorDim { expected: space.dim, actual: v.len() })
+                    }
+                    Err(e) => bad = Some(e),
+                }
+            },
+        )
+            .await?;
+        if let Some(e) = bad {
+            tx.rollback().await?;
+            return Err(e);
+        }
+
+        let previous = tx
+            .query_one("SELECT generation FROM _embeddb_ann_meta WHERE model = ?", (model,))
+            .await?;
+        let generation = previous.map(|r| int(r.get(0))).unwrap_or(0) + 1;
+
+        tx.execute("DELETE FROM _embeddb_ann_centroids WHERE model = ?", (model,)).await?;
+        tx.execute(
+            "DELETE FROM _embeddb_ann_assign WHERE model = ? AND NOT EXISTS (\
+SELECT 1 FROM _embeddb_vectors v WHERE v.model = _embeddb_ann_assign.model \
+AND v.ref_kind = _embeddb_ann_assign.ref_kind AND v.ref_id = _embeddb_ann_assign.ref_id)",
+            (model,),
+        )
+            .await?;
+        for (list, c) in centroids.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO _embeddb_ann_centroids (model, list_id, centroid) VALUES (?, ?, ?)",
+                (model, list as i64, pack(c)),
+            )
+                .await?;
+        }
+        for (kind, id, list) in assigned {
+            tx.execute(ASSIGN_SQL, (model, kind, id, list)).await?;
+        }
+        tx.execute(META_UPSERT_SQL, (
+            model,
+            space.dim as i64,
+            centroids.len() as i64,
+            config.probes as i64,
+            generation,
+        ))
+            .await?;
+        tx.commit().await?;
+        self.ann_cache().evict(model);
+
+        self.vector_ann_status(model).await?.ok_or_else(|| {
+            EmbedError::AnnIndex(format!("index for model '{model}' vanished after build"))
+        })
+    }
+
+    pub async fn vector_ann_status(&self, model: &str) -> Result<Option<AnnStatus>> {
+        let row = self
+            .query_one(
+                "SELECT dim, lists, probes, generation, built_at FROM _embeddb_ann_meta WHERE model = ?",
+                (model,),
+            )
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let assigned = self
+            .query_scalar_i64("SELECT count(*) FROM _embeddb_ann_assign WHERE model = ?", (model,))
+            .await?;
+        Ok(Some(AnnStatus {
+            model: model.to_string(),
+            dim: int(row.get(0)) as usize,
+            lists: int(row.get(1)) as usize,
+            probes: int(row.get(2)) as usize,
+            generation: int(row.get(3)),
+            built_at: int(row.get(4)),
+            assigned,
+        }))
+    }
+
+    pub async fn vector_ann_set_probes(&self, model: &str, probes: usize) -> Result<()> {
+        if probes == 0 {
+            return Err(EmbedError::AnnIndex("probes must be >= 1".into()));
+        }
+        let n = self
+            .execute(
+                "UPDATE _embeddb_ann_meta SET probes = ?, generation = generation + 1 WHERE model = ?",
+                (probes as i64, model),
+            )
+            .await?;
+        if n == 0 {
+            return Err(EmbedError::AnnIndex(format!("no index built for model '{model}'")));
+        }
+        self.ann_cache().evict(model);
+        Ok(())
+    }
+
+    pub async fn vector_ann_drop(&self, model: &str) -> Result<()> {
+        let tx = self.begin().await?;
+        drop_index(&tx, model).await?;
+        tx.commit().await?;
+        self.ann_cache().evict(model);
+        Ok(())
+    }
+
+    pub async fn vector_search_with(
+        &self,
+        space: &VectorSpace,
+        query: &[f32],
+        k: usize,
+        filter: Option<&VectorFilter>,
+        mode: SearchMode,
+    ) -> Result<Vec<VectorHit>> {
+        check_dim(space, query)?;
+        let (unit, _) = normalize(query)?;
+        let index = match mode {
+            SearchMode::Exact => None,
+            SearchMode::Auto | SearchMode::Probes(_) => self.ann_index(space).await?,
+        };
+        // Codes-only models have no f32 copy for the IVF scan to score, so
+        // they search their codes even when an index exists.
+        let quant = match mode {
+            SearchMode::Exact => None,
+            _ => self.quant_mode(space).await?,
+        };
+        if let Some(quant) = quant.filter(|q| index.is_none() || !q.keep_f32) {
+            return self.vector_search_quantized(space, quant.mode, &unit, k, filter).await;
+        }
+        let Some(index) = index else {
+            return self.vector_search_exact(space, &unit, k, filter).await;
+        };
+        let probes = match mode {
+            SearchMode::Probes(p) => p.max(1),
+            _ => index.probes,
+        };
+        match self.vector_search_ivf(space, &index, &unit, k, filter, probes).await? {
+            Some(hits) => Ok(hits),
+            None => self.vector_search_exact(space, &unit, k, filter).await,
+        }
+    }
+
+    /// Probes the `probes` nearest lists, doubling the probe count while
+    /// fewer than `k` hits turn up (a selective filter thins every list).
+    /// `None` once the next round would cover every list, where the exact
+    /// scan is cheaper than the join.
+    async fn vector_search_ivf(
+        &self,
+        space: &VectorSpace,
+        index: &IvfIndex,
+        unit: &[f32],
+        k: usize,
+        filter: Option<&VectorFilter>,
+        probes: usize,
+    ) -> Result<Option<Vec<VectorHit>>> {
+        let ranked = index.ranked_lists(unit);
+        let (where_sql, extra) = crate::filter::clauses(filter, "v.")?;
+        let payload = filter.is_some_and(|f| f.with_payload);
+        let mut hits: Vec<VectorHit> = Vec::new();
+        let (mut probed, mut probes) = (0, probes);
+        while probes < ranked.len() {
+            let lists = &ranked[probed..probes];
+            let placeholders = vec!["?"; lists.len()].join(", ");
+            let mut params = vec![turso::Value::Text(space.model.clone())];
+            params.extend(lists.iter().map(|l| turso::Value::Integer(*l as i64)));
+            params.extend(extra.iter().cloned());
+            let sql = format!(
+                "SELECT v.ref_kind, v.ref_id, v.vec, v.meta FROM _embeddb_ann_assign a \
+JOIN _embeddb_vectors v ON v.model = a.model AND v.ref_kind = a.ref_kind AND v.ref_id = a.ref_id \
+WHERE a.model = ? AND a.list_id IN ({placeholders}){where_sql}"
+            );
+            let found = self.vector_scan(&sql, params, space, unit, k, payload).await?;
+            let mut top = TopK::new(k);
+            for hit in hits.into_iter().chain(found) {
+                top.push(hit);
+            }
+            hits = top.finish();
+            if hits.len() >= k {
+                return Ok(Some(hits));
+            }
+            probed = probes;
+            probes = probes.saturating_mul(2);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.vector_init().await.unwrap();
+        (dir, db)
+    }
+
+    fn seeded(seed: u64, dim: usize) -> Vec<f32> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..dim)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((state >> 33) as f32 / u32::MAX as f32) - 0.5
+            })
+            .collect()
+    }
+
+    async fn seed_corpus(db: &EmbedDb, space: &VectorSpace, n: u64) {
+        let items: Vec<(String, String, Vec<f32>)> = (0..n)
+            .map(|i| ("msg".to_string(), i.to_string(), seeded(i, space.dim)))
+            .collect();
+        db.vector_upsert_batch(space, &items).await.unwrap();
+    }
+
+    #[test]
+    fn config_validation_rejects_zero_knobs() {
+        assert!(AnnConfig::default().with_lists(0).validate().is_err());
+        assert!(AnnConfig::default().with_probes(0).validate().is_err());
+        assert!(AnnConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn auto_lists_scale_with_sqrt_of_rows() {
+        let c = AnnConfig::default();
+        assert_eq!(c.lists_for(10_000), 100);
+        assert_eq!(c.lists_for(1), 1);
+        assert_eq!(c.clone().with_lists(50).lists_for(10), 10);
+    }
+
+    #[test]
+    fn train_separates_obvious_clusters() {
+        let mut sample = Vec::new();
+        for i in 0..20 {
+            let jitter = i as f32 / 1000.0;
+            sample.push(normalize(&[1.0, jitter, 0.0]).unwrap().0);
+            sample.push(normalize(&[0.0, jitter, 1.0]).unwrap().0);
+        }
+        let centroids = train(&sample, 2, 10);
+        assert_eq!(centroids.len(), 2);
+        let a = nearest(&centroids, &[1.0, 0.0, 0.0]).0;
+        let b = nearest(&centroids, &[0.0, 0.0, 1.0]).0;
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn train_caps_lists_at_sample_size() {
+        let sample = vec![vec![1.0_f32, 0.0], vec![0.0, 1.0]];
+        assert_eq!(train(&sample, 8, 3).len(), 2);
+        assert!(train(&[], 4, 3).is_empty());
+    }
+
+    #[tokio::test]
+    async fn build_on_empty_model_errors() {
+        let (_d, db) = open("ann_empty.db").await;
+        let s = VectorSpace::new("local", "m", 4);
+        let err = db.vector_ann_build(&s, &AnnConfig::default()).await.unwrap_err();
+        assert!(matches!(err, EmbedError::AnnIndex(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn build_assigns_every_vector() {
+        let (_d, db) = open("ann_build.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 200).await;
+        let status = db.vector_ann_build(&s, &AnnConfig::default().with_lists(10)).await.unwrap();
+        assert_eq!(status.lists, 10);
+        assert_eq!(status.assigned, 200);
+        assert_eq!(status.generation, 1);
+        let rebuilt = db.vector_ann_build(&s, &AnnConfig::default().with_lists(10)).await.unwrap();
+        assert_eq!(rebuilt.generation, 2);
+        assert_eq!(rebuilt.assigned, 200);
+    }
+
+    #[tokio::test]
+    async fn index_tracks_upsert_and_delete() {
+        let (_d, db) = open("ann_sync.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 50).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(5)).await.unwrap();
+
+        db.vector_upsert(&s, "msg", "new", &seeded(999, 8)).await.unwrap();
+        assert_eq!(db.vector_ann_status("m").await.unwrap().unwrap().assigned, 51);
+
+        let batch = vec![("doc".to_string(), "d1".to_string(), seeded(1000, 8))];
+        db.vector_upsert_batch(&s, &batch).await.unwrap();
+        assert_eq!(db.vector_ann_status("m").await.unwrap().unwrap().assigned, 52);
+
+        db.vector_delete("m", "msg", "new").await.unwrap();
+        assert_eq!(db.vector_ann_status("m").await.unwrap().unwrap().assigned, 51);
+    }
+
+    #[tokio::test]
+    async fn ann_search_finds_exact_match() {
+        let (_d, db) = open("ann_search.db").await;
+        let s = VectorSpace::new("local", "m", 16);
+        seed_corpus(&db, &s, 300).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(12).with_probes(3))
+            .await
+            .unwrap();
+        for i in [0_u64, 42, 299] {
+            let hits = db.vector_search(&s, &seeded(i, 16), 1, None).await.unwrap();
+            assert_eq!(hits[0].ref_id, i.to_string());
+            assert!((hits[0].score - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[tokio::test]
+    async fn full_probe_matches_exact_results() {
+        let (_d, db) = open("ann_full_probe.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 100).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(6)).await.unwrap();
+        let q = seeded(7_777, 8);
+        let exact = db.vector_search_with(&s, &q, 10, None, SearchMode::Exact).await.unwrap();
+        let probed = db.vector_search_with(&s, &q, 10, None, SearchMode::Probes(6)).await.unwrap();
+        assert_eq!(exact, probed);
+    }
+
+    #[tokio::test]
+    async fn sparse_probe_falls_back_to_exact_when_short() {
+        let (_d, db) = open("ann_fallback.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 40).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(8)).await.unwrap();
+        let hits = db
+            .vector_search_with(&s, &seeded(3, 8), 40, None, SearchMode::Probes(1))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 40);
+    }
+
+    #[tokio::test]
+    async fn selective_filter_widens_probes_instead_of_scanning() {
+        let (_d, db) = open("ann_selective.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 300).await;
+        let target = seeded(5, 8);
+        for (i, scale) in [1.0_f32, 2.0, 3.0].into_iter().enumerate() {
+            let v: Vec<f32> = target.iter().map(|x| x * scale).collect();
+            db.vector_upsert(&s, "doc", &i.to_string(), &v).await.unwrap();
+        }
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(12).with_probes(1))
+            .await
+            .unwrap();
+
+        let index = db.ann_index(&s).await.unwrap().unwrap();
+        let (unit, _) = normalize(&target).unwrap();
+        let filter = VectorFilter::kind("doc");
+        let hits = db
+            .vector_search_ivf(&s, &index, &unit, 3, Some(&filter), 1)
+            .await
+            .unwrap()
+            .expect("served from the index");
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|h| h.ref_kind == "doc"));
+
+        let short = db.vector_search_ivf(&s, &index, &unit, 4, Some(&filter), 1).await.unwrap();
+        assert!(short.is_none(), "only three docs exist, so every list gets probed");
+    }
+
+    #[tokio::test]
+    async fn filter_applies_under_ann() {
+        let (_d, db) = open("ann_filter.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 60).await;
+        db.vector_upsert(&s, "doc", "only", &seeded(5, 8)).await.unwrap();
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(4).with_probes(2))
+            .await
+            .unwrap();
+        let hits = db
+            .vector_search(&s, &seeded(5, 8), 1, Some(&VectorFilter::kind("doc")))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_kind, "doc");
+    }
+
+    #[tokio::test]
+    async fn set_probes_persists_and_invalidates_cache() {
+        let (_d, db) = open("ann_probes.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 30).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(5).with_probes(1))
+            .await
+            .unwrap();
+        db.vector_search(&s, &seeded(1, 8), 1, None).await.unwrap();
+        db.vector_ann_set_probes("m", 4).await.unwrap();
+        assert_eq!(db.vector_ann_status("m").await.unwrap().unwrap().probes, 4);
+        assert_eq!(db.ann_index(&s).await.unwrap().unwrap().probes, 4);
+        assert!(db.vector_ann_set_probes("missing", 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cached_index_follows_changes_made_by_another_handle() {
+        let (dir, db) = open("ann_stale.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 30).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(5).with_probes(1))
+            .await
+            .unwrap();
+        assert_eq!(db.ann_index(&s).await.unwrap().unwrap().probes, 1);
+
+        let other = EmbedDb::open(dir.path().join("ann_stale.db")).await.unwrap();
+        other.vector_ann_set_probes("m", 3).await.unwrap();
+        assert_eq!(db.ann_index(&s).await.unwrap().unwrap().probes, 3);
+
+        other.vector_ann_drop("m").await.unwrap();
+        assert!(db.ann_index(&s).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn upserts_assign_lists_of_an_index_built_by_another_handle() {
+        let (dir, db) = open("ann_handles.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 40).await;
+        assert!(db.ann_index(&s).await.unwrap().is_none());
+
+        let other = EmbedDb::open(dir.path().join("ann_handles.db")).await.unwrap();
+        other.vector_ann_build(&s, &AnnConfig::default().with_lists(4)).await.unwrap();
+
+        db.vector_upsert(&s, "doc", "late", &seeded(99, 8)).await.unwrap();
+        assert_eq!(db.vector_ann_status("m").await.unwrap().unwrap().assigned, 41);
+        let hits = other
+            .vector_search_with(&s, &seeded(99, 8), 1, None, SearchMode::Probes(1))
+            .await
+            .unwrap();
+        assert_eq!(hits[0].ref_id, "late");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn upserts_racing_a_build_all_get_lists() {
+        let (dir, db) = open("ann_race.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 40).await;
+        let builder = EmbedDb::open(dir.path().join("ann_race.db")).await.unwrap();
+
+        let space = s.clone();
+        let writer = tokio::spawn(async move {
+            for i in 100..200 {
+                // A write whose snapshot the build overtook fails; retry it.
+                let mut attempts = 0;
+                while db.vector_upsert(&space, "doc", &i.to_string(), &seeded(i, 8)).await.is_err() {
+                    attempts += 1;
+                    assert!(attempts < 1000, "upsert {i} never went through");
+                    tokio::task::yield_now().await;
+                }
+            }
+            db
+        });
+        let config = AnnConfig::default().with_lists(4);
+        let mut attempts = 0;
+        while builder.vector_ann_build(&s, &config).await.is_err() {
+            attempts += 1;
+            assert!(attempts < 1000, "build never went through");
+            tokio::task::yield_now().await;
+        }
+        let db = writer.await.unwrap();
+
+        let unassigned = db
+            .query_scalar_i64(
+                "SELECT count(*) FROM _embeddb_vectors v WHERE v.model = 'm' AND NOT EXISTS (\
+SELECT 1 FROM _embeddb_ann_assign a WHERE a.model = v.model \
+AND a.ref_kind = v.ref_kind AND a.ref_id = v.ref_id)",
+                (),
+            )
+            .await
+            .unwrap();
+        assert_eq!(unassigned, 0);
+        assert_eq!(db.vector_count("m").await.unwrap(), 140);
+    }
+
+    #[tokio::test]
+    async fn drop_and_delete_model_remove_the_index() {
+        let (_d, db) = open("ann_drop.db").await;
+        let s = VectorSpace::new("local", "m", 8);
+        seed_corpus(&db, &s, 20).await;
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(4)).await.unwrap();
+        db.vector_ann_drop("m").await.unwrap();
+        assert!(db.vector_ann_status("m").await.unwrap().is_none());
+        assert_eq!(db.vector_search(&s, &seeded(2, 8), 1, None).await.unwrap()[0].ref_id, "2");
+
+        db.vector_ann_build(&s, &AnnConfig::default().with_lists(4)).await.unwrap();
+        db.vector_delete_model("m").await.unwrap();
+        assert!(db.vector_ann_status("m").await.unwrap().is_none());
+        assert_eq!(db.vector_count("m").await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,680 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{EmbedDb, EmbedError, Result};
+
+const DB_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+const JOURNAL_MAGIC: &[u8; 8] = b"EMBJRNL1";
+const MANIFEST_TAG: &str = "embeddb-pages v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupReport {
+    pub page_size: usize,
+    pub pages: u64,
+    pub pages_written: u64,
+    pub incremental: bool,
+}
+
+impl BackupReport {
+    pub fn bytes_written(&self) -> u64 {
+        self.pages_written * self.page_size as u64
+    }
+}
+
+fn backup_err(msg: impl Into<String>) -> EmbedError {
+    EmbedError::Backup(msg.into())
+}
+
+// Resolves symlinks and relative segments. A path that doesn't exist yet
+// is resolved through its parent directory.
+fn resolved(path: &Path) -> PathBuf {
+    if let Ok(p) = fs::canonicalize(path) {
+        return p;
+    }
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    match (fs::canonicalize(parent), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut s: OsString = path.as_os_str().to_os_string();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+fn manifest_path(path: &Path) -> PathBuf {
+    sidecar(path, ".pages")
+}
+
+fn journal_path(path: &Path) -> PathBuf {
+    sidecar(path, ".journal")
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+fn hash_page(page: &[u8]) -> u64 {
+    let mut h = Fnv::new();
+    h.update(page);
+    h.0
+}
+
+pub(crate) fn page_size(header: &[u8]) -> Result<usize> {
+    if header.len() < 100 || &header[..16] != DB_MAGIC {
+        return Err(backup_err("not a database file: bad header magic"));
+    }
+    let raw = u16::from_be_bytes([header[16], header[17]]);
+    let size = if raw == 1 { 65536 } else { usize::from(raw) };
+    if !(512..=65536).contains(&size) || !size.is_power_of_two() {
+        return Err(backup_err(format!("invalid page size {size}")));
+    }
+    Ok(size)
+}
+
+struct PageReader {
+    reader: BufReader<File>,
+    page_size: usize,
+    pages: u32,
+    header: [u8; 100],
+    buf: Vec<u8>,
+}
+
+impl PageReader {
+    fn open(path: &Path) -> Result<PageReader> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut header = [0u8; 100];
+        file.read_exact(&mut header)
+            .map_err(|_| backup_err(format!("{} is too short to be a database", path.display())))?;
+        let page_size = page_size(&header)?;
+        if len % page_size as u64 != 0 {
+            return Err(backup_err(format!(
+                "{} is {len} bytes, not a whole number of {page_size}-byte pages",
+                path.display()
+            )));
+        }
+        let pages = u32::try_from(len / page_size as u64)
+            .map_err(|_| backup_err("database has more pages than the format allows"))?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(PageReader {
+            reader: BufReader::new(file),
+            page_size,
+            pages,
+            header,
+            buf: vec![0u8; page_size],
+        })
+    }
+
+    fn read_page(&mut self) -> Result<&[u8]> {
+        self.reader.read_exact(&mut self.buf)?;
+        Ok(&self.buf)
+    }
+
+    fn hashes(mut self) -> Result<Vec<u64>> {
+        let mut out = Vec::with_capacity(self.pages as usize);
+        for _ in 0..self.pages {
+            out.push(hash_page(self.read_page()?));
+        }
+        Ok(out)
+    }
+}
+
+fn sync_dir(path: &Path) {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Ok(d) = File::open(dir) {
+            d.sync_all().ok();
+        }
+    }
+}
+
+fn finish(out: BufWriter<File>) -> Result<()> {
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(())
+}
+
+fn read_manifest(path: &Path) -> Option<(usize, Vec<u64>)> {
+    let text = fs::read_to_string(manifest_path(path)).ok()?;
+    let mut lines = text.lines();
+    let page_size = lines.next()?.strip_prefix(MANIFEST_TAG)?.trim().parse().ok()?;
+    let hashes = lines.map(|l| u64::from_str_radix(l, 16).ok()).collect::<Option<Vec<_>>>()?;
+    Some((page_size, hashes))
+}
+
+fn write_manifest(path: &Path, page_size: usize, hashes: &[u64]) -> Result<()> {
+    let target = manifest_path(path);
+    let tmp = sidecar(&target, ".tmp");
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    writeln!(out, "{MANIFEST_TAG} {page_size}")?;
+    for h in hashes {
+        writeln!(out, "{h:016x}")?;
+    }
+    finish(out)?;
+    fs::rename(&tmp, &target)?;
+    Ok(())
+}
+
+struct Journal {
+    out: BufWriter<File>,
+    hash: Fnv,
+}
+
+impl Journal {
+    fn create(path: &Path, page_size: usize, pages: u32) -> Result<Journal> {
+        let mut j = Journal { out: BufWriter::new(File::create(path)?), hash: Fnv::new() };
+        j.put(JOURNAL_MAGIC)?;
+        j.put(&(page_size as u32).to_le_bytes())?;
+        j.put(&pages.to_le_bytes())?;
+        Ok(j)
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<()> {
+        self.hash.update(bytes);
+        self.out.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn seal(mut self) -> Result<()> {
+        let sum = self.hash.0.to_le_bytes();
+        self.out.write_all(&sum)?;
+        finish(self.out)
+    }
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn journal_is_sealed(path: &Path) -> Result<bool> {
+    let len = fs::metadata(path)?.len();
+    if len < 24 {
+        return Ok(false);
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hash = Fnv::new();
+    let mut remaining = len - 8;
+    let mut buf = [0u8; 8192];
+    while remaining > 0 {
+        let n = buf.len().min(remaining as usize);
+        reader.read_exact(&mut buf[..n])?;
+        hash.update(&buf[..n]);
+        remaining -= n as u64;
+    }
+    let mut sum = [0u8; 8];
+    reader.read_exact(&mut sum)?;
+    let mut magic = [0u8; 8];
+    File::open(path)?.read_exact(&mut magic)?;
+    Ok(magic == *JOURNAL_MAGIC && u64::from_le_bytes(sum) == hash.0)
+}
+
+fn apply_journal(dest: &Path, journal: &Path) -> Result<()> {
+    let len = fs::metadata(journal)?.len();
+    let mut reader = BufReader::new(File::open(journal)?);
+    reader.seek(SeekFrom::Start(JOURNAL_MAGIC.len() as u64))?;
+    let page_size = read_u32(&mut reader)? as usize;
+    let pages = read_u32(&mut reader)?;
+    let mut out = OpenOptions::new().write(true).create(true).truncate(false).open(dest)?;
+    let mut page = vec![0u8; page_size];
+    let mut offset = 16_u64;
+    while offset + 8 < len {
+        let n = read_u32(&mut reader)?;
+        reader.read_exact(&mut page)?;
+        out.seek(SeekFrom::Start(u64::from(n) * page_size as u64))?;
+        out.write_all(&page)?;
+        offset += 4 + page_size as u64;
+    }
+    out.set_len(u64::from(pages) * page_size as u64)?;
+    out.sync_all()?;
+    Ok(())
+}
+
+fn recover(dest: &Path) -> Result<()> {
+    let journal = journal_path(dest);
+    if !journal.exists() {
+        return Ok(());
+    }
+    if journal_is_sealed(&journal)? && dest.exists() {
+        apply_journal(dest, &journal)?;
+        let reader = PageReader::open(dest)?;
+        let page_size = reader.page_size;
+        write_manifest(dest, page_size, &reader.hashes()?)?;
+    }
+    fs::remove_file(&journal)?;
+    Ok(())
+}
+
+fn write_full(src: &Path, dest: &Path) -> Result<BackupReport> {
+    let tmp = sidecar(dest, ".tmp");
+    let mut reader = PageReader::open(src)?;
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    let mut hashes = Vec::with_capacity(reader.pages as usize);
+    for _ in 0..reader.pages {
+        let page = reader.read_page()?;
+        hashes.push(hash_page(page));
+        out.write_all(page)?;
+    }
+    finish(out)?;
+    fs::remove_file(journal_path(dest)).ok();
+    fs::rename(&tmp, dest)?;
+    sync_dir(dest);
+    write_manifest(dest, reader.page_size, &hashes)?;
+    Ok(BackupReport {
+        page_size: reader.page_size,
+        pages: u64::from(reader.pages),
+        pages_written: u64::from(reader.pages),
+        incremental: false,
+    })
+}
+
+fn write_incremental(src: &Path, dest: &Path) -> Result<BackupReport> {
+    recover(dest)?;
+    let mut reader = PageReader::open(src)?;
+    let previous = read_manifest(dest).filter(|(size, hashes)| {
+        *size == reader.page_size
+            && fs::metadata(dest).map(|m| m.len()).ok()
+                == Some(hashes.len() as u64 * *size as u64)
+    });
+    let Some((_, previous)) = previous else {
+        return write_full(src, dest);
+    };
+    let journal_file = journal_path(dest);
+    let mut journal = Journal::create(&journal_file, reader.page_size, reader.pages)?;
+    let mut hashes = Vec::with_capacity(reader.pages as usize);
+    let mut written = 0_u64;
+    for n in 0..reader.pages {
+        let page = reader.read_page()?;
+        let h = hash_page(page);
+        if previous.get(n as usize) != Some(&h) {
+            journal.put(&n.to_le_bytes())?;
+            journal.put(page)?;
+            written += 1;
+        }
+        hashes.push(h);
+    }
+    let report = BackupReport {
+        page_size: reader.page_size,
+        pages: u64::from(reader.pages),
+        pages_written: written,
+        incremental: true,
+    };
+    if written == 0 && previous.len() == hashes.len() {
+        drop(journal);
+        fs::remove_file(&journal_file)?;
+        return Ok(report);
+    }
+    journal.seal()?;
+    apply_journal(dest, &journal_file)?;
+    write_manifest(dest, reader.page_size, &hashes)?;
+    fs::remove_file(&journal_file)?;
+    Ok(report)
+}
+
+fn verify(path: &Path) -> Result<BackupReport> {
+    let mut reader = PageReader::open(path)?;
+    let header = reader.header;
+    let in_header = u32::from_be_bytes([header[28], header[29], header[30], header[31]]);
+    let change_counter = &header[24..28];
+    let valid_for = &header[92..96];
+    if in_header != 0 && change_counter == valid_for && in_header != reader.pages {
+        return Err(backup_err(format!(
+            "header records {in_header} pages but the file holds {}",
+            reader.pages
+        )));
+    }
+    let manifest = read_manifest(path).filter(|(size, _)| *size == reader.page_size);
+    if let Some((_, expected)) = &manifest {
+        if expected.len() != reader.pages as usize {
+            return Err(backup_err(format!(
+                "manifest lists {} pages but the file holds {}",
+                expected.len(),
+                reader.pages
+            )));
+        }
+        for (n, want) in expected.iter().enumerate() {
+            if hash_page(reader.read_page()?) != *want {
+                return Err(backup_err(format!("page {n} does not match the backup manifest")));
+            }
+        }
+    }
+    Ok(BackupReport {
+        page_size: reader.page_size,
+        pages: u64::from(reader.pages),
+        pages_written: 0,
+        incremental: false,
+    })
+}
+
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| EmbedError::Other(e.to_string()))?
+}
+
+impl EmbedDb {
+    /// Writes the pinned snapshot as a single self-contained database file.
+    /// No WAL is copied: the snapshot is checkpointed into the main file
+    /// before the copy, so `dest` needs no `-wal` to open.
+    pub async fn backup_to(&self, dest: impl AsRef<Path>) -> Result<BackupReport> {
+        self.backup(dest.as_ref(), false).await
+    }
+
+    pub async fn backup_incremental(&self, dest: impl AsRef<Path>) -> Result<BackupReport> {
+        self.backup(dest.as_ref(), true).await
+    }
+
+    pub async fn verify_backup(path: impl AsRef<Path>) -> Result<BackupReport> {
+        let path = path.as_ref().to_path_buf();
+        blocking(move || {
+            recover(&path)?;
+            verify(&path)
+        })
+            .await
+    }
+
+    pub async fn restore_from(
+        backup: impl AsRef<Path>,
+        target: impl AsRef<Path>,
+    ) -> Result<BackupReport> {
+        let backup = backup.as_ref().to_path_buf();
+        let target = target.as_ref().to_path_buf();
+        let staged = sidecar(&target, ".restore");
+        let report = {
+            let (backup, staged) = (backup.clone(), staged.clone());
+            blocking(move || {
+                recover(&backup)?;
+                let report = verify(&backup)?;
+                fs::copy(&backup, &staged)?;
+                File::open(&staged)?.sync_all()?;
+                Ok(report)
+            })
+                .await?
+        };
+        if let Err(e) = open_check(&staged).await {
+            fs::remove_file(&staged).ok();
+            fs::remove_file(sidecar(&staged, "-wal")).ok();
+            return Err(backup_err(format!("{} failed to open: {e}", backup.display())));
+        }
+        blocking(move || {
+            fs::remove_file(sidecar(&staged, "-wal")).ok();
+            fs::remove_file(sidecar(&target, "-wal")).ok();
+            fs::remove_file(sidecar(&target, "-shm")).ok();
+            fs::rename(&staged, &target)?;
+            sync_dir(&target);
+            Ok(report)
+        })
+            .await
+    }
+
+    async fn backup(&self, dest: &Path, incremental: bool) -> Result<BackupReport> {
+        if resolved(dest) == resolved(self.path()) {
+            return Err(backup_err("backup destination is the live database"));
+        }
+        let pin = self.pin_snapshot().await?;
+        let src = self.path().to_path_buf();
+        let dest = dest.to_path_buf();
+        let result = blocking(move || {
+            if incremental {
+                write_incremental(&src, &dest)
+            } else {
+                write_full(&src, &dest)
+            }
+        })
+            .await;
+        pin.execute("ROLLBACK", ()).await.ok();
+        result
+    }
+
+    // Starts the read transaction first and checkpoints under it. A
+    // checkpoint never backfills past a live reader's snapshot, so once one
+    // reports every WAL frame backfilled the main file holds exactly the
+    // pinned snapshot, and keeps holding it until the pin is released.
+    async fn pin_snapshot(&self) -> Result<turso::Connection> {
+        for _ in 0..=self.config().checkpoint_max_retries {
+            let pin = self.database().connect()?;
+            pin.execute("BEGIN", ()).await?;
+            let mut rows = pin.query("SELECT count(*) FROM sqlite_schema", ()).await?;
+            while rows.next().await?.is_some() {}
+            let (busy, log, checkpointed) = self.wal_checkpoint("PASSIVE").await?;
+            if busy == 0 && checkpointed >= log {
+                return Ok(pin);
+            }
+            pin.execute("ROLLBACK", ()).await.ok();
+            tokio::task::yield_now().await;
+        }
+        Err(EmbedError::CheckpointBusy)
+    }
+}
+
+async fn open_check(path: &Path) -> Result<()> {
+    let db = turso::Builder::new_local(crate::db::path_str(path)?).build().await?;
+    let conn = db.connect()?;
+    let mut rows = conn.query("SELECT count(*) FROM sqlite_schema", ()).await?;
+    while rows.next().await?.is_some() {}
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmbedDb;
+
+    async fn seeded(dir: &Path, rows: i64) -> EmbedDb {
+        let db = EmbedDb::open(dir.join("live.db")).await.unwrap();
+        db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, body TEXT)", ()).await.unwrap();
+        let params: Vec<(i64, String)> = (0..rows).map(|i| (i, format!("row-{i:0>200}"))).collect();
+        db.execute_batch("INSERT INTO t VALUES (?, ?)", params).await.unwrap();
+        db
+    }
+
+    async fn count(path: &Path) -> i64 {
+        let db = EmbedDb::open(path).await.unwrap();
+        db.query_scalar_i64("SELECT count(*) FROM t", ()).await.unwrap()
+    }
+
+    #[test]
+    fn page_size_reads_header_and_rejects_garbage() {
+        let mut header = [0u8; 100];
+        header[..16].copy_from_slice(DB_MAGIC);
+        header[16..18].copy_from_slice(&4096_u16.to_be_bytes());
+        assert_eq!(page_size(&header).unwrap(), 4096);
+        header[16..18].copy_from_slice(&1_u16.to_be_bytes());
+        assert_eq!(page_size(&header).unwrap(), 65536);
+        header[16..18].copy_from_slice(&1000_u16.to_be_bytes());
+        assert!(page_size(&header).is_err());
+        assert!(matches!(page_size(&[0u8; 100]).unwrap_err(), EmbedError::Backup(_)));
+    }
+
+    #[tokio::test]
+    async fn full_backup_round_trips_and_ignores_later_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 10).await;
+        let backup = dir.path().join("backup.db");
+        let report = db.backup_to(&backup).await.unwrap();
+        assert!(!report.incremental);
+        assert_eq!(report.pages_written, report.pages);
+
+        db.execute("INSERT INTO t VALUES (100, 'after')", ()).await.unwrap();
+        assert_eq!(db.query_scalar_i64("SELECT count(*) FROM t", ()).await.unwrap(), 11);
+
+        let restored = dir.path().join("restored.db");
+        EmbedDb::restore_from(&backup, &restored).await.unwrap();
+        assert_eq!(count(&restored).await, 10);
+    }
+
+    #[tokio::test]
+    async fn backup_under_concurrent_writes_is_a_committed_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 200).await;
+        let backup = dir.path().join("busy.db");
+        let writes = async {
+            for i in 1000..1100_i64 {
+                db.execute("INSERT INTO t VALUES (?, 'during')", (i,)).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        };
+        let (report, ()) = tokio::join!(db.backup_to(&backup), writes);
+        report.unwrap();
+
+        EmbedDb::verify_backup(&backup).await.unwrap();
+        let restored = dir.path().join("restored.db");
+        EmbedDb::restore_from(&backup, &restored).await.unwrap();
+        let check = EmbedDb::open(&restored).await.unwrap();
+        let during = check
+            .query_scalar_i64("SELECT count(*) FROM t WHERE id >= 1000", ())
+            .await
+            .unwrap();
+        let last = check
+            .query_scalar_i64("SELECT COALESCE(max(id), 999) FROM t WHERE id >= 1000", ())
+            .await
+            .unwrap();
+        assert_eq!(last, 999 + during);
+        assert_eq!(count(&restored).await, 200 + during);
+    }
+
+    #[tokio::test]
+    async fn incremental_backup_ships_only_changed_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 500).await;
+        let backup = dir.path().join("rolling.db");
+
+        let first = db.backup_incremental(&backup).await.unwrap();
+        assert!(!first.incremental, "no previous snapshot means a full copy");
+
+        db.execute("UPDATE t SET body = 'changed' WHERE id = 250", ()).await.unwrap();
+        let second = db.backup_incremental(&backup).await.unwrap();
+        assert!(second.incremental);
+        assert!(second.pages_written > 0);
+        assert!(second.pages_written < second.pages / 2, "{second:?}");
+
+        let third = db.backup_incremental(&backup).await.unwrap();
+        assert_eq!(third.pages_written, 0);
+        assert!(!journal_path(&backup).exists());
+
+        EmbedDb::verify_backup(&backup).await.unwrap();
+        let restored = dir.path().join("restored.db");
+        EmbedDb::restore_from(&backup, &restored).await.unwrap();
+        let check = EmbedDb::open(&restored).await.unwrap();
+        let body = check
+            .query_scalar_string("SELECT body FROM t WHERE id = 250", ())
+            .await
+            .unwrap();
+        assert_eq!(body, "changed");
+    }
+
+    #[tokio::test]
+    async fn incremental_backup_picks_up_growth() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 50).await;
+        let backup = dir.path().join("grow.db");
+        db.backup_incremental(&backup).await.unwrap();
+        let params: Vec<(i64, String)> = (50..400).map(|i| (i, format!("{i:0>200}"))).collect();
+        db.execute_batch("INSERT INTO t VALUES (?, ?)", params).await.unwrap();
+        let report = db.backup_incremental(&backup).await.unwrap();
+        assert!(report.incremental);
+        EmbedDb::verify_backup(&backup).await.unwrap();
+        let restored = dir.path().join("restored.db");
+        EmbedDb::restore_from(&backup, &restored).await.unwrap();
+        assert_eq!(count(&restored).await, 400);
+    }
+
+    #[tokio::test]
+    async fn verify_detects_a_corrupted_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 100).await;
+        let backup = dir.path().join("corrupt.db");
+        let report = db.backup_to(&backup).await.unwrap();
+
+        let mut file = OpenOptions::new().write(true).open(&backup).unwrap();
+        file.seek(SeekFrom::Start(report.page_size as u64 + 10)).unwrap();
+        file.write_all(&[0xAB; 4]).unwrap();
+        drop(file);
+
+        let err = EmbedDb::verify_backup(&backup).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Backup(_)), "{err}");
+        let target = dir.path().join("never.db");
+        assert!(EmbedDb::restore_from(&backup, &target).await.is_err());
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_files_that_are_not_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let junk = dir.path().join("junk.db");
+        fs::write(&junk, vec![7u8; 8192]).unwrap();
+        let err = EmbedDb::restore_from(&junk, dir.path().join("out.db")).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Backup(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn torn_journal_is_discarded_and_sealed_journal_is_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 100).await;
+        let backup = dir.path().join("journal.db");
+        db.backup_to(&backup).await.unwrap();
+
+        fs::write(journal_path(&backup), b"EMBJRNL1 torn").unwrap();
+        EmbedDb::verify_backup(&backup).await.unwrap();
+        assert!(!journal_path(&backup).exists());
+
+        let reader = PageReader::open(&backup).unwrap();
+        let (page_size, pages) = (reader.page_size, reader.pages);
+        let mut journal = Journal::create(&journal_path(&backup), page_size, pages).unwrap();
+        let mut page = vec![0u8; page_size];
+        let mut file = File::open(&backup).unwrap();
+        file.seek(SeekFrom::Start(page_size as u64)).unwrap();
+        file.read_exact(&mut page).unwrap();
+        journal.put(&1_u32.to_le_bytes()).unwrap();
+        journal.put(&page).unwrap();
+        journal.seal().unwrap();
+
+        let report = EmbedDb::verify_backup(&backup).await.unwrap();
+        assert_eq!(report.pages, u64::from(pages));
+        assert!(!journal_path(&backup).exists());
+    }
+
+    #[tokio::test]
+    async fn backup_refuses_to_overwrite_the_live_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path(), 1).await;
+        let err = db.backup_to(db.path().to_path_buf()).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Backup(_)));
+
+        let name = db.path().file_name().unwrap();
+        let dotted = dir.path().join(".").join(name);
+        let err = db.backup_to(dotted).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Backup(_)));
+
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link.db");
+            std::os::unix::fs::symlink(db.path(), &link).unwrap();
+            let err = db.backup_to(&link).await.unwrap_err();
+            assert!(matches!(err, EmbedError::Backup(_)));
+        }
+        assert_eq!(db.query_scalar_i64("SELECT count(*) FROM t", ()).await.unwrap(), 1);
+    }
+}
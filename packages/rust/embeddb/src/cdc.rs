@@ -0,0 +1,566 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures_util::Stream;
+use serde_json::Value;
+
+use crate::{EmbedDb, EmbedError, Result};
+
+pub const CDC_LOG_TABLE: &str = "_embeddb_cdc_log";
+
+const CREATE_LOG: &str = "CREATE TABLE IF NOT EXISTS _embeddb_cdc_log (\
+ seq INTEGER PRIMARY KEY,\
+ table_name TEXT NOT NULL,\
+ op TEXT NOT NULL,\
+ row_id INTEGER,\
+ old_row TEXT,\
+ new_row TEXT,\
+ changed_at INTEGER NOT NULL)";
+
+const CREATE_LOG_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_cdc_log_table \
+ON _embeddb_cdc_log (table_name, seq)";
+
+const CREATE_TABLES: &str = "CREATE TABLE IF NOT EXISTS _embeddb_cdc_tables (\
+ table_name TEXT PRIMARY KEY,\
+ columns TEXT NOT NULL,\
+ enabled_at INTEGER NOT NULL)";
+
+const CREATE_CURSORS: &str = "CREATE TABLE IF NOT EXISTS _embeddb_cdc_cursors (\
+ name TEXT PRIMARY KEY,\
+ seq INTEGER NOT NULL,\
+ updated_at INTEGER NOT NULL)";
+
+const SAVE_CURSOR_SQL: &str = "INSERT INTO _embeddb_cdc_cursors (name, seq, updated_at) \
+VALUES (?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT(name) DO UPDATE SET seq = MAX(seq, excluded.seq), updated_at = excluded.updated_at";
+
+const SELECT_COLUMNS: &str = "seq, table_name, op, row_id, old_row, new_row, changed_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChangeOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeOp::Insert => "insert",
+            ChangeOp::Update => "update",
+            ChangeOp::Delete => "delete",
+        }
+    }
+
+    fn parse(s: &str) -> Result<ChangeOp> {
+        match s {
+            "insert" => Ok(ChangeOp::Insert),
+            "update" => Ok(ChangeOp::Update),
+            "delete" => Ok(ChangeOp::Delete),
+            other => Err(EmbedError::Cdc(format!("unknown change op '{other}'"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub seq: i64,
+    pub table: String,
+    pub op: ChangeOp,
+    pub row_id: Option<i64>,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    pub changed_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CdcOptions {
+    pub cursor: Option<String>,
+    pub after: i64,
+    pub tables: Vec<String>,
+    pub batch_size: usize,
+    pub poll_interval: Duration,
+}
+
+impl Default for CdcOptions {
+    fn default() -> Self {
+        CdcOptions {
+            cursor: None,
+            after: 0,
+            tables: Vec::new(),
+            batch_size: 256,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl CdcOptions {
+    pub fn cursor(name: impl Into<String>) -> CdcOptions {
+        CdcOptions { cursor: Some(name.into()), ..Default::default() }
+    }
+
+    pub fn after(mut self, seq: i64) -> Self {
+        self.after = seq;
+        self
+    }
+
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.tables.push(table.into());
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+fn check_ident(name: &str) -> Result<()> {
+    let ok = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("_embeddb_");
+    if ok {
+        Ok(())
+    } else {
+        Err(EmbedError::Cdc(format!("'{name}' is not a capturable table name")))
+    }
+}
+
+fn row_json(prefix: &str, columns: &[String]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|c| {
+            format!(
+                "'{c}', CASE WHEN typeof({prefix}.\"{c}\") = 'blob' \
+THEN hex({prefix}.\"{c}\") ELSE {prefix}.\"{c}\" END"
+            )
+        })
+        .collect();
+    format!("json_object({})", fields.join(", "))
+}
+
+fn trigger_sql(table: &str, op: ChangeOp, columns: &[String]) -> String {
+    let (event, row_id, old, new) = match op {
+        ChangeOp::Insert => ("INSERT", "NEW.rowid", "NULL".to_string(), row_json("NEW", columns)),
+        ChangeOp::Update => (
+            "UPDATE",
+            "NEW.rowid",
+            row_json("OLD", columns),
+            row_json("NEW", columns),
+        ),
+        ChangeOp::Delete => ("DELETE", "OLD.rowid", row_json("OLD", columns), "NULL".to_string()),
+    };
+    format!(
+        "CREATE TRIGGER \"{name}\" AFTER {event} ON \"{table}\" BEGIN \
+INSERT INTO _embeddb_cdc_log (table_name, op, row_id, old_row, new_row, changed_at) \
+VALUES ('{table}', '{op}', {row_id}, {old}, {new}, CAST(strftime('%s','now') AS INTEGER)); END",
+        name = trigger_name(table, op),
+        op = op.as_str(),
+    )
+}
+
+fn trigger_name(table: &str, op: ChangeOp) -> String {
+    format!("_embeddb_cdc_{table}_{}", op.as_str())
+}
+
+fn json_column(v: turso::Value) -> Result<Option<Value>> {
+    match v {
+        turso::Value::Null => Ok(None),
+        turso::Value::Text(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| EmbedError::Cdc(format!("captured row is not JSON: {e}"))),
+        other => Err(EmbedError::Cdc(format!("captured row is not text: {:?}", other))),
+    }
+}
+
+fn event_from_row(row: &turso::Row) -> Result<ChangeEvent> {
+    let int = |i: usize| -> Result<Option<i64>> {
+        match row.get_value(i)? {
+            turso::Value::Integer(n) => Ok(Some(n)),
+            _ => Ok(None),
+        }
+    };
+    let text = |i: usize| -> Result<String> {
+        match row.get_value(i)? {
+            turso::Value::Text(s) => Ok(s),
+            other => Err(EmbedError::Cdc(format!("expected text column, got {:?}", other))),
+        }
+    };
+    Ok(ChangeEvent {
+        seq: int(0)?.unwrap_or_default(),
+        table: text(1)?,
+        op: ChangeOp::parse(&text(2)?)?,
+        row_id: int(3)?,
+        old: json_column(row.get_value(4)?)?,
+        new: json_column(row.get_value(5)?)?,
+        changed_at: int(6)?.unwrap_or_default(),
+    })
+}
+
+async fn fetch(
+    conn: &turso::Connection,
+    after: i64,
+    tables: &[String],
+    limit: usize,
+) -> Result<Vec<ChangeEvent>> {
+    let mut sql = format!("SELECT {SELECT_COLUMNS} FROM _embeddb_cdc_log WHERE seq > ?");
+    let mut params = vec![turso::Value::Integer(after)];
+    if !tables.is_empty() {
+        let placeholders = vec!["?"; tables.len()].join(", ");
+        sql.push_str(&format!(" AND table_name IN ({placeholders})"));
+        params.extend(tables.iter().map(|t| turso::Value::Text(t.clone())));
+    }
+    sql.push_str(" ORDER BY seq LIMIT ?");
+    params.push(turso::Value::Integer(limit as i64));
+    let mut rows = conn.query(&sql, params).await?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().await? {
+        out.push(event_from_row(&row)?);
+    }
+    Ok(out)
+}
+
+async fn save_cursor(conn: &turso::Connection, name: &str, seq: i64) -> Result<()> {
+    conn.execute(SAVE_CURSOR_SQL, (name, seq)).await?;
+    Ok(())
+}
+
+struct CdcState<'a> {
+    db: &'a EmbedDb,
+    conn: Option<turso::Connection>,
+    changes: tokio::sync::watch::Receiver<u64>,
+    options: CdcOptions,
+    buffer: VecDeque<ChangeEvent>,
+    position: i64,
+    acked: i64,
+    done: bool,
+}
+
+impl CdcState<'_> {
+    async fn next_event(&mut self) -> Result<ChangeEvent> {
+        if self.conn.is_none() {
+            self.db.cdc_init().await?;
+            let conn = self.db.database().connect()?;
+            if let Some(name) = &self.options.cursor {
+                let mut rows = conn
+                    .query("SELECT seq FROM _embeddb_cdc_cursors WHERE name = ?", (name.as_str(),))
+                    .await?;
+                if let Some(row) = rows.next().await? {
+                    if let turso::Value::Integer(seq) = row.get_value(0)? {
+                        self.position = self.position.max(seq);
+                    }
+                }
+            }
+            self.acked = self.position;
+            self.conn = Some(conn);
+        }
+        loop {
+            if let Some(event) = self.buffer.pop_front() {
+                self.position = event.seq;
+                return Ok(event);
+            }
+            let conn = self.conn.as_ref().expect("connection opened above");
+            if let Some(name) = &self.options.cursor {
+                if self.position > self.acked {
+                    save_cursor(self.db.conn(), name, self.position).await?;
+                    self.acked = self.position;
+                }
+            }
+            self.changes.borrow_and_update();
+            let batch =
+                fetch(conn, self.position, &self.options.tables, self.options.batch_size).await?;
+            if batch.is_empty() {
+                let wait = self.changes.changed();
+                if let Ok(Err(_)) = tokio::time::timeout(self.options.poll_interval, wait).await {
+                    tokio::time::sleep(self.options.poll_interval).await;
+                }
+                continue;
+            }
+            self.buffer.extend(batch);
+        }
+    }
+}
+
+impl EmbedDb {
+    pub(crate) async fn cdc_init(&self) -> Result<()> {
+        self.execute(CREATE_LOG, ()).await?;
+        self.execute(CREATE_LOG_INDEX, ()).await?;
+        self.execute(CREATE_TABLES, ()).await?;
+        self.execute(CREATE_CURSORS, ()).await?;
+        Ok(())
+    }
+
+    pub async fn cdc_enable(&self, table: &str) -> Result<Vec<String>> {
+        check_ident(table)?;
+        self.cdc_init().await?;
+        let info = self.query(&format!("PRAGMA table_info(\"{table}\")"), ()).await?;
+        let columns: Vec<String> =
+            info.rows.iter().filter_map(|r| r.as_str(1).map(str::to_string)).collect();
+        if columns.is_empty() {
+            return Err(EmbedError::Cdc(format!("table '{table}' does not exist")));
+        }
+        if let Some(bad) = columns.iter().find(|c| check_ident(c).is_err()) {
+            return Err(EmbedError::Cdc(format!("column '{bad}' of '{table}' cannot be captured")));
+        }
+        let tx = self.begin().await?;
+        for op in [ChangeOp::Insert, ChangeOp::Update, ChangeOp::Delete] {
+            tx.execute(&format!("DROP TRIGGER IF EXISTS \"{}\"", trigger_name(table, op)), ())
+                .await?;
+            tx.execute(&trigger_sql(table, op, &columns), ()).await?;
+        }
+        tx.execute(
+            "INSERT INTO _embeddb_cdc_tables (table_name, columns, enabled_at) \
+VALUES (?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT(table_name) DO UPDATE SET columns = excluded.columns",
+            (table, columns.join(",")),
+        )
+            .await?;
+        tx.commit().await?;
+        Ok(columns)
+    }
+
+    pub async fn cdc_disable(&self, table: &str) -> Result<bool> {
+        check_ident(table)?;
+        self.cdc_init().await?;
+        let tx = self.begin().await?;
+        for op in [ChangeOp::Insert, ChangeOp::Update, ChangeOp::Delete] {
+            tx.execute(&format!("DROP TRIGGER IF EXISTS \"{}\"", trigger_name(table, op)), ())
+                .await?;
+        }
+        let removed = tx
+            .execute("DELETE FROM _embeddb_cdc_tables WHERE table_name = ?", (table,))
+            .await?;
+        tx.commit().await?;
+        Ok(removed > 0)
+    }
+
+    pub async fn cdc_tables(&self) -> Result<Vec<String>> {
+        self.cdc_init().await?;
+        let rows = self
+            .query_rows("SELECT table_name FROM _embeddb_cdc_tables ORDER BY table_name", ())
+            .await?;
+        Ok(rows.iter().filter_map(|r| r.as_str(0).map(str::to_string)).collect())
+    }
+
+    pub async fn cdc_changes(&self, after: i64, limit: usize) -> Result<Vec<ChangeEvent>> {
+        self.cdc_init().await?;
+        let conn = self.database().connect()?;
+        fetch(&conn, after, &[], limit).await
+    }
+
+    pub async fn cdc_cursor(&self, name: &str) -> Result<i64> {
+        self.cdc_init().await?;
+        let row = self
+            .query_one("SELECT seq FROM _embeddb_cdc_cursors WHERE name = ?", (name,))
+            .await?;
+        Ok(row.and_then(|r| r.as_i64(0)).unwrap_or(0))
+    }
+
+    pub async fn cdc_ack(&self, name: &str, seq: i64) -> Result<()> {
+        self.cdc_init().await?;
+        save_cursor(self.conn(), name, seq).await
+    }
+
+    pub async fn cdc_prune(&self) -> Result<u64> {
+        self.cdc_init().await?;
+        let floor = self
+            .query_one("SELECT MIN(seq) FROM _embeddb_cdc_cursors", ())
+            .await?
+            .and_then(|r| r.as_i64(0));
+        let Some(floor) = floor else {
+            return Ok(0);
+        };
+        self.execute(
+            "DELETE FROM _embeddb_cdc_log WHERE seq <= ? \
+AND seq < (SELECT MAX(seq) FROM _embeddb_cdc_log)",
+            (floor,),
+        )
+            .await
+    }
+
+    pub fn cdc_stream(&self, options: CdcOptions) -> impl Stream<Item = Result<ChangeEvent>> + '_ {
+        let state = CdcState {
+            db: self,
+            conn: None,
+            changes: self.subscribe_changes(),
+            position: options.after,
+            options,
+            buffer: VecDeque::new(),
+            acked: 0,
+            done: false,
+        };
+        futures_util::stream::unfold(state, |mut state| async move {
+            if state.done {
+                return None;
+            }
+            let item = state.next_event().await;
+            state.done = item.is_err();
+            Some((item, state))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.execute("CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, body BLOB)", ())
+            .await
+            .unwrap();
+        (dir, db)
+    }
+
+    async fn next(stream: &mut (impl Stream<Item = Result<ChangeEvent>> + Unpin)) -> ChangeEvent {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("stream stalled")
+            .expect("stream ended")
+            .unwrap()
+    }
+
+    #[test]
+    fn identifiers_are_validated() {
+        assert!(check_ident("docs").is_ok());
+        assert!(check_ident("doc_2").is_ok());
+        assert!(check_ident("").is_err());
+        assert!(check_ident("2docs").is_err());
+        assert!(check_ident("docs; DROP TABLE x").is_err());
+        assert!(check_ident("_embeddb_vectors").is_err());
+    }
+
+    #[test]
+    fn trigger_sql_captures_old_and_new_rows() {
+        let cols = vec!["id".to_string(), "title".to_string()];
+        let sql = trigger_sql("docs", ChangeOp::Update, &cols);
+        let head = "CREATE TRIGGER \"_embeddb_cdc_docs_update\" AFTER UPDATE ON \"docs\"";
+        assert!(sql.starts_with(head));
+        assert!(sql.contains("json_object('id', CASE WHEN typeof(OLD.\"id\")"));
+        assert!(sql.contains("'update', NEW.rowid"));
+    }
+
+    #[tokio::test]
+    async fn captures_insert_update_delete_with_row_images() {
+        let (_d, db) = open("cdc_ops.db").await;
+        assert_eq!(db.cdc_enable("docs").await.unwrap(), vec!["id", "title", "body"]);
+        db.execute("INSERT INTO docs VALUES (1, 'a', X'00FF')", ()).await.unwrap();
+        db.execute("UPDATE docs SET title = 'b' WHERE id = 1", ()).await.unwrap();
+        db.execute("DELETE FROM docs WHERE id = 1", ()).await.unwrap();
+
+        let events = db.cdc_changes(0, 10).await.unwrap();
+        let ops: Vec<ChangeOp> = events.iter().map(|e| e.op).collect();
+        assert_eq!(ops, vec![ChangeOp::Insert, ChangeOp::Update, ChangeOp::Delete]);
+        assert_eq!(events[0].new, Some(json!({"id": 1, "title": "a", "body": "00FF"})));
+        assert_eq!(events[0].old, None);
+        assert_eq!(events[1].old.as_ref().unwrap()["title"], "a");
+        assert_eq!(events[1].new.as_ref().unwrap()["title"], "b");
+        assert_eq!(events[2].new, None);
+        assert!(events.iter().all(|e| e.row_id == Some(1) && e.table == "docs"));
+        assert!(events.windows(2).all(|w| w[0].seq < w[1].seq));
+    }
+
+    #[tokio::test]
+    async fn only_enabled_tables_are_captured() {
+        let (_d, db) = open("cdc_opt_in.db").await;
+        db.execute("CREATE TABLE other (id INTEGER)", ()).await.unwrap();
+        db.cdc_enable("docs").await.unwrap();
+        db.execute("INSERT INTO other VALUES (1)", ()).await.unwrap();
+        db.execute("INSERT INTO docs (id, title) VALUES (1, 'a')", ()).await.unwrap();
+        assert_eq!(db.cdc_tables().await.unwrap(), vec!["docs"]);
+        assert_eq!(db.cdc_changes(0, 10).await.unwrap().len(), 1);
+
+        assert!(db.cdc_disable("docs").await.unwrap());
+        db.execute("INSERT INTO docs (id, title) VALUES (2, 'b')", ()).await.unwrap();
+        assert_eq!(db.cdc_changes(0, 10).await.unwrap().len(), 1);
+        assert!(db.cdc_tables().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rolled_back_writes_emit_nothing() {
+        let (_d, db) = open("cdc_rollback.db").await;
+        db.cdc_enable("docs").await.unwrap();
+        let tx = db.begin().await.unwrap();
+        tx.execute("INSERT INTO docs (id, title) VALUES (1, 'a')", ()).await.unwrap();
+        tx.rollback().await.unwrap();
+        assert!(db.cdc_changes(0, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn stream_wakes_on_commit() {
+        let (_d, db) = open("cdc_stream.db").await;
+        db.cdc_enable("docs").await.unwrap();
+        let mut stream = Box::pin(db.cdc_stream(CdcOptions::default().with_table("docs")));
+
+        let writer = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let tx = db.begin().await.unwrap();
+            tx.execute("INSERT INTO docs (id, title) VALUES (1, 'a')", ()).await.unwrap();
+            tx.execute("INSERT INTO docs (id, title) VALUES (2, 'b')", ()).await.unwrap();
+            tx.commit().await.unwrap();
+        };
+        let reader = async { (next(&mut stream).await, next(&mut stream).await) };
+        let ((first, second), ()) = tokio::join!(reader, writer);
+        assert_eq!(first.row_id, Some(1));
+        assert_eq!(second.row_id, Some(2));
+    }
+
+    #[tokio::test]
+    async fn named_cursor_resumes_after_acknowledged_events() {
+        let (_d, db) = open("cdc_resume.db").await;
+        db.cdc_enable("docs").await.unwrap();
+        for i in 1..=3_i64 {
+            db.execute("INSERT INTO docs (id, title) VALUES (?, 'x')", (i,)).await.unwrap();
+        }
+
+        {
+            let mut stream =
+                Box::pin(db.cdc_stream(CdcOptions::cursor("indexer").with_batch_size(2)));
+            assert_eq!(next(&mut stream).await.row_id, Some(1));
+            assert_eq!(next(&mut stream).await.row_id, Some(2));
+            assert_eq!(next(&mut stream).await.row_id, Some(3));
+        }
+        let saved = db.cdc_cursor("indexer").await.unwrap();
+        assert!(saved >= 2, "cursor advanced past the first batch");
+
+        let last = db.cdc_changes(0, 10).await.unwrap().last().unwrap().seq;
+        db.cdc_ack("indexer", last).await.unwrap();
+        db.cdc_ack("indexer", 1).await.unwrap();
+        assert_eq!(db.cdc_cursor("indexer").await.unwrap(), last, "cursors never move back");
+
+        db.execute("INSERT INTO docs (id, title) VALUES (4, 'x')", ()).await.unwrap();
+        let mut stream = Box::pin(db.cdc_stream(CdcOptions::cursor("indexer")));
+        assert_eq!(next(&mut stream).await.row_id, Some(4));
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_events_no_cursor_has_seen() {
+        let (_d, db) = open("cdc_prune.db").await;
+        db.cdc_enable("docs").await.unwrap();
+        for i in 1..=4_i64 {
+            db.execute("INSERT INTO docs (id, title) VALUES (?, 'x')", (i,)).await.unwrap();
+        }
+        assert_eq!(db.cdc_prune().await.unwrap(), 0);
+        let events = db.cdc_changes(0, 10).await.unwrap();
+        db.cdc_ack("a", events[1].seq).await.unwrap();
+        db.cdc_ack("b", events[3].seq).await.unwrap();
+        assert_eq!(db.cdc_prune().await.unwrap(), 2);
+        let left: Vec<i64> = db.cdc_changes(0, 10).await.unwrap().iter().map(|e| e.seq).collect();
+        assert_eq!(left, vec![events[2].seq, events[3].seq]);
+    }
+
+    #[tokio::test]
+    async fn enable_rejects_unknown_tables() {
+        let (_d, db) = open("cdc_missing.db").await;
+        assert!(matches!(db.cdc_enable("nope").await.unwrap_err(), EmbedError::Cdc(_)));
+    }
+}
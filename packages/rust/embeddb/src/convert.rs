@@ -60,6 +60,7 @@ impl FromEmbedValue for i128 {
         match v {
             Some(EmbedValue::HugeInt(n)) => Ok(*n),
             Some(EmbedValue::Int(n)) => Ok(*n as i128),
+            Some(text @ EmbedValue::Text(s)) => s.parse().map_err(|_| mismatch("i128", text)),
             Some(other) => Err(mismatch("i128", other)),
             None => Err(absent("i128")),
         }
@@ -85,6 +86,76 @@ impl<T: FromEmbedValue> FromEmbedValue for Option<T> {
     }
 }
 
+pub trait ToEmbedValue {
+    const SQL_TYPE: &'static str;
+    const NULLABLE: bool = false;
+
+    fn to_embed_value(&self) -> EmbedValue;
+}
+
+impl ToEmbedValue for i64 {
+    const SQL_TYPE: &'static str = "INTEGER";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Int(*self)
+    }
+}
+
+impl ToEmbedValue for f64 {
+    const SQL_TYPE: &'static str = "REAL";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Float(*self)
+    }
+}
+
+impl ToEmbedValue for String {
+    const SQL_TYPE: &'static str = "TEXT";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Text(self.clone())
+    }
+}
+
+impl ToEmbedValue for bool {
+    const SQL_TYPE: &'static str = "BOOLEAN";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Bool(*self)
+    }
+}
+
+// SQLite integers stop at i64 and an INTEGER column would round wider
+// values to REAL, so i128 is stored as decimal text. Range filters on
+// such a column compare as text.
+impl ToEmbedValue for i128 {
+    const SQL_TYPE: &'static str = "TEXT";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Text(self.to_string())
+    }
+}
+
+impl ToEmbedValue for Vec<u8> {
+    const SQL_TYPE: &'static str = "BLOB";
+
+    fn to_embed_value(&self) -> EmbedValue {
+        EmbedValue::Blob(self.clone())
+    }
+}
+
+impl<T: ToEmbedValue> ToEmbedValue for Option<T> {
+    const SQL_TYPE: &'static str = T::SQL_TYPE;
+    const NULLABLE: bool = true;
+
+    fn to_embed_value(&self) -> EmbedValue {
+        match self {
+            Some(v) => v.to_embed_value(),
+            None => EmbedValue::Null,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,10 +199,19 @@ mod tests {
     fn i128_accepts_hugeint_and_int() {
         assert_eq!(i128::from_embed_value(Some(&EmbedValue::HugeInt(9))).unwrap(), 9);
         assert_eq!(i128::from_embed_value(Some(&EmbedValue::Int(9))).unwrap(), 9);
-        assert!(i128::from_embed_value(Some(&EmbedValue::Text("9".into()))).is_err());
+        assert_eq!(i128::from_embed_value(Some(&EmbedValue::Text("-9".into()))).unwrap(), -9);
+        assert!(i128::from_embed_value(Some(&EmbedValue::Text("nine".into()))).is_err());
+        assert!(i128::from_embed_value(Some(&EmbedValue::Float(9.0))).is_err());
         assert!(i128::from_embed_value(None).is_err());
     }
 
+    #[test]
+    fn i128_writes_decimal_text() {
+        assert_eq!(<i128 as ToEmbedValue>::SQL_TYPE, "TEXT");
+        assert_eq!(i128::MIN.to_embed_value(), EmbedValue::Text(i128::MIN.to_string()));
+        assert_eq!(i128::from_embed_value(Some(&(-3_i128).to_embed_value())).unwrap(), -3);
+    }
+
     #[test]
     fn blob_converts_and_rejects_other_types() {
         let bytes = vec![1_u8, 2, 3];
@@ -175,4 +255,14 @@ mod tests {
         assert!(absent.contains("String"));
         assert!(absent.contains("absent"));
     }
+
+    #[test]
+    fn to_embed_value_round_trips_through_from() {
+        assert_eq!(i64::from_embed_value(Some(&5_i64.to_embed_value())).unwrap(), 5);
+        assert_eq!(Some(2.5_f64).to_embed_value(), EmbedValue::Float(2.5));
+        assert_eq!(None::<String>.to_embed_value(), EmbedValue::Null);
+        assert_eq!(<Option<String> as ToEmbedValue>::SQL_TYPE, "TEXT");
+        const { assert!(<Option<String> as ToEmbedValue>::NULLABLE) };
+        const { assert!(!<Vec<u8> as ToEmbedValue>::NULLABLE) };
+    }
 }
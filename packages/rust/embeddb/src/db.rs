@@ -5,10 +5,17 @@ use crate::Result;
 
 pub struct EmbedDb {
     path: PathBuf,
+    database: turso::Database,
     conn: turso::Connection,
     config: crate::EmbedConfig,
     #[cfg(feature = "analytics")]
     reader: Arc<crate::pool::LazyReaderPool>,
+    #[cfg(feature = "vector")]
+    ann: crate::ann::AnnCache,
+    #[cfg(feature = "vector")]
+    quant: crate::quant::QuantCache,
+    #[cfg(feature = "cdc")]
+    changes: tokio::sync::watch::Sender<u64>,
 }
 
 impl std::fmt::Debug for EmbedDb {
@@ -44,10 +51,17 @@ impl EmbedDb {
         ));
         Ok(EmbedDb {
             path,
+            database: db,
             conn,
             config,
             #[cfg(feature = "analytics")]
             reader,
+            #[cfg(feature = "vector")]
+            ann: crate::ann::AnnCache::default(),
+            #[cfg(feature = "vector")]
+            quant: crate::quant::QuantCache::default(),
+            #[cfg(feature = "cdc")]
+            changes: tokio::sync::watch::channel(0).0,
         })
     }
 
@@ -59,19 +73,43 @@ impl EmbedDb {
         &self.conn
     }
 
+    pub(crate) fn database(&self) -> &turso::Database {
+        &self.database
+    }
+
+    pub(crate) fn config(&self) -> &crate::EmbedConfig {
+        &self.config
+    }
+
+    pub(crate) fn notify_commit(&self) {
+        #[cfg(feature = "cdc")]
+        self.changes.send_modify(|n| *n = n.wrapping_add(1));
+    }
+
+    #[cfg(feature = "cdc")]
+    pub(crate) fn subscribe_changes(&self) -> tokio::sync::watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    #[cfg(feature = "vector")]
+    pub(crate) fn ann_cache(&self) -> &crate::ann::AnnCache {
+        &self.ann
+    }
+
+    #[cfg(feature = "vector")]
+    pub(crate) fn quant_cache(&self) -> &crate::quant::QuantCache {
+        &self.quant
+    }
+
     pub async fn execute(&self, sql: &str, params: impl turso::IntoParams) -> Result<u64> {
         let affected = self.conn.execute(sql, params).await?;
+        self.notify_commit();
         Ok(affected)
     }
 
     pub async fn checkpoint(&self) -> Result<()> {
         for _ in 0..=self.config.checkpoint_max_retries {
-            let mut rows = self.conn.query("PRAGMA wal_checkpoint(TRUNCATE)", ()).await?;
-            let mut busy = 0_i64;
-            if let Some(row) = rows.next().await? {
-                busy = row.get::<i64>(0).unwrap_or(0);
-            }
-            while rows.next().await?.is_some() {}
+            let (busy, _, _) = self.wal_checkpoint("TRUNCATE").await?;
             if busy == 0 {
                 return Ok(());
             }
@@ -80,6 +118,21 @@ impl EmbedDb {
         Err(crate::EmbedError::CheckpointBusy)
     }
 
+    // The `(busy, log, checkpointed)` row of `PRAGMA wal_checkpoint(mode)`.
+    pub(crate) async fn wal_checkpoint(&self, mode: &str) -> Result<(i64, i64, i64)> {
+        let mut rows = self.conn.query(&format!("PRAGMA wal_checkpoint({mode})"), ()).await?;
+        let mut out = (0_i64, 0_i64, 0_i64);
+        if let Some(row) = rows.next().await? {
+            out = (
+                row.get::<i64>(0).unwrap_or(0),
+                row.get::<i64>(1).unwrap_or(0),
+                row.get::<i64>(2).unwrap_or(0),
+            );
+        }
+        while rows.next().await?.is_some() {}
+        Ok(out)
+    }
+
     pub async fn checkpoint_passive(&self) -> Result<()> {
         let mut rows = self.conn.query("PRAGMA wal_checkpoint(PASSIVE)", ()).await?;
         while rows.next().await?.is_some() {}
@@ -89,6 +142,23 @@ impl EmbedDb {
 
 #[cfg(feature = "analytics")]
 impl EmbedDb {
+    #[cfg(feature = "transfer")]
+    pub(crate) async fn on_reader<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&duckdb::Connection, &Path) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let path = self.path.clone();
+        let reader = self.reader.clone();
+        tokio::task::spawn_blocking(move || {
+            let pool = reader.get()?;
+            let guard = pool.checkout();
+            f(&guard, &path)
+        })
+            .await
+            .map_err(|e| crate::EmbedError::Other(e.to_string()))?
+    }
+
     pub async fn analytics_scalar_i64(&self, sql: &str) -> Result<i64> {
         let path = self.path.clone();
         let sql = sql.to_string();
@@ -201,7 +271,7 @@ impl EmbedDb {
 
     pub async fn begin(&self) -> Result<crate::EmbedTx<'_>> {
         let tx = self.conn.unchecked_transaction().await?;
-        Ok(crate::EmbedTx::new(tx))
+        Ok(crate::EmbedTx::new(tx, self))
     }
 
     pub async fn close(self) -> Result<()> {
@@ -209,6 +279,7 @@ impl EmbedDb {
         Ok(())
     }
 
+    #[cfg(test)]
     pub(crate) async fn max_migration_version(&self) -> Result<i64> {
         let mut rows = self
             .conn
@@ -225,6 +296,25 @@ impl EmbedDb {
     pub async fn migrate(&self, migrations: &[&str]) -> Result<()> {
         crate::migrate::run(self, migrations).await
     }
+
+    pub async fn migrate_named(&self, migrations: &[crate::Migration]) -> Result<()> {
+        crate::migrate::run_named(self, migrations).await
+    }
+
+    pub async fn migration_status(
+        &self,
+        migrations: &[crate::Migration],
+    ) -> Result<crate::MigrationReport> {
+        crate::migrate::status(self, migrations).await
+    }
+
+    pub async fn rollback_to(
+        &self,
+        migrations: &[crate::Migration],
+        version: i64,
+    ) -> Result<Vec<i64>> {
+        crate::migrate::rollback_to(self, migrations, version).await
+    }
 }
 
 #[cfg(all(test, feature = "analytics"))]
@@ -914,6 +1004,7 @@ mod tests {
     #[tokio::test]
     async fn derive_missing_column_errors() {
         #[derive(Debug, crate::FromEmbedRow)]
+        #[allow(dead_code)]
         struct Rec { id: i64, missing: i64 }
         let dir = tempfile::tempdir().unwrap();
         let db = EmbedDb::open(dir.path().join("derive_err.db")).await.unwrap();
@@ -1,4 +1,6 @@
-use crate::{EmbedDb, EmbedError, Result, VectorFilter, VectorHit, VectorSpace};
+use crate::{
+    EmbedDb, EmbedError, HybridConfig, HybridHit, Result, VectorFilter, VectorHit, VectorSpace,
+};
 use std::future::Future;
 use std::pin::Pin;
 
@@ -79,6 +81,33 @@ impl EmbedDb {
         check_batch(embedder, &inputs, &vectors)?;
         self.vector_search(&embedder.space(), &vectors[0], k, filter).await
     }
+
+    pub async fn hybrid_upsert_text(
+        &self,
+        embedder: &dyn Embedder,
+        index: &str,
+        ref_kind: &str,
+        ref_id: &str,
+        text: &str,
+    ) -> Result<()> {
+        self.vector_upsert_text(embedder, ref_kind, ref_id, text).await?;
+        self.fts_upsert(index, ref_kind, ref_id, text).await
+    }
+
+    pub async fn hybrid_search_text(
+        &self,
+        embedder: &dyn Embedder,
+        index: &str,
+        query: &str,
+        k: usize,
+        filter: Option<&VectorFilter>,
+        config: &HybridConfig,
+    ) -> Result<Vec<HybridHit>> {
+        let inputs = vec![query.to_string()];
+        let vectors = embedder.embed(&inputs).await?;
+        check_batch(embedder, &inputs, &vectors)?;
+        self.hybrid_search(&embedder.space(), index, query, &vectors[0], k, filter, config).await
+    }
 }
 
 #[cfg(test)]
@@ -243,6 +272,23 @@ mod tests {
         assert!(matches!(err, EmbedError::VectorDim { expected: 4, actual: 3 }), "{err}");
     }
 
+    #[tokio::test]
+    async fn hybrid_text_round_trip_indexes_both_paths() {
+        let (_d, db) = open("embed_hybrid.db").await;
+        let e = HashEmbedder::new("m1", 16);
+        db.hybrid_upsert_text(&e, "mem", "message", "a", "deploy the cluster").await.unwrap();
+        db.hybrid_upsert_text(&e, "mem", "message", "b", "lunch at noon").await.unwrap();
+        assert_eq!(db.fts_count("mem").await.unwrap(), 2);
+
+        let hits = db
+            .hybrid_search_text(&e, "mem", "deploy the cluster", 1, None, &HybridConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(hits[0].ref_id, "a");
+        assert_eq!(hits[0].lexical_rank, Some(1));
+        assert_eq!(hits[0].vector_rank, Some(1));
+    }
+
     #[tokio::test]
     async fn embedder_is_usable_behind_dyn() {
         let (_d, db) = open("embed_dyn.db").await;
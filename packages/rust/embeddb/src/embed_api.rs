@@ -80,15 +80,23 @@ impl ApiEmbedder {
         let res = req
             .send()
             .await
-            .map_err(|e| EmbedError::Embedder(format!("embedding request failed: {e}")))?;
+            .map_err(|e| {
+                EmbedError::EmbedderUnavailable(format!("embedding request failed: {e}"))
+            })?;
 
         let status = res.status();
         if !status.is_success() {
             let body = res.text().await.unwrap_or_default();
             let body = body.chars().take(500).collect::<String>();
-            return Err(EmbedError::Embedder(format!(
-                "embedding endpoint returned {status}: {body}"
-            )));
+            let message = format!("embedding endpoint returned {status}: {body}");
+            let transient = status.is_server_error()
+                || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::REQUEST_TIMEOUT;
+            return Err(if transient {
+                EmbedError::EmbedderUnavailable(message)
+            } else {
+                EmbedError::Embedder(message)
+            });
         }
 
         let parsed: EmbeddingResponse = res
@@ -240,6 +248,11 @@ mod tests {
         let msg = err.to_string();
         assert!(msg.contains("429"), "{msg}");
         assert!(msg.contains("rate limited"), "{msg}");
+        assert!(matches!(err, EmbedError::EmbedderUnavailable(_)), "{err}");
+
+        let server = stub("400 Bad Request", r#"{"error":"input too long"}"#).await;
+        let err = embedder(&server, 2).embed(&["a".to_string()]).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Embedder(_)), "{err}");
     }
 
     #[tokio::test]
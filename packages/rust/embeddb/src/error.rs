@@ -23,6 +23,35 @@ pub enum EmbedError {
     #[cfg(feature = "vector")]
     #[error("embedder error: {0}")]
     Embedder(String),
+    /// The provider was unreachable, rate limited or failing; retrying the
+    /// same input later may succeed.
+    #[cfg(feature = "vector")]
+    #[error("embedder unavailable: {0}")]
+    EmbedderUnavailable(String),
+    #[cfg(feature = "vector")]
+    #[error("vector metadata error: {0}")]
+    VectorMeta(String),
+    #[cfg(feature = "vector")]
+    #[error("ann index error: {0}")]
+    AnnIndex(String),
+    #[error("migration drift at version {version}: {reason}")]
+    MigrationDrift { version: i64, reason: String },
+    #[error("migration error: {0}")]
+    Migration(String),
+    /// Batches are committed one at a time; `committed` rows from earlier
+    /// batches stay in the table.
+    #[cfg(feature = "transfer")]
+    #[error("import failed after {committed} committed rows: {source}")]
+    Import {
+        committed: u64,
+        #[source]
+        source: Box<EmbedError>,
+    },
+    #[error("backup error: {0}")]
+    Backup(String),
+    #[cfg(feature = "cdc")]
+    #[error("cdc error: {0}")]
+    Cdc(String),
     #[error("{0}")]
     Other(String),
 }
@@ -0,0 +1,314 @@
+use serde_json::Value;
+
+use crate::{EmbedError, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaPredicate {
+    Eq(String, Value),
+    Ne(String, Value),
+    Gt(String, Value),
+    Gte(String, Value),
+    Lt(String, Value),
+    Lte(String, Value),
+    In(String, Vec<Value>),
+    Exists(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorFilter {
+    pub ref_kind: Option<String>,
+    pub meta: Vec<MetaPredicate>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+    pub with_payload: bool,
+}
+
+impl VectorFilter {
+    pub fn kind(ref_kind: impl Into<String>) -> VectorFilter {
+        VectorFilter { ref_kind: Some(ref_kind.into()), ..Default::default() }
+    }
+
+    pub fn with_kind(mut self, ref_kind: impl Into<String>) -> Self {
+        self.ref_kind = Some(ref_kind.into());
+        self
+    }
+
+    pub fn meta_eq(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Eq(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_ne(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Ne(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_gt(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Gt(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_gte(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Gte(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_lt(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Lt(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_lte(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.meta.push(MetaPredicate::Lte(key.into(), value.into()));
+        self
+    }
+
+    pub fn meta_range(
+        self,
+        key: impl Into<String>,
+        min: impl Into<Value>,
+        max: impl Into<Value>,
+    ) -> Self {
+        let key = key.into();
+        self.meta_gte(key.clone(), min).meta_lte(key, max)
+    }
+
+    pub fn meta_in<V: Into<Value>>(
+        mut self,
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.meta.push(MetaPredicate::In(key.into(), values));
+        self
+    }
+
+    pub fn meta_exists(mut self, key: impl Into<String>) -> Self {
+        self.meta.push(MetaPredicate::Exists(key.into()));
+        self
+    }
+
+    pub fn created_after(mut self, unix_secs: i64) -> Self {
+        self.created_after = Some(unix_secs);
+        self
+    }
+
+    pub fn created_before(mut self, unix_secs: i64) -> Self {
+        self.created_before = Some(unix_secs);
+        self
+    }
+
+    pub fn created_between(self, from_secs: i64, to_secs: i64) -> Self {
+        self.created_after(from_secs).created_before(to_secs)
+    }
+
+    pub fn with_payload(mut self) -> Self {
+        self.with_payload = true;
+        self
+    }
+
+    pub(crate) fn has_row_predicates(&self) -> bool {
+        !self.meta.is_empty() || self.created_after.is_some() || self.created_before.is_some()
+    }
+}
+
+fn json_path(key: &str) -> String {
+    if key.starts_with('$') {
+        key.to_string()
+    } else {
+        format!("$.{key}")
+    }
+}
+
+fn scalar(key: &str, value: &Value) -> Result<turso::Value> {
+    match value {
+        Value::Bool(b) => Ok(turso::Value::Integer(i64::from(*b))),
+        Value::String(s) => Ok(turso::Value::Text(s.clone())),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(turso::Value::Integer(i)),
+            None => n.as_f64().map(turso::Value::Real).ok_or_else(|| {
+                EmbedError::VectorMeta(format!("'{key}': number {n} is not representable"))
+            }),
+        },
+        Value::Null => Err(EmbedError::VectorMeta(format!(
+            "'{key}': null is only valid in equality predicates"
+        ))),
+        Value::Array(_) | Value::Object(_) => Err(EmbedError::VectorMeta(format!(
+            "'{key}': predicates only compare scalar values"
+        ))),
+    }
+}
+
+pub(crate) fn clauses(
+    filter: Option<&VectorFilter>,
+    prefix: &str,
+) -> Result<(String, Vec<turso::Value>)> {
+    let mut sql = String::new();
+    let mut params = Vec::new();
+    let Some(filter) = filter else {
+        return Ok((sql, params));
+    };
+    if let Some(kind) = &filter.ref_kind {
+        sql.push_str(&format!(" AND {prefix}ref_kind = ?"));
+        params.push(turso::Value::Text(kind.clone()));
+    }
+    if let Some(after) = filter.created_after {
+        sql.push_str(&format!(" AND {prefix}created_at >= ?"));
+        params.push(turso::Value::Integer(after));
+    }
+    if let Some(before) = filter.created_before {
+        sql.push_str(&format!(" AND {prefix}created_at < ?"));
+        params.push(turso::Value::Integer(before));
+    }
+    let extract = format!("json_extract({prefix}meta, ?)");
+    for predicate in &filter.meta {
+        let (key, op, value) = match predicate {
+            MetaPredicate::Eq(key, Value::Null) => {
+                sql.push_str(&format!(" AND {extract} IS NULL"));
+                params.push(turso::Value::Text(json_path(key)));
+                continue;
+            }
+            MetaPredicate::Ne(key, Value::Null) | MetaPredicate::Exists(key) => {
+                sql.push_str(&format!(" AND {extract} IS NOT NULL"));
+                params.push(turso::Value::Text(json_path(key)));
+                continue;
+            }
+            MetaPredicate::In(key, values) => {
+                if values.is_empty() {
+                    sql.push_str(" AND 0");
+                    continue;
+                }
+                let placeholders = vec!["?"; values.len()].join(", ");
+                sql.push_str(&format!(" AND {extract} IN ({placeholders})"));
+                params.push(turso::Value::Text(json_path(key)));
+                for v in values {
+                    params.push(scalar(key, v)?);
+                }
+                continue;
+            }
+            MetaPredicate::Eq(key, v) => (key, "=", v),
+            MetaPredicate::Ne(key, v) => (key, "!=", v),
+            MetaPredicate::Gt(key, v) => (key, ">", v),
+            MetaPredicate::Gte(key, v) => (key, ">=", v),
+            MetaPredicate::Lt(key, v) => (key, "<", v),
+            MetaPredicate::Lte(key, v) => (key, "<=", v),
+        };
+        sql.push_str(&format!(" AND {extract} {op} ?"));
+        params.push(turso::Value::Text(json_path(key)));
+        params.push(scalar(key, value)?);
+    }
+    Ok((sql, params))
+}
+
+pub(crate) fn meta_param(meta: Option<&Value>) -> Result<turso::Value> {
+    match meta {
+        None | Some(Value::Null) => Ok(turso::Value::Null),
+        Some(v @ Value::Object(_)) => serde_json::to_string(v)
+            .map(turso::Value::Text)
+            .map_err(|e| EmbedError::VectorMeta(e.to_string())),
+        Some(_) => Err(EmbedError::VectorMeta("metadata must be a JSON object".into())),
+    }
+}
+
+pub(crate) fn parse_meta(v: turso::Value) -> Result<Option<Value>> {
+    match v {
+        turso::Value::Null => Ok(None),
+        turso::Value::Text(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| EmbedError::VectorMeta(format!("stored metadata is not JSON: {e}"))),
+        other => Err(EmbedError::VectorMeta(format!("meta column is not text: {:?}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn no_filter_yields_no_clauses() {
+        let (sql, params) = clauses(None, "").unwrap();
+        assert!(sql.is_empty());
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn kind_and_time_window_bind_in_order() {
+        let f = VectorFilter::kind("doc").created_between(10, 20);
+        let (sql, params) = clauses(Some(&f), "v.").unwrap();
+        assert_eq!(sql, " AND v.ref_kind = ? AND v.created_at >= ? AND v.created_at < ?");
+        assert_eq!(
+            params,
+            vec![
+                turso::Value::Text("doc".into()),
+                turso::Value::Integer(10),
+                turso::Value::Integer(20),
+            ]
+        );
+    }
+
+    #[test]
+    fn meta_predicates_bind_path_and_value() {
+        let f = VectorFilter::default().meta_eq("lang", "en").meta_range("score", 1, 5.5);
+        let (sql, params) = clauses(Some(&f), "").unwrap();
+        assert_eq!(
+            sql,
+            " AND json_extract(meta, ?) = ? AND json_extract(meta, ?) >= ? \
+AND json_extract(meta, ?) <= ?"
+        );
+        assert_eq!(params[0], turso::Value::Text("$.lang".into()));
+        assert_eq!(params[1], turso::Value::Text("en".into()));
+        assert_eq!(params[3], turso::Value::Integer(1));
+        assert_eq!(params[5], turso::Value::Real(5.5));
+    }
+
+    #[test]
+    fn null_and_exists_use_is_checks() {
+        let f = VectorFilter::default().meta_eq("a", Value::Null).meta_exists("b");
+        let (sql, params) = clauses(Some(&f), "").unwrap();
+        assert_eq!(sql, " AND json_extract(meta, ?) IS NULL AND json_extract(meta, ?) IS NOT NULL");
+        assert_eq!(params.len(), 2);
+    }
+
+    #[test]
+    fn in_expands_placeholders_and_empty_in_matches_nothing() {
+        let f = VectorFilter::default().meta_in("tag", ["a", "b", "c"]);
+        let (sql, params) = clauses(Some(&f), "").unwrap();
+        assert_eq!(sql, " AND json_extract(meta, ?) IN (?, ?, ?)");
+        assert_eq!(params.len(), 4);
+
+        let f = VectorFilter::default().meta_in("tag", Vec::<Value>::new());
+        assert_eq!(clauses(Some(&f), "").unwrap().0, " AND 0");
+    }
+
+    #[test]
+    fn bools_bind_as_integers_and_structured_values_are_rejected() {
+        let f = VectorFilter::default().meta_eq("pinned", true);
+        assert_eq!(clauses(Some(&f), "").unwrap().1[1], turso::Value::Integer(1));
+        let f = VectorFilter::default().meta_eq("obj", json!({"x": 1}));
+        assert!(matches!(clauses(Some(&f), "").unwrap_err(), EmbedError::VectorMeta(_)));
+        let f = VectorFilter::default().meta_gt("n", Value::Null);
+        assert!(clauses(Some(&f), "").is_err());
+    }
+
+    #[test]
+    fn explicit_json_paths_pass_through() {
+        assert_eq!(json_path("$.a[0]"), "$.a[0]");
+        assert_eq!(json_path("a.b"), "$.a.b");
+    }
+
+    #[test]
+    fn metadata_must_be_an_object() {
+        assert_eq!(meta_param(None).unwrap(), turso::Value::Null);
+        assert!(matches!(meta_param(Some(&json!({"a": 1}))).unwrap(), turso::Value::Text(_)));
+        assert!(meta_param(Some(&json!([1, 2]))).is_err());
+    }
+
+    #[test]
+    fn parse_meta_round_trips() {
+        assert_eq!(parse_meta(turso::Value::Null).unwrap(), None);
+        let v = parse_meta(turso::Value::Text("{\"a\":1}".into())).unwrap();
+        assert_eq!(v, Some(json!({"a": 1})));
+        assert!(parse_meta(turso::Value::Text("nope".into())).is_err());
+    }
+}
@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+
+use crate::{EmbedDb, EmbedValue, Result, VectorFilter, VectorSpace};
+
+pub const FTS_DOCS_TABLE: &str = "_embeddb_fts_docs";
+
+const CREATE_DOCS: &str = "CREATE TABLE IF NOT EXISTS _embeddb_fts_docs (\
+ index_name TEXT NOT NULL,\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ len INTEGER NOT NULL,\
+ updated_at INTEGER NOT NULL,\
+ PRIMARY KEY (index_name, ref_kind, ref_id))";
+
+const CREATE_TERMS: &str = "CREATE TABLE IF NOT EXISTS _embeddb_fts_terms (\
+ index_name TEXT NOT NULL,\
+ term TEXT NOT NULL,\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ tf INTEGER NOT NULL,\
+ PRIMARY KEY (index_name, term, ref_kind, ref_id))";
+
+const CREATE_TERMS_DOC_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_fts_terms_doc \
+ON _embeddb_fts_terms (index_name, ref_kind, ref_id)";
+
+const DOC_UPSERT_SQL: &str = "INSERT INTO _embeddb_fts_docs \
+(index_name, ref_kind, ref_id, len, updated_at) \
+VALUES (?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (index_name, ref_kind, ref_id) DO UPDATE SET \
+len = excluded.len, updated_at = excluded.updated_at";
+
+const TERM_INSERT_SQL: &str =
+    "INSERT INTO _embeddb_fts_terms (index_name, term, ref_kind, ref_id, tf) VALUES (?, ?, ?, ?, ?)";
+
+const TERM_DF_SQL: &str = "SELECT term, count(*) FROM _embeddb_fts_terms \
+WHERE index_name = ? AND term IN";
+
+const TERM_SCORES_SQL: &str = "FROM _embeddb_fts_terms t \
+JOIN _embeddb_fts_docs d ON d.index_name = t.index_name AND d.ref_kind = t.ref_kind \
+AND d.ref_id = t.ref_id WHERE t.index_name = ? AND t.term IN";
+
+const DELETE_TERMS_SQL: &str =
+    "DELETE FROM _embeddb_fts_terms WHERE index_name = ? AND ref_kind = ? AND ref_id = ?";
+
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+pub(crate) async fn init(db: &EmbedDb) -> Result<()> {
+    db.execute(CREATE_DOCS, ()).await?;
+    db.execute(CREATE_TERMS, ()).await?;
+    db.execute(CREATE_TERMS_DOC_INDEX, ()).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextHit {
+    pub ref_kind: String,
+    pub ref_id: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridHit {
+    pub ref_kind: String,
+    pub ref_id: String,
+    pub score: f32,
+    pub lexical_rank: Option<usize>,
+    pub vector_rank: Option<usize>,
+    pub payload: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridConfig {
+    pub rrf_k: f32,
+    pub candidates: usize,
+    pub lexical_weight: f32,
+    pub vector_weight: f32,
+}
+
+impl Default for HybridConfig {
+    fn default() -> Self {
+        HybridConfig { rrf_k: 60.0, candidates: 50, lexical_weight: 1.0, vector_weight: 1.0 }
+    }
+}
+
+impl HybridConfig {
+    pub fn with_candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    pub fn with_weights(mut self, lexical: f32, vector: f32) -> Self {
+        self.lexical_weight = lexical;
+        self.vector_weight = vector;
+        self
+    }
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+fn term_counts(text: &str) -> (HashMap<String, i64>, i64) {
+    let tokens = tokenize(text);
+    let len = tokens.len() as i64;
+    let mut counts = HashMap::new();
+    for t in tokens {
+        *counts.entry(t).or_insert(0_i64) += 1;
+    }
+    (counts, len)
+}
+
+fn idf(docs: f32, df: f32) -> f32 {
+    (1.0 + (docs - df + 0.5) / (df + 0.5)).ln()
+}
+
+pub(crate) fn rrf(
+    lexical: &[TextHit],
+    vector: &[crate::VectorHit],
+    k: usize,
+    config: &HybridConfig,
+) -> Vec<HybridHit> {
+    let mut fused: HashMap<(String, String), HybridHit> = HashMap::new();
+    for (i, hit) in lexical.iter().enumerate() {
+        let entry = fused
+            .entry((hit.ref_kind.clone(), hit.ref_id.clone()))
+            .or_insert_with(|| HybridHit {
+                ref_kind: hit.ref_kind.clone(),
+                ref_id: hit.ref_id.clone(),
+                score: 0.0,
+                lexical_rank: None,
+                vector_rank: None,
+                payload: None,
+            });
+        entry.lexical_rank = Some(i + 1);
+        entry.score += config.lexical_weight / (config.rrf_k + (i + 1) as f32);
+    }
+    for (i, hit) in vector.iter().enumerate() {
+        let entry = fused
+            .entry((hit.ref_kind.clone(), hit.ref_id.clone()))
+            .or_insert_with(|| HybridHit {
+                ref_kind: hit.ref_kind.clone(),
+                ref_id: hit.ref_id.clone(),
+                score: 0.0,
+                lexical_rank: None,
+                vector_rank: None,
+                payload: None,
+            });
+        entry.vector_rank = Some(i + 1);
+        entry.payload = hit.payload.clone();
+        entry.score += config.vector_weight / (config.rrf_k + (i + 1) as f32);
+    }
+    let mut out: Vec<HybridHit> = fused.into_values().collect();
+    out.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.ref_kind.cmp(&b.ref_kind))
+            .then_with(|| a.ref_id.cmp(&b.ref_id))
+    });
+    out.truncate(k);
+    out
+}
+
+impl EmbedDb {
+    pub async fn fts_upsert(
+        &self,
+        index: &str,
+        ref_kind: &str,
+        ref_id: &str,
+        text: &str,
+    ) -> Result<()> {
+        let tx = self.begin().await?;
+        tx.execute(DELETE_TERMS_SQL, (index, ref_kind, ref_id)).await?;
+        let (counts, len) = term_counts(text);
+        tx.execute(DOC_UPSERT_SQL, (index, ref_kind, ref_id, len)).await?;
+        for (term, tf) in counts {
+            tx.execute(TERM_INSERT_SQL, (index, term, ref_kind, ref_id, tf)).await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn fts_upsert_batch(
+        &self,
+        index: &str,
+        items: &[(String, String, String)],
+    ) -> Result<u64> {
+        let tx = self.begin().await?;
+        let mut total = 0_u64;
+        for (ref_kind, ref_id, text) in items {
+            tx.execute(DELETE_TERMS_SQL, (index, ref_kind.as_str(), ref_id.as_str())).await?;
+            let (counts, len) = term_counts(text);
+            total += tx
+                .execute(DOC_UPSERT_SQL, (index, ref_kind.as_str(), ref_id.as_str(), len))
+                .await?;
+            for (term, tf) in counts {
+                tx.execute(TERM_INSERT_SQL, (index, term, ref_kind.as_str(), ref_id.as_str(), tf))
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(total)
+    }
+
+    pub async fn fts_delete(&self, index: &str, ref_kind: &str, ref_id: &str) -> Result<u64> {
+        let tx = self.begin().await?;
+        tx.execute(DELETE_TERMS_SQL, (index, ref_kind, ref_id)).await?;
+        let n = tx
+            .execute(
+                "DELETE FROM _embeddb_fts_docs WHERE index_name = ? AND ref_kind = ? AND ref_id = ?",
+                (index, ref_kind, ref_id),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok(n)
+    }
+
+    pub async fn fts_count(&self, index: &str) -> Result<i64> {
+        self.query_scalar_i64(
+            "SELECT count(*) FROM _embeddb_fts_docs WHERE index_name = ?",
+            (index,),
+        )
+            .await
+    }
+
+    pub async fn fts_search(
+        &self,
+        index: &str,
+        query: &str,
+        k: usize,
+        filter: Option<&VectorFilter>,
+    ) -> Result<Vec<TextHit>> {
+        self.fts_rank(index, query, k, filter, None).await
+    }
+
+    // Scores and ranks in SQL so only the top `k` rows come back. Meta and
+    // created_at predicates live on the vector rows, so a document matches
+    // when one of its rows (in `model`, if given) passes them.
+    async fn fts_rank(
+        &self,
+        index: &str,
+        query: &str,
+        k: usize,
+        filter: Option<&VectorFilter>,
+        model: Option<&str>,
+    ) -> Result<Vec<TextHit>> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+        let stats = self
+            .query_one(
+                "SELECT count(*), COALESCE(avg(len), 0) FROM _embeddb_fts_docs WHERE index_name = ?",
+                (index,),
+            )
+            .await?;
+        let (docs, avg_len) = match stats {
+            Some(row) => (
+                row.as_i64(0).unwrap_or(0) as f32,
+                match row.get(1) {
+                    Some(EmbedValue::Float(f)) => *f as f32,
+                    Some(EmbedValue::Int(n)) => *n as f32,
+                    _ => 0.0,
+                },
+            ),
+            None => (0.0, 0.0),
+        };
+        if docs == 0.0 || avg_len <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; terms.len()].join(", ");
+        let term_params = || terms.iter().map(|t| turso::Value::Text(t.clone()));
+
+        // Document frequencies are index-wide, whatever the filter.
+        let mut params = vec![turso::Value::Text(index.to_string())];
+        params.extend(term_params());
+        let df_rows = self
+            .query_rows(&format!("{TERM_DF_SQL} ({placeholders}) GROUP BY term"), params)
+            .await?;
+        if df_rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut idf_sql = String::from("CASE t.term");
+        let mut params = Vec::with_capacity(df_rows.len() * 2 + terms.len() + 4);
+        for row in &df_rows {
+            let (Some(term), Some(df)) = (row.as_str(0), row.as_i64(1)) else {
+                continue;
+            };
+            idf_sql.push_str(" WHEN ? THEN ?");
+            params.push(turso::Value::Text(term.to_string()));
+            params.push(turso::Value::Real(idf(docs, df as f32) as f64));
+        }
+        idf_sql.push_str(" ELSE 0 END");
+        params.push(turso::Value::Real(avg_len as f64));
+        params.push(turso::Value::Text(index.to_string()));
+        params.extend(term_params());
+
+        let mut where_sql = String::new();
+        if let Some(kind) = filter.and_then(|f| f.ref_kind.as_deref()) {
+            where_sql.push_str(" AND t.ref_kind = ?");
+            params.push(turso::Value::Text(kind.to_string()));
+        }
+        if let Some(f) = filter.filter(|f| f.has_row_predicates()) {
+            let (row_sql, extra) = crate::filter::clauses(Some(f), "v.")?;
+            where_sql.push_str(
+                " AND EXISTS (SELECT 1 FROM _embeddb_vectors v \
+WHERE v.ref_kind = t.ref_kind AND v.ref_id = t.ref_id",
+            );
+            if let Some(model) = model {
+                where_sql.push_str(" AND v.model = ?");
+                params.push(turso::Value::Text(model.to_string()));
+            }
+            where_sql.push_str(&row_sql);
+            where_sql.push(')');
+            params.extend(extra);
+        }
+        params.push(turso::Value::Integer(k.min(i64::MAX as usize) as i64));
+
+        let sql = format!(
+            "SELECT t.ref_kind, t.ref_id, \
+sum(({idf_sql}) * (t.tf * {tf_scale}) \
+/ (t.tf + {BM25_K1} * ({len_base} + {BM25_B} * d.len / ?))) AS score \
+{TERM_SCORES_SQL} ({placeholders}){where_sql} \
+GROUP BY t.ref_kind, t.ref_id ORDER BY score DESC, t.ref_kind, t.ref_id LIMIT ?",
+            tf_scale = BM25_K1 + 1.0,
+            len_base = 1.0 - BM25_B,
+        );
+        let mut hits = Vec::new();
+        for row in self.query_rows(&sql, params).await? {
+            let (Some(ref_kind), Some(ref_id)) = (row.as_str(0), row.as_str(1)) else {
+                continue;
+            };
+            let score = match row.get(2) {
+                Some(EmbedValue::Float(f)) => *f as f32,
+                Some(EmbedValue::Int(n)) => *n as f32,
+                _ => 0.0,
+            };
+            hits.push(TextHit {
+                ref_kind: ref_kind.to_string(),
+                ref_id: ref_id.to_string(),
+                score,
+            });
+        }
+        Ok(hits)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn hybrid_search(
+        &self,
+        space: &VectorSpace,
+        index: &str,
+        text: &str,
+        query: &[f32],
+        k: usize,
+        filter: Option<&VectorFilter>,
+        config: &HybridConfig,
+    ) -> Result<Vec<HybridHit>> {
+        let candidates = config.candidates.max(k);
+        let lexical = self.fts_rank(index, text, candidates, filter, Some(&space.model)).await?;
+        let vector = self.vector_search(space, query, candidates, filter).await?;
+        let mut fused = rrf(&lexical, &vector, k, config);
+        if !filter.is_some_and(|f| f.with_payload) {
+            return Ok(fused);
+        }
+        // Vector hits already carry their payload; fetch the rest at once.
+        let (refs_sql, refs) = crate::vector::refs_clause(
+            fused
+                .iter()
+                .filter(|h| h.vector_rank.is_none())
+                .map(|h| (h.ref_kind.as_str(), h.ref_id.as_str())),
+            "",
+        );
+        if refs.is_empty() {
+            return Ok(fused);
+        }
+        let mut params = vec![turso::Value::Text(space.model.clone())];
+        params.extend(refs);
+        let sql =
+            format!("SELECT ref_kind, ref_id, meta FROM _embeddb_vectors WHERE model = ?{refs_sql}");
+        let mut payloads = HashMap::new();
+        for row in self.query_rows(&sql, params).await? {
+            let (Some(ref_kind), Some(ref_id)) = (row.as_str(0), row.as_str(1)) else {
+                continue;
+            };
+            let meta =
+                crate::read::value_to_turso(row.get(2).cloned().unwrap_or(EmbedValue::Null));
+            payloads.insert(
+                (ref_kind.to_string(), ref_id.to_string()),
+                crate::filter::parse_meta(meta)?,
+            );
+        }
+        for hit in fused.iter_mut().filter(|h| h.vector_rank.is_none()) {
+            if let Some(p) = payloads.remove(&(hit.ref_kind.clone(), hit.ref_id.clone())) {
+                hit.payload = p;
+            }
+        }
+        Ok(fused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VectorHit;
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.vector_init().await.unwrap();
+        (dir, db)
+    }
+
+    fn text_hit(id: &str) -> TextHit {
+        TextHit { ref_kind: "msg".into(), ref_id: id.into(), score: 1.0 }
+    }
+
+    fn vector_hit(id: &str) -> VectorHit {
+        VectorHit { ref_kind: "msg".into(), ref_id: id.into(), score: 1.0, payload: None }
+    }
+
+    #[test]
+    fn tokenize_lowercases_and_splits_on_punctuation() {
+        assert_eq!(
+            tokenize("Deploy the K8s-cluster, NOW!"),
+            vec!["deploy", "the", "k8s", "cluster", "now"]
+        );
+        assert!(tokenize("  ...  ").is_empty());
+    }
+
+    #[test]
+    fn idf_favours_rare_terms() {
+        assert!(idf(100.0, 1.0) > idf(100.0, 50.0));
+        assert!(idf(100.0, 100.0) > 0.0);
+    }
+
+    #[tokio::test]
+    async fn bm25_saturates_term_frequency() {
+        let (_d, db) = open("fts_saturate.db").await;
+        db.fts_upsert("mem", "msg", "one", "deploy a b c d e f g h i").await.unwrap();
+        db.fts_upsert("mem", "msg", "ten", &["deploy"; 10].join(" ")).await.unwrap();
+        db.fts_upsert("mem", "msg", "none", "lunch at noon").await.unwrap();
+        let hits = db.fts_search("mem", "deploy", 10, None).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        let (ten, one) = (hits[0].score, hits[1].score);
+        assert_eq!(hits[0].ref_id, "ten");
+        assert!(ten > one);
+        assert!(ten < one * 10.0);
+    }
+
+    #[test]
+    fn rrf_rewards_agreement_between_lists() {
+        let lexical = vec![text_hit("a"), text_hit("b")];
+        let vector = vec![vector_hit("b"), vector_hit("c")];
+        let fused = rrf(&lexical, &vector, 3, &HybridConfig::default());
+        assert_eq!(fused[0].ref_id, "b");
+        assert_eq!(fused[0].lexical_rank, Some(2));
+        assert_eq!(fused[0].vector_rank, Some(1));
+        assert_eq!(fused.len(), 3);
+    }
+
+    #[test]
+    fn rrf_weights_shift_the_winner() {
+        let lexical = vec![text_hit("a")];
+        let vector = vec![vector_hit("b")];
+        let cfg = HybridConfig::default().with_weights(0.1, 1.0);
+        assert_eq!(rrf(&lexical, &vector, 1, &cfg)[0].ref_id, "b");
+        let cfg = HybridConfig::default().with_weights(1.0, 0.1);
+        assert_eq!(rrf(&lexical, &vector, 1, &cfg)[0].ref_id, "a");
+    }
+
+    #[tokio::test]
+    async fn fts_ranks_by_bm25() {
+        let (_d, db) = open("fts_rank.db").await;
+        db.fts_upsert("mem", "msg", "a", "deploy the cluster tonight").await.unwrap();
+        db.fts_upsert("mem", "msg", "b", "lunch at noon").await.unwrap();
+        db.fts_upsert("mem", "msg", "c", "deploy deploy deploy").await.unwrap();
+        let hits = db.fts_search("mem", "deploy cluster", 10, None).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].ref_id, "a");
+        assert!(hits.iter().all(|h| h.ref_id != "b"));
+    }
+
+    #[tokio::test]
+    async fn fts_upsert_replaces_previous_terms() {
+        let (_d, db) = open("fts_replace.db").await;
+        db.fts_upsert("mem", "msg", "a", "old words").await.unwrap();
+        db.fts_upsert("mem", "msg", "a", "new words").await.unwrap();
+        assert!(db.fts_search("mem", "old", 10, None).await.unwrap().is_empty());
+        assert_eq!(db.fts_search("mem", "new", 10, None).await.unwrap().len(), 1);
+        assert_eq!(db.fts_count("mem").await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn fts_filter_and_delete() {
+        let (_d, db) = open("fts_filter.db").await;
+        let items = vec![
+            ("msg".to_string(), "a".to_string(), "shared term".to_string()),
+            ("doc".to_string(), "b".to_string(), "shared term".to_string()),
+        ];
+        assert_eq!(db.fts_upsert_batch("mem", &items).await.unwrap(), 2);
+        let docs = VectorFilter::kind("doc");
+        let hits = db.fts_search("mem", "shared", 10, Some(&docs)).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_kind, "doc");
+
+        assert_eq!(db.fts_delete("mem", "doc", "b").await.unwrap(), 1);
+        assert_eq!(db.fts_count("mem").await.unwrap(), 1);
+        assert!(db.fts_search("mem", "shared", 10, Some(&docs)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fts_applies_row_predicates_in_sql() {
+        let (_d, db) = open("fts_row_filter.db").await;
+        let s = VectorSpace::new("local", "m", 3);
+        let en = serde_json::json!({"lang": "en"});
+        db.vector_upsert_meta(&s, "msg", "en", &[1.0, 0.0, 0.0], Some(&en)).await.unwrap();
+        db.vector_upsert(&s, "msg", "bare", &[0.0, 1.0, 0.0]).await.unwrap();
+        for id in ["en", "bare", "unembedded"] {
+            db.fts_upsert("mem", "msg", id, "release notes").await.unwrap();
+        }
+
+        let english = VectorFilter::default().meta_eq("lang", "en");
+        let hits = db.fts_search("mem", "release", 10, Some(&english)).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "en");
+
+        let future = VectorFilter::default().created_after(i64::MAX - 1);
+        assert!(db.fts_search("mem", "release", 10, Some(&future)).await.unwrap().is_empty());
+        assert_eq!(db.fts_search("mem", "release", 10, None).await.unwrap().len(), 3);
+        assert_eq!(db.fts_search("mem", "release", 2, None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fts_indexes_are_isolated_by_name() {
+        let (_d, db) = open("fts_isolation.db").await;
+        db.fts_upsert("one", "msg", "a", "alpha").await.unwrap();
+        db.fts_upsert("two", "msg", "b", "alpha").await.unwrap();
+        let hits = db.fts_search("one", "alpha", 10, None).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "a");
+    }
+
+    #[tokio::test]
+    async fn empty_query_or_index_returns_empty() {
+        let (_d, db) = open("fts_empty.db").await;
+        assert!(db.fts_search("mem", "anything", 5, None).await.unwrap().is_empty());
+        db.fts_upsert("mem", "msg", "a", "words").await.unwrap();
+        assert!(db.fts_search("mem", "!!", 5, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn hybrid_fuses_lexical_and_vector_hits() {
+        let (_d, db) = open("fts_hybrid.db").await;
+        let s = VectorSpace::new("local", "m", 3);
+        db.vector_upsert(&s, "msg", "keyword", &[0.0, 1.0, 0.0]).await.unwrap();
+        db.vector_upsert(&s, "msg", "semantic", &[1.0, 0.0, 0.0]).await.unwrap();
+        db.vector_upsert(&s, "msg", "both", &[0.9, 0.1, 0.0]).await.unwrap();
+        db.fts_upsert("mem", "msg", "keyword", "error code E1234").await.unwrap();
+        db.fts_upsert("mem", "msg", "semantic", "the build broke").await.unwrap();
+        db.fts_upsert("mem", "msg", "both", "build failed with E1234").await.unwrap();
+
+        let hits = db
+            .hybrid_search(&s, "mem", "E1234", &[0.9, 0.1, 0.0], 3, None, &HybridConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].ref_id, "both");
+        assert!(hits[0].lexical_rank.is_some() && hits[0].vector_rank.is_some());
+    }
+
+    #[tokio::test]
+    async fn hybrid_applies_metadata_filter_to_lexical_hits() {
+        let (_d, db) = open("fts_hybrid_meta.db").await;
+        let s = VectorSpace::new("local", "m", 3);
+        let en = serde_json::json!({"lang": "en"});
+        let de = serde_json::json!({"lang": "de"});
+        db.vector_upsert_meta(&s, "msg", "en", &[1.0, 0.0, 0.0], Some(&en)).await.unwrap();
+        db.vector_upsert_meta(&s, "msg", "de", &[0.0, 1.0, 0.0], Some(&de)).await.unwrap();
+        db.fts_upsert("mem", "msg", "en", "release notes").await.unwrap();
+        db.fts_upsert("mem", "msg", "de", "release notes").await.unwrap();
+
+        let filter = VectorFilter::default().meta_eq("lang", "de").with_payload();
+        let cfg = HybridConfig::default();
+        let hits = db
+            .hybrid_search(&s, "mem", "release", &[1.0, 0.0, 0.0], 5, Some(&filter), &cfg)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "de");
+        assert_eq!(hits[0].payload, Some(de));
+    }
+}
@@ -0,0 +1,696 @@
+use std::future::Future;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::{EmbedDb, EmbedError, EmbedValue, Embedder, Result, VectorSpace};
+
+pub const EMBED_SOURCES_TABLE: &str = "_embeddb_embed_sources";
+
+const CREATE_SOURCES: &str = "CREATE TABLE IF NOT EXISTS _embeddb_embed_sources (\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ text TEXT NOT NULL,\
+ text_hash TEXT NOT NULL,\
+ updated_at INTEGER NOT NULL,\
+ PRIMARY KEY (ref_kind, ref_id))";
+
+const CREATE_STATE: &str = "CREATE TABLE IF NOT EXISTS _embeddb_embed_state (\
+ model TEXT NOT NULL,\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ provider TEXT NOT NULL,\
+ dim INTEGER NOT NULL,\
+ text_hash TEXT NOT NULL,\
+ status TEXT NOT NULL,\
+ attempts INTEGER NOT NULL,\
+ next_attempt_at INTEGER NOT NULL,\
+ last_error TEXT,\
+ updated_at INTEGER NOT NULL,\
+ PRIMARY KEY (model, ref_kind, ref_id))";
+
+const CREATE_STATE_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_embed_state_status \
+ON _embeddb_embed_state (model, status)";
+
+const SOURCE_UPSERT_SQL: &str = "INSERT INTO _embeddb_embed_sources \
+(ref_kind, ref_id, text, text_hash, updated_at) \
+VALUES (?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (ref_kind, ref_id) DO UPDATE SET text = excluded.text, \
+text_hash = excluded.text_hash, updated_at = excluded.updated_at \
+WHERE _embeddb_embed_sources.text_hash != excluded.text_hash";
+
+const STATE_UPSERT_SQL: &str = "INSERT INTO _embeddb_embed_state \
+(model, ref_kind, ref_id, provider, dim, text_hash, status, attempts, next_attempt_at, \
+last_error, updated_at) \
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER) + ?, ?, \
+CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (model, ref_kind, ref_id) DO UPDATE SET provider = excluded.provider, \
+dim = excluded.dim, text_hash = excluded.text_hash, status = excluded.status, \
+attempts = excluded.attempts, next_attempt_at = excluded.next_attempt_at, \
+last_error = excluded.last_error, updated_at = excluded.updated_at";
+
+// A source is due for a space when it has never been embedded for the model, its text
+// changed, the space's provider or dim changed, or a retry's backoff has elapsed.
+const PENDING_SQL: &str = "SELECT s.ref_kind, s.ref_id, s.text, s.text_hash, \
+CASE WHEN e.status = 'retry' AND e.text_hash = s.text_hash AND e.provider = ? AND e.dim = ? \
+THEN e.attempts ELSE 0 END \
+FROM _embeddb_embed_sources s LEFT JOIN _embeddb_embed_state e \
+ON e.model = ? AND e.ref_kind = s.ref_kind AND e.ref_id = s.ref_id \
+WHERE e.model IS NULL OR e.text_hash != s.text_hash OR e.provider != ? OR e.dim != ? \
+OR (e.status = 'retry' AND e.next_attempt_at <= CAST(strftime('%s','now') AS INTEGER)) \
+ORDER BY s.updated_at, s.ref_kind, s.ref_id LIMIT ?";
+
+const CURRENT_SQL: &str = "SELECT e.status, count(*) FROM _embeddb_embed_state e \
+JOIN _embeddb_embed_sources s ON s.ref_kind = e.ref_kind AND s.ref_id = e.ref_id \
+WHERE e.model = ? AND e.provider = ? AND e.dim = ? AND e.text_hash = s.text_hash \
+GROUP BY e.status";
+
+pub(crate) async fn init(db: &EmbedDb) -> Result<()> {
+    db.execute(CREATE_SOURCES, ()).await?;
+    db.execute(CREATE_STATE, ()).await?;
+    db.execute(CREATE_STATE_INDEX, ()).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedJobOptions {
+    pub batch_size: usize,
+    pub max_attempts: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for EmbedJobOptions {
+    fn default() -> Self {
+        EmbedJobOptions {
+            batch_size: 32,
+            max_attempts: 5,
+            base_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(300),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl EmbedJobOptions {
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_backoff = base;
+        self.max_backoff = max.max(base);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub(crate) fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 1_u32.checked_shl(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbedRunReport {
+    pub batches: u64,
+    pub embedded: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedProgress {
+    pub model: String,
+    pub sources: i64,
+    pub embedded: i64,
+    pub retrying: i64,
+    pub failed: i64,
+    pub pending: i64,
+}
+
+impl EmbedProgress {
+    pub fn is_complete(&self) -> bool {
+        self.pending == 0 && self.failed == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedFailure {
+    pub ref_kind: String,
+    pub ref_id: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+}
+
+struct Job {
+    ref_kind: String,
+    ref_id: String,
+    text: String,
+    text_hash: String,
+    attempts: u32,
+}
+
+// Failures that say nothing about the texts themselves: the provider was
+// unavailable or the vector write hit the database.
+fn is_transient(err: &EmbedError) -> bool {
+    matches!(err, EmbedError::EmbedderUnavailable(_) | EmbedError::Turso(_) | EmbedError::Io(_))
+}
+
+fn int(value: Option<&EmbedValue>) -> i64 {
+    match value {
+        Some(EmbedValue::Int(n)) => *n,
+        _ => 0,
+    }
+}
+
+fn state_row(
+    space: &VectorSpace,
+    job: &Job,
+    status: &str,
+    attempts: u32,
+    delay_secs: i64,
+    error: turso::Value,
+) -> Vec<turso::Value> {
+    vec![
+        turso::Value::Text(space.model.clone()),
+        turso::Value::Text(job.ref_kind.clone()),
+        turso::Value::Text(job.ref_id.clone()),
+        turso::Value::Text(space.provider.clone()),
+        turso::Value::Integer(space.dim as i64),
+        turso::Value::Text(job.text_hash.clone()),
+        turso::Value::Text(status.to_string()),
+        turso::Value::Integer(i64::from(attempts)),
+        turso::Value::Integer(delay_secs),
+        error,
+    ]
+}
+
+// FNV-1a over the exact bytes. Unlike the migration checksum it keeps
+// surrounding whitespace, so a text that only gained a trailing newline
+// still counts as changed.
+fn text_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+impl EmbedDb {
+    pub async fn embed_enqueue(&self, ref_kind: &str, ref_id: &str, text: &str) -> Result<()> {
+        self.execute(SOURCE_UPSERT_SQL, (ref_kind, ref_id, text, text_hash(text))).await?;
+        Ok(())
+    }
+
+    pub async fn embed_enqueue_batch(&self, items: &[(String, String, String)]) -> Result<u64> {
+        let rows: Vec<(&str, &str, &str, String)> = items
+            .iter()
+            .map(|(kind, id, text)| (kind.as_str(), id.as_str(), text.as_str(), text_hash(text)))
+            .collect();
+        self.execute_batch(SOURCE_UPSERT_SQL, rows).await
+    }
+
+    pub async fn embed_remove(&self, ref_kind: &str, ref_id: &str) -> Result<u64> {
+        // One tx, so a crash can't leave vectors behind for a ref whose
+        // state rows (the only record of its models) are already gone.
+        let tx = self.begin().await?;
+        let mut models = Vec::new();
+        tx.query_for_each(
+            "SELECT model FROM _embeddb_embed_state WHERE ref_kind = ? AND ref_id = ?",
+            (ref_kind, ref_id),
+            |row| models.extend(row.as_str(0).map(str::to_string)),
+        )
+            .await?;
+        for model in &models {
+            crate::vector::delete_ref(&tx, model, ref_kind, ref_id).await?;
+        }
+        tx.execute(
+            "DELETE FROM _embeddb_embed_state WHERE ref_kind = ? AND ref_id = ?",
+            (ref_kind, ref_id),
+        )
+            .await?;
+        let n = tx
+            .execute(
+                "DELETE FROM _embeddb_embed_sources WHERE ref_kind = ? AND ref_id = ?",
+                (ref_kind, ref_id),
+            )
+            .await?;
+        tx.commit().await?;
+        Ok(n)
+    }
+
+    pub async fn embed_retire(&self, model: &str) -> Result<u64> {
+        let tx = self.begin().await?;
+        tx.execute("DELETE FROM _embeddb_embed_state WHERE model = ?", (model,)).await?;
+        let n = crate::vector::delete_model(&tx, model).await?;
+        tx.commit().await?;
+        self.evict_model(model);
+        Ok(n)
+    }
+
+    pub async fn embed_retry_failed(&self, model: &str) -> Result<u64> {
+        self.execute(
+            "UPDATE _embeddb_embed_state SET status = 'retry', attempts = 0, next_attempt_at = 0 \
+WHERE model = ? AND status = 'failed'",
+            (model,),
+        )
+            .await
+    }
+
+    async fn embed_pending(&self, space: &VectorSpace, limit: usize) -> Result<Vec<Job>> {
+        let dim = space.dim as i64;
+        let provider = space.provider.as_str();
+        let rows = self
+            .query_rows(
+                PENDING_SQL,
+                (provider, dim, space.model.as_str(), provider, dim, limit as i64),
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Job {
+                ref_kind: row.as_str(0).unwrap_or_default().to_string(),
+                ref_id: row.as_str(1).unwrap_or_default().to_string(),
+                text: row.as_str(2).unwrap_or_default().to_string(),
+                text_hash: row.as_str(3).unwrap_or_default().to_string(),
+                attempts: int(row.get(4)).max(0) as u32,
+            })
+            .collect())
+    }
+
+    pub async fn embed_run_once(
+        &self,
+        embedder: &dyn Embedder,
+        options: &EmbedJobOptions,
+    ) -> Result<EmbedRunReport> {
+        let space = embedder.space();
+        let jobs = self.embed_pending(&space, options.batch_size.max(1)).await?;
+        let mut report = EmbedRunReport::default();
+        if jobs.is_empty() {
+            return Ok(report);
+        }
+        report.batches = 1;
+        // A batch rejected for its input is split in half until the sources
+        // that fail are isolated, so one bad text doesn't hold back the rest.
+        // Transient failures retry the whole range instead: splitting would
+        // only repeat the failing call for every half.
+        let mut rows = Vec::with_capacity(jobs.len());
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        ranges.push(0..jobs.len());
+        while let Some(range) = ranges.pop() {
+            let items: Vec<(String, String, String)> = jobs[range.clone()]
+                .iter()
+                .map(|j| (j.ref_kind.clone(), j.ref_id.clone(), j.text.clone()))
+                .collect();
+            match self.vector_upsert_texts(embedder, &items).await {
+                Ok(_) => {
+                    rows.extend(
+                        jobs[range.clone()]
+                            .iter()
+                            .map(|j| state_row(&space, j, "done", 0, 0, turso::Value::Null)),
+                    );
+                    report.embedded += range.len() as u64;
+                }
+                Err(err) if range.len() > 1 && !is_transient(&err) => {
+                    let mid = range.start + range.len() / 2;
+                    ranges.push(mid..range.end);
+                    ranges.push(range.start..mid);
+                }
+                Err(err) => {
+                    let error = err.to_string();
+                    for j in &jobs[range.clone()] {
+                        let attempts = j.attempts.saturating_add(1);
+                        let status =
+                            if attempts >= options.max_attempts { "failed" } else { "retry" };
+                        let delay = options.backoff(attempts).as_secs_f64().ceil() as i64;
+                        let error = turso::Value::Text(error.clone());
+                        rows.push(state_row(&space, j, status, attempts, delay, error));
+                    }
+                    report.failed += range.len() as u64;
+                }
+            }
+        }
+        self.execute_batch(STATE_UPSERT_SQL, rows).await?;
+        Ok(report)
+    }
+
+    pub async fn embed_drain(
+        &self,
+        embedder: &dyn Embedder,
+        options: &EmbedJobOptions,
+    ) -> Result<EmbedRunReport> {
+        let mut total = EmbedRunReport::default();
+        loop {
+            let report = self.embed_run_once(embedder, options).await?;
+            if report.batches == 0 {
+                return Ok(total);
+            }
+            total.batches += report.batches;
+            total.embedded += report.embedded;
+            total.failed += report.failed;
+        }
+    }
+
+    /// Embeds due sources until `shutdown` resolves. A batch already in
+    /// flight is finished and recorded before the worker returns. Database
+    /// errors are logged and retried after the job backoff rather than
+    /// stopping the worker.
+    pub async fn embed_worker(
+        &self,
+        embedder: &dyn Embedder,
+        options: &EmbedJobOptions,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        tokio::pin!(shutdown);
+        let mut errors = 0_u32;
+        loop {
+            tokio::select! {
+                biased;
+                _ = &mut shutdown => return Ok(()),
+                _ = std::future::ready(()) => {}
+            }
+            let delay = match self.embed_run_once(embedder, options).await {
+                Ok(report) => {
+                    errors = 0;
+                    if report.embedded > 0 {
+                        continue;
+                    }
+                    options.poll_interval
+                }
+                Err(err) => {
+                    errors = errors.saturating_add(1);
+                    let delay = options.backoff(errors);
+                    tracing::warn!(
+                        model = embedder.model(),
+                        error = %err,
+                        retry_in = ?delay,
+                        "embed worker run failed"
+                    );
+                    delay
+                }
+            };
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    pub async fn embed_progress(&self, space: &VectorSpace) -> Result<EmbedProgress> {
+        let sources =
+            self.query_scalar_i64("SELECT count(*) FROM _embeddb_embed_sources", ()).await?;
+        let rows = self
+            .query_rows(
+                CURRENT_SQL,
+                (space.model.as_str(), space.provider.as_str(), space.dim as i64),
+            )
+            .await?;
+        let mut progress = EmbedProgress {
+            model: space.model.clone(),
+            sources,
+            embedded: 0,
+            retrying: 0,
+            failed: 0,
+            pending: 0,
+        };
+        for row in &rows {
+            let n = int(row.get(1));
+            match row.as_str(0) {
+                Some("done") => progress.embedded = n,
+                Some("retry") => progress.retrying = n,
+                Some("failed") => progress.failed = n,
+                _ => {}
+            }
+        }
+        progress.pending = (sources - progress.embedded - progress.failed).max(0);
+        Ok(progress)
+    }
+
+    pub async fn embed_failures(&self, model: &str, limit: usize) -> Result<Vec<EmbedFailure>> {
+        let rows = self
+            .query_rows(
+                "SELECT ref_kind, ref_id, attempts, last_error FROM _embeddb_embed_state \
+WHERE model = ? AND status != 'done' ORDER BY updated_at DESC, ref_kind, ref_id LIMIT ?",
+                (model, limit as i64),
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| EmbedFailure {
+                ref_kind: row.as_str(0).unwrap_or_default().to_string(),
+                ref_id: row.as_str(1).unwrap_or_default().to_string(),
+                attempts: int(row.get(2)),
+                last_error: row.as_str(3).map(str::to_string),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embed::testing::HashEmbedder;
+    use crate::{BoxFuture, EmbedError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.vector_init().await.unwrap();
+        (dir, db)
+    }
+
+    struct FlakyEmbedder {
+        inner: HashEmbedder,
+        failures: AtomicUsize,
+    }
+
+    impl Embedder for FlakyEmbedder {
+        fn provider(&self) -> &str {
+            self.inner.provider()
+        }
+        fn model(&self) -> &str {
+            self.inner.model()
+        }
+        fn dim(&self) -> usize {
+            self.inner.dim()
+        }
+        fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Box::pin(async {
+                    Err(EmbedError::EmbedderUnavailable("rate limited".into()))
+                });
+            }
+            self.inner.embed(texts)
+        }
+    }
+
+    fn no_wait() -> EmbedJobOptions {
+        EmbedJobOptions::default().with_backoff(Duration::ZERO, Duration::ZERO)
+    }
+
+    async fn seed(db: &EmbedDb, n: usize) {
+        let items: Vec<(String, String, String)> =
+            (0..n).map(|i| ("doc".into(), i.to_string(), format!("text number {i}"))).collect();
+        db.embed_enqueue_batch(&items).await.unwrap();
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let opts = EmbedJobOptions::default()
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(opts.backoff(1), Duration::from_secs(1));
+        assert_eq!(opts.backoff(3), Duration::from_secs(4));
+        assert_eq!(opts.backoff(5), Duration::from_secs(10));
+        assert_eq!(opts.backoff(64), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn drain_embeds_every_source_in_batches() {
+        let (_d, db) = open("jobs_drain.db").await;
+        seed(&db, 10).await;
+        let e = HashEmbedder::new("m1", 8);
+        let report = db.embed_drain(&e, &no_wait().with_batch_size(4)).await.unwrap();
+        assert_eq!(report, EmbedRunReport { batches: 3, embedded: 10, failed: 0 });
+        assert_eq!(e.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(db.vector_count("m1").await.unwrap(), 10);
+
+        let progress = db.embed_progress(&e.space()).await.unwrap();
+        assert_eq!(progress.embedded, 10);
+        assert!(progress.is_complete());
+        assert_eq!(db.embed_drain(&e, &no_wait()).await.unwrap().batches, 0);
+    }
+
+    #[tokio::test]
+    async fn changed_text_is_re_embedded_and_unchanged_text_is_not() {
+        let (_d, db) = open("jobs_text.db").await;
+        seed(&db, 3).await;
+        let e = HashEmbedder::new("m1", 8);
+        db.embed_drain(&e, &no_wait()).await.unwrap();
+
+        db.embed_enqueue("doc", "0", "text number 0").await.unwrap();
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().pending, 0);
+        db.embed_enqueue("doc", "1", "rewritten").await.unwrap();
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().pending, 1);
+        assert_eq!(db.embed_drain(&e, &no_wait()).await.unwrap().embedded, 1);
+
+        db.embed_enqueue("doc", "2", "text number 2\n").await.unwrap();
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().pending, 1);
+    }
+
+    #[tokio::test]
+    async fn model_or_dim_change_rebuilds_vectors() {
+        let (_d, db) = open("jobs_model.db").await;
+        seed(&db, 5).await;
+        db.embed_drain(&HashEmbedder::new("m1", 8), &no_wait()).await.unwrap();
+
+        let next = HashEmbedder::new("m2", 8);
+        assert_eq!(db.embed_progress(&next.space()).await.unwrap().pending, 5);
+        assert_eq!(db.embed_drain(&next, &no_wait()).await.unwrap().embedded, 5);
+        assert_eq!(db.vector_count("m2").await.unwrap(), 5);
+        db.embed_retire("m1").await.unwrap();
+        assert_eq!(db.vector_count("m1").await.unwrap(), 0);
+
+        let wider = HashEmbedder::new("m2", 16);
+        assert_eq!(db.embed_drain(&wider, &no_wait()).await.unwrap().embedded, 5);
+        let hits = db.vector_search_text(&wider, "text number 3", 1, None).await.unwrap();
+        assert_eq!(hits[0].ref_id, "3");
+    }
+
+    #[tokio::test]
+    async fn failures_retry_then_give_up() {
+        let (_d, db) = open("jobs_retry.db").await;
+        seed(&db, 2).await;
+        let e = FlakyEmbedder { inner: HashEmbedder::new("m1", 8), failures: AtomicUsize::new(1) };
+        let opts = no_wait().with_max_attempts(2);
+        let report = db.embed_drain(&e, &opts).await.unwrap();
+        assert_eq!(report, EmbedRunReport { batches: 2, embedded: 2, failed: 2 });
+
+        e.failures.store(2, Ordering::SeqCst);
+        db.embed_enqueue("doc", "0", "changed").await.unwrap();
+        db.embed_drain(&e, &opts).await.unwrap();
+        let progress = db.embed_progress(&e.space()).await.unwrap();
+        assert_eq!((progress.embedded, progress.failed, progress.pending), (1, 1, 0));
+        let failures = db.embed_failures("m1", 10).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempts, 2);
+        assert!(failures[0].last_error.as_deref().unwrap().contains("rate limited"));
+
+        assert_eq!(db.embed_retry_failed("m1").await.unwrap(), 1);
+        assert_eq!(db.embed_drain(&e, &opts).await.unwrap().embedded, 1);
+        assert!(db.embed_progress(&e.space()).await.unwrap().is_complete());
+    }
+
+    struct PoisonEmbedder {
+        inner: HashEmbedder,
+    }
+
+    impl Embedder for PoisonEmbedder {
+        fn provider(&self) -> &str {
+            self.inner.provider()
+        }
+        fn model(&self) -> &str {
+            self.inner.model()
+        }
+        fn dim(&self) -> usize {
+            self.inner.dim()
+        }
+        fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>> {
+            if texts.iter().any(|t| t.contains("poison")) {
+                return Box::pin(async { Err(EmbedError::Embedder("rejected input".into())) });
+            }
+            self.inner.embed(texts)
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_source_is_isolated_from_its_batch() {
+        let (_d, db) = open("jobs_bisect.db").await;
+        seed(&db, 7).await;
+        db.embed_enqueue("doc", "bad", "poison pill").await.unwrap();
+        let e = PoisonEmbedder { inner: HashEmbedder::new("m1", 8) };
+        let opts = no_wait().with_batch_size(8).with_max_attempts(1);
+        let report = db.embed_run_once(&e, &opts).await.unwrap();
+        assert_eq!(report, EmbedRunReport { batches: 1, embedded: 7, failed: 1 });
+        assert_eq!(db.vector_count("m1").await.unwrap(), 7);
+        let failures = db.embed_failures("m1", 10).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].ref_id, "bad");
+        assert_eq!(db.embed_drain(&e, &opts).await.unwrap().batches, 0);
+    }
+
+    #[tokio::test]
+    async fn transient_failure_retries_the_batch_without_bisecting() {
+        let (_d, db) = open("jobs_transient.db").await;
+        seed(&db, 8).await;
+        let e = FlakyEmbedder { inner: HashEmbedder::new("m1", 8), failures: AtomicUsize::new(1) };
+        let opts = no_wait().with_batch_size(8);
+        let report = db.embed_run_once(&e, &opts).await.unwrap();
+        assert_eq!(report, EmbedRunReport { batches: 1, embedded: 0, failed: 8 });
+        assert_eq!(e.inner.calls.load(Ordering::SeqCst), 0);
+        let failures = db.embed_failures("m1", 10).await.unwrap();
+        assert!(failures.iter().all(|f| f.attempts == 1));
+
+        assert_eq!(db.embed_run_once(&e, &opts).await.unwrap().embedded, 8);
+        assert_eq!(e.inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn worker_stops_on_shutdown() {
+        let (_d, db) = open("jobs_worker.db").await;
+        seed(&db, 3).await;
+        let e = HashEmbedder::new("m1", 8);
+        let opts = no_wait().with_poll_interval(Duration::from_secs(3600));
+        let caught_up = async {
+            while db.embed_progress(&e.space()).await.unwrap().pending > 0 {
+                tokio::task::yield_now().await;
+            }
+        };
+        db.embed_worker(&e, &opts, caught_up).await.unwrap();
+        assert_eq!(db.vector_count("m1").await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn backoff_delays_the_next_attempt() {
+        let (_d, db) = open("jobs_backoff.db").await;
+        seed(&db, 1).await;
+        let e = FlakyEmbedder { inner: HashEmbedder::new("m1", 8), failures: AtomicUsize::new(1) };
+        let opts = EmbedJobOptions::default();
+        assert_eq!(db.embed_run_once(&e, &opts).await.unwrap().failed, 1);
+        assert_eq!(db.embed_run_once(&e, &opts).await.unwrap().batches, 0);
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().retrying, 1);
+    }
+
+    #[tokio::test]
+    async fn remove_drops_source_and_vectors() {
+        let (_d, db) = open("jobs_remove.db").await;
+        seed(&db, 2).await;
+        let e = HashEmbedder::new("m1", 8);
+        db.embed_drain(&e, &no_wait()).await.unwrap();
+        assert_eq!(db.embed_remove("doc", "0").await.unwrap(), 1);
+        assert_eq!(db.vector_count("m1").await.unwrap(), 1);
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().sources, 1);
+    }
+
+    #[tokio::test]
+    async fn retire_drops_state_and_vectors_together() {
+        let (_d, db) = open("jobs_retire.db").await;
+        seed(&db, 3).await;
+        let e = HashEmbedder::new("m1", 8);
+        db.embed_drain(&e, &no_wait()).await.unwrap();
+        assert_eq!(db.embed_retire("m1").await.unwrap(), 3);
+        assert_eq!(db.vector_count("m1").await.unwrap(), 0);
+        assert_eq!(db.embed_progress(&e.space()).await.unwrap().pending, 3);
+    }
+}
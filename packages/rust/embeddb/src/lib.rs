@@ -8,17 +8,33 @@ mod tx;
 mod value;
 mod config;
 mod migrate;
+mod backup;
 mod query;
 #[cfg(feature = "analytics")]
 mod pool;
+#[cfg(feature = "transfer")]
+mod transfer;
 mod convert;
+mod table;
 mod read;
 #[cfg(feature = "vector")]
 mod vector;
 #[cfg(feature = "vector")]
+mod filter;
+#[cfg(feature = "vector")]
+mod ann;
+#[cfg(feature = "vector")]
+mod fts;
+#[cfg(feature = "vector")]
+mod quant;
+#[cfg(feature = "vector")]
 mod embed;
 #[cfg(feature = "embed-api")]
 mod embed_api;
+#[cfg(feature = "cdc")]
+mod cdc;
+#[cfg(feature = "jobs")]
+mod jobs;
 
 pub use error::{EmbedError, Result};
 pub use db::EmbedDb;
@@ -26,22 +42,46 @@ pub use tx::EmbedTx;
 pub use value::{EmbedValue, EmbedRow};
 pub use turso::IntoParams;
 pub use config::EmbedConfig;
+pub use backup::BackupReport;
+pub use migrate::{Migration, MigrationReport, MigrationState, MigrationStatus};
 pub use query::QueryResult;
 pub use query::FromEmbedRow;
-pub use convert::FromEmbedValue;
+pub use convert::{FromEmbedValue, ToEmbedValue};
+pub use table::{Cmp, ColumnDef, EmbedTable, Select};
 
 pub use read::params_from_values;
 
 #[cfg(feature = "vector")]
 pub use embed::{BoxFuture, Embedder};
 
+#[cfg(feature = "vector")]
+pub use ann::{AnnConfig, AnnStatus, SearchMode, ANN_META_TABLE};
+
+#[cfg(feature = "vector")]
+pub use quant::{QuantStatus, Quantization, QUANT_META_TABLE};
+
+#[cfg(feature = "vector")]
+pub use fts::{tokenize, HybridConfig, HybridHit, TextHit, FTS_DOCS_TABLE};
+
 #[cfg(feature = "embed-api")]
 pub use embed_api::{ApiEmbedder, ApiEmbedderConfig};
 
 #[cfg(feature = "vector")]
-pub use vector::{
-    dot, normalize, norm, pack, unpack, VectorFilter, VectorHit, VectorSpace, VECTOR_TABLE,
+pub use vector::{dot, normalize, norm, pack, unpack, VectorHit, VectorSpace, VECTOR_TABLE};
+
+#[cfg(feature = "vector")]
+pub use filter::{MetaPredicate, VectorFilter};
+
+#[cfg(feature = "jobs")]
+pub use jobs::{
+    EmbedFailure, EmbedJobOptions, EmbedProgress, EmbedRunReport, EMBED_SOURCES_TABLE,
 };
 
+#[cfg(feature = "transfer")]
+pub use transfer::{DataFormat, ImportOptions, ImportReport};
+
+#[cfg(feature = "cdc")]
+pub use cdc::{CdcOptions, ChangeEvent, ChangeOp, CDC_LOG_TABLE};
+
 #[cfg(feature = "derive")]
-pub use embeddb_derive::FromEmbedRow;
+pub use embeddb_derive::{EmbedTable, FromEmbedRow};
@@ -1,25 +1,259 @@
-use crate::{EmbedDb, Result};
+use crate::{EmbedDb, EmbedError, Result};
 
-pub(crate) async fn run(db: &EmbedDb, migrations: &[&str]) -> Result<()> {
-    db.execute(
-        "CREATE TABLE IF NOT EXISTS _embeddb_migrations (version INTEGER PRIMARY KEY, applied_at TEXT)",
-        (),
-    ).await?;
-    let applied = db.max_migration_version().await?;
-    for (i, sql) in migrations.iter().enumerate() {
+const CREATE_LEDGER: &str = "CREATE TABLE IF NOT EXISTS _embeddb_migrations (\
+ version INTEGER PRIMARY KEY,\
+ applied_at TEXT,\
+ name TEXT,\
+ checksum TEXT)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+}
+
+impl Migration {
+    pub fn new(name: impl Into<String>, up: impl Into<String>) -> Migration {
+        Migration { name: name.into(), up: up.into(), down: None }
+    }
+
+    pub fn with_down(mut self, down: impl Into<String>) -> Migration {
+        self.down = Some(down.into());
+        self
+    }
+
+    pub fn checksum(&self) -> String {
+        checksum(&self.up)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    Drifted(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub state: MigrationState,
+    pub applied_at: Option<String>,
+    pub reversible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MigrationReport {
+    pub migrations: Vec<MigrationStatus>,
+    pub unknown: Vec<i64>,
+}
+
+impl MigrationReport {
+    pub fn current_version(&self) -> i64 {
+        self.migrations
+            .iter()
+            .filter(|m| m.state == MigrationState::Applied)
+            .map(|m| m.version)
+            .max()
+            .unwrap_or(-1)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &MigrationStatus> {
+        self.migrations.iter().filter(|m| m.state == MigrationState::Pending)
+    }
+
+    pub fn drifted(&self) -> impl Iterator<Item = &MigrationStatus> {
+        self.migrations.iter().filter(|m| matches!(m.state, MigrationState::Drifted(_)))
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.unknown.is_empty() && self.drifted().next().is_none()
+    }
+
+    fn first_error(&self) -> Option<EmbedError> {
+        if let Some(m) = self.drifted().next() {
+            let MigrationState::Drifted(reason) = &m.state else {
+                unreachable!("drifted() only yields drifted entries");
+            };
+            return Some(EmbedError::MigrationDrift { version: m.version, reason: reason.clone() });
+        }
+        self.unknown.first().map(|v| EmbedError::MigrationDrift {
+            version: *v,
+            reason: "applied in the ledger but missing from the compiled list".into(),
+        })
+    }
+}
+
+pub(crate) fn checksum(sql: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in sql.trim().bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+// `migrate` has no names to record, so its ledger rows carry the
+// zero-padded version instead.
+fn legacy_name(version: i64) -> String {
+    format!("{version:04}")
+}
+
+fn is_legacy_name(name: &str, version: i64) -> bool {
+    name == legacy_name(version)
+}
+
+fn legacy(migrations: &[&str]) -> Vec<Migration> {
+    migrations
+        .iter()
+        .enumerate()
+        .map(|(i, sql)| Migration::new(legacy_name(i as i64), *sql))
+        .collect()
+}
+
+struct LedgerRow {
+    version: i64,
+    applied_at: Option<String>,
+    name: Option<String>,
+    checksum: Option<String>,
+}
+
+async fn ensure_ledger(db: &EmbedDb) -> Result<()> {
+    db.execute(CREATE_LEDGER, ()).await?;
+    let cols = db.query("PRAGMA table_info(_embeddb_migrations)", ()).await?;
+    for column in ["name", "checksum"] {
+        if !cols.rows.iter().any(|r| r.as_str(1) == Some(column)) {
+            db.execute(&format!("ALTER TABLE _embeddb_migrations ADD COLUMN {column} TEXT"), ())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn ledger(db: &EmbedDb) -> Result<Vec<LedgerRow>> {
+    let rows = db
+        .query_rows(
+            "SELECT version, applied_at, name, checksum FROM _embeddb_migrations ORDER BY version",
+            (),
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|r| LedgerRow {
+            version: r.as_i64(0).unwrap_or(-1),
+            applied_at: r.as_str(1).map(str::to_string),
+            name: r.as_str(2).map(str::to_string),
+            checksum: r.as_str(3).map(str::to_string),
+        })
+        .collect())
+}
+
+fn compare(migrations: &[Migration], ledger: &[LedgerRow]) -> MigrationReport {
+    let max_applied = ledger.iter().map(|r| r.version).max().unwrap_or(-1);
+    let mut report = MigrationReport::default();
+    for (i, m) in migrations.iter().enumerate() {
         let version = i as i64;
-        if version <= applied {
+        let sum = m.checksum();
+        let row = ledger.iter().find(|r| r.version == version);
+        let state = match row {
+            None if version < max_applied => MigrationState::Drifted(format!(
+                "'{}' was never applied but later version {max_applied} was",
+                m.name
+            )),
+            None => MigrationState::Pending,
+            Some(r) => match (&r.name, &r.checksum) {
+                (Some(name), _)
+                    if *name != m.name
+                        && !is_legacy_name(name, version)
+                        && !is_legacy_name(&m.name, version) =>
+                {
+                    MigrationState::Drifted(format!(
+                        "ledger records '{name}' but the compiled list has '{}'",
+                        m.name
+                    ))
+                }
+                (_, Some(stored)) if *stored != sum => MigrationState::Drifted(format!(
+                    "'{}' checksum changed from {stored} to {sum}",
+                    m.name
+                )),
+                _ => MigrationState::Applied,
+            },
+        };
+        report.migrations.push(MigrationStatus {
+            version,
+            name: m.name.clone(),
+            checksum: sum,
+            state,
+            applied_at: row.and_then(|r| r.applied_at.clone()),
+            reversible: m.down.is_some(),
+        });
+    }
+    report.unknown = ledger
+        .iter()
+        .map(|r| r.version)
+        .filter(|v| *v < 0 || *v as usize >= migrations.len())
+        .collect();
+    report
+}
+
+// Backfills checksums on pre-checksum ledgers and replaces the
+// placeholder names `migrate` recorded once a named list is applied.
+async fn adopt_legacy_rows(
+    db: &EmbedDb,
+    migrations: &[Migration],
+    ledger: &[LedgerRow],
+) -> Result<()> {
+    for row in ledger {
+        let Some(m) = usize::try_from(row.version).ok().and_then(|i| migrations.get(i)) else {
+            continue;
+        };
+        let unnamed = row.name.as_deref().is_none_or(|n| is_legacy_name(n, row.version));
+        let rename = unnamed && row.name.as_deref() != Some(m.name.as_str());
+        if row.checksum.is_some() && !rename {
             continue;
         }
+        let name = if unnamed { m.name.as_str() } else { row.name.as_deref().unwrap_or_default() };
+        db.execute(
+            "UPDATE _embeddb_migrations SET name = ?, checksum = ? WHERE version = ?",
+            (name, m.checksum(), row.version),
+        )
+            .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn status(db: &EmbedDb, migrations: &[Migration]) -> Result<MigrationReport> {
+    ensure_ledger(db).await?;
+    Ok(compare(migrations, &ledger(db).await?))
+}
+
+pub(crate) async fn run(db: &EmbedDb, migrations: &[&str]) -> Result<()> {
+    run_named(db, &legacy(migrations)).await
+}
+
+pub(crate) async fn run_named(db: &EmbedDb, migrations: &[Migration]) -> Result<()> {
+    ensure_ledger(db).await?;
+    let rows = ledger(db).await?;
+    let report = compare(migrations, &rows);
+    if let Some(err) = report.first_error() {
+        return Err(err);
+    }
+    adopt_legacy_rows(db, migrations, &rows).await?;
+    for pending in report.pending() {
+        let m = &migrations[pending.version as usize];
         let tx = db.begin().await?;
-        if let Err(e) = tx.execute(sql, ()).await {
+        if let Err(e) = tx.execute(&m.up, ()).await {
             tx.rollback().await.ok();
             return Err(e);
         }
         if let Err(e) = tx
             .execute(
-                "INSERT INTO _embeddb_migrations (version, applied_at) VALUES (?, datetime('now'))",
-                (version,),
+                "INSERT INTO _embeddb_migrations (version, applied_at, name, checksum) \
+VALUES (?, datetime('now'), ?, ?)",
+                (pending.version, m.name.as_str(), pending.checksum.as_str()),
             )
             .await
         {
@@ -31,8 +265,53 @@ pub(crate) async fn run(db: &EmbedDb, migrations: &[&str]) -> Result<()> {
     Ok(())
 }
 
+pub(crate) async fn rollback_to(
+    db: &EmbedDb,
+    migrations: &[Migration],
+    target: i64,
+) -> Result<Vec<i64>> {
+    ensure_ledger(db).await?;
+    let rows = ledger(db).await?;
+    let report = compare(migrations, &rows);
+    if let Some(err) = report.first_error() {
+        return Err(err);
+    }
+    let mut undo: Vec<&MigrationStatus> = report
+        .migrations
+        .iter()
+        .filter(|m| m.state == MigrationState::Applied && m.version > target)
+        .collect();
+    undo.sort_by_key(|m| std::cmp::Reverse(m.version));
+    if let Some(m) = undo.iter().find(|m| !m.reversible) {
+        return Err(EmbedError::Migration(format!(
+            "cannot roll back past version {} ('{}'): it has no down step",
+            m.version, m.name
+        )));
+    }
+    let mut reverted = Vec::with_capacity(undo.len());
+    for status in undo {
+        let down = migrations[status.version as usize].down.as_deref().unwrap_or_default();
+        let tx = db.begin().await?;
+        if let Err(e) = tx.execute(down, ()).await {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+        if let Err(e) = tx
+            .execute("DELETE FROM _embeddb_migrations WHERE version = ?", (status.version,))
+            .await
+        {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+        tx.commit().await?;
+        reverted.push(status.version);
+    }
+    Ok(reverted)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::EmbedDb;
 
     async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
@@ -41,6 +320,21 @@ mod tests {
         (dir, db)
     }
 
+    fn named() -> Vec<Migration> {
+        vec![
+            Migration::new("create_a", "CREATE TABLE a (id INTEGER)").with_down("DROP TABLE a"),
+            Migration::new("create_b", "CREATE TABLE b (id INTEGER)").with_down("DROP TABLE b"),
+        ]
+    }
+
+    #[test]
+    fn checksum_is_stable_and_ignores_surrounding_whitespace() {
+        let sql = "CREATE TABLE a (id INTEGER)";
+        assert_eq!(checksum(sql), checksum(&format!("  {sql}\n")));
+        assert_ne!(checksum(sql), checksum("CREATE TABLE a (id TEXT)"));
+        assert_eq!(checksum("").len(), 16);
+    }
+
     #[tokio::test]
     async fn empty_migrations_is_noop_but_creates_ledger() {
         let (_d, db) = open("mig_empty.db").await;
@@ -95,4 +389,146 @@ mod tests {
         assert!(res.is_err());
         assert_eq!(db.max_migration_version().await.unwrap(), 0);
     }
+
+    #[tokio::test]
+    async fn ledger_records_name_and_checksum() {
+        let (_d, db) = open("mig_named.db").await;
+        db.migrate_named(&named()).await.unwrap();
+        let row = db
+            .query_one("SELECT name, checksum FROM _embeddb_migrations WHERE version = 1", ())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.as_str(0), Some("create_b"));
+        assert_eq!(row.as_str(1), Some(checksum("CREATE TABLE b (id INTEGER)").as_str()));
+    }
+
+    #[tokio::test]
+    async fn edited_migration_is_a_hard_error() {
+        let (_d, db) = open("mig_edit.db").await;
+        db.migrate(&["CREATE TABLE a (id INTEGER)"]).await.unwrap();
+        let err = db
+            .migrate(&["CREATE TABLE a (id INTEGER, extra TEXT)", "CREATE TABLE b (id INTEGER)"])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EmbedError::MigrationDrift { version: 0, .. }), "{err}");
+        assert_eq!(db.max_migration_version().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn reordered_or_renamed_migrations_are_detected() {
+        let (_d, db) = open("mig_reorder.db").await;
+        db.migrate_named(&named()).await.unwrap();
+        let mut swapped = named();
+        swapped.swap(0, 1);
+        let err = db.migrate_named(&swapped).await.unwrap_err();
+        assert!(matches!(err, EmbedError::MigrationDrift { version: 0, .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn truncated_list_reports_unknown_ledger_entries() {
+        let (_d, db) = open("mig_truncated.db").await;
+        db.migrate_named(&named()).await.unwrap();
+        let err = db.migrate_named(&named()[..1]).await.unwrap_err();
+        assert!(matches!(err, EmbedError::MigrationDrift { version: 1, .. }), "{err}");
+        let report = db.migration_status(&named()[..1]).await.unwrap();
+        assert_eq!(report.unknown, vec![1]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn status_is_a_dry_run() {
+        let (_d, db) = open("mig_status.db").await;
+        db.migrate_named(&named()[..1]).await.unwrap();
+        let report = db.migration_status(&named()).await.unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.current_version(), 0);
+        let pending: Vec<&str> = report.pending().map(|m| m.name.as_str()).collect();
+        assert_eq!(pending, vec!["create_b"]);
+        assert!(report.migrations[0].applied_at.is_some());
+        assert!(report.migrations.iter().all(|m| m.reversible));
+        assert_eq!(db.max_migration_version().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn rollback_runs_down_steps_in_reverse() {
+        let (_d, db) = open("mig_rollback.db").await;
+        db.migrate_named(&named()).await.unwrap();
+        let reverted = db.rollback_to(&named(), -1).await.unwrap();
+        assert_eq!(reverted, vec![1, 0]);
+        assert_eq!(db.max_migration_version().await.unwrap(), -1);
+        assert!(db.query("SELECT * FROM a", ()).await.is_err());
+
+        db.migrate_named(&named()).await.unwrap();
+        assert_eq!(db.max_migration_version().await.unwrap(), 1);
+        assert!(db.rollback_to(&named(), 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rollback_refuses_irreversible_steps_before_touching_anything() {
+        let (_d, db) = open("mig_irreversible.db").await;
+        let m = vec![
+            Migration::new("create_a", "CREATE TABLE a (id INTEGER)"),
+            Migration::new("create_b", "CREATE TABLE b (id INTEGER)").with_down("DROP TABLE b"),
+        ];
+        db.migrate_named(&m).await.unwrap();
+        let err = db.rollback_to(&m, -1).await.unwrap_err();
+        assert!(matches!(err, EmbedError::Migration(_)), "{err}");
+        assert_eq!(db.max_migration_version().await.unwrap(), 1);
+        assert_eq!(db.rollback_to(&m, 0).await.unwrap(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn legacy_ledger_is_adopted_without_drift() {
+        let (_d, db) = open("mig_legacy.db").await;
+        db.execute(
+            "CREATE TABLE _embeddb_migrations (version INTEGER PRIMARY KEY, applied_at TEXT)",
+            (),
+        )
+            .await
+            .unwrap();
+        db.execute("CREATE TABLE a (id INTEGER)", ()).await.unwrap();
+        db.execute("INSERT INTO _embeddb_migrations VALUES (0, datetime('now'))", ())
+            .await
+            .unwrap();
+
+        db.migrate(&["CREATE TABLE a (id INTEGER)", "CREATE TABLE b (id INTEGER)"])
+            .await
+            .unwrap();
+        assert_eq!(db.max_migration_version().await.unwrap(), 1);
+        let sum = db
+            .query_scalar_string("SELECT checksum FROM _embeddb_migrations WHERE version = 0", ())
+            .await
+            .unwrap();
+        assert_eq!(sum, checksum("CREATE TABLE a (id INTEGER)"));
+    }
+
+    #[tokio::test]
+    async fn unnamed_ledger_is_upgraded_to_named_migrations() {
+        let (_d, db) = open("mig_upgrade_names.db").await;
+        db.migrate(&["CREATE TABLE a (id INTEGER)"]).await.unwrap();
+        assert!(db.migration_status(&named()).await.unwrap().is_clean());
+
+        db.migrate_named(&named()).await.unwrap();
+        let rows = db
+            .query_rows("SELECT name FROM _embeddb_migrations ORDER BY version", ())
+            .await
+            .unwrap();
+        let names: Vec<_> = rows.iter().map(|r| r.as_str(0).unwrap().to_string()).collect();
+        assert_eq!(names, vec!["create_a", "create_b"]);
+
+        db.migrate(&["CREATE TABLE a (id INTEGER)", "CREATE TABLE b (id INTEGER)"])
+            .await
+            .unwrap();
+        let name = db
+            .query_scalar_string("SELECT name FROM _embeddb_migrations WHERE version = 0", ())
+            .await
+            .unwrap();
+        assert_eq!(name, "create_a");
+
+        let mut renamed = named();
+        renamed[0].name = "create_alpha".into();
+        let err = db.migrate_named(&renamed).await.unwrap_err();
+        assert!(matches!(err, EmbedError::MigrationDrift { version: 0, .. }), "{err}");
+    }
 }
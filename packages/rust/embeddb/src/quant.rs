@@ -0,0 +1,779 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::filter::{clauses, parse_meta};
+use crate::vector::{check_dim, refs_clause, value_text, TopK};
+use crate::{unpack, EmbedDb, EmbedError, EmbedValue, Result, VectorFilter, VectorHit, VectorSpace};
+
+pub const QUANT_META_TABLE: &str = "_embeddb_quant_meta";
+
+const CREATE_META: &str = "CREATE TABLE IF NOT EXISTS _embeddb_quant_meta (\
+ model TEXT PRIMARY KEY,\
+ mode TEXT NOT NULL,\
+ dim INTEGER NOT NULL,\
+ generation INTEGER NOT NULL DEFAULT 0,\
+ keep_f32 INTEGER NOT NULL DEFAULT 1,\
+ built_at INTEGER NOT NULL)";
+
+const CREATE_CODES: &str = "CREATE TABLE IF NOT EXISTS _embeddb_vector_codes (\
+ model TEXT NOT NULL,\
+ ref_kind TEXT NOT NULL,\
+ ref_id TEXT NOT NULL,\
+ code BLOB NOT NULL,\
+ PRIMARY KEY (model, ref_kind, ref_id))";
+
+pub(crate) const CODE_SQL: &str = "INSERT INTO _embeddb_vector_codes \
+(model, ref_kind, ref_id, code) VALUES (?, ?, ?, ?) \
+ON CONFLICT (model, ref_kind, ref_id) DO UPDATE SET code = excluded.code";
+
+const META_UPSERT_SQL: &str = "INSERT INTO _embeddb_quant_meta \
+(model, mode, dim, keep_f32, generation, built_at) \
+VALUES (?, ?, ?, ?, 1, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (model) DO UPDATE SET mode = excluded.mode, dim = excluded.dim, \
+keep_f32 = excluded.keep_f32, generation = generation + 1, built_at = excluded.built_at";
+
+const RESCORE_SQL: &str =
+    "SELECT ref_kind, ref_id, vec, meta FROM _embeddb_vectors WHERE model = ?";
+
+pub(crate) async fn init(db: &EmbedDb) -> Result<()> {
+    db.execute(CREATE_META, ()).await?;
+    let cols = db.query("PRAGMA table_info(_embeddb_quant_meta)", ()).await?;
+    let has = |name: &str| cols.rows.iter().any(|r| r.as_str(1) == Some(name));
+    if !has("generation") {
+        db.execute(
+            "ALTER TABLE _embeddb_quant_meta ADD COLUMN generation INTEGER NOT NULL DEFAULT 0",
+            (),
+        )
+            .await?;
+    }
+    if !has("keep_f32") {
+        db.execute(
+            "ALTER TABLE _embeddb_quant_meta ADD COLUMN keep_f32 INTEGER NOT NULL DEFAULT 1",
+            (),
+        )
+            .await?;
+        // Stores written before the column existed only show it in their rows.
+        db.execute(
+            "UPDATE _embeddb_quant_meta SET keep_f32 = 0 WHERE EXISTS (\
+SELECT 1 FROM _embeddb_vectors v WHERE v.model = _embeddb_quant_meta.model AND length(v.vec) = 0)",
+            (),
+        )
+            .await?;
+    }
+    db.execute(CREATE_CODES, ()).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantization {
+    #[default]
+    F32,
+    Int8,
+    Binary,
+}
+
+impl Quantization {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Quantization::F32 => "f32",
+            Quantization::Int8 => "int8",
+            Quantization::Binary => "binary",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Quantization> {
+        match s {
+            "f32" => Some(Quantization::F32),
+            "int8" => Some(Quantization::Int8),
+            "binary" => Some(Quantization::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn default_rescore(self) -> usize {
+        match self {
+            Quantization::F32 => 1,
+            Quantization::Int8 => 4,
+            Quantization::Binary => 10,
+        }
+    }
+
+    pub fn code_len(self, dim: usize) -> usize {
+        match self {
+            Quantization::F32 => dim * 4,
+            Quantization::Int8 => 4 + dim,
+            Quantization::Binary => dim.div_ceil(8),
+        }
+    }
+
+    pub fn encode(self, unit: &[f32]) -> Vec<u8> {
+        match self {
+            Quantization::F32 => crate::pack(unit),
+            Quantization::Int8 => {
+                let scale = unit.iter().fold(0.0_f32, |m, v| m.max(v.abs()));
+                let mut out = Vec::with_capacity(4 + unit.len());
+                out.extend_from_slice(&scale.to_le_bytes());
+                let inv = if scale > 0.0 { 127.0 / scale } else { 0.0 };
+                out.extend(unit.iter().map(|v| (v * inv).round().clamp(-127.0, 127.0) as i8 as u8));
+                out
+            }
+            Quantization::Binary => {
+                let mut out = vec![0_u8; unit.len().div_ceil(8)];
+                for (i, v) in unit.iter().enumerate() {
+                    if *v > 0.0 {
+                        out[i / 8] |= 1 << (i % 8);
+                    }
+                }
+                out
+            }
+        }
+    }
+
+    pub(crate) fn scorer(self, unit: &[f32]) -> Scorer {
+        match self {
+            Quantization::Binary => Scorer::Binary {
+                bits: self.encode(unit),
+                dim: unit.len() as f32,
+            },
+            _ => Scorer::Dense { query: unit.to_vec(), mode: self },
+        }
+    }
+}
+
+pub(crate) enum Scorer {
+    Dense { query: Vec<f32>, mode: Quantization },
+    Binary { bits: Vec<u8>, dim: f32 },
+}
+
+impl Scorer {
+    pub(crate) fn score(&self, code: &[u8]) -> Result<f32> {
+        match self {
+            Scorer::Dense { query, mode: Quantization::Int8 } => {
+                if code.len() != 4 + query.len() {
+                    return Err(code_len_error(Quantization::Int8, query.len(), code.len()));
+                }
+                let scale = f32::from_le_bytes([code[0], code[1], code[2], code[3]]);
+                let sum: f32 =
+                    query.iter().zip(&code[4..]).map(|(q, c)| q * f32::from(*c as i8)).sum();
+                Ok(sum * scale / 127.0)
+            }
+            Scorer::Dense { query, .. } => {
+                let stored = unpack(code)?;
+                if stored.len() != query.len() {
+                    return Err(EmbedError::VectorDim {
+                        expected: query.len(),
+                        actual: stored.len(),
+                    });
+                }
+                Ok(crate::dot(query, &stored))
+            }
+            Scorer::Binary { bits, dim } => {
+                if code.len() != bits.len() {
+                    return Err(code_len_error(Quantization::Binary, *dim as usize, code.len()));
+                }
+                let hamming: u32 = bits.iter().zip(code).map(|(a, b)| (a ^ b).count_ones()).sum();
+                Ok(1.0 - 2.0 * hamming as f32 / dim)
+            }
+        }
+    }
+}
+
+fn code_len_error(mode: Quantization, dim: usize, actual: usize) -> EmbedError {
+    EmbedError::VectorBlob(format!(
+        "{} code for dim {dim} must be {} bytes, got {actual}",
+        mode.as_str(),
+        mode.code_len(dim)
+    ))
+}
+
+/// A model's recorded quantization. `keep_f32` comes from the meta row,
+/// not the caller's `VectorSpace`, so every handle agrees on whether the
+/// rows still carry an f32 copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QuantMode {
+    pub(crate) mode: Quantization,
+    pub(crate) keep_f32: bool,
+}
+
+// Mirrors `AnnCache`: a quantized model's mode and dim are cached with
+// the meta row's `generation` and dropped once the row moves on, while
+// "not quantized" is re-read so a mode set by another handle is picked up
+// on the next write.
+#[derive(Debug, Default)]
+pub(crate) struct QuantCache {
+    inner: Mutex<HashMap<String, (i64, QuantMode, usize)>>,
+}
+
+impl QuantCache {
+    fn get(&self, model: &str) -> Option<(i64, QuantMode, usize)> {
+        let guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.get(model).copied()
+    }
+
+    fn put(&self, model: &str, generation: i64, mode: QuantMode, dim: usize) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.insert(model.to_string(), (generation, mode, dim));
+    }
+
+    pub(crate) fn evict(&self, model: &str) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.remove(model);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantStatus {
+    pub model: String,
+    pub mode: Quantization,
+    pub dim: usize,
+    pub keep_f32: bool,
+    pub built_at: i64,
+    pub coded: i64,
+}
+
+fn int(value: Option<&EmbedValue>) -> i64 {
+    match value {
+        Some(EmbedValue::Int(n)) => *n,
+        _ => 0,
+    }
+}
+
+// Codes and IVF lists can only be built from full-precision vectors.
+pub(crate) async fn ensure_f32_copies(tx: &crate::EmbedTx<'_>, model: &str) -> Result<()> {
+    let stripped = tx
+        .query_one(
+            "SELECT count(*) FROM _embeddb_vectors WHERE model = ? AND length(vec) = 0",
+            (model,),
+        )
+        .await?
+        .map(|r| int(r.get(0)))
+        .unwrap_or(0);
+    if stripped > 0 {
+        return Err(EmbedError::VectorBlob(format!(
+            "model '{model}' has {stripped} vectors stored without an f32 copy; \
+re-embed them with an f32 copy first"
+        )));
+    }
+    Ok(())
+}
+
+impl EmbedDb {
+    pub(crate) async fn quant_mode(&self, space: &VectorSpace) -> Result<Option<QuantMode>> {
+        let row = self
+            .query_one(
+                "SELECT mode, dim, generation, keep_f32 FROM _embeddb_quant_meta WHERE model = ?",
+                (space.model.as_str(),),
+            )
+            .await?;
+        let Some(row) = row else {
+            self.quant_cache().evict(&space.model);
+            return Ok(None);
+        };
+        let generation = int(row.get(2));
+        match self.quant_cache().get(&space.model) {
+            Some((cached, mode, dim)) if cached == generation => {
+                return Ok(Some(mode).filter(|_| dim == space.dim));
+            }
+            Some(_) => self.quant_cache().evict(&space.model),
+            None => {}
+        }
+        let dim = int(row.get(1)) as usize;
+        let Some(mode) =
+            row.as_str(0).and_then(Quantization::parse).filter(|m| *m != Quantization::F32)
+        else {
+            return Ok(None);
+        };
+        let mode = QuantMode { mode, keep_f32: int(row.get(3)) != 0 };
+        self.quant_cache().put(&space.model, generation, mode, dim);
+        Ok(Some(mode).filter(|_| dim == space.dim))
+    }
+
+    pub(crate) async fn quant_mode_for_write(
+        &self,
+        space: &VectorSpace,
+    ) -> Result<Option<QuantMode>> {
+        if let Some(mode) = self.quant_mode(space).await? {
+            return Ok(Some(mode));
+        }
+        if space.quantization == Quantization::F32 {
+            return Ok(None);
+        }
+        // Existing unquantized rows need `vector_quantize` first; an index
+        // probe answers that without counting the model.
+        let populated = self
+            .query_one("SELECT 1 FROM _embeddb_vectors WHERE model = ? LIMIT 1", (space.model.as_str(),))
+            .await?
+            .is_some();
+        if populated {
+            return Ok(None);
+        }
+        self.execute(
+            META_UPSERT_SQL,
+            (
+                space.model.as_str(),
+                space.quantization.as_str(),
+                space.dim as i64,
+                space.keep_f32 as i64,
+            ),
+        )
+            .await?;
+        self.quant_cache().evict(&space.model);
+        Ok(Some(QuantMode { mode: space.quantization, keep_f32: space.keep_f32 }))
+    }
+
+    pub async fn vector_quantize(&self, space: &VectorSpace) -> Result<Option<QuantStatus>> {
+        let model = space.model.as_str();
+        // The f32 check, the scan and the rewrites share one transaction, so
+        // a vector upserted meanwhile can't lose its code or its f32 copy.
+        let tx = self.begin().await?;
+        if let Err(e) = ensure_f32_copies(&tx, model).await {
+            tx.rollback().await?;
+            return Err(e);
+        }
+        if space.quantization == Quantization::F32 {
+            tx.execute("DELETE FROM _embeddb_vector_codes WHERE model = ?", (model,)).await?;
+            tx.execute("DELETE FROM _embeddb_quant_meta WHERE model = ?", (model,)).await?;
+            tx.commit().await?;
+            self.quant_cache().evict(model);
+            return Ok(None);
+        }
+
+        let mode = space.quantization;
+        let mut codes = Vec::new();
+        let mut bad = None;
+        tx.query_for_each(
+            "SELECT ref_kind, ref_id, vec FROM _embeddb_vectors WHERE model = ?",
+            (model,),
+            |row| {
+                if bad.is_some() {
+                    return;
+                }
+                let v = match row.get(2) {
+                    Some(EmbedValue::Blob(b)) => unpack(b),
+                    other => Err(EmbedError::VectorBlob(format!(
+                        "vec column is not a blob: {:?}",
+                        other
+                    ))),
+                };
+                match v {
+                    Ok(v) if v.len() == space.dim => {
+                        let kind = row.as_str(0).unwrap_or_default().to_string();
+                        let id = row.as_str(1).unwrap_or_default().to_string();
+                        codes.push((kind, id, mode.encode(&v)));
+                    }
+                    Ok(v) => {
+                        bad = Some(EmbedError::VectorDim { expected: space.dim, actual: v.len() })
+                    }
+                    Err(e) => bad = Some(e),
+                }
+            },
+        )
+            .await?;
+        if let Some(e) = bad {
+            tx.rollback().await?;
+            return Err(e);
+        }
+
+        tx.execute("DELETE FROM _embeddb_vector_codes WHERE model = ?", (model,)).await?;
+        for (kind, id, code) in codes {
+            tx.execute(CODE_SQL, (model, kind, id, code)).await?;
+        }
+        if !space.keep_f32 {
+            let empty = Vec::<u8>::new();
+            tx.execute("UPDATE _embeddb_vectors SET vec = ? WHERE model = ?", (empty, model)).await?;
+        }
+        tx.execute(
+            META_UPSERT_SQL,
+            (model, mode.as_str(), space.dim as i64, space.keep_f32 as i64),
+        )
+            .await?;
+        tx.commit().await?;
+        self.quant_cache().evict(model);
+        self.vector_quant_status(model).await
+    }
+
+    pub async fn vector_quant_status(&self, model: &str) -> Result<Option<QuantStatus>> {
+        let row = self
+            .query_one(
+                "SELECT mode, dim, built_at, keep_f32 FROM _embeddb_quant_meta WHERE model = ?",
+                (model,),
+            )
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let mode = row.as_str(0).unwrap_or_default();
+        let mode = Quantization::parse(mode).ok_or_else(|| {
+            EmbedError::VectorBlob(format!("unknown quantization '{mode}' for model '{model}'"))
+        })?;
+        let coded = self
+            .query_scalar_i64(
+                "SELECT count(*) FROM _embeddb_vector_codes WHERE model = ?",
+                (model,),
+            )
+            .await?;
+        Ok(Some(QuantStatus {
+            model: model.to_string(),
+            mode,
+            dim: int(row.get(1)) as usize,
+            keep_f32: int(row.get(3)) != 0,
+            built_at: int(row.get(2)),
+            coded,
+        }))
+    }
+
+    pub(crate) async fn vector_search_quantized(
+        &self,
+        space: &VectorSpace,
+        mode: Quantization,
+        unit: &[f32],
+        k: usize,
+        filter: Option<&VectorFilter>,
+    ) -> Result<Vec<VectorHit>> {
+        check_dim(space, unit)?;
+        let (where_sql, extra) = clauses(filter, "v.")?;
+        let sql = if where_sql.is_empty() {
+            "SELECT ref_kind, ref_id, code FROM _embeddb_vector_codes WHERE model = ?".to_string()
+        } else {
+            format!(
+                "SELECT c.ref_kind, c.ref_id, c.code FROM _embeddb_vector_codes c \
+JOIN _embeddb_vectors v ON v.model = c.model AND v.ref_kind = c.ref_kind AND v.ref_id = c.ref_id \
+WHERE c.model = ?{where_sql}"
+            )
+        };
+        let mut params = vec![turso::Value::Text(space.model.clone())];
+        params.extend(extra);
+
+        let scorer = mode.scorer(unit);
+        let mut candidates = TopK::new(space.candidates(k, mode));
+        let mut rows = self.conn().query(&sql, params).await?;
+        while let Some(row) = rows.next().await? {
+            let code = match row.get_value(2)? {
+                turso::Value::Blob(b) => b,
+                other => {
+                    return Err(EmbedError::VectorBlob(format!(
+                        "code column is not a blob: {:?}",
+                        other
+                    )))
+                }
+            };
+            let score = scorer.score(&code)?;
+            if !candidates.admits(score) {
+                continue;
+            }
+            let (turso::Value::Text(ref_kind), turso::Value::Text(ref_id)) =
+                (row.get_value(0)?, row.get_value(1)?)
+            else {
+                return Err(EmbedError::VectorBlob("code row has non-text ref columns".into()));
+            };
+            candidates.push(VectorHit { ref_kind, ref_id, score, payload: None });
+        }
+
+        let candidates = candidates.finish();
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+
+        // Rescore every candidate in one query.
+        let (refs_sql, refs) =
+            refs_clause(candidates.iter().map(|c| (c.ref_kind.as_str(), c.ref_id.as_str())), "");
+        let mut params = vec![turso::Value::Text(space.model.clone())];
+        params.extend(refs);
+        let sql = format!("{RESCORE_SQL}{refs_sql}");
+        let approx: HashMap<(&str, &str), f32> = candidates
+            .iter()
+            .map(|c| ((c.ref_kind.as_str(), c.ref_id.as_str()), c.score))
+            .collect();
+
+        let payload = filter.is_some_and(|f| f.with_payload);
+        let mut top = TopK::new(k);
+        let mut rows = self.conn().query(&sql, params).await?;
+        while let Some(row) = rows.next().await? {
+            let ref_kind = value_text(row.get_value(0)?)?;
+            let ref_id = value_text(row.get_value(1)?)?;
+            let bytes = match row.get_value(2)? {
+                turso::Value::Blob(b) => b,
+                other => {
+                    return Err(EmbedError::VectorBlob(format!(
+                        "vec column is not a blob: {:?}",
+                        other
+                    )))
+                }
+            };
+            // Rows stored without an f32 copy keep their code score.
+            let score = if bytes.is_empty() {
+                approx.get(&(ref_kind.as_str(), ref_id.as_str())).copied().unwrap_or(f32::MIN)
+            } else {
+                let stored = unpack(&bytes)?;
+                check_dim(space, &stored)?;
+                crate::dot(unit, &stored)
+            };
+            if !top.admits(score) {
+                continue;
+            }
+            top.push(VectorHit {
+                ref_kind,
+                ref_id,
+                score,
+                payload: if payload { parse_meta(row.get_value(3)?)? } else { None },
+            });
+        }
+        Ok(top.finish())
+    }
+}
+
+impl VectorSpace {
+    pub fn with_quantization(mut self, quantization: Quantization) -> VectorSpace {
+        self.quantization = quantization;
+        self
+    }
+
+    pub fn with_rescore(mut self, factor: usize) -> VectorSpace {
+        self.rescore = Some(factor.max(1));
+        self
+    }
+
+    /// With `false`, quantized models store only their codes: searches
+    /// return code scores instead of rescoring, and exact search, ANN builds
+    /// and re-quantizing fail until the vectors are re-embedded. The choice
+    /// is recorded with the model's mode, so later handles follow it
+    /// whatever their own setting.
+    pub fn with_f32_copy(mut self, keep: bool) -> VectorSpace {
+        self.keep_f32 = keep;
+        self
+    }
+
+    pub(crate) fn candidates(&self, k: usize, mode: Quantization) -> usize {
+        k.saturating_mul(self.rescore.unwrap_or_else(|| mode.default_rescore()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize;
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.vector_init().await.unwrap();
+        (dir, db)
+    }
+
+    fn seeded(seed: u64, dim: usize) -> Vec<f32> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..dim)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((state >> 33) as f32 / (1_u64 << 31) as f32) - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modes_round_trip_through_names() {
+        for mode in [Quantization::F32, Quantization::Int8, Quantization::Binary] {
+            assert_eq!(Quantization::parse(mode.as_str()), Some(mode));
+        }
+        assert_eq!(Quantization::parse("int4"), None);
+    }
+
+    #[test]
+    fn codes_have_expected_length() {
+        let (unit, _) = normalize(&seeded(1, 1536)).unwrap();
+        assert_eq!(Quantization::Int8.encode(&unit).len(), 1540);
+        assert_eq!(Quantization::Binary.encode(&unit).len(), 192);
+        assert_eq!(Quantization::Binary.code_len(10), 2);
+    }
+
+    #[test]
+    fn int8_score_tracks_full_precision_dot() {
+        let (a, _) = normalize(&seeded(2, 256)).unwrap();
+        let (b, _) = normalize(&seeded(3, 256)).unwrap();
+        let code = Quantization::Int8.encode(&b);
+        let approx = Quantization::Int8.scorer(&a).score(&code).unwrap();
+        assert!((approx - crate::dot(&a, &b)).abs() < 0.02);
+        let own = Quantization::Int8.scorer(&b).score(&code).unwrap();
+        assert!((own - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn binary_score_is_one_for_identical_signs() {
+        let v = [0.5_f32, -0.5, 0.1, -0.1, 0.7];
+        let code = Quantization::Binary.encode(&v);
+        assert_eq!(Quantization::Binary.scorer(&v).score(&code).unwrap(), 1.0);
+        let flipped: Vec<f32> = v.iter().map(|x| -x).collect();
+        assert_eq!(Quantization::Binary.scorer(&flipped).score(&code).unwrap(), -1.0);
+    }
+
+    #[test]
+    fn scorer_rejects_wrong_code_length() {
+        let v = [0.5_f32, 0.5];
+        let err = Quantization::Int8.scorer(&v).score(&[0, 0, 0, 0, 1]).unwrap_err();
+        assert!(matches!(err, EmbedError::VectorBlob(_)));
+    }
+
+    #[tokio::test]
+    async fn fresh_model_registers_space_quantization() {
+        let (_d, db) = open("quant_fresh.db").await;
+        let s = VectorSpace::new("local", "q", 16).with_quantization(Quantization::Int8);
+        for i in 0..20 {
+            db.vector_upsert(&s, "doc", &i.to_string(), &seeded(i, 16)).await.unwrap();
+        }
+        let status = db.vector_quant_status("q").await.unwrap().unwrap();
+        assert_eq!(status.mode, Quantization::Int8);
+        assert_eq!(status.coded, 20);
+    }
+
+    #[tokio::test]
+    async fn quantized_search_matches_exact_top_hits() {
+        let (_d, db) = open("quant_search.db").await;
+        for mode in [Quantization::Int8, Quantization::Binary] {
+            let s = VectorSpace::new("local", mode.as_str(), 32).with_quantization(mode);
+            let items: Vec<(String, String, Vec<f32>)> =
+                (0..200).map(|i| ("doc".into(), i.to_string(), seeded(i, 32))).collect();
+            db.vector_upsert_batch(&s, &items).await.unwrap();
+
+            let q = seeded(7, 32);
+            let exact = db
+                .vector_search_with(&s, &q, 5, None, crate::SearchMode::Exact)
+                .await
+                .unwrap();
+            let fast = db.vector_search(&s, &q, 5, None).await.unwrap();
+            assert_eq!(fast[0].ref_id, "7");
+            assert_eq!(fast[0].ref_id, exact[0].ref_id);
+            assert!((fast[0].score - exact[0].score).abs() < 1e-6);
+        }
+    }
+
+    #[tokio::test]
+    async fn existing_store_migrates_with_vector_quantize() {
+        let (_d, db) = open("quant_migrate.db").await;
+        let plain = VectorSpace::new("local", "legacy", 8);
+        for i in 0..30 {
+            db.vector_upsert(&plain, "doc", &i.to_string(), &seeded(i, 8)).await.unwrap();
+        }
+        let quantized = plain.clone().with_quantization(Quantization::Binary);
+        db.vector_upsert(&quantized, "doc", "30", &seeded(30, 8)).await.unwrap();
+        assert!(db.vector_quant_status("legacy").await.unwrap().is_none());
+
+        let status = db.vector_quantize(&quantized).await.unwrap().unwrap();
+        assert_eq!(status.mode, Quantization::Binary);
+        assert_eq!(status.coded, 31);
+
+        db.vector_upsert(&plain, "doc", "31", &seeded(31, 8)).await.unwrap();
+        assert_eq!(db.vector_quant_status("legacy").await.unwrap().unwrap().coded, 32);
+        db.vector_delete("legacy", "doc", "0").await.unwrap();
+        assert_eq!(db.vector_quant_status("legacy").await.unwrap().unwrap().coded, 31);
+
+        assert!(db.vector_quantize(&plain).await.unwrap().is_none());
+        assert!(db.vector_quant_status("legacy").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn quantized_search_applies_filters_and_payload() {
+        let (_d, db) = open("quant_filter.db").await;
+        let s = VectorSpace::new("local", "qf", 4).with_quantization(Quantization::Int8);
+        db.vector_upsert(&s, "doc", "a", &[1.0, 0.0, 0.0, 0.0]).await.unwrap();
+        let meta = serde_json::json!({"lang": "en"});
+        db.vector_upsert_meta(&s, "note", "b", &[0.9, 0.1, 0.0, 0.0], Some(&meta)).await.unwrap();
+        let filter = VectorFilter::kind("note").with_payload();
+        let hits = db.vector_search(&s, &[1.0, 0.0, 0.0, 0.0], 5, Some(&filter)).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "b");
+        assert_eq!(hits[0].payload, Some(meta));
+    }
+
+    #[tokio::test]
+    async fn codes_only_store_searches_on_code_scores() {
+        let (_d, db) = open("quant_codes_only.db").await;
+        let s = VectorSpace::new("local", "qc", 32)
+            .with_quantization(Quantization::Int8)
+            .with_f32_copy(false);
+        let items: Vec<(String, String, Vec<f32>)> =
+            (0..50).map(|i| ("doc".into(), i.to_string(), seeded(i, 32))).collect();
+        db.vector_upsert_batch(&s, &items).await.unwrap();
+        let stripped = db
+            .query_scalar_i64("SELECT count(*) FROM _embeddb_vectors WHERE length(vec) = 0", ())
+            .await
+            .unwrap();
+        assert_eq!(stripped, 50);
+
+        let hits = db.vector_search(&s, &seeded(9, 32), 3, None).await.unwrap();
+        assert_eq!(hits[0].ref_id, "9");
+        assert!((hits[0].score - 1.0).abs() < 0.02);
+        assert!(db.vector_quantize(&s.clone().with_f32_copy(true)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn codes_only_store_with_an_index_searches_codes() {
+        let (_d, db) = open("quant_codes_ivf.db").await;
+        let plain = VectorSpace::new("local", "qi", 16);
+        let items: Vec<(String, String, Vec<f32>)> =
+            (0..40).map(|i| ("doc".into(), i.to_string(), seeded(i, 16))).collect();
+        db.vector_upsert_batch(&plain, &items).await.unwrap();
+        db.vector_ann_build(&plain, &crate::AnnConfig::default().with_lists(4)).await.unwrap();
+
+        let codes = plain.with_quantization(Quantization::Int8).with_f32_copy(false);
+        db.vector_quantize(&codes).await.unwrap();
+        db.vector_upsert(&codes, "doc", "late", &seeded(99, 16)).await.unwrap();
+        for mode in [crate::SearchMode::Auto, crate::SearchMode::Probes(1)] {
+            let hits = db.vector_search_with(&codes, &seeded(99, 16), 1, None, mode).await.unwrap();
+            assert_eq!(hits[0].ref_id, "late");
+        }
+    }
+
+    #[tokio::test]
+    async fn codes_only_is_followed_by_a_reopened_store() {
+        let (dir, db) = open("quant_reopen.db").await;
+        let codes = VectorSpace::new("local", "qr", 16)
+            .with_quantization(Quantization::Int8)
+            .with_f32_copy(false);
+        let items: Vec<(String, String, Vec<f32>)> =
+            (0..20).map(|i| ("doc".into(), i.to_string(), seeded(i, 16))).collect();
+        db.vector_upsert_batch(&codes, &items).await.unwrap();
+        drop(db);
+
+        let db = EmbedDb::open(dir.path().join("quant_reopen.db")).await.unwrap();
+        db.vector_init().await.unwrap();
+        let fresh = VectorSpace::new("local", "qr", 16);
+        assert!(!db.vector_quant_status("qr").await.unwrap().unwrap().keep_f32);
+        db.vector_upsert(&fresh, "doc", "late", &seeded(99, 16)).await.unwrap();
+        let stripped = db
+            .query_scalar_i64("SELECT count(*) FROM _embeddb_vectors WHERE length(vec) = 0", ())
+            .await
+            .unwrap();
+        assert_eq!(stripped, 21);
+
+        let hits = db.vector_search(&fresh, &seeded(99, 16), 1, None).await.unwrap();
+        assert_eq!(hits[0].ref_id, "late");
+        let exact = db
+            .vector_search_with(&fresh, &seeded(99, 16), 1, None, crate::SearchMode::Exact)
+            .await
+            .unwrap_err();
+        assert!(matches!(exact, EmbedError::VectorBlob(_)), "{exact:?}");
+        let build = db
+            .vector_ann_build(&fresh, &crate::AnnConfig::default().with_lists(2))
+            .await
+            .unwrap_err();
+        assert!(matches!(build, EmbedError::VectorBlob(_)), "{build:?}");
+    }
+
+    #[tokio::test]
+    async fn vector_quantize_can_drop_f32_copies() {
+        let (_d, db) = open("quant_strip.db").await;
+        let plain = VectorSpace::new("local", "qs", 16);
+        for i in 0..10 {
+            db.vector_upsert(&plain, "doc", &i.to_string(), &seeded(i, 16)).await.unwrap();
+        }
+        let binary = plain.with_quantization(Quantization::Binary).with_f32_copy(false);
+        assert_eq!(db.vector_quantize(&binary).await.unwrap().unwrap().coded, 10);
+        let kept = db
+            .query_scalar_i64("SELECT count(*) FROM _embeddb_vectors WHERE length(vec) > 0", ())
+            .await
+            .unwrap();
+        assert_eq!(kept, 0);
+        let hits = db.vector_search(&binary, &seeded(4, 16), 1, None).await.unwrap();
+        assert_eq!(hits[0].ref_id, "4");
+    }
+}
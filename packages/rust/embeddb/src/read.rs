@@ -29,6 +29,29 @@ pub(crate) fn value_to_turso(v: EmbedValue) -> turso::Value {
     }
 }
 
+pub(crate) async fn for_each_row<F>(
+    conn: &turso::Connection,
+    sql: &str,
+    params: impl turso::IntoParams,
+    mut f: F,
+) -> Result<u64>
+where
+    F: FnMut(&EmbedRow),
+{
+    let mut rows = conn.query(sql, params).await?;
+    let ncols = rows.column_count();
+    let mut count = 0_u64;
+    while let Some(row) = rows.next().await? {
+        let mut vals = Vec::with_capacity(ncols);
+        for i in 0..ncols {
+            vals.push(value_from_turso(row.get_value(i)?));
+        }
+        f(&EmbedRow(vals));
+        count += 1;
+    }
+    Ok(count)
+}
+
 impl EmbedDb {
     pub async fn query(&self, sql: &str, params: impl turso::IntoParams) -> Result<QueryResult> {
         let mut rows = self.conn().query(sql, params).await?;
@@ -78,23 +101,12 @@ impl EmbedDb {
         &self,
         sql: &str,
         params: impl turso::IntoParams,
-        mut f: F,
+        f: F,
     ) -> Result<u64>
     where
         F: FnMut(&EmbedRow),
     {
-        let mut rows = self.conn().query(sql, params).await?;
-        let ncols = rows.column_count();
-        let mut count = 0_u64;
-        while let Some(row) = rows.next().await? {
-            let mut vals = Vec::with_capacity(ncols);
-            for i in 0..ncols {
-                vals.push(value_from_turso(row.get_value(i)?));
-            }
-            f(&EmbedRow(vals));
-            count += 1;
-        }
-        Ok(count)
+        for_each_row(self.conn(), sql, params, f).await
     }
 
     pub async fn query_scalar_i64(&self, sql: &str, params: impl turso::IntoParams) -> Result<i64> {
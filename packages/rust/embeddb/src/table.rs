@@ -0,0 +1,502 @@
+use std::marker::PhantomData;
+
+use crate::read::value_to_turso;
+use crate::{EmbedDb, EmbedError, EmbedValue, Result, ToEmbedValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnDef {
+    pub name: &'static str,
+    pub sql_type: &'static str,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+pub trait EmbedTable: Sized {
+    type Key;
+    const TABLE: &'static str;
+    const COLUMNS: &'static [ColumnDef];
+
+    fn values(&self) -> Vec<EmbedValue>;
+    fn key_values(key: &Self::Key) -> Vec<EmbedValue>;
+
+    fn create_table_sql() -> String {
+        let mut defs: Vec<String> = Self::COLUMNS
+            .iter()
+            .map(|c| {
+                let null = if c.nullable { "" } else { " NOT NULL" };
+                format!("{} {}{null}", ident(c.name), c.sql_type)
+            })
+            .collect();
+        let keys = column_list(Self::COLUMNS.iter().filter(|c| c.primary_key));
+        defs.push(format!("PRIMARY KEY ({keys})"));
+        format!("CREATE TABLE IF NOT EXISTS {} ({})", ident(Self::TABLE), defs.join(", "))
+    }
+}
+
+fn ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn column_list<'a>(cols: impl Iterator<Item = &'a ColumnDef>) -> String {
+    cols.map(|c| ident(c.name)).collect::<Vec<_>>().join(", ")
+}
+
+fn params(values: Vec<EmbedValue>) -> Vec<turso::Value> {
+    values.into_iter().map(value_to_turso).collect()
+}
+
+fn insert_sql<T: EmbedTable>() -> String {
+    let placeholders = vec!["?"; T::COLUMNS.len()].join(", ");
+    format!(
+        "INSERT INTO {} ({}) VALUES ({placeholders})",
+        ident(T::TABLE),
+        column_list(T::COLUMNS.iter())
+    )
+}
+
+fn upsert_sql<T: EmbedTable>() -> String {
+    let keys = column_list(T::COLUMNS.iter().filter(|c| c.primary_key));
+    let updates: Vec<String> = T::COLUMNS
+        .iter()
+        .filter(|c| !c.primary_key)
+        .map(|c| format!("{0} = excluded.{0}", ident(c.name)))
+        .collect();
+    let action = if updates.is_empty() {
+        "NOTHING".to_string()
+    } else {
+        format!("UPDATE SET {}", updates.join(", "))
+    };
+    format!("{} ON CONFLICT ({keys}) DO {action}", insert_sql::<T>())
+}
+
+fn key_clause<T: EmbedTable>() -> String {
+    T::COLUMNS
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| format!("{} = ?", ident(c.name)))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+impl EmbedDb {
+    pub async fn create_table<T: EmbedTable>(&self) -> Result<()> {
+        self.execute(&T::create_table_sql(), ()).await?;
+        Ok(())
+    }
+
+    pub async fn insert<T: EmbedTable>(&self, row: &T) -> Result<u64> {
+        self.execute(&insert_sql::<T>(), params(row.values())).await
+    }
+
+    pub async fn insert_all<T: EmbedTable>(&self, rows: &[T]) -> Result<u64> {
+        self.execute_batch(&insert_sql::<T>(), rows.iter().map(|r| params(r.values()))).await
+    }
+
+    pub async fn upsert<T: EmbedTable>(&self, row: &T) -> Result<u64> {
+        self.execute(&upsert_sql::<T>(), params(row.values())).await
+    }
+
+    pub async fn upsert_all<T: EmbedTable>(&self, rows: &[T]) -> Result<u64> {
+        self.execute_batch(&upsert_sql::<T>(), rows.iter().map(|r| params(r.values()))).await
+    }
+
+    pub async fn delete_by_pk<T: EmbedTable>(&self, key: &T::Key) -> Result<u64> {
+        let sql = format!("DELETE FROM {} WHERE {}", ident(T::TABLE), key_clause::<T>());
+        self.execute(&sql, params(T::key_values(key))).await
+    }
+
+    pub async fn get_by_pk<T>(&self, key: &T::Key) -> Result<Option<T>>
+    where
+        T: EmbedTable + crate::FromEmbedRow,
+    {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {}",
+            column_list(T::COLUMNS.iter()),
+            ident(T::TABLE),
+            key_clause::<T>()
+        );
+        Ok(self.query_as::<T>(&sql, params(T::key_values(key))).await?.into_iter().next())
+    }
+
+    pub fn select<T: EmbedTable>(&self) -> Select<'_, T> {
+        Select {
+            db: self,
+            predicates: Vec::new(),
+            order: Vec::new(),
+            limit: None,
+            offset: None,
+            _row: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl Cmp {
+    fn sql(self) -> &'static str {
+        match self {
+            Cmp::Eq => "=",
+            Cmp::Ne => "!=",
+            Cmp::Lt => "<",
+            Cmp::Lte => "<=",
+            Cmp::Gt => ">",
+            Cmp::Gte => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    Cmp(String, Cmp, EmbedValue),
+    In(String, Vec<EmbedValue>),
+    IsNull(String),
+    NotNull(String),
+}
+
+pub struct Select<'a, T> {
+    db: &'a EmbedDb,
+    predicates: Vec<Predicate>,
+    order: Vec<(String, bool)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    _row: PhantomData<fn() -> T>,
+}
+
+fn literal(v: &EmbedValue) -> Result<String> {
+    Ok(match v {
+        EmbedValue::Null => "NULL".to_string(),
+        EmbedValue::Int(n) | EmbedValue::Timestamp(n) | EmbedValue::Time(n) => n.to_string(),
+        EmbedValue::Date(n) => n.to_string(),
+        EmbedValue::HugeInt(n) => n.to_string(),
+        EmbedValue::Float(f) if f.is_finite() => format!("{f:?}"),
+        EmbedValue::Float(f) => {
+            return Err(EmbedError::Other(format!("cannot compare against non-finite {f}")));
+        }
+        EmbedValue::Bool(true) => "TRUE".to_string(),
+        EmbedValue::Bool(false) => "FALSE".to_string(),
+        EmbedValue::Text(s) => format!("'{}'", s.replace('\'', "''")),
+        EmbedValue::Blob(b) => {
+            let hex: String = b.iter().map(|byte| format!("{byte:02X}")).collect();
+            format!("from_hex('{hex}')")
+        }
+    })
+}
+
+impl<T: EmbedTable> Select<'_, T> {
+    pub fn filter(mut self, column: &str, cmp: Cmp, value: impl ToEmbedValue) -> Self {
+        let value = value.to_embed_value();
+        self.predicates.push(match (cmp, &value) {
+            (Cmp::Eq, EmbedValue::Null) => Predicate::IsNull(column.to_string()),
+            (Cmp::Ne, EmbedValue::Null) => Predicate::NotNull(column.to_string()),
+            _ => Predicate::Cmp(column.to_string(), cmp, value),
+        });
+        self
+    }
+
+    pub fn eq(self, column: &str, value: impl ToEmbedValue) -> Self {
+        self.filter(column, Cmp::Eq, value)
+    }
+
+    pub fn in_list<V: ToEmbedValue>(
+        mut self,
+        column: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(|v| v.to_embed_value()).collect();
+        self.predicates.push(Predicate::In(column.to_string(), values));
+        self
+    }
+
+    pub fn is_null(mut self, column: &str) -> Self {
+        self.predicates.push(Predicate::IsNull(column.to_string()));
+        self
+    }
+
+    pub fn is_not_null(mut self, column: &str) -> Self {
+        self.predicates.push(Predicate::NotNull(column.to_string()));
+        self
+    }
+
+    pub fn order_by(mut self, column: &str) -> Self {
+        self.order.push((column.to_string(), false));
+        self
+    }
+
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order.push((column.to_string(), true));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    fn column(name: &str) -> Result<String> {
+        if T::COLUMNS.iter().any(|c| c.name == name) {
+            Ok(ident(name))
+        } else {
+            Err(EmbedError::Other(format!("table '{}' has no column '{name}'", T::TABLE)))
+        }
+    }
+
+    fn render(&self, inline: bool) -> Result<(String, Vec<EmbedValue>)> {
+        let mut binds = Vec::new();
+        let mut bind = |v: &EmbedValue| -> Result<String> {
+            if inline {
+                literal(v)
+            } else {
+                binds.push(v.clone());
+                Ok("?".to_string())
+            }
+        };
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            column_list(T::COLUMNS.iter()),
+            ident(T::TABLE)
+        );
+        let mut clauses = Vec::with_capacity(self.predicates.len());
+        for p in &self.predicates {
+            clauses.push(match p {
+                Predicate::Cmp(col, cmp, v) => {
+                    format!("{} {} {}", Self::column(col)?, cmp.sql(), bind(v)?)
+                }
+                Predicate::In(col, values) if values.is_empty() => {
+                    Self::column(col)?;
+                    "FALSE".to_string()
+                }
+                Predicate::In(col, values) => {
+                    let items = values.iter().map(&mut bind).collect::<Result<Vec<_>>>()?;
+                    format!("{} IN ({})", Self::column(col)?, items.join(", "))
+                }
+                Predicate::IsNull(col) => format!("{} IS NULL", Self::column(col)?),
+                Predicate::NotNull(col) => format!("{} IS NOT NULL", Self::column(col)?),
+            });
+        }
+        if !clauses.is_empty() {
+            sql.push_str(&format!(" WHERE {}", clauses.join(" AND ")));
+        }
+        if !self.order.is_empty() {
+            let order = self
+                .order
+                .iter()
+                .map(|(col, desc)| {
+                    Ok(format!("{}{}", Self::column(col)?, if *desc { " DESC" } else { "" }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        match (self.limit, self.offset) {
+            (Some(limit), Some(offset)) => {
+                sql.push_str(&format!(" LIMIT {limit} OFFSET {offset}"))
+            }
+            (Some(limit), None) => sql.push_str(&format!(" LIMIT {limit}")),
+            // SQLite needs a LIMIT before OFFSET; DuckDB rejects `LIMIT -1`.
+            (None, Some(offset)) if inline => sql.push_str(&format!(" OFFSET {offset}")),
+            (None, Some(offset)) => sql.push_str(&format!(" LIMIT -1 OFFSET {offset}")),
+            (None, None) => {}
+        }
+        Ok((sql, binds))
+    }
+
+    pub fn to_sql(&self) -> Result<String> {
+        Ok(self.render(true)?.0)
+    }
+
+    pub async fn fetch_live(&self) -> Result<Vec<T>>
+    where
+        T: crate::FromEmbedRow,
+    {
+        let (sql, binds) = self.render(false)?;
+        self.db.query_as(&sql, params(binds)).await
+    }
+
+    #[cfg(feature = "analytics")]
+    pub async fn fetch(&self) -> Result<Vec<T>>
+    where
+        T: crate::FromEmbedRow,
+    {
+        self.db.analytics_query_as(&self.to_sql()?).await
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, crate::FromEmbedRow, crate::EmbedTable)]
+    #[embeddb(table = "accounts")]
+    struct Account {
+        #[embeddb(primary_key)]
+        id: i64,
+        handle: String,
+        balance: f64,
+        active: bool,
+        note: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, crate::FromEmbedRow, crate::EmbedTable)]
+    struct MemberRole {
+        #[embeddb(primary_key)]
+        team: String,
+        #[embeddb(primary_key)]
+        member: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, crate::FromEmbedRow, crate::EmbedTable)]
+    struct Ledger {
+        #[embeddb(primary_key)]
+        id: i64,
+        total: i128,
+    }
+
+    fn account(id: i64, handle: &str, balance: f64) -> Account {
+        Account { id, handle: handle.into(), balance, active: true, note: None }
+    }
+
+    async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join(name)).await.unwrap();
+        db.create_table::<Account>().await.unwrap();
+        (dir, db)
+    }
+
+    #[test]
+    fn derive_emits_ddl_from_field_types() {
+        assert_eq!(
+            Account::create_table_sql(),
+            "CREATE TABLE IF NOT EXISTS \"accounts\" (\"id\" INTEGER NOT NULL, \
+\"handle\" TEXT NOT NULL, \"balance\" REAL NOT NULL, \"active\" BOOLEAN NOT NULL, \
+\"note\" TEXT, PRIMARY KEY (\"id\"))"
+        );
+        assert_eq!(MemberRole::TABLE, "member_role");
+        assert!(MemberRole::create_table_sql().ends_with("PRIMARY KEY (\"team\", \"member\"))"));
+    }
+
+    #[test]
+    fn upsert_updates_only_non_key_columns() {
+        let sql = upsert_sql::<Account>();
+        assert!(sql.contains("ON CONFLICT (\"id\") DO UPDATE SET "));
+        assert!(sql.contains("\"handle\" = excluded.\"handle\", "));
+        assert!(!sql.contains("\"id\" = excluded"));
+        assert!(upsert_sql::<MemberRole>().ends_with("DO NOTHING"));
+    }
+
+    #[tokio::test]
+    async fn select_renders_literals_and_validates_columns() {
+        let (_d, db) = open("table_sql.db").await;
+        let sql = db
+            .select::<Account>()
+            .eq("handle", "o'brien".to_string())
+            .filter("balance", Cmp::Gte, 10.0)
+            .eq("note", None::<String>)
+            .order_by_desc("balance")
+            .limit(5)
+            .to_sql()
+            .unwrap();
+        assert!(sql.ends_with(
+            "WHERE \"handle\" = 'o''brien' AND \"balance\" >= 10.0 AND \"note\" IS NULL \
+ORDER BY \"balance\" DESC LIMIT 5"
+        ));
+        let err = db.select::<Account>().eq("nope", 1_i64).to_sql().unwrap_err();
+        assert!(err.to_string().contains("no column 'nope'"));
+        assert!(db.select::<Account>().filter("balance", Cmp::Lt, f64::NAN).to_sql().is_err());
+    }
+
+    #[tokio::test]
+    async fn insert_upsert_delete_round_trip() {
+        let (_d, db) = open("table_crud.db").await;
+        db.insert(&account(1, "ada", 10.0)).await.unwrap();
+        assert!(db.insert(&account(1, "dup", 0.0)).await.is_err());
+        db.upsert(&Account { note: Some("vip".into()), ..account(1, "ada", 99.5) }).await.unwrap();
+
+        let got = db.get_by_pk::<Account>(&1).await.unwrap().unwrap();
+        assert_eq!(got.balance, 99.5);
+        assert_eq!(got.note.as_deref(), Some("vip"));
+
+        assert_eq!(db.delete_by_pk::<Account>(&1).await.unwrap(), 1);
+        assert_eq!(db.delete_by_pk::<Account>(&1).await.unwrap(), 0);
+        assert!(db.get_by_pk::<Account>(&1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn i128_columns_round_trip_past_i64() {
+        let (_d, db) = open("table_i128.db").await;
+        db.create_table::<Ledger>().await.unwrap();
+        let wide = Ledger { id: 1, total: i128::from(i64::MAX) + 1 };
+        let small = Ledger { id: 2, total: -5 };
+        db.insert_all(&[wide.clone(), small.clone()]).await.unwrap();
+
+        assert_eq!(db.get_by_pk::<Ledger>(&1).await.unwrap(), Some(wide.clone()));
+        assert_eq!(db.get_by_pk::<Ledger>(&2).await.unwrap(), Some(small));
+        let found = db.select::<Ledger>().eq("total", wide.total).fetch_live().await.unwrap();
+        assert_eq!(found, vec![wide]);
+    }
+
+    #[tokio::test]
+    async fn composite_keys_bind_in_field_order() {
+        let (_d, db) = open("table_composite.db").await;
+        db.create_table::<MemberRole>().await.unwrap();
+        let rows = vec![
+            MemberRole { team: "core".into(), member: 1 },
+            MemberRole { team: "core".into(), member: 2 },
+        ];
+        assert_eq!(db.insert_all(&rows).await.unwrap(), 2);
+        assert_eq!(db.upsert_all(&rows).await.unwrap(), 0);
+        assert_eq!(db.delete_by_pk::<MemberRole>(&("core".to_string(), 2)).await.unwrap(), 1);
+        let left = db.select::<MemberRole>().fetch_live().await.unwrap();
+        assert_eq!(left, vec![rows[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn select_fetch_live_binds_parameters() {
+        let (_d, db) = open("table_live.db").await;
+        let rows = [account(1, "ada", 5.0), account(2, "grace", 50.0), account(3, "alan", 500.0)];
+        db.insert_all(&rows).await.unwrap();
+        let rows = db
+            .select::<Account>()
+            .filter("balance", Cmp::Gt, 10.0)
+            .in_list("handle", ["grace".to_string(), "alan".to_string()])
+            .order_by("id")
+            .fetch_live()
+            .await
+            .unwrap();
+        let ids: Vec<i64> = rows.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        let none = db.select::<Account>().in_list("id", Vec::<i64>::new()).fetch_live().await;
+        assert!(none.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "analytics")]
+    #[tokio::test]
+    async fn select_fetch_goes_through_analytics() {
+        let (_d, db) = open("table_analytics.db").await;
+        db.insert_all(&[account(1, "ada", 5.0), account(2, "grace", 50.0)]).await.unwrap();
+        db.checkpoint().await.unwrap();
+        let rows = db
+            .select::<Account>()
+            .filter("balance", Cmp::Lt, 10.0)
+            .fetch()
+            .await
+            .unwrap();
+        assert_eq!(rows, vec![account(1, "ada", 5.0)]);
+
+        let skipped = db.select::<Account>().order_by("id").offset(1);
+        assert!(skipped.to_sql().unwrap().ends_with("ORDER BY \"id\" OFFSET 1"));
+        assert_eq!(skipped.fetch().await.unwrap(), vec![account(2, "grace", 50.0)]);
+        assert_eq!(skipped.fetch_live().await.unwrap(), vec![account(2, "grace", 50.0)]);
+    }
+}
@@ -0,0 +1,350 @@
+use std::path::Path;
+
+use crate::read::value_to_turso;
+use crate::{EmbedDb, EmbedError, EmbedRow, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Parquet,
+    Csv,
+    Ndjson,
+}
+
+impl DataFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<DataFormat> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "parquet" => Some(DataFormat::Parquet),
+            "csv" => Some(DataFormat::Csv),
+            "ndjson" | "jsonl" | "json" => Some(DataFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn copy_options(self) -> &'static str {
+        match self {
+            DataFormat::Parquet => "FORMAT PARQUET",
+            DataFormat::Csv => "FORMAT CSV, HEADER true",
+            DataFormat::Ndjson => "FORMAT JSON",
+        }
+    }
+
+    pub(crate) fn reader_sql(self, quoted_path: &str) -> String {
+        match self {
+            DataFormat::Parquet => format!("read_parquet('{quoted_path}')"),
+            DataFormat::Csv => format!("read_csv_auto('{quoted_path}', header = true)"),
+            DataFormat::Ndjson => format!("read_ndjson_auto('{quoted_path}')"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    pub batch_size: usize,
+    pub create_table: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions { batch_size: 1000, create_table: true }
+    }
+}
+
+impl ImportOptions {
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn without_create(mut self) -> Self {
+        self.create_table = false;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub rows: u64,
+    pub batches: u64,
+    pub columns: Vec<String>,
+}
+
+fn check_ident(name: &str) -> Result<()> {
+    let ok = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if ok {
+        Ok(())
+    } else {
+        Err(EmbedError::Other(format!("'{name}' is not a valid table name")))
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn sqlite_type(duck_type: &str) -> &'static str {
+    let t = duck_type.to_ascii_uppercase();
+    let integral = t.contains("INT") && t != "INTERVAL";
+    if t == "BOOLEAN" {
+        "BOOLEAN"
+    } else if integral || t.starts_with("DATE") || t.starts_with("TIME") {
+        "INTEGER"
+    } else if t == "FLOAT" || t == "DOUBLE" || t == "REAL" {
+        "REAL"
+    } else if t == "BLOB" {
+        "BLOB"
+    } else {
+        "TEXT"
+    }
+}
+
+impl EmbedDb {
+    pub async fn export_table(
+        &self,
+        table: &str,
+        format: DataFormat,
+        dest: impl AsRef<Path>,
+    ) -> Result<u64> {
+        check_ident(table)?;
+        self.export_query(&format!("SELECT * FROM {}", quote_ident(table)), format, dest).await
+    }
+
+    pub async fn export_query(
+        &self,
+        sql: &str,
+        format: DataFormat,
+        dest: impl AsRef<Path>,
+    ) -> Result<u64> {
+        let sql = sql.trim().trim_end_matches(';').to_string();
+        let dest = dest.as_ref().to_path_buf();
+        self.on_reader(move |conn, path| crate::analytics::export(conn, path, &sql, format, &dest))
+            .await
+    }
+
+    /// Streams `src` into `table` and commits each batch on its own. A
+    /// failure part-way returns [`EmbedError::Import`] with the number of
+    /// rows already committed; those rows stay in the table.
+    pub async fn import(
+        &self,
+        src: impl AsRef<Path>,
+        format: DataFormat,
+        table: &str,
+        options: &ImportOptions,
+    ) -> Result<ImportReport> {
+        check_ident(table)?;
+        let src = src.as_ref().to_path_buf();
+        let schema = {
+            let src = src.clone();
+            self.on_reader(move |conn, _| crate::analytics::describe_file(conn, &src, format))
+                .await?
+        };
+        if schema.is_empty() {
+            return Err(EmbedError::Other(format!("{} has no columns", src.display())));
+        }
+        if options.create_table {
+            let defs: Vec<String> = schema
+                .iter()
+                .map(|(name, ty)| format!("{} {}", quote_ident(name), sqlite_type(ty)))
+                .collect();
+            let ddl = format!(
+                "CREATE TABLE IF NOT EXISTS {} ({})",
+                quote_ident(table),
+                defs.join(", ")
+            );
+            self.execute(&ddl, ()).await?;
+        }
+        let columns: Vec<String> = schema.into_iter().map(|(name, _)| name).collect();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_ident(table),
+            columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+
+        let (tx_batches, mut rx) = tokio::sync::mpsc::channel::<Vec<EmbedRow>>(2);
+        let batch_size = options.batch_size.max(1);
+        let producer = self.on_reader(move |conn, _| {
+            crate::analytics::scan_file(conn, &src, format, batch_size, &mut |batch| {
+                tx_batches.blocking_send(batch).is_ok()
+            })
+        });
+        // Owns `rx`: if an insert fails the receiver drops with it, the
+        // producer's `blocking_send` errors and the scan stops. Each batch
+        // commits on its own, so the write lock is only held per batch.
+        let insert = &insert;
+        let mut report = ImportReport { rows: 0, batches: 0, columns };
+        let committed = &mut report;
+        let consumer = async move {
+            while let Some(batch) = rx.recv().await {
+                let tx = self.begin().await?;
+                let mut rows = 0;
+                for row in batch {
+                    let params: Vec<turso::Value> = row.0.into_iter().map(value_to_turso).collect();
+                    match tx.execute(insert, params).await {
+                        Ok(n) => rows += n,
+                        Err(e) => {
+                            tx.rollback().await?;
+                            return Err(e);
+                        }
+                    }
+                }
+                tx.commit().await?;
+                committed.rows += rows;
+                committed.batches += 1;
+            }
+            Ok::<_, EmbedError>(())
+        };
+        let (scanned, inserted) = tokio::join!(producer, consumer);
+        match inserted.and(scanned) {
+            Ok(_) => Ok(report),
+            Err(e) => Err(EmbedError::Import { committed: report.rows, source: Box::new(e) }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded(dir: &Path) -> EmbedDb {
+        let db = EmbedDb::open(dir.join("src.db")).await.unwrap();
+        db.execute(
+            "CREATE TABLE events (id INTEGER, kind TEXT, score REAL, ok BOOLEAN, note TEXT)",
+            (),
+        )
+            .await
+            .unwrap();
+        let rows: Vec<(i64, String, f64, bool, Option<String>)> = (0..25)
+            .map(|i| {
+                let note = (i % 5 == 0).then(|| "n'ote".to_string());
+                (i, format!("k{}", i % 3), i as f64 / 2.0, i % 2 == 0, note)
+            })
+            .collect();
+        db.execute_batch("INSERT INTO events VALUES (?, ?, ?, ?, ?)", rows).await.unwrap();
+        db
+    }
+
+    #[test]
+    fn formats_from_extension() {
+        assert_eq!(DataFormat::from_path("a/b.parquet"), Some(DataFormat::Parquet));
+        assert_eq!(DataFormat::from_path("b.CSV"), Some(DataFormat::Csv));
+        assert_eq!(DataFormat::from_path("b.jsonl"), Some(DataFormat::Ndjson));
+        assert_eq!(DataFormat::from_path("b.txt"), None);
+        assert_eq!(DataFormat::from_path("noext"), None);
+    }
+
+    #[test]
+    fn duckdb_types_map_to_sqlite_affinity() {
+        assert_eq!(sqlite_type("BIGINT"), "INTEGER");
+        assert_eq!(sqlite_type("INTEGER"), "INTEGER");
+        assert_eq!(sqlite_type("TIMESTAMP"), "INTEGER");
+        assert_eq!(sqlite_type("DOUBLE"), "REAL");
+        assert_eq!(sqlite_type("BOOLEAN"), "BOOLEAN");
+        assert_eq!(sqlite_type("BLOB"), "BLOB");
+        assert_eq!(sqlite_type("VARCHAR"), "TEXT");
+        assert_eq!(sqlite_type("DECIMAL(18,3)"), "TEXT");
+        assert_eq!(sqlite_type("INTERVAL"), "TEXT");
+    }
+
+    #[test]
+    fn table_names_are_validated() {
+        assert!(check_ident("events_2024").is_ok());
+        assert!(check_ident("events; DROP TABLE x").is_err());
+        assert!(check_ident("").is_err());
+    }
+
+    async fn round_trip(format: DataFormat, file: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path()).await;
+        let dest = dir.path().join(file);
+        assert_eq!(db.export_table("events", format, &dest).await.unwrap(), 25);
+
+        let report = db
+            .import(&dest, format, "events_copy", &ImportOptions::default().with_batch_size(10))
+            .await
+            .unwrap();
+        assert_eq!(report.rows, 25);
+        assert_eq!(report.batches, 3);
+        assert_eq!(report.columns, vec!["id", "kind", "score", "ok", "note"]);
+
+        let diff = "SELECT count(*) FROM (SELECT id, kind, score, note FROM events \
+EXCEPT SELECT id, kind, score, note FROM events_copy)";
+        assert_eq!(db.query_scalar_i64(diff, ()).await.unwrap(), 0);
+        let quoted = db
+            .query_scalar_i64("SELECT count(*) FROM events_copy WHERE note = 'n''ote'", ())
+            .await
+            .unwrap();
+        assert_eq!(quoted, 5);
+    }
+
+    #[tokio::test]
+    async fn parquet_round_trip() {
+        round_trip(DataFormat::Parquet, "events.parquet").await;
+    }
+
+    #[tokio::test]
+    async fn csv_round_trip() {
+        round_trip(DataFormat::Csv, "events.csv").await;
+    }
+
+    #[tokio::test]
+    async fn ndjson_round_trip() {
+        round_trip(DataFormat::Ndjson, "events.ndjson").await;
+    }
+
+    #[tokio::test]
+    async fn export_query_writes_only_selected_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path()).await;
+        let dest = dir.path().join("k0.csv");
+        let n = db
+            .export_query("SELECT id, kind FROM events WHERE kind = 'k0';", DataFormat::Csv, &dest)
+            .await
+            .unwrap();
+        assert_eq!(n, 9);
+        let text = std::fs::read_to_string(&dest).unwrap();
+        assert!(text.starts_with("id,kind"));
+    }
+
+    #[tokio::test]
+    async fn import_into_existing_table_without_create() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path()).await;
+        let dest = dir.path().join("events.parquet");
+        db.export_query("SELECT id, kind FROM events", DataFormat::Parquet, &dest).await.unwrap();
+
+        let opts = ImportOptions::default().without_create();
+        let missing = db.import(&dest, DataFormat::Parquet, "nowhere", &opts).await;
+        assert!(missing.is_err());
+
+        db.execute("CREATE TABLE slim (id INTEGER PRIMARY KEY, kind TEXT)", ()).await.unwrap();
+        let report = db.import(&dest, DataFormat::Parquet, "slim", &opts).await.unwrap();
+        assert_eq!(report.rows, 25);
+        assert_eq!(db.query_scalar_i64("SELECT count(*) FROM slim", ()).await.unwrap(), 25);
+    }
+
+    #[tokio::test]
+    async fn failed_insert_stops_the_scan_and_keeps_committed_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = seeded(dir.path()).await;
+        let dest = dir.path().join("events.parquet");
+        db.export_query("SELECT id, kind FROM events", DataFormat::Parquet, &dest).await.unwrap();
+        db.execute("CREATE TABLE slim (id INTEGER PRIMARY KEY, kind TEXT)", ()).await.unwrap();
+        db.execute("INSERT INTO slim VALUES (20, 'taken')", ()).await.unwrap();
+
+        // One-row batches keep the producer ahead of the failed consumer.
+        let opts = ImportOptions::default().without_create().with_batch_size(1);
+        let import = db.import(&dest, DataFormat::Parquet, "slim", &opts);
+        let result = tokio::time::timeout(std::time::Duration::from_secs(30), import)
+            .await
+            .expect("import hung after a failed insert");
+        let Err(EmbedError::Import { committed, .. }) = result else {
+            panic!("expected an import error, got {result:?}");
+        };
+        assert!(committed < 25);
+        let count = db.query_scalar_i64("SELECT count(*) FROM slim", ()).await.unwrap();
+        assert_eq!(count as u64, committed + 1);
+    }
+}
@@ -2,11 +2,12 @@ use crate::Result;
 
 pub struct EmbedTx<'a> {
     tx: turso::transaction::Transaction<'a>,
+    db: &'a crate::EmbedDb,
 }
 
 impl<'a> EmbedTx<'a> {
-    pub(crate) fn new(tx: turso::transaction::Transaction<'a>) -> Self {
-        EmbedTx { tx }
+    pub(crate) fn new(tx: turso::transaction::Transaction<'a>, db: &'a crate::EmbedDb) -> Self {
+        EmbedTx { tx, db }
     }
 
     pub async fn execute(&self, sql: &str, params: impl turso::IntoParams) -> Result<u64> {
@@ -14,8 +15,38 @@ impl<'a> EmbedTx<'a> {
         Ok(affected)
     }
 
+    /// Streams rows inside the transaction, so they are read from the same
+    /// snapshot its writes apply to.
+    pub async fn query_for_each<F>(
+        &self,
+        sql: &str,
+        params: impl turso::IntoParams,
+        f: F,
+    ) -> Result<u64>
+    where
+        F: FnMut(&crate::EmbedRow),
+    {
+        crate::read::for_each_row(&self.tx, sql, params, f).await
+    }
+
+    pub async fn query_one(
+        &self,
+        sql: &str,
+        params: impl turso::IntoParams,
+    ) -> Result<Option<crate::EmbedRow>> {
+        let mut first = None;
+        crate::read::for_each_row(&self.tx, sql, params, |row| {
+            if first.is_none() {
+                first = Some(row.clone());
+            }
+        })
+        .await?;
+        Ok(first)
+    }
+
     pub async fn commit(self) -> Result<()> {
         self.tx.commit().await?;
+        self.db.notify_commit();
         Ok(())
     }
 
@@ -1,4 +1,6 @@
-use crate::{EmbedDb, EmbedError, Result};
+use crate::filter::{clauses, meta_param, parse_meta};
+use crate::quant::CODE_SQL;
+use crate::{EmbedDb, EmbedError, Quantization, Result, VectorFilter};
 
 pub const VECTOR_TABLE: &str = "_embeddb_vectors";
 
@@ -11,7 +13,8 @@ const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS _embeddb_vectors (\
  dim INTEGER NOT NULL,\
  norm REAL NOT NULL,\
  vec BLOB NOT NULL,\
- created_at INTEGER NOT NULL)";
+ created_at INTEGER NOT NULL,\
+ meta TEXT)";
 
 const CREATE_UNIQUE_INDEX: &str = "CREATE UNIQUE INDEX IF NOT EXISTS _embeddb_vectors_ref_model \
 ON _embeddb_vectors (ref_kind, ref_id, model)";
@@ -19,16 +22,29 @@ ON _embeddb_vectors (ref_kind, ref_id, model)";
 const CREATE_LOOKUP_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_vectors_model_kind \
 ON _embeddb_vectors (model, ref_kind)";
 
+const CREATE_CREATED_INDEX: &str = "CREATE INDEX IF NOT EXISTS _embeddb_vectors_model_created \
+ON _embeddb_vectors (model, created_at)";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VectorSpace {
     pub provider: String,
     pub model: String,
     pub dim: usize,
+    pub quantization: Quantization,
+    pub rescore: Option<usize>,
+    pub keep_f32: bool,
 }
 
 impl VectorSpace {
     pub fn new(provider: impl Into<String>, model: impl Into<String>, dim: usize) -> VectorSpace {
-        VectorSpace { provider: provider.into(), model: model.into(), dim }
+        VectorSpace {
+            provider: provider.into(),
+            model: model.into(),
+            dim,
+            quantization: Quantization::F32,
+            rescore: None,
+            keep_f32: true,
+        }
     }
 }
 
@@ -37,17 +53,7 @@ pub struct VectorHit {
     pub ref_kind: String,
     pub ref_id: String,
     pub score: f32,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct VectorFilter {
-    pub ref_kind: Option<String>,
-}
-
-impl VectorFilter {
-    pub fn kind(ref_kind: impl Into<String>) -> VectorFilter {
-        VectorFilter { ref_kind: Some(ref_kind.into()) }
-    }
+    pub payload: Option<serde_json::Value>,
 }
 
 pub fn pack(vec: &[f32]) -> Vec<u8> {
@@ -59,7 +65,7 @@ pub fn pack(vec: &[f32]) -> Vec<u8> {
 }
 
 pub fn unpack(bytes: &[u8]) -> Result<Vec<f32>> {
-    if bytes.len() % 4 != 0 {
+    if !bytes.len().is_multiple_of(4) {
         return Err(EmbedError::VectorBlob(format!(
             "blob length {} is not a multiple of 4",
             bytes.len()
@@ -88,24 +94,24 @@ pub fn dot(a: &[f32], b: &[f32]) -> f32 {
     a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
 }
 
-fn check_dim(space: &VectorSpace, vec: &[f32]) -> Result<()> {
+pub(crate) fn check_dim(space: &VectorSpace, vec: &[f32]) -> Result<()> {
     if vec.len() != space.dim {
         return Err(EmbedError::VectorDim { expected: space.dim, actual: vec.len() });
     }
     Ok(())
 }
 
-struct TopK {
+pub(crate) struct TopK {
     k: usize,
     items: Vec<VectorHit>,
 }
 
 impl TopK {
-    fn new(k: usize) -> TopK {
+    pub(crate) fn new(k: usize) -> TopK {
         TopK { k, items: Vec::with_capacity(k.saturating_add(1)) }
     }
 
-    fn push(&mut self, hit: VectorHit) {
+    pub(crate) fn push(&mut self, hit: VectorHit) {
         if self.k == 0 {
             return;
         }
@@ -124,7 +130,13 @@ impl TopK {
         self.items.sort_by(|a, b| b.score.total_cmp(&a.score));
     }
 
-    fn finish(mut self) -> Vec<VectorHit> {
+    pub(crate) fn admits(&self, score: f32) -> bool {
+        self.k > 0
+            && (self.items.len() < self.k
+                || score.total_cmp(&self.items[self.k - 1].score) == std::cmp::Ordering::Greater)
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<VectorHit> {
         self.items.sort_by(|a, b| b.score.total_cmp(&a.score));
         self.items
     }
@@ -135,6 +147,22 @@ impl EmbedDb {
         self.execute(CREATE_TABLE, ()).await?;
         self.execute(CREATE_UNIQUE_INDEX, ()).await?;
         self.execute(CREATE_LOOKUP_INDEX, ()).await?;
+        self.ensure_meta_column().await?;
+        self.execute(CREATE_CREATED_INDEX, ()).await?;
+        crate::ann::init(self).await?;
+        crate::fts::init(self).await?;
+        crate::quant::init(self).await?;
+        #[cfg(feature = "jobs")]
+        crate::jobs::init(self).await?;
+        Ok(())
+    }
+
+    async fn ensure_meta_column(&self) -> Result<()> {
+        let cols = self.query("PRAGMA table_info(_embeddb_vectors)", ()).await?;
+        let has_meta = cols.rows.iter().any(|r| r.as_str(1) == Some("meta"));
+        if !has_meta {
+            self.execute("ALTER TABLE _embeddb_vectors ADD COLUMN meta TEXT", ()).await?;
+        }
         Ok(())
     }
 
@@ -144,10 +172,35 @@ impl EmbedDb {
         ref_kind: &str,
         ref_id: &str,
         vec: &[f32],
+    ) -> Result<()> {
+        self.upsert_one(space, ref_kind, ref_id, vec, None, UPSERT_SQL).await
+    }
+
+    /// Replaces the stored metadata with `meta`; `None` clears it.
+    /// [`EmbedDb::vector_upsert`] leaves existing metadata untouched.
+    pub async fn vector_upsert_meta(
+        &self,
+        space: &VectorSpace,
+        ref_kind: &str,
+        ref_id: &str,
+        vec: &[f32],
+        meta: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        self.upsert_one(space, ref_kind, ref_id, vec, meta, UPSERT_META_SQL).await
+    }
+
+    async fn upsert_one(
+        &self,
+        space: &VectorSpace,
+        ref_kind: &str,
+        ref_id: &str,
+        vec: &[f32],
+        meta: Option<&serde_json::Value>,
+        sql: &str,
     ) -> Result<()> {
         check_dim(space, vec)?;
         let (unit, magnitude) = normalize(vec)?;
-        self.execute(UPSERT_SQL, (
+        let mut params = (
             ref_kind,
             ref_id,
             space.provider.as_str(),
@@ -155,31 +208,137 @@ impl EmbedDb {
             space.dim as i64,
             magnitude as f64,
             pack(&unit),
-        ))
-            .await?;
-        Ok(())
+            meta_param(meta)?,
+        );
+        // `begin` opens the transaction on this handle's connection, so the
+        // index and quant mode below are read from the snapshot the row is
+        // written in. A build that commits after that read makes the write
+        // fail instead of landing without a list or code.
+        let tx = self.begin().await?;
+        let index = self.ann_index(space).await?;
+        let quant = self.quant_mode_for_write(space).await?;
+        if quant.is_some_and(|q| !q.keep_f32) {
+            params.6 = Vec::new();
+        }
+        tx.execute(sql, params).await?;
+        if let Some(index) = index {
+            tx.execute(
+                crate::ann::ASSIGN_SQL,
+                (space.model.as_str(), ref_kind, ref_id, index.nearest(&unit) as i64),
+            )
+                .await?;
+        }
+        if let Some(quant) = quant {
+            let code = quant.mode.encode(&unit);
+            tx.execute(CODE_SQL, (space.model.as_str(), ref_kind, ref_id, code)).await?;
+        }
+        tx.commit().await
     }
 
     pub async fn vector_upsert_batch(
         &self,
         space: &VectorSpace,
         items: &[(String, String, Vec<f32>)],
+    ) -> Result<u64> {
+        let items: Vec<(&String, &String, &Vec<f32>, Option<&serde_json::Value>)> =
+            items.iter().map(|(kind, id, vec)| (kind, id, vec, None)).collect();
+        self.upsert_rows(space, &items, UPSERT_SQL).await
+    }
+
+    pub async fn vector_upsert_batch_meta(
+        &self,
+        space: &VectorSpace,
+        items: &[(String, String, Vec<f32>, serde_json::Value)],
+    ) -> Result<u64> {
+        let items: Vec<(&String, &String, &Vec<f32>, Option<&serde_json::Value>)> =
+            items.iter().map(|(kind, id, vec, meta)| (kind, id, vec, Some(meta))).collect();
+        self.upsert_rows(space, &items, UPSERT_META_SQL).await
+    }
+
+    async fn upsert_rows(
+        &self,
+        space: &VectorSpace,
+        items: &[(&String, &String, &Vec<f32>, Option<&serde_json::Value>)],
+        sql: &str,
     ) -> Result<u64> {
         let mut rows = Vec::with_capacity(items.len());
-        for (ref_kind, ref_id, vec) in items {
+        let mut units = Vec::with_capacity(items.len());
+        for (ref_kind, ref_id, vec, meta) in items {
             check_dim(space, vec)?;
             let (unit, magnitude) = normalize(vec)?;
             rows.push((
-                ref_kind.clone(),
-                ref_id.clone(),
+                (*ref_kind).clone(),
+                (*ref_id).clone(),
                 space.provider.clone(),
                 space.model.clone(),
                 space.dim as i64,
                 magnitude as f64,
                 pack(&unit),
+                meta_param(*meta)?,
             ));
+            units.push(unit);
+        }
+        // Resolved inside the transaction, as in `upsert_one`.
+        let tx = self.begin().await?;
+        let index = self.ann_index(space).await?;
+        let quant = self.quant_mode_for_write(space).await?;
+        if quant.is_some_and(|q| !q.keep_f32) {
+            rows.iter_mut().for_each(|row| row.6 = Vec::new());
+        }
+        let mut total = 0_u64;
+        for (row, unit) in rows.into_iter().zip(units) {
+            let (ref_kind, ref_id) = (row.0.clone(), row.1.clone());
+            total += tx.execute(sql, row).await?;
+            if let Some(index) = &index {
+                let list = index.nearest(&unit) as i64;
+                tx.execute(
+                    crate::ann::ASSIGN_SQL,
+                    (space.model.as_str(), ref_kind.as_str(), ref_id.as_str(), list),
+                )
+                    .await?;
+            }
+            if let Some(quant) = quant {
+                let code = quant.mode.encode(&unit);
+                tx.execute(CODE_SQL, (space.model.as_str(), ref_kind, ref_id, code)).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(total)
+    }
+
+    pub async fn vector_set_meta(
+        &self,
+        model: &str,
+        ref_kind: &str,
+        ref_id: &str,
+        meta: Option<&serde_json::Value>,
+    ) -> Result<u64> {
+        self.execute(
+            "UPDATE _embeddb_vectors SET meta = ? WHERE model = ? AND ref_kind = ? AND ref_id = ?",
+            (meta_param(meta)?, model, ref_kind, ref_id),
+        )
+            .await
+    }
+
+    pub async fn vector_meta(
+        &self,
+        model: &str,
+        ref_kind: &str,
+        ref_id: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        let mut rows = self
+            .conn()
+            .query(
+                "SELECT meta FROM _embeddb_vectors WHERE model = ? AND ref_kind = ? AND ref_id = ?",
+                (model, ref_kind, ref_id),
+            )
+            .await?;
+        let mut meta = None;
+        if let Some(row) = rows.next().await? {
+            meta = parse_meta(row.get_value(0)?)?;
         }
-        self.execute_batch(UPSERT_SQL, rows).await
+        while rows.next().await?.is_some() {}
+        Ok(meta)
     }
 
     pub async fn vector_search(
@@ -189,17 +348,34 @@ impl EmbedDb {
         k: usize,
         filter: Option<&VectorFilter>,
     ) -> Result<Vec<VectorHit>> {
-        check_dim(space, query)?;
-        let (unit, _) = normalize(query)?;
-        let kind = filter.and_then(|f| f.ref_kind.clone());
-        let mut rows = match kind.as_deref() {
-            Some(kind) => {
-                self.conn()
-                    .query(SEARCH_SQL_KIND, (space.model.as_str(), kind))
-                    .await?
-            }
-            None => self.conn().query(SEARCH_SQL, (space.model.as_str(),)).await?,
-        };
+        self.vector_search_with(space, query, k, filter, crate::SearchMode::Auto).await
+    }
+
+    pub(crate) async fn vector_search_exact(
+        &self,
+        space: &VectorSpace,
+        unit: &[f32],
+        k: usize,
+        filter: Option<&VectorFilter>,
+    ) -> Result<Vec<VectorHit>> {
+        let (where_sql, extra) = clauses(filter, "")?;
+        let sql = format!("{SEARCH_SQL}{where_sql}");
+        let mut params = vec![turso::Value::Text(space.model.clone())];
+        params.extend(extra);
+        let payload = filter.is_some_and(|f| f.with_payload);
+        self.vector_scan(&sql, params, space, unit, k, payload).await
+    }
+
+    pub(crate) async fn vector_scan(
+        &self,
+        sql: &str,
+        params: impl turso::IntoParams,
+        space: &VectorSpace,
+        unit: &[f32],
+        k: usize,
+        payload: bool,
+    ) -> Result<Vec<VectorHit>> {
+        let mut rows = self.conn().query(sql, params).await?;
         let mut top = TopK::new(k);
         while let Some(row) = rows.next().await? {
             let ref_kind = row.get_value(0)?;
@@ -214,29 +390,50 @@ impl EmbedDb {
                     )))
                 }
             };
+            if bytes.is_empty() {
+                return Err(EmbedError::VectorBlob(format!(
+                    "model '{}' stores codes only; this search needs its f32 vectors",
+                    space.model
+                )));
+            }
             let stored = unpack(&bytes)?;
             if stored.len() != space.dim {
                 return Err(EmbedError::VectorDim { expected: space.dim, actual: stored.len() });
             }
+            let score = dot(unit, &stored);
+            if !top.admits(score) {
+                continue;
+            }
             top.push(VectorHit {
                 ref_kind: value_text(ref_kind)?,
                 ref_id: value_text(ref_id)?,
-                score: dot(&unit, &stored),
+                score,
+                payload: if payload { parse_meta(row.get_value(3)?)? } else { None },
             });
         }
         Ok(top.finish())
     }
 
     pub async fn vector_delete(&self, model: &str, ref_kind: &str, ref_id: &str) -> Result<u64> {
-        self.execute(
-            "DELETE FROM _embeddb_vectors WHERE model = ? AND ref_kind = ? AND ref_id = ?",
-            (model, ref_kind, ref_id),
-        )
-            .await
+        let tx = self.begin().await?;
+        let n = delete_ref(&tx, model, ref_kind, ref_id).await?;
+        tx.commit().await?;
+        Ok(n)
     }
 
     pub async fn vector_delete_model(&self, model: &str) -> Result<u64> {
-        self.execute("DELETE FROM _embeddb_vectors WHERE model = ?", (model,)).await
+        let tx = self.begin().await?;
+        let n = delete_model(&tx, model).await?;
+        tx.commit().await?;
+        self.evict_model(model);
+        Ok(n)
+    }
+
+    /// Drops the cached index and quant mode of a model whose rows were
+    /// deleted by [`delete_model`]; call it once the tx has committed.
+    pub(crate) fn evict_model(&self, model: &str) {
+        self.ann_cache().evict(model);
+        self.quant_cache().evict(model);
     }
 
     pub async fn vector_count(&self, model: &str) -> Result<i64> {
@@ -253,20 +450,82 @@ impl EmbedDb {
     }
 }
 
+pub(crate) async fn delete_ref(
+    tx: &crate::EmbedTx<'_>,
+    model: &str,
+    ref_kind: &str,
+    ref_id: &str,
+) -> Result<u64> {
+    tx.execute(
+        "DELETE FROM _embeddb_ann_assign WHERE model = ? AND ref_kind = ? AND ref_id = ?",
+        (model, ref_kind, ref_id),
+    )
+        .await?;
+    tx.execute(
+        "DELETE FROM _embeddb_vector_codes WHERE model = ? AND ref_kind = ? AND ref_id = ?",
+        (model, ref_kind, ref_id),
+    )
+        .await?;
+    tx.execute(
+        "DELETE FROM _embeddb_vectors WHERE model = ? AND ref_kind = ? AND ref_id = ?",
+        (model, ref_kind, ref_id),
+    )
+        .await
+}
+
+pub(crate) async fn delete_model(tx: &crate::EmbedTx<'_>, model: &str) -> Result<u64> {
+    crate::ann::drop_index(tx, model).await?;
+    tx.execute("DELETE FROM _embeddb_vector_codes WHERE model = ?", (model,)).await?;
+    tx.execute("DELETE FROM _embeddb_quant_meta WHERE model = ?", (model,)).await?;
+    tx.execute("DELETE FROM _embeddb_vectors WHERE model = ?", (model,)).await
+}
+
+// Plain upserts keep the stored meta; the `_META` variant overwrites it,
+// so an explicit `None` clears it.
 const UPSERT_SQL: &str = "INSERT INTO _embeddb_vectors \
-(ref_kind, ref_id, provider, model, dim, norm, vec, created_at) \
-VALUES (?, ?, ?, ?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+(ref_kind, ref_id, provider, model, dim, norm, vec, meta, created_at) \
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
 ON CONFLICT (ref_kind, ref_id, model) DO UPDATE SET \
 provider = excluded.provider, dim = excluded.dim, norm = excluded.norm, \
 vec = excluded.vec, created_at = excluded.created_at";
 
-const SEARCH_SQL: &str =
-    "SELECT ref_kind, ref_id, vec FROM _embeddb_vectors WHERE model = ?";
+const UPSERT_META_SQL: &str = "INSERT INTO _embeddb_vectors \
+(ref_kind, ref_id, provider, model, dim, norm, vec, meta, created_at) \
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, CAST(strftime('%s','now') AS INTEGER)) \
+ON CONFLICT (ref_kind, ref_id, model) DO UPDATE SET \
+provider = excluded.provider, dim = excluded.dim, norm = excluded.norm, \
+vec = excluded.vec, meta = excluded.meta, created_at = excluded.created_at";
 
-const SEARCH_SQL_KIND: &str =
-    "SELECT ref_kind, ref_id, vec FROM _embeddb_vectors WHERE model = ? AND ref_kind = ?";
+const SEARCH_SQL: &str =
+    "SELECT ref_kind, ref_id, vec, meta FROM _embeddb_vectors WHERE model = ?";
+
+// ` AND (...)` matching the given `(ref_kind, ref_id)` pairs with one
+// `ref_id IN (...)` per kind, plus its bind values.
+pub(crate) fn refs_clause<'a>(
+    refs: impl IntoIterator<Item = (&'a str, &'a str)>,
+    prefix: &str,
+) -> (String, Vec<turso::Value>) {
+    let mut by_kind: std::collections::BTreeMap<&str, Vec<&str>> = Default::default();
+    for (kind, id) in refs {
+        by_kind.entry(kind).or_default().push(id);
+    }
+    let mut params = Vec::new();
+    let mut groups = Vec::with_capacity(by_kind.len());
+    for (kind, ids) in by_kind {
+        params.push(turso::Value::Text(kind.to_string()));
+        params.extend(ids.iter().map(|id| turso::Value::Text(id.to_string())));
+        groups.push(format!(
+            "({prefix}ref_kind = ? AND {prefix}ref_id IN ({}))",
+            vec!["?"; ids.len()].join(", ")
+        ));
+    }
+    if groups.is_empty() {
+        return (" AND 0".to_string(), params);
+    }
+    (format!(" AND ({})", groups.join(" OR ")), params)
+}
 
-fn value_text(v: turso::Value) -> Result<String> {
+pub(crate) fn value_text(v: turso::Value) -> Result<String> {
     match v {
         turso::Value::Text(s) => Ok(s),
         other => Err(EmbedError::VectorBlob(format!("expected text column, got {:?}", other))),
@@ -277,6 +536,7 @@ fn value_text(v: turso::Value) -> Result<String> {
 mod tests {
     use super::*;
     use crate::EmbedDb;
+    use serde_json::json;
 
     async fn open(name: &str) -> (tempfile::TempDir, EmbedDb) {
         let dir = tempfile::tempdir().unwrap();
@@ -331,6 +591,7 @@ mod tests {
                 ref_kind: "m".into(),
                 ref_id: i.to_string(),
                 score: *score,
+                payload: None,
             });
         }
         let hits = top.finish();
@@ -342,7 +603,12 @@ mod tests {
     #[test]
     fn topk_zero_returns_empty() {
         let mut top = TopK::new(0);
-        top.push(VectorHit { ref_kind: "m".into(), ref_id: "a".into(), score: 1.0 });
+        top.push(VectorHit {
+            ref_kind: "m".into(),
+            ref_id: "a".into(),
+            score: 1.0,
+            payload: None,
+        });
         assert!(top.finish().is_empty());
     }
 
@@ -428,6 +694,115 @@ mod tests {
         assert_eq!(hits[0].ref_kind, "doc");
     }
 
+    #[tokio::test]
+    async fn metadata_filters_and_payloads() {
+        let (_d, db) = open("vec_meta.db").await;
+        let s = space();
+        let row = |id: &str, v: Vec<f32>, meta| ("msg".to_string(), id.to_string(), v, meta);
+        let rows = vec![
+            row("a", vec![1.0, 0.0, 0.0], json!({"lang": "en", "n": 1})),
+            row("b", vec![0.9, 0.1, 0.0], json!({"lang": "de", "n": 5})),
+            row("c", vec![0.8, 0.2, 0.0], json!({"lang": "fr", "n": 9})),
+        ];
+        db.vector_upsert_batch_meta(&s, &rows).await.unwrap();
+        let q = [1.0, 0.0, 0.0];
+
+        let f = VectorFilter::default().meta_eq("lang", "de");
+        let hits = db.vector_search(&s, &q, 10, Some(&f)).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "b");
+        assert_eq!(hits[0].payload, None);
+
+        let f = VectorFilter::default().meta_range("n", 2, 10).with_payload();
+        let hits = db.vector_search(&s, &q, 10, Some(&f)).await.unwrap();
+        let ids: Vec<&str> = hits.iter().map(|h| h.ref_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+        assert_eq!(hits[1].payload, Some(json!({"lang": "fr", "n": 9})));
+
+        let f = VectorFilter::kind("msg").meta_in("lang", ["en", "fr"]);
+        assert_eq!(db.vector_search(&s, &q, 10, Some(&f)).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn plain_upsert_keeps_existing_metadata() {
+        let (_d, db) = open("vec_meta_keep.db").await;
+        let s = space();
+        let meta = json!({"pinned": true});
+        db.vector_upsert_meta(&s, "msg", "a", &[1.0, 0.0, 0.0], Some(&meta)).await.unwrap();
+        db.vector_upsert(&s, "msg", "a", &[0.0, 1.0, 0.0]).await.unwrap();
+        assert_eq!(db.vector_meta("test-model", "msg", "a").await.unwrap(), Some(meta));
+
+        let f = VectorFilter::default().meta_eq("pinned", true);
+        assert_eq!(db.vector_search(&s, &[0.0, 1.0, 0.0], 1, Some(&f)).await.unwrap().len(), 1);
+
+        db.vector_set_meta("test-model", "msg", "a", None).await.unwrap();
+        assert_eq!(db.vector_meta("test-model", "msg", "a").await.unwrap(), None);
+        assert!(db.vector_search(&s, &[0.0, 1.0, 0.0], 1, Some(&f)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn explicit_none_metadata_clears_it() {
+        let (_d, db) = open("vec_meta_clear.db").await;
+        let s = space();
+        let meta = json!({"pinned": true});
+        db.vector_upsert_meta(&s, "msg", "a", &[1.0, 0.0, 0.0], Some(&meta)).await.unwrap();
+        db.vector_upsert_meta(&s, "msg", "a", &[1.0, 0.0, 0.0], None).await.unwrap();
+        assert_eq!(db.vector_meta("test-model", "msg", "a").await.unwrap(), None);
+
+        db.vector_upsert_meta(&s, "msg", "a", &[1.0, 0.0, 0.0], Some(&meta)).await.unwrap();
+        let items = vec![("msg".to_string(), "a".to_string(), vec![1.0, 0.0, 0.0], json!(null))];
+        db.vector_upsert_batch_meta(&s, &items).await.unwrap();
+        assert_eq!(db.vector_meta("test-model", "msg", "a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn time_window_filters_on_created_at() {
+        let (_d, db) = open("vec_time.db").await;
+        let s = space();
+        db.vector_upsert(&s, "msg", "a", &[1.0, 0.0, 0.0]).await.unwrap();
+        db.execute("UPDATE _embeddb_vectors SET created_at = 100 WHERE ref_id = 'a'", ())
+            .await
+            .unwrap();
+        db.vector_upsert(&s, "msg", "b", &[1.0, 0.0, 0.0]).await.unwrap();
+
+        let f = VectorFilter::default().created_between(50, 150);
+        let hits = db.vector_search(&s, &[1.0, 0.0, 0.0], 10, Some(&f)).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].ref_id, "a");
+        let f = VectorFilter::default().created_after(150);
+        let hits = db.vector_search(&s, &[1.0, 0.0, 0.0], 10, Some(&f)).await.unwrap();
+        assert_eq!(hits[0].ref_id, "b");
+    }
+
+    #[tokio::test]
+    async fn non_object_metadata_is_rejected() {
+        let (_d, db) = open("vec_meta_bad.db").await;
+        let err = db
+            .vector_upsert_meta(&space(), "msg", "a", &[1.0, 0.0, 0.0], Some(&json!([1])))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EmbedError::VectorMeta(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn init_adds_meta_column_to_legacy_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EmbedDb::open(dir.path().join("vec_legacy.db")).await.unwrap();
+        db.execute(
+            "CREATE TABLE _embeddb_vectors (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+ref_kind TEXT NOT NULL, ref_id TEXT NOT NULL, provider TEXT NOT NULL, model TEXT NOT NULL, \
+dim INTEGER NOT NULL, norm REAL NOT NULL, vec BLOB NOT NULL, created_at INTEGER NOT NULL)",
+            (),
+        )
+            .await
+            .unwrap();
+        db.vector_init().await.unwrap();
+        db.vector_init().await.unwrap();
+        let meta = json!({"k": "v"});
+        db.vector_upsert_meta(&space(), "msg", "a", &[1.0, 0.0, 0.0], Some(&meta)).await.unwrap();
+        assert_eq!(db.vector_meta("test-model", "msg", "a").await.unwrap(), Some(meta));
+    }
+
     #[tokio::test]
     async fn dim_mismatch_errors_on_upsert_and_search() {
         let (_d, db) = open("vec_dim.db").await;
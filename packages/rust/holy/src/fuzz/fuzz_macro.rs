@@ -1,13 +1,14 @@
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::spanned::Spanned;
 use syn::{Data, DeriveInput, Fields, Type};
 
-use crate::utils::get_holy_string_value;
+use crate::utils::{get_holy_string_value, get_holy_u32_value, has_holy_argument};
 
 enum FuzzStrategy {
     Ascii(usize, usize),
     AlphanumericStr(usize, usize),
-    Range(String, String),
+    Range(Box<syn::Expr>, Box<syn::Expr>),
 }
 
 enum FuzzTypeKind {
@@ -51,6 +52,53 @@ fn parse_two_args(
     Ok((parts[0].trim().to_string(), parts[1].trim().to_string()))
 }
 
+// A `range` bound is any expression (a literal, a constant, `u8::MAX`, ...).
+// Anything beyond a plain or negated literal is parenthesized so it keeps
+// its meaning inside the generated `as`/arithmetic expressions.
+fn parse_range_bound(raw: &str, span: proc_macro2::Span) -> Result<syn::Expr, syn::Error> {
+    let expr: syn::Expr = syn::parse_str(raw)
+        .map_err(|_| syn::Error::new(span, format!("invalid range bound: '{}'", raw)))?;
+    let lit = match &expr {
+        syn::Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => &*unary.expr,
+        other => other,
+    };
+    if matches!(lit, syn::Expr::Lit(_)) {
+        return Ok(expr);
+    }
+    Ok(syn::Expr::Paren(syn::ExprParen {
+        attrs: Vec::new(),
+        paren_token: Default::default(),
+        expr: Box::new(expr),
+    }))
+}
+
+fn is_float_bound(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Unary(unary) => is_float_bound(&unary.expr),
+        syn::Expr::Lit(lit) => matches!(lit.lit, syn::Lit::Float(_)),
+        _ => false,
+    }
+}
+
+// Integer bounds on float fields are emitted as float literals so
+// `range(0, 10)` type-checks against an `f32`/`f64` field.
+fn float_bound(expr: &syn::Expr) -> proc_macro2::TokenStream {
+    match expr {
+        syn::Expr::Unary(unary) => {
+            let inner = float_bound(&unary.expr);
+            quote! { -#inner }
+        }
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => {
+            let lit = syn::LitFloat::new(&format!("{}.0", int.base10_digits()), int.span());
+            quote! { #lit }
+        }
+        other => quote! { #other },
+    }
+}
+
 fn parse_fuzz_strategy(raw: &str, span: proc_macro2::Span) -> Result<FuzzStrategy, syn::Error> {
     let trimmed = raw.trim();
 
@@ -87,7 +135,10 @@ fn parse_fuzz_strategy(raw: &str, span: proc_macro2::Span) -> Result<FuzzStrateg
         .and_then(|s| s.strip_suffix(')'))
     {
         let (a, b) = parse_two_args(inner, "range", span)?;
-        return Ok(FuzzStrategy::Range(a, b));
+        return Ok(FuzzStrategy::Range(
+            Box::new(parse_range_bound(&a, span)?),
+            Box::new(parse_range_bound(&b, span)?),
+        ));
     }
 
     Err(syn::Error::new(
@@ -99,7 +150,7 @@ fn parse_fuzz_strategy(raw: &str, span: proc_macro2::Span) -> Result<FuzzStrateg
 fn validate_fuzz_strategy(
     strategy: &FuzzStrategy,
     type_kind: &FuzzTypeKind,
-    field_name: &syn::Ident,
+    field_name: &str,
     span: proc_macro2::Span,
 ) -> Result<(), syn::Error> {
     match strategy {
@@ -114,7 +165,18 @@ fn validate_fuzz_strategy(
                 ));
             }
         }
-        FuzzStrategy::Range(_, _) => {
+        FuzzStrategy::Range(min, max) => {
+            if matches!(type_kind, FuzzTypeKind::Integer)
+                && (is_float_bound(min) || is_float_bound(max))
+            {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "fuzz strategy 'range' needs integer bounds for integer field '{}'",
+                        field_name
+                    ),
+                ));
+            }
             if !matches!(type_kind, FuzzTypeKind::Integer | FuzzTypeKind::Float) {
                 return Err(syn::Error::new(
                     span,
@@ -132,7 +194,7 @@ fn validate_fuzz_strategy(
 fn generate_fuzz_default_expr(
     strategy: Option<&FuzzStrategy>,
     type_kind: &FuzzTypeKind,
-    field_name: &syn::Ident,
+    field_name: &str,
     span: proc_macro2::Span,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     match (type_kind, strategy) {
@@ -149,15 +211,12 @@ fn generate_fuzz_default_expr(
         (FuzzTypeKind::String, None) => Ok(quote! { String::from("aaaa") }),
         (FuzzTypeKind::Bool, None) => Ok(quote! { false }),
         (FuzzTypeKind::Integer, Some(FuzzStrategy::Range(min, max))) => {
-            let min_lit: proc_macro2::TokenStream = min.parse().unwrap();
-            let max_lit: proc_macro2::TokenStream = max.parse().unwrap();
-            Ok(quote! { (#min_lit + #max_lit) / 2 })
+            Ok(quote! { (#min + #max) / 2 })
         }
         (FuzzTypeKind::Integer, None) => Ok(quote! { 0 }),
         (FuzzTypeKind::Float, Some(FuzzStrategy::Range(min, max))) => {
-            let min_lit: proc_macro2::TokenStream = min.parse().unwrap();
-            let max_lit: proc_macro2::TokenStream = max.parse().unwrap();
-            Ok(quote! { (#min_lit + #max_lit) / 2.0 })
+            let (min, max) = (float_bound(min), float_bound(max));
+            Ok(quote! { (#min + #max) / 2.0 })
         }
         (FuzzTypeKind::Float, None) => Ok(quote! { 0.0 }),
         (FuzzTypeKind::Other(type_name), _) => Err(syn::Error::new(
@@ -177,7 +236,7 @@ fn generate_fuzz_default_expr(
 fn generate_fuzz_rng_expr(
     strategy: Option<&FuzzStrategy>,
     type_kind: &FuzzTypeKind,
-    field_name: &syn::Ident,
+    field_name: &str,
     span: proc_macro2::Span,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     match (type_kind, strategy) {
@@ -202,18 +261,15 @@ fn generate_fuzz_rng_expr(
         }),
         (FuzzTypeKind::Bool, None) => Ok(quote! { rand::RngExt::random_bool(rng, 0.5) }),
         (FuzzTypeKind::Integer, Some(FuzzStrategy::Range(min, max))) => {
-            let min_lit: proc_macro2::TokenStream = min.parse().unwrap();
-            let max_lit: proc_macro2::TokenStream = max.parse().unwrap();
-            Ok(quote! { rand::RngExt::random_range(rng, #min_lit..=#max_lit) })
+            Ok(quote! { rand::RngExt::random_range(rng, #min..=#max) })
         }
         (FuzzTypeKind::Integer, None) => {
             let random_ident = syn::Ident::new("random", proc_macro2::Span::call_site());
             Ok(quote! { rand::RngExt::#random_ident(rng) })
         }
         (FuzzTypeKind::Float, Some(FuzzStrategy::Range(min, max))) => {
-            let min_lit: proc_macro2::TokenStream = min.parse().unwrap();
-            let max_lit: proc_macro2::TokenStream = max.parse().unwrap();
-            Ok(quote! { rand::RngExt::random_range(rng, #min_lit..=#max_lit) })
+            let (min, max) = (float_bound(min), float_bound(max));
+            Ok(quote! { rand::RngExt::random_range(rng, #min..=#max) })
         }
         (FuzzTypeKind::Float, None) => Ok(quote! {
             rand::RngExt::random_range(rng, -1_000_000.0f64..1_000_000.0f64)
@@ -232,33 +288,103 @@ fn generate_fuzz_rng_expr(
     }
 }
 
-pub fn impl_fuzz_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
-    let struct_name = &ast.ident;
-    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+// proptest strategy for one field. String constraints become regex
+// strategies and numeric ones plain ranges, so failing cases shrink
+// instead of staying as opaque rng output.
+fn generate_fuzz_proptest_expr(
+    strategy: Option<&FuzzStrategy>,
+    type_kind: &FuzzTypeKind,
+    ty: &Type,
+) -> proc_macro2::TokenStream {
+    match (type_kind, strategy) {
+        (FuzzTypeKind::String, Some(FuzzStrategy::Ascii(min, max))) => {
+            let pattern = format!("[ -~]{{{},{}}}", min, max);
+            quote! { ::proptest::strategy::Strategy::boxed(#pattern) }
+        }
+        (FuzzTypeKind::String, Some(FuzzStrategy::AlphanumericStr(min, max))) => {
+            let pattern = format!("[a-zA-Z0-9]{{{},{}}}", min, max);
+            quote! { ::proptest::strategy::Strategy::boxed(#pattern) }
+        }
+        (FuzzTypeKind::String, _) => quote! { ::proptest::strategy::Strategy::boxed("[ -~]{0,64}") },
+        (
+            FuzzTypeKind::Integer | FuzzTypeKind::Float,
+            Some(FuzzStrategy::Range(min, max)),
+        ) => quote! { ::proptest::strategy::Strategy::boxed((#min as #ty)..=(#max as #ty)) },
+        (FuzzTypeKind::Float, _) => quote! {
+            ::proptest::strategy::Strategy::boxed((-1_000_000.0 as #ty)..(1_000_000.0 as #ty))
+        },
+        _ => quote! { ::proptest::strategy::Strategy::boxed(::proptest::arbitrary::any::<#ty>()) },
+    }
+}
 
-    let fields = match &ast.data {
-        Data::Struct(data) => match &data.fields {
-            Fields::Named(named) => &named.named,
-            _ => {
-                return Err(syn::Error::new_spanned(
-                    ast,
-                    "Fuzz macro only supports structs with named fields",
-                ));
+// `arbitrary` expression for one field, drawing from `u` with the same
+// bounds as `fuzz_with`.
+fn generate_fuzz_arbitrary_expr(
+    strategy: Option<&FuzzStrategy>,
+    type_kind: &FuzzTypeKind,
+    ty: &Type,
+) -> proc_macro2::TokenStream {
+    let ascii = |min: usize, max: usize| {
+        quote! {
+            {
+                let len = u.int_in_range(#min..=#max)?;
+                (0..len)
+                    .map(|_| u.int_in_range(b' '..=b'~').map(char::from))
+                    .collect::<::arbitrary::Result<String>>()?
             }
-        },
-        _ => {
-            return Err(syn::Error::new_spanned(
-                ast,
-                "Fuzz macro only supports structs",
-            ));
         }
     };
+    let float = |min: proc_macro2::TokenStream, max: proc_macro2::TokenStream| {
+        quote! {
+            {
+                let t = f64::from(<u32 as ::arbitrary::Arbitrary>::arbitrary(u)?) / f64::from(u32::MAX);
+                ((#min) as f64 + ((#max) as f64 - (#min) as f64) * t) as #ty
+            }
+        }
+    };
+    match (type_kind, strategy) {
+        (FuzzTypeKind::String, Some(FuzzStrategy::Ascii(min, max))) => ascii(*min, *max),
+        (FuzzTypeKind::String, Some(FuzzStrategy::AlphanumericStr(min, max))) => quote! {
+            {
+                let len = u.int_in_range(#min..=#max)?;
+                let chars: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+                (0..len)
+                    .map(|_| u.choose(chars).map(|c| *c as char))
+                    .collect::<::arbitrary::Result<String>>()?
+            }
+        },
+        (FuzzTypeKind::String, _) => ascii(0, 64),
+        (FuzzTypeKind::Integer, Some(FuzzStrategy::Range(min, max))) => {
+            quote! { u.int_in_range((#min as #ty)..=(#max as #ty))? }
+        }
+        (FuzzTypeKind::Float, Some(FuzzStrategy::Range(min, max))) => {
+            float(quote! { #min }, quote! { #max })
+        }
+        (FuzzTypeKind::Float, _) => float(quote! { -1_000_000.0 }, quote! { 1_000_000.0 }),
+        _ => quote! { <#ty as ::arbitrary::Arbitrary>::arbitrary(u)? },
+    }
+}
+
+// Constructor expressions for one set of fields, built on `path` (`Self`
+// for structs, `Self::Variant` for enum variants).
+struct FuzzCtors {
+    default: proc_macro2::TokenStream,
+    rng: proc_macro2::TokenStream,
+    proptest: proc_macro2::TokenStream,
+    arbitrary: proc_macro2::TokenStream,
+}
 
-    let mut default_field_inits = Vec::new();
-    let mut rng_field_inits = Vec::new();
+fn fields_ctor(path: &proc_macro2::TokenStream, fields: &Fields) -> Result<FuzzCtors, syn::Error> {
+    let mut default_exprs = Vec::new();
+    let mut rng_exprs = Vec::new();
+    let mut proptest_exprs = Vec::new();
+    let mut arbitrary_exprs = Vec::new();
 
-    for field in fields.iter() {
-        let field_name = field.ident.as_ref().unwrap();
+    for (index, field) in fields.iter().enumerate() {
+        let (field_name, span) = match &field.ident {
+            Some(ident) => (ident.to_string(), ident.span()),
+            None => (index.to_string(), field.ty.span()),
+        };
         let type_kind = classify_fuzz_type(&field.ty);
 
         let strategy = match get_holy_string_value(&field.attrs, "fuzz") {
@@ -267,36 +393,208 @@ pub fn impl_fuzz_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
         };
 
         if let Some(ref strat) = strategy {
-            validate_fuzz_strategy(strat, &type_kind, field_name, field_name.span())?;
+            validate_fuzz_strategy(strat, &type_kind, &field_name, span)?;
         }
 
-        let default_expr = generate_fuzz_default_expr(
+        default_exprs.push(generate_fuzz_default_expr(
             strategy.as_ref(),
             &type_kind,
-            field_name,
-            field_name.span(),
-        )?;
-        let rng_expr =
-            generate_fuzz_rng_expr(strategy.as_ref(), &type_kind, field_name, field_name.span())?;
-
-        default_field_inits.push(quote! { #field_name: #default_expr });
-        rng_field_inits.push(quote! { #field_name: #rng_expr });
+            &field_name,
+            span,
+        )?);
+        rng_exprs.push(generate_fuzz_rng_expr(strategy.as_ref(), &type_kind, &field_name, span)?);
+        proptest_exprs.push(generate_fuzz_proptest_expr(strategy.as_ref(), &type_kind, &field.ty));
+        arbitrary_exprs.push(generate_fuzz_arbitrary_expr(strategy.as_ref(), &type_kind, &field.ty));
+    }
+
+    // proptest only implements `Strategy` for small tuples, so the field
+    // strategies are nested as pairs `(s0, (s1, (s2, s3)))` and the
+    // matching pattern is destructured inside `prop_map`.
+    let bindings: Vec<syn::Ident> = (0..proptest_exprs.len())
+        .map(|i| syn::Ident::new(&format!("__f{}", i), proc_macro2::Span::call_site()))
+        .collect();
+    let build = |exprs: &[proc_macro2::TokenStream]| match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote! { #path { #(#names: #exprs,)* } }
+        }
+        Fields::Unnamed(_) => quote! { #path(#(#exprs),*) },
+        Fields::Unit => quote! { #path },
+    };
+    let binding_exprs: Vec<_> = bindings.iter().map(|b| quote! { #b }).collect();
+    let proptest = match proptest_exprs.len() {
+        0 => {
+            let value = build(&[]);
+            quote! { ::proptest::strategy::Strategy::boxed(::proptest::strategy::LazyJust::new(|| #value)) }
+        }
+        _ => {
+            let mut strategy = proptest_exprs.last().unwrap().clone();
+            let mut pattern = bindings.last().map(|b| quote! { #b }).unwrap();
+            for (expr, binding) in proptest_exprs.iter().zip(&bindings).rev().skip(1) {
+                strategy = quote! { (#expr, #strategy) };
+                pattern = quote! { (#binding, #pattern) };
+            }
+            let value = build(&binding_exprs);
+            quote! {
+                ::proptest::strategy::Strategy::boxed(
+                    ::proptest::strategy::Strategy::prop_map(#strategy, |#pattern| #value)
+                )
+            }
+        }
+    };
+
+    Ok(FuzzCtors {
+        default: build(&default_exprs),
+        rng: build(&rng_exprs),
+        proptest,
+        arbitrary: build(&arbitrary_exprs),
+    })
+}
+
+// Weighted variant pick shared by `fuzz_with` and `Arbitrary`: `pick` is a
+// `u32` in `0..total`, matched against cumulative weights.
+fn weighted_match(
+    pick: proc_macro2::TokenStream,
+    arms: &[(u32, proc_macro2::TokenStream)],
+) -> proc_macro2::TokenStream {
+    let last = arms.len() - 1;
+    let mut upper = 0u32;
+    let arms = arms.iter().enumerate().map(|(i, (weight, ctor))| {
+        upper += weight;
+        if i == last {
+            quote! { _ => #ctor }
+        } else {
+            quote! { __pick if __pick < #upper => #ctor }
+        }
+    });
+    quote! {
+        match #pick {
+            #(#arms,)*
+        }
     }
+}
+
+pub fn impl_fuzz_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let type_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let (default_body, rng_body, proptest_body, arbitrary_body) = match &ast.data {
+        Data::Struct(data) => {
+            let ctors = fields_ctor(&quote! { Self }, &data.fields)?;
+            let arbitrary = ctors.arbitrary;
+            (ctors.default, ctors.rng, ctors.proptest, quote! { Ok(#arbitrary) })
+        }
+        Data::Enum(data) => {
+            // `fuzz_default()` builds the first variant that can be picked;
+            // `fuzz_with()`, the proptest strategy and `Arbitrary` draw a
+            // variant with probability proportional to its
+            // `#[holy(weight = N)]` (default 1, 0 disables it).
+            let mut weighted = Vec::new();
+            for variant in &data.variants {
+                let weight = get_holy_u32_value(&variant.attrs, "weight")?.map_or(1, |(w, _)| w);
+                if weight == 0 {
+                    continue;
+                }
+                let variant_name = &variant.ident;
+                let ctors = fields_ctor(&quote! { Self::#variant_name }, &variant.fields)?;
+                weighted.push((weight, ctors));
+            }
+            if weighted.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    type_name,
+                    format!(
+                        "Fuzz: enum '{}' has no variant with a non-zero weight",
+                        type_name
+                    ),
+                ));
+            }
+
+            let default_body = weighted[0].1.default.clone();
+            let total: u32 = weighted
+                .iter()
+                .try_fold(0u32, |acc, (w, _)| acc.checked_add(*w))
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(type_name, "Fuzz: variant weights overflow u32")
+                })?;
+            let rng_arms: Vec<_> = weighted.iter().map(|(w, c)| (*w, c.rng.clone())).collect();
+            let rng_body = weighted_match(
+                quote! { rand::RngExt::random_range(rng, 0u32..#total) },
+                &rng_arms,
+            );
+            let arbitrary_arms: Vec<_> =
+                weighted.iter().map(|(w, c)| (*w, c.arbitrary.clone())).collect();
+            let arbitrary_match = weighted_match(
+                quote! { u.int_in_range(0u32..=#total - 1)? },
+                &arbitrary_arms,
+            );
+            let proptest_arms = weighted.iter().map(|(w, c)| {
+                let strategy = &c.proptest;
+                quote! { (#w, #strategy) }
+            });
+            let proptest_body = quote! {
+                ::proptest::strategy::Strategy::boxed(
+                    ::proptest::strategy::Union::new_weighted(vec![#(#proptest_arms),*])
+                )
+            };
+            (default_body, rng_body, proptest_body, quote! { Ok(#arbitrary_match) })
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ast,
+                "Fuzz macro only supports structs and enums",
+            ));
+        }
+    };
+
+    // Both impls are opt-in per type through `#[holy(fuzz_proptest)]` /
+    // `#[holy(fuzz_arbitrary)]` and compile only under the deriving crate's
+    // `proptest` / `arbitrary` feature, so the dependency can stay optional.
+    let proptest_impl = if has_holy_argument(&ast.attrs, "fuzz_proptest") {
+        quote! {
+            #[cfg(feature = "proptest")]
+            impl #impl_generics ::proptest::arbitrary::Arbitrary for #type_name #ty_generics #where_clause {
+                type Parameters = ();
+                type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    #proptest_body
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let arbitrary_impl = if has_holy_argument(&ast.attrs, "fuzz_arbitrary") {
+        let mut generics = ast.generics.clone();
+        generics.params.insert(0, syn::parse_quote! { 'holy_arbitrary });
+        let (arb_impl_generics, _, _) = generics.split_for_impl();
+        quote! {
+            #[cfg(feature = "arbitrary")]
+            impl #arb_impl_generics ::arbitrary::Arbitrary<'holy_arbitrary> for #type_name #ty_generics #where_clause {
+                fn arbitrary(u: &mut ::arbitrary::Unstructured<'holy_arbitrary>) -> ::arbitrary::Result<Self> {
+                    #arbitrary_body
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
 
     let expanded = quote! {
-        impl #impl_generics #struct_name #ty_generics #where_clause {
+        impl #impl_generics #type_name #ty_generics #where_clause {
             pub fn fuzz_default() -> Self {
-                Self {
-                    #(#default_field_inits,)*
-                }
+                #default_body
             }
 
             pub fn fuzz_with(rng: &mut impl rand::RngExt) -> Self {
-                Self {
-                    #(#rng_field_inits,)*
-                }
+                #rng_body
             }
         }
+
+        #proptest_impl
+
+        #arbitrary_impl
     };
 
     Ok(TokenStream::from(expanded))
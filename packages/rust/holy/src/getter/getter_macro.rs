@@ -16,10 +16,17 @@ pub fn impl_getters_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error>
                 .filter(|f| !should_skip(&f.attrs))
                 .map(generate_getter)
                 .collect::<Result<Vec<_>, syn::Error>>()?,
-            _ => {
+            Fields::Unnamed(fields) => fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| !should_skip(&f.attrs))
+                .map(|(index, f)| generate_positional_getter(index, f))
+                .collect::<Result<Vec<_>, syn::Error>>()?,
+            Fields::Unit => {
                 return Err(syn::Error::new_spanned(
                     ast,
-                    "Getters macro only supports structs with named fields",
+                    "Getters macro only supports structs with named or tuple fields",
                 ));
             }
         },
@@ -54,3 +61,21 @@ fn generate_getter(field: &Field) -> Result<proc_macro2::TokenStream, syn::Error
 
     Ok(getter)
 }
+
+// Tuple structs get `_0()`, `_1()`, ... mirroring the `.0` field syntax,
+// since a method name cannot start with a digit.
+fn generate_positional_getter(
+    index: usize,
+    field: &Field,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let member = syn::Index::from(index);
+    let getter_name = syn::Ident::new(&format!("_{}", index), proc_macro2::Span::call_site());
+    let field_type = &field.ty;
+    let getter_vis = determine_visibility(&field.vis, &field.attrs)?;
+
+    Ok(quote! {
+        #getter_vis fn #getter_name(&self) -> &#field_type {
+            &self.#member
+        }
+    })
+}
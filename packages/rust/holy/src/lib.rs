@@ -7,6 +7,7 @@ mod observer;
 mod sanitize;
 mod setter;
 mod utils;
+mod validate;
 
 #[proc_macro_derive(Getters, attributes(holy))]
 pub fn getters_derive(input: TokenStream) -> TokenStream {
@@ -32,8 +33,19 @@ pub fn setters_derive(input: TokenStream) -> TokenStream {
 	setter::impl_setters_macro(&ast).unwrap_or_else(|e| e.to_compile_error().into())
 }
 
+/// With `#[holy(fuzz_proptest)]` / `#[holy(fuzz_arbitrary)]` on the type,
+/// the derive also emits `proptest` / `arbitrary` impls gated on
+/// `#[cfg(feature = "proptest")]` / `#[cfg(feature = "arbitrary")]`. The
+/// deriving crate must declare those features (an optional dependency of
+/// the same name does), or rustc reports `unexpected_cfgs`.
 #[proc_macro_derive(Fuzz, attributes(holy))]
 pub fn fuzz_derive(input: TokenStream) -> TokenStream {
 	let ast = parse_macro_input!(input as DeriveInput);
 	fuzz::impl_fuzz_macro(&ast).unwrap_or_else(|e| e.to_compile_error().into())
 }
+
+#[proc_macro_derive(Validate, attributes(holy))]
+pub fn validate_derive(input: TokenStream) -> TokenStream {
+	let ast = parse_macro_input!(input as DeriveInput);
+	validate::impl_validate_macro(&ast).unwrap_or_else(|e| e.to_compile_error().into())
+}
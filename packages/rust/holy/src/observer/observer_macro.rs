@@ -2,11 +2,11 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{Data, DeriveInput, Fields};
 
-use crate::utils::has_holy_argument;
+use crate::utils::{get_holy_u32_value, has_holy_argument};
 
 pub fn impl_observer_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
 	let struct_name = &ast.ident;
-	let (_impl_generics, ty_generics, _where_clause) = ast.generics.split_for_impl();
+	let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
 	let fields = match &ast.data {
 		Data::Struct(data) => match &data.fields {
@@ -85,20 +85,164 @@ pub fn impl_observer_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error>
 		}
 	});
 
+	let subscription_name =
+		syn::Ident::new(&format!("{}Subscription", struct_name), struct_name.span());
+	let async_enabled = has_holy_argument(&ast.attrs, "observer_tokio");
+
+	let mut subscriber_fields = Vec::new();
+	let mut subscriber_defaults = Vec::new();
+	let mut diff_methods = Vec::new();
+	let mut update_methods = Vec::new();
+
+	for f in &observed {
+		let name = f.ident.as_ref().unwrap();
+		let ty = &f.ty;
+		let ident = |pattern: &str| {
+			syn::Ident::new(&pattern.replace("{}", &name.to_string()), name.span())
+		};
+		let subscribers = ident("{}_subscribers");
+		let changes_tx = ident("{}_changes");
+		let watch_tx = ident("{}_watch");
+		let subscribe = ident("subscribe_{}");
+		let publish = ident("publish_{}");
+		let changes = ident("changes_{}");
+		let watch = ident("watch_{}");
+		let update = ident("update_{}");
+		let notify = ident("notify_{}_observers");
+
+		subscriber_fields.push(quote! {
+			#subscribers: ::std::sync::Arc<::std::sync::Mutex<Vec<(u64, ::std::sync::Arc<dyn Fn(&#ty, &#ty) + Send + Sync>)>>>
+		});
+		subscriber_defaults.push(quote! { #subscribers: Default::default() });
+
+		// Channels are only created with `#[holy(observer_tokio)]` on the
+		// struct; `watch` always holds the latest value, so it is updated
+		// even with no receivers, while `broadcast` carries every
+		// `(old, new)` and skips the clone when nobody is listening.
+		let (async_publish, async_methods) = if async_enabled {
+			let capacity = get_holy_u32_value(&f.attrs, "capacity")?.map_or(64, |(c, _)| c).max(1) as usize;
+			subscriber_fields.push(quote! {
+				#changes_tx: ::tokio::sync::broadcast::Sender<(#ty, #ty)>
+			});
+			subscriber_fields.push(quote! {
+				#watch_tx: ::tokio::sync::watch::Sender<Option<#ty>>
+			});
+			subscriber_defaults.push(quote! {
+				#changes_tx: ::tokio::sync::broadcast::channel(#capacity).0
+			});
+			subscriber_defaults.push(quote! {
+				#watch_tx: ::tokio::sync::watch::channel(None).0
+			});
+			(
+				quote! {
+					if self.#changes_tx.receiver_count() > 0 {
+						let _ = self.#changes_tx.send((old.clone(), new.clone()));
+					}
+					self.#watch_tx.send_replace(Some(new.clone()));
+				},
+				quote! {
+					pub fn #changes(&self) -> ::tokio::sync::broadcast::Receiver<(#ty, #ty)> {
+						self.#changes_tx.subscribe()
+					}
+
+					pub fn #watch(&self) -> ::tokio::sync::watch::Receiver<Option<#ty>> {
+						self.#watch_tx.subscribe()
+					}
+				},
+			)
+		} else {
+			(quote! {}, quote! {})
+		};
+
+		diff_methods.push(quote! {
+			pub fn #subscribe<F>(&self, observer: F) -> #subscription_name
+			where
+				F: Fn(&#ty, &#ty) + 'static + Send + Sync,
+			{
+				let id = self.next_id.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed);
+				self.#subscribers
+					.lock()
+					.unwrap_or_else(|e| e.into_inner())
+					.push((id, ::std::sync::Arc::new(observer)));
+				let subscribers = ::std::sync::Arc::downgrade(&self.#subscribers);
+				#subscription_name {
+					cancel: Some(Box::new(move || {
+						if let Some(subscribers) = subscribers.upgrade() {
+							subscribers
+								.lock()
+								.unwrap_or_else(|e| e.into_inner())
+								.retain(|(other, _)| *other != id);
+						}
+					})),
+				}
+			}
+
+			pub fn #publish(&self, old: &#ty, new: &#ty) {
+				// Snapshot first so observers may subscribe or drop their
+				// own guard without deadlocking.
+				let snapshot: Vec<_> = self.#subscribers
+					.lock()
+					.unwrap_or_else(|e| e.into_inner())
+					.iter()
+					.map(|(_, observer)| observer.clone())
+					.collect();
+				for observer in snapshot {
+					observer(old, new);
+				}
+				#async_publish
+			}
+
+			#async_methods
+		});
+
+		update_methods.push(quote! {
+			pub fn #update(&mut self, value: #ty, observers: &#companion_name) -> #ty {
+				let old = ::std::mem::replace(&mut self.#name, value);
+				observers.#publish(&old, &self.#name);
+				observers.#notify(self);
+				old
+			}
+		});
+	}
+
 	let expanded = quote! {
 		pub struct #companion_name {
 			#(#storage_fields,)*
+			#(#subscriber_fields,)*
+			next_id: ::std::sync::atomic::AtomicU64,
+		}
+
+		#[must_use = "dropping the subscription unsubscribes the observer"]
+		pub struct #subscription_name {
+			cancel: Option<Box<dyn FnOnce() + Send + Sync>>,
+		}
+
+		impl #subscription_name {
+			pub fn detach(mut self) {
+				self.cancel = None;
+			}
+		}
+
+		impl Drop for #subscription_name {
+			fn drop(&mut self) {
+				if let Some(cancel) = self.cancel.take() {
+					cancel();
+				}
+			}
 		}
 
 		impl #companion_name {
 			pub fn new() -> Self {
 				Self {
 					#(#storage_defaults,)*
+					#(#subscriber_defaults,)*
+					next_id: ::std::sync::atomic::AtomicU64::new(0),
 				}
 			}
 
 			#(#add_methods)*
 			#(#notify_methods)*
+			#(#diff_methods)*
 		}
 
 		impl Default for #companion_name {
@@ -106,6 +250,10 @@ pub fn impl_observer_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error>
 				Self::new()
 			}
 		}
+
+		impl #impl_generics #struct_name #ty_generics #where_clause {
+			#(#update_methods)*
+		}
 	};
 
 	Ok(TokenStream::from(expanded))
@@ -1,8 +1,8 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, Field, Fields, GenericArgument, PathArguments, Type};
+use syn::{Data, DeriveInput, Field, Fields, GenericArgument, PathArguments, Type, Variant};
 
-use crate::utils::{determine_visibility, get_holy_string_value};
+use crate::utils::{determine_visibility, get_holy_string_value, split_rules};
 
 enum SanitizeRule {
     Trim,
@@ -15,81 +15,81 @@ enum SanitizeRule {
     ControlStrip,
     Slug,
     Clamp(proc_macro2::TokenStream, proc_macro2::TokenStream),
+    Nested,
+    Each(Vec<SanitizeRule>),
+    Keys(Vec<SanitizeRule>),
+    Values(Vec<SanitizeRule>),
+    MaxLen(usize),
+    Dedupe,
 }
 
-enum FieldTypeKind {
+impl SanitizeRule {
+    fn name(&self) -> &'static str {
+        match self {
+            SanitizeRule::Trim => "trim",
+            SanitizeRule::Lowercase => "lowercase",
+            SanitizeRule::Uppercase => "uppercase",
+            SanitizeRule::Truncate(_) => "truncate",
+            SanitizeRule::Alphanumeric => "alphanumeric",
+            SanitizeRule::EscapeHtml => "escape_html",
+            SanitizeRule::NulStrip => "nul_strip",
+            SanitizeRule::ControlStrip => "control_strip",
+            SanitizeRule::Slug => "slug",
+            SanitizeRule::Clamp(_, _) => "clamp",
+            SanitizeRule::Nested => "nested",
+            SanitizeRule::Each(_) => "each",
+            SanitizeRule::Keys(_) => "keys",
+            SanitizeRule::Values(_) => "values",
+            SanitizeRule::MaxLen(_) => "max_len",
+            SanitizeRule::Dedupe => "dedupe",
+        }
+    }
+}
+
+enum FieldTypeKind<'a> {
     String,
-    OptionString,
     Numeric,
+    Option(&'a Type),
+    Vec(&'a Type),
+    Map(&'a Type, &'a Type),
     Other,
 }
 
-fn is_string_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty
-        && let Some(segment) = type_path.path.segments.last()
-    {
-        return segment.ident == "String";
-    }
-    false
+fn type_args(segment: &syn::PathSegment) -> Vec<&Type> {
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Vec::new();
+    };
+    args.args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
 }
 
-fn classify_type(ty: &Type) -> FieldTypeKind {
+fn classify_type(ty: &Type) -> FieldTypeKind<'_> {
     if let Type::Path(type_path) = ty
         && let Some(segment) = type_path.path.segments.last()
     {
         let ident = segment.ident.to_string();
-        if ident == "Option" {
-            if let PathArguments::AngleBracketed(args) = &segment.arguments
-                && let Some(GenericArgument::Type(inner)) = args.args.first()
-                && is_string_type(inner)
-            {
-                return FieldTypeKind::OptionString;
-            }
-            return FieldTypeKind::Other;
-        }
-        return match ident.as_str() {
-            "String" => FieldTypeKind::String,
-            "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "f32" | "f64"
-            | "isize" | "usize" => FieldTypeKind::Numeric,
+        let args = type_args(segment);
+        return match (ident.as_str(), args.as_slice()) {
+            ("String", _) => FieldTypeKind::String,
+            ("Option", [inner]) => FieldTypeKind::Option(inner),
+            ("Vec", [inner]) => FieldTypeKind::Vec(inner),
+            ("HashMap" | "BTreeMap", [key, value, ..]) => FieldTypeKind::Map(key, value),
+            (
+                "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "f32" | "f64"
+                | "isize" | "usize",
+                _,
+            ) => FieldTypeKind::Numeric,
             _ => FieldTypeKind::Other,
         };
     }
     FieldTypeKind::Other
 }
 
-fn split_rules(input: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    let mut current = String::new();
-    let mut depth = 0u32;
-    for ch in input.chars() {
-        match ch {
-            '(' => {
-                depth += 1;
-                current.push(ch);
-            }
-            ')' => {
-                depth = depth.saturating_sub(1);
-                current.push(ch);
-            }
-            ',' if depth == 0 => {
-                let trimmed = current.trim().to_string();
-                if !trimmed.is_empty() {
-                    result.push(trimmed);
-                }
-                current.clear();
-            }
-            _ => {
-                current.push(ch);
-            }
-        }
-    }
-    let trimmed = current.trim().to_string();
-    if !trimmed.is_empty() {
-        result.push(trimmed);
-    }
-    result
-}
-
 fn parse_sanitize_rules(
     raw: &str,
     span: proc_macro2::Span,
@@ -145,6 +145,27 @@ fn parse_sanitize_rules(
                 syn::Error::new(span, format!("invalid clamp max argument: '{}'", max_raw))
             })?;
             SanitizeRule::Clamp(min_ts, max_ts)
+        } else if token == "nested" {
+            SanitizeRule::Nested
+        } else if token == "dedupe" {
+            SanitizeRule::Dedupe
+        } else if let Some(inner) = token
+            .strip_prefix("max_len(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            let n: usize = inner.trim().parse().map_err(|_| {
+                syn::Error::new(span, format!("invalid max_len length: '{}'", inner.trim()))
+            })?;
+            SanitizeRule::MaxLen(n)
+        } else if let Some(inner) = token.strip_prefix("each(").and_then(|s| s.strip_suffix(')')) {
+            SanitizeRule::Each(parse_sanitize_rules(inner, span)?)
+        } else if let Some(inner) = token.strip_prefix("keys(").and_then(|s| s.strip_suffix(')')) {
+            SanitizeRule::Keys(parse_sanitize_rules(inner, span)?)
+        } else if let Some(inner) = token
+            .strip_prefix("values(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            SanitizeRule::Values(parse_sanitize_rules(inner, span)?)
         } else {
             return Err(syn::Error::new(
                 span,
@@ -157,13 +178,44 @@ fn parse_sanitize_rules(
     Ok(rules)
 }
 
+fn describe(type_kind: &FieldTypeKind) -> &'static str {
+    match type_kind {
+        FieldTypeKind::String => "type String",
+        FieldTypeKind::Numeric => "a numeric type",
+        FieldTypeKind::Option(_) => "an Option type",
+        FieldTypeKind::Vec(_) => "a Vec type",
+        FieldTypeKind::Map(_, _) => "a map type",
+        FieldTypeKind::Other => "an unsupported type",
+    }
+}
+
+fn type_mismatch(
+    rule: &SanitizeRule,
+    expected: &str,
+    type_kind: &FieldTypeKind,
+    field_name: &str,
+    span: proc_macro2::Span,
+) -> syn::Error {
+    let actual = describe(type_kind);
+    syn::Error::new(
+        span,
+        format!(
+            "sanitize rule '{}' is only valid for {} fields, but field '{}' has {}",
+            rule.name(),
+            expected,
+            field_name,
+            actual
+        ),
+    )
+}
+
 fn validate_rule_for_type(
     rule: &SanitizeRule,
     type_kind: &FieldTypeKind,
-    field_name: &syn::Ident,
+    field_name: &str,
     span: proc_macro2::Span,
 ) -> Result<(), syn::Error> {
-    match rule {
+    let (valid, expected) = match rule {
         SanitizeRule::Trim
         | SanitizeRule::Lowercase
         | SanitizeRule::Uppercase
@@ -172,45 +224,169 @@ fn validate_rule_for_type(
         | SanitizeRule::EscapeHtml
         | SanitizeRule::NulStrip
         | SanitizeRule::ControlStrip
-        | SanitizeRule::Slug => {
-            if !matches!(
-                type_kind,
-                FieldTypeKind::String | FieldTypeKind::OptionString
-            ) {
-                let rule_name = match rule {
-                    SanitizeRule::Trim => "trim",
-                    SanitizeRule::Lowercase => "lowercase",
-                    SanitizeRule::Uppercase => "uppercase",
-                    SanitizeRule::Truncate(_) => "truncate",
-                    SanitizeRule::Alphanumeric => "alphanumeric",
-                    SanitizeRule::EscapeHtml => "escape_html",
-                    SanitizeRule::NulStrip => "nul_strip",
-                    SanitizeRule::ControlStrip => "control_strip",
-                    SanitizeRule::Slug => "slug",
-                    _ => unreachable!(),
-                };
-                return Err(syn::Error::new(
-                    span,
-                    format!(
-                        "sanitize rule '{}' is only valid for String fields, but field '{}' has a numeric type",
-                        rule_name, field_name
-                    ),
-                ));
+        | SanitizeRule::Slug => (matches!(type_kind, FieldTypeKind::String), "String"),
+        SanitizeRule::Clamp(_, _) => (matches!(type_kind, FieldTypeKind::Numeric), "numeric"),
+        SanitizeRule::Nested => (
+            !matches!(type_kind, FieldTypeKind::String | FieldTypeKind::Numeric),
+            "struct, Vec or map",
+        ),
+        SanitizeRule::Each(_) | SanitizeRule::MaxLen(_) | SanitizeRule::Dedupe => {
+            (matches!(type_kind, FieldTypeKind::Vec(_)), "Vec")
+        }
+        SanitizeRule::Keys(_) | SanitizeRule::Values(_) => {
+            (matches!(type_kind, FieldTypeKind::Map(_, _)), "map")
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(type_mismatch(rule, expected, type_kind, field_name, span))
+    }
+}
+
+fn binding(prefix: &str, depth: usize) -> syn::Ident {
+    syn::Ident::new(&format!("__{}{}", prefix, depth), proc_macro2::Span::call_site())
+}
+
+// Emits the rules for a value of type `ty` stored at `place`. `Option`
+// layers are unwrapped first, so every rule runs inside an
+// `if let Some(..)` and `None` passes through untouched. Collection rules
+// recurse into their element type with a fresh binding per depth.
+fn apply_rules(
+    rules: &[SanitizeRule],
+    ty: &Type,
+    place: &proc_macro2::TokenStream,
+    field_name: &str,
+    span: proc_macro2::Span,
+    depth: usize,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let type_kind = classify_type(ty);
+    if let FieldTypeKind::Option(inner) = type_kind {
+        let some = binding("s", depth);
+        let body = apply_rules(rules, inner, &quote! { (*#some) }, field_name, span, depth + 1)?;
+        return Ok(quote! {
+            if let Some(#some) = #place.as_mut() {
+                #body
+            }
+        });
+    }
+
+    let mut tokens = Vec::new();
+    for rule in rules {
+        validate_rule_for_type(rule, &type_kind, field_name, span)?;
+        let rule_tokens = match (rule, &type_kind) {
+            (SanitizeRule::Nested, _) => nested_tokens(ty, place, field_name, span, depth)?,
+            (SanitizeRule::Each(inner), FieldTypeKind::Vec(elem)) => {
+                let item = binding("e", depth);
+                let item_place = quote! { (*#item) };
+                let body = apply_rules(inner, elem, &item_place, field_name, span, depth + 1)?;
+                quote! {
+                    for #item in #place.iter_mut() {
+                        #body
+                    }
+                }
+            }
+            (SanitizeRule::Values(inner), FieldTypeKind::Map(_, value)) => {
+                let item = binding("v", depth);
+                let item_place = quote! { (*#item) };
+                let body = apply_rules(inner, value, &item_place, field_name, span, depth + 1)?;
+                quote! {
+                    for #item in #place.values_mut() {
+                        #body
+                    }
+                }
+            }
+            // Keys cannot be mutated in place: rebuild the map. Entries are
+            // visited in original key order so that, whatever the map type,
+            // keys colliding after sanitizing keep the value of the smallest
+            // original key.
+            (SanitizeRule::Keys(inner), FieldTypeKind::Map(key, _)) => {
+                let k = binding("k", depth);
+                let v = binding("v", depth);
+                let entries = binding("entries", depth);
+                let body = apply_rules(inner, key, &quote! { #k }, field_name, span, depth + 1)?;
+                quote! {
+                    let mut #entries: ::std::vec::Vec<_> =
+                        ::std::mem::take(&mut #place).into_iter().collect();
+                    #entries.sort_unstable_by(|__a, __b| ::std::cmp::Ord::cmp(&__a.0, &__b.0));
+                    for (mut #k, #v) in #entries {
+                        #body
+                        #place.entry(#k).or_insert(#v);
+                    }
+                }
+            }
+            (SanitizeRule::MaxLen(n), _) => quote! {
+                #place.truncate(#n);
+            },
+            // Keeps the first occurrence of each element, preserving order.
+            (SanitizeRule::Dedupe, _) => {
+                let seen = binding("seen", depth);
+                quote! {
+                    let mut #seen = ::std::collections::HashSet::new();
+                    #place.retain(|__item| #seen.insert(::std::clone::Clone::clone(__item)));
+                }
+            }
+            _ => rule_to_tokens(place, rule),
+        };
+        tokens.push(rule_tokens);
+    }
+    Ok(quote! { #(#tokens)* })
+}
+
+// `nested` calls the inner type's own `sanitize()` (the method this derive
+// generates), walking through Option, Vec elements and map values. A
+// String or numeric leaf has no `sanitize()`, so it is rejected here with
+// the field's span instead of failing inside the generated code.
+fn nested_tokens(
+    ty: &Type,
+    place: &proc_macro2::TokenStream,
+    field_name: &str,
+    span: proc_macro2::Span,
+    depth: usize,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    Ok(match classify_type(ty) {
+        FieldTypeKind::Option(inner) => {
+            let some = binding("s", depth);
+            let body = nested_tokens(inner, &quote! { (*#some) }, field_name, span, depth + 1)?;
+            quote! {
+                if let Some(#some) = #place.as_mut() {
+                    #body
+                }
             }
         }
-        SanitizeRule::Clamp(_, _) => {
-            if !matches!(type_kind, FieldTypeKind::Numeric) {
-                return Err(syn::Error::new(
-                    span,
-                    format!(
-                        "sanitize rule 'clamp' is only valid for numeric fields, but field '{}' has type String",
-                        field_name
-                    ),
-                ));
+        FieldTypeKind::Vec(elem) => {
+            let item = binding("e", depth);
+            let body = nested_tokens(elem, &quote! { (*#item) }, field_name, span, depth + 1)?;
+            quote! {
+                for #item in #place.iter_mut() {
+                    #body
+                }
             }
         }
-    }
-    Ok(())
+        FieldTypeKind::Map(_, value) => {
+            let item = binding("v", depth);
+            let body = nested_tokens(value, &quote! { (*#item) }, field_name, span, depth + 1)?;
+            quote! {
+                for #item in #place.values_mut() {
+                    #body
+                }
+            }
+        }
+        leaf @ (FieldTypeKind::String | FieldTypeKind::Numeric) => {
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "sanitize rule 'nested' needs a type that derives Sanitize, but field '{}' \
+holds {}; use each(...) or values(...) to run string rules on it",
+                    field_name,
+                    describe(&leaf)
+                ),
+            ));
+        }
+        FieldTypeKind::Other => quote! {
+            #place.sanitize();
+        },
+    })
 }
 
 fn rule_to_tokens(
@@ -300,80 +476,60 @@ fn rule_to_tokens(
         SanitizeRule::Clamp(min, max) => quote! {
             #access = #access.clamp(#min, #max);
         },
+        // Structural rules need the field type, so `apply_rules` expands
+        // them itself and never reaches this point.
+        SanitizeRule::Nested
+        | SanitizeRule::Each(_)
+        | SanitizeRule::Keys(_)
+        | SanitizeRule::Values(_)
+        | SanitizeRule::MaxLen(_)
+        | SanitizeRule::Dedupe => {
+            unreachable!("`{}` is expanded by apply_rules", rule.name())
+        }
     }
 }
 
-fn process_field(
+// Builds the rule body for one field. `place` is a place expression for
+// the field (`self.name`, `self.0` or `(*binding)` inside an enum arm) so
+// the same rule codegen serves structs, tuple structs and enum variants.
+fn field_body(
     field: &Field,
-) -> Result<Option<(syn::Ident, proc_macro2::TokenStream)>, syn::Error> {
+    field_name: &str,
+    place: &proc_macro2::TokenStream,
+) -> Result<Option<proc_macro2::TokenStream>, syn::Error> {
     let Some((raw_rules, span)) = get_holy_string_value(&field.attrs, "sanitize") else {
         return Ok(None);
     };
 
-    let field_name = field.ident.as_ref().unwrap().clone();
-    let type_kind = classify_type(&field.ty);
     let rules = parse_sanitize_rules(&raw_rules, span)?;
+    let body = apply_rules(&rules, &field.ty, place, field_name, span, 0)?;
 
-    for rule in &rules {
-        validate_rule_for_type(rule, &type_kind, &field_name, span)?;
-    }
-
-    // For Option<String> we want every rule to operate inside an
-    // `if let Some(__s)` so callers don't have to unwrap manually. The
-    // `__s` binding is `&mut String`, so `*__s` is a place expression
-    // that the rule codegen can both read from and assign to.
-    let body = match type_kind {
-        FieldTypeKind::OptionString => {
-            let access = quote! { (*__s) };
-            let rule_tokens = rules.iter().map(|r| rule_to_tokens(&access, r));
-            quote! {
-                if let Some(__s) = self.#field_name.as_mut() {
-                    #(#rule_tokens)*
-                }
-            }
-        }
-        _ => {
-            let access = quote! { self.#field_name };
-            let rule_tokens = rules.iter().map(|r| rule_to_tokens(&access, r));
-            quote! { #(#rule_tokens)* }
-        }
-    };
-
-    Ok(Some((field_name, body)))
+    Ok(Some(body))
 }
 
-pub fn impl_sanitize_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
-    let struct_name = &ast.ident;
-    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-
-    let fields = match &ast.data {
-        Data::Struct(data) => match &data.fields {
-            Fields::Named(named) => &named.named,
-            _ => {
-                return Err(syn::Error::new_spanned(
-                    ast,
-                    "Sanitize macro only supports structs with named fields",
-                ));
-            }
-        },
-        _ => {
-            return Err(syn::Error::new_spanned(
-                ast,
-                "Sanitize macro only supports structs",
-            ));
-        }
-    };
-
+fn struct_methods(
+    fields: &Fields,
+) -> Result<(Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>), syn::Error> {
     let mut per_field_methods = Vec::new();
     let mut all_field_calls = Vec::new();
 
-    for field in fields.iter() {
-        let Some((field_name, body)) = process_field(field)? else {
+    for (index, field) in fields.iter().enumerate() {
+        let (field_name, place) = match &field.ident {
+            Some(ident) => (ident.to_string(), quote! { self.#ident }),
+            None => {
+                let member = syn::Index::from(index);
+                (index.to_string(), quote! { self.#member })
+            }
+        };
+        let Some(body) = field_body(field, &field_name, &place)? else {
             continue;
         };
 
-        let sanitize_method_name =
-            syn::Ident::new(&format!("sanitize_{}", field_name), field_name.span());
+        let span = field
+            .ident
+            .as_ref()
+            .map_or_else(proc_macro2::Span::call_site, |ident| ident.span());
+        let sanitize_method_name = syn::Ident::new(&format!("sanitize_{}", field_name), span);
 
         // Per-field helper inherits the field's own visibility (or
         // its #[holy(public|private)] override) so private fields
@@ -392,17 +548,107 @@ pub fn impl_sanitize_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error>
         });
     }
 
-    if per_field_methods.is_empty() {
-        return Ok(TokenStream::from(quote! {}));
-    }
+    Ok((per_field_methods, all_field_calls))
+}
+
+// Enum variants have no per-field helpers; `sanitize()` matches on the
+// variant and runs the rules against the bound fields. Fields without
+// rules are left out of the pattern (`..` / `_`).
+fn variant_arm(variant: &Variant) -> Result<Option<proc_macro2::TokenStream>, syn::Error> {
+    let variant_name = &variant.ident;
+    let mut bodies = Vec::new();
 
-    let expanded = quote! {
-        impl #impl_generics #struct_name #ty_generics #where_clause {
-            pub fn sanitize(&mut self) {
-                #(#all_field_calls)*
+    let pattern = match &variant.fields {
+        Fields::Named(named) => {
+            let mut bound = Vec::new();
+            for field in &named.named {
+                let ident = field.ident.as_ref().unwrap();
+                if let Some(body) = field_body(field, &ident.to_string(), &quote! { (*#ident) })? {
+                    bound.push(ident);
+                    bodies.push(body);
+                }
+            }
+            quote! { Self::#variant_name { #(#bound,)* .. } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let mut slots = Vec::new();
+            for (index, field) in unnamed.unnamed.iter().enumerate() {
+                let binding =
+                    syn::Ident::new(&format!("__f{}", index), proc_macro2::Span::call_site());
+                match field_body(field, &index.to_string(), &quote! { (*#binding) })? {
+                    Some(body) => {
+                        slots.push(quote! { #binding });
+                        bodies.push(body);
+                    }
+                    None => slots.push(quote! { _ }),
+                }
             }
+            quote! { Self::#variant_name(#(#slots),*) }
+        }
+        Fields::Unit => return Ok(None),
+    };
+
+    if bodies.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(quote! {
+        #pattern => {
+            #(#bodies)*
+        }
+    }))
+}
+
+pub fn impl_sanitize_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let type_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-            #(#per_field_methods)*
+    let expanded = match &ast.data {
+        Data::Struct(data) => {
+            let (per_field_methods, all_field_calls) = struct_methods(&data.fields)?;
+            // Emitted even without rules, so a type with nothing of its own
+            // to clean can still sit behind another type's `nested`.
+            quote! {
+                impl #impl_generics #type_name #ty_generics #where_clause {
+                    pub fn sanitize(&mut self) {
+                        #(#all_field_calls)*
+                    }
+
+                    #(#per_field_methods)*
+                }
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                if let Some(arm) = variant_arm(variant)? {
+                    arms.push(arm);
+                }
+            }
+            let body = if arms.is_empty() {
+                quote! {}
+            } else {
+                let fallback = (arms.len() < data.variants.len()).then(|| quote! { _ => {} });
+                quote! {
+                    match self {
+                        #(#arms)*
+                        #fallback
+                    }
+                }
+            };
+            quote! {
+                impl #impl_generics #type_name #ty_generics #where_clause {
+                    pub fn sanitize(&mut self) {
+                        #body
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ast,
+                "Sanitize macro only supports structs and enums",
+            ));
         }
     };
 
@@ -16,10 +16,17 @@ pub fn impl_setters_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error>
                 .filter(|f| !should_skip(&f.attrs))
                 .map(generate_setter)
                 .collect::<Result<Vec<_>, syn::Error>>()?,
-            _ => {
+            Fields::Unnamed(fields) => fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| !should_skip(&f.attrs))
+                .map(|(index, f)| generate_positional_setter(index, f))
+                .collect::<Result<Vec<_>, syn::Error>>()?,
+            Fields::Unit => {
                 return Err(syn::Error::new_spanned(
                     ast,
-                    "Setters macro only supports structs with named fields",
+                    "Setters macro only supports structs with named or tuple fields",
                 ));
             }
         },
@@ -58,3 +65,19 @@ fn generate_setter(field: &Field) -> Result<proc_macro2::TokenStream, syn::Error
 
     Ok(setter)
 }
+
+fn generate_positional_setter(
+    index: usize,
+    field: &Field,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let member = syn::Index::from(index);
+    let setter_name = syn::Ident::new(&format!("set_{}", index), proc_macro2::Span::call_site());
+    let field_type = &field.ty;
+    let setter_vis = determine_visibility(&field.vis, &field.attrs)?;
+
+    Ok(quote! {
+        #setter_vis fn #setter_name(&mut self, value: #field_type) {
+            self.#member = value;
+        }
+    })
+}
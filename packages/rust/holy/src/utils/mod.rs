@@ -1,3 +1,5 @@
+pub mod rules;
 pub mod visibility;
 
+pub use rules::*;
 pub use visibility::*;
\ No newline at end of file
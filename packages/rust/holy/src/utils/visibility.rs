@@ -93,3 +93,39 @@ pub fn get_holy_string_value(
         None
     })
 }
+
+pub fn get_holy_u32_value(
+    attrs: &[Attribute],
+    key: &str,
+) -> Result<Option<(u32, proc_macro2::Span)>, syn::Error> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("holy")) {
+        let Meta::List(meta_list) = &attr.meta else {
+            continue;
+        };
+        let Ok(nested) =
+            Punctuated::<Meta, Token![,]>::parse_terminated.parse2(meta_list.tokens.clone())
+        else {
+            continue;
+        };
+        for meta in &nested {
+            if let Meta::NameValue(nv) = meta
+                && nv.path.is_ident(key)
+            {
+                let Expr::Lit(expr_lit) = &nv.value else {
+                    return Err(syn::Error::new_spanned(
+                        &nv.value,
+                        format!("`{}` expects an integer literal", key),
+                    ));
+                };
+                let Lit::Int(lit_int) = &expr_lit.lit else {
+                    return Err(syn::Error::new_spanned(
+                        &expr_lit.lit,
+                        format!("`{}` expects an integer literal", key),
+                    ));
+                };
+                return Ok(Some((lit_int.base10_parse()?, lit_int.span())));
+            }
+        }
+    }
+    Ok(None)
+}
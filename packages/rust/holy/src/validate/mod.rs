@@ -0,0 +1,3 @@
+pub mod validate_macro;
+
+pub use validate_macro::*;
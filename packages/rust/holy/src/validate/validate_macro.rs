@@ -0,0 +1,616 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Field, Fields, GenericArgument, PathArguments, Type};
+
+use crate::utils::{determine_visibility, get_holy_string_value, has_holy_argument, split_rules};
+
+enum ValidateRule {
+    Required,
+    Email,
+    Url,
+    Regex(String),
+    Range(String, String),
+    Length(usize, usize),
+    OneOf(Vec<String>),
+    Custom(syn::Path),
+    Nested,
+}
+
+impl ValidateRule {
+    fn name(&self) -> &'static str {
+        match self {
+            ValidateRule::Required => "required",
+            ValidateRule::Email => "email",
+            ValidateRule::Url => "url",
+            ValidateRule::Regex(_) => "regex",
+            ValidateRule::Range(_, _) => "range",
+            ValidateRule::Length(_, _) => "length",
+            ValidateRule::OneOf(_) => "one_of",
+            ValidateRule::Custom(_) => "custom",
+            ValidateRule::Nested => "nested",
+        }
+    }
+}
+
+enum FieldTypeKind<'a> {
+    String,
+    Numeric,
+    Float,
+    Option(&'a Type),
+    Vec(&'a Type),
+    Other,
+}
+
+fn classify_type(ty: &Type) -> FieldTypeKind<'_> {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        let inner = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }),
+            _ => None,
+        };
+        return match (segment.ident.to_string().as_str(), inner) {
+            ("String", _) => FieldTypeKind::String,
+            ("Option", Some(inner)) => FieldTypeKind::Option(inner),
+            ("Vec", Some(inner)) => FieldTypeKind::Vec(inner),
+            (
+                "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "isize" | "usize",
+                _,
+            ) => FieldTypeKind::Numeric,
+            ("f32" | "f64", _) => FieldTypeKind::Float,
+            _ => FieldTypeKind::Other,
+        };
+    }
+    FieldTypeKind::Other
+}
+
+fn unquote(raw: &str) -> String {
+    let trimmed = raw.trim();
+    trimmed
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+// Integer bounds are written as float literals for f32/f64 fields so
+// `range(0, 1)` compares against `0.0..=1.0` instead of failing to type-check.
+fn numeric_literal(raw: &str, type_kind: &FieldTypeKind) -> proc_macro2::TokenStream {
+    let raw = raw.trim();
+    let literal = match type_kind {
+        FieldTypeKind::Float if raw.parse::<i128>().is_ok() => format!("{}.0", raw),
+        _ => raw.to_string(),
+    };
+    literal.parse().unwrap_or_default()
+}
+
+fn call_args<'a>(token: &'a str, name: &str) -> Option<&'a str> {
+    token
+        .strip_prefix(name)
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+}
+
+fn parse_validate_rules(
+    raw: &str,
+    span: proc_macro2::Span,
+) -> Result<Vec<ValidateRule>, syn::Error> {
+    let mut rules = Vec::new();
+
+    for token in &split_rules(raw) {
+        let rule = if token == "required" {
+            ValidateRule::Required
+        } else if token == "email" {
+            ValidateRule::Email
+        } else if token == "url" {
+            ValidateRule::Url
+        } else if token == "nested" {
+            ValidateRule::Nested
+        } else if let Some(inner) = call_args(token, "regex") {
+            let pattern = unquote(inner);
+            if let Err(err) = regex::Regex::new(&pattern) {
+                return Err(syn::Error::new(
+                    span,
+                    format!("invalid regex '{}': {}", pattern, err),
+                ));
+            }
+            ValidateRule::Regex(pattern)
+        } else if let Some(inner) = call_args(token, "range") {
+            let parts = split_rules(inner);
+            let [min, max] = parts.as_slice() else {
+                return Err(syn::Error::new(
+                    span,
+                    format!("range requires two arguments: range(min, max), got '{}'", token),
+                ));
+            };
+            for bound in [min, max] {
+                if bound.parse::<f64>().is_err() {
+                    return Err(syn::Error::new(
+                        span,
+                        format!("invalid range argument: '{}'", bound),
+                    ));
+                }
+            }
+            ValidateRule::Range(min.clone(), max.clone())
+        } else if let Some(inner) = call_args(token, "length") {
+            let parts = split_rules(inner);
+            let [min, max] = parts.as_slice() else {
+                return Err(syn::Error::new(
+                    span,
+                    format!("length requires two arguments: length(min, max), got '{}'", token),
+                ));
+            };
+            let min: usize = min.parse().map_err(|_| {
+                syn::Error::new(span, format!("invalid length min argument: '{}'", min))
+            })?;
+            let max: usize = max.parse().map_err(|_| {
+                syn::Error::new(span, format!("invalid length max argument: '{}'", max))
+            })?;
+            ValidateRule::Length(min, max)
+        } else if let Some(inner) = call_args(token, "one_of") {
+            let options: Vec<String> = split_rules(inner).iter().map(|o| unquote(o)).collect();
+            if options.is_empty() {
+                return Err(syn::Error::new(span, "one_of requires at least one option"));
+            }
+            ValidateRule::OneOf(options)
+        } else if let Some(inner) = call_args(token, "custom") {
+            let path: syn::Path = syn::parse_str(inner.trim()).map_err(|_| {
+                syn::Error::new(span, format!("invalid custom validator path: '{}'", inner.trim()))
+            })?;
+            ValidateRule::Custom(path)
+        } else {
+            return Err(syn::Error::new(
+                span,
+                format!("unknown validate rule: '{}'", token),
+            ));
+        };
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+fn validate_rule_for_type(
+    rule: &ValidateRule,
+    type_kind: &FieldTypeKind,
+    field_name: &syn::Ident,
+    span: proc_macro2::Span,
+) -> Result<(), syn::Error> {
+    let (valid, expected) = match rule {
+        ValidateRule::Required => (
+            matches!(
+                type_kind,
+                FieldTypeKind::String | FieldTypeKind::Option(_) | FieldTypeKind::Vec(_)
+            ),
+            "String, Option or Vec",
+        ),
+        ValidateRule::Email | ValidateRule::Url | ValidateRule::Regex(_) => {
+            (matches!(type_kind, FieldTypeKind::String), "String")
+        }
+        ValidateRule::Range(min, max) => {
+            if matches!(type_kind, FieldTypeKind::Numeric)
+                && let Some(bound) = [min, max].into_iter().find(|b| b.parse::<i128>().is_err())
+            {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "range bound '{}' is not an integer, but field '{}' has an integer type",
+                        bound, field_name
+                    ),
+                ));
+            }
+            (
+                matches!(type_kind, FieldTypeKind::Numeric | FieldTypeKind::Float),
+                "numeric",
+            )
+        }
+        ValidateRule::Length(_, _) => (
+            matches!(type_kind, FieldTypeKind::String | FieldTypeKind::Vec(_)),
+            "String or Vec",
+        ),
+        ValidateRule::OneOf(options) => {
+            let bad = match type_kind {
+                FieldTypeKind::Numeric => options.iter().find(|o| o.parse::<i128>().is_err()),
+                FieldTypeKind::Float => options
+                    .iter()
+                    .find(|o| !o.parse::<f64>().is_ok_and(f64::is_finite)),
+                _ => None,
+            };
+            if let Some(option) = bad {
+                let expected = match type_kind {
+                    FieldTypeKind::Float => "a number",
+                    _ => "an integer",
+                };
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "one_of option '{}' is not {}, but field '{}' has a numeric type",
+                        option, expected, field_name
+                    ),
+                ));
+            }
+            (
+                matches!(
+                    type_kind,
+                    FieldTypeKind::String | FieldTypeKind::Numeric | FieldTypeKind::Float
+                ),
+                "String or numeric",
+            )
+        }
+        ValidateRule::Custom(_) => (true, ""),
+        ValidateRule::Nested => (
+            !matches!(
+                type_kind,
+                FieldTypeKind::String | FieldTypeKind::Numeric | FieldTypeKind::Float
+            ),
+            "struct or Vec",
+        ),
+    };
+    if valid {
+        return Ok(());
+    }
+    Err(syn::Error::new(
+        span,
+        format!(
+            "validate rule '{}' is only valid for {} fields, but field '{}' has a different type",
+            rule.name(),
+            expected,
+            field_name
+        ),
+    ))
+}
+
+fn push_error(
+    error_ty: &syn::Ident,
+    path: &proc_macro2::TokenStream,
+    code: &str,
+    message: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        __errors.push(#error_ty {
+            path: #path,
+            code: #code,
+            message: #message,
+        });
+    }
+}
+
+// Emits the check for one rule against `value`, a reference to the field
+// (or to the value inside `Some`). `path` evaluates to the field path
+// `String` used in reported errors.
+fn rule_to_tokens(
+    rule: &ValidateRule,
+    type_kind: &FieldTypeKind,
+    value: &proc_macro2::TokenStream,
+    path: &proc_macro2::TokenStream,
+    error_ty: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    let code = rule.name();
+    match rule {
+        ValidateRule::Required => {
+            let missing = match type_kind {
+                FieldTypeKind::Option(_) => quote! { #value.is_none() },
+                FieldTypeKind::String => quote! { #value.trim().is_empty() },
+                _ => quote! { #value.is_empty() },
+            };
+            let push = push_error(error_ty, path, code, quote! { "is required".to_string() });
+            quote! {
+                if #missing {
+                    #push
+                }
+            }
+        }
+        ValidateRule::Email => {
+            let push = push_error(
+                error_ty,
+                path,
+                code,
+                quote! { "must be a valid email address".to_string() },
+            );
+            quote! {
+                let __s: &str = #value.as_str();
+                let __ok = __s.len() <= 254
+                    && !__s.chars().any(char::is_whitespace)
+                    && match __s.split_once('@') {
+                        Some((__local, __domain)) => {
+                            !__local.is_empty()
+                                && !__domain.contains('@')
+                                && __domain.contains('.')
+                                && !__domain.starts_with('.')
+                                && !__domain.ends_with('.')
+                        }
+                        None => false,
+                    };
+                if !__ok {
+                    #push
+                }
+            }
+        }
+        ValidateRule::Url => {
+            let push = push_error(
+                error_ty,
+                path,
+                code,
+                quote! { "must be a valid http(s) URL".to_string() },
+            );
+            quote! {
+                let __s: &str = #value.as_str();
+                let __ok = !__s.chars().any(char::is_whitespace)
+                    && __s
+                        .strip_prefix("https://")
+                        .or_else(|| __s.strip_prefix("http://"))
+                        .and_then(|__rest| __rest.split(['/', '?', '#']).next())
+                        .is_some_and(|__host| !__host.is_empty());
+                if !__ok {
+                    #push
+                }
+            }
+        }
+        // The pattern is checked at expansion time, then compiled once per
+        // field into a `OnceLock`; the deriving crate must depend on `regex`.
+        ValidateRule::Regex(pattern) => {
+            let message = format!("must match the pattern {}", pattern);
+            let push = push_error(error_ty, path, code, quote! { #message.to_string() });
+            quote! {
+                static __RE: ::std::sync::OnceLock<::regex::Regex> = ::std::sync::OnceLock::new();
+                let __re = __RE.get_or_init(|| {
+                    ::regex::Regex::new(#pattern).expect("pattern checked by holy at expansion")
+                });
+                if !__re.is_match(#value.as_str()) {
+                    #push
+                }
+            }
+        }
+        ValidateRule::Range(min, max) => {
+            let message = format!("must be between {} and {}", min, max);
+            let push = push_error(error_ty, path, code, quote! { #message.to_string() });
+            let min = numeric_literal(min, type_kind);
+            let max = numeric_literal(max, type_kind);
+            quote! {
+                if !(#min..=#max).contains(#value) {
+                    #push
+                }
+            }
+        }
+        ValidateRule::Length(min, max) => {
+            let message = format!("length must be between {} and {}", min, max);
+            let push = push_error(error_ty, path, code, quote! { #message.to_string() });
+            let len = match type_kind {
+                FieldTypeKind::String => quote! { #value.chars().count() },
+                _ => quote! { #value.len() },
+            };
+            quote! {
+                if !(#min..=#max).contains(&#len) {
+                    #push
+                }
+            }
+        }
+        ValidateRule::OneOf(options) => {
+            let message = format!("must be one of: {}", options.join(", "));
+            let push = push_error(error_ty, path, code, quote! { #message.to_string() });
+            let check = match type_kind {
+                FieldTypeKind::String => quote! { [#(#options),*].contains(&#value.as_str()) },
+                _ => {
+                    let literals = options.iter().map(|o| numeric_literal(o, type_kind));
+                    quote! { [#(#literals),*].contains(#value) }
+                }
+            };
+            quote! {
+                if !#check {
+                    #push
+                }
+            }
+        }
+        // Custom hooks take `&FieldType` (`&str` for `String` fields) and
+        // return `Result<(), String>`; the `Err` message is reported with
+        // code "custom".
+        ValidateRule::Custom(func) => {
+            let push = push_error(error_ty, path, code, quote! { __message });
+            let arg = match type_kind {
+                FieldTypeKind::String => quote! { #value.as_str() },
+                _ => quote! { #value },
+            };
+            quote! {
+                if let Err(__message) = #func(#arg) {
+                    #push
+                }
+            }
+        }
+        ValidateRule::Nested => nested_tokens(type_kind, value, path, error_ty, 0),
+    }
+}
+
+// `nested` calls the inner type's own `validate()` and re-roots its errors
+// under this field's path, indexing into Vec elements (`members[2].name`).
+fn nested_tokens(
+    type_kind: &FieldTypeKind,
+    value: &proc_macro2::TokenStream,
+    path: &proc_macro2::TokenStream,
+    error_ty: &syn::Ident,
+    depth: usize,
+) -> proc_macro2::TokenStream {
+    match type_kind {
+        FieldTypeKind::Option(inner) => {
+            let some = syn::Ident::new(&format!("__some{}", depth), proc_macro2::Span::call_site());
+            let body =
+                nested_tokens(&classify_type(inner), &quote! { #some }, path, error_ty, depth + 1);
+            quote! {
+                if let Some(#some) = #value.as_ref() {
+                    #body
+                }
+            }
+        }
+        FieldTypeKind::Vec(inner) => {
+            let index = syn::Ident::new(&format!("__i{}", depth), proc_macro2::Span::call_site());
+            let item = syn::Ident::new(&format!("__item{}", depth), proc_macro2::Span::call_site());
+            let item_path = quote! { format!("{}[{}]", #path, #index) };
+            let body =
+                nested_tokens(&classify_type(inner), &quote! { #item }, &item_path, error_ty, depth + 1);
+            quote! {
+                for (#index, #item) in #value.iter().enumerate() {
+                    #body
+                }
+            }
+        }
+        _ => quote! {
+            if let Err(__nested) = #value.validate() {
+                for __e in __nested {
+                    __errors.push(#error_ty {
+                        path: format!("{}.{}", #path, __e.path),
+                        code: __e.code,
+                        message: __e.message,
+                    });
+                }
+            }
+        },
+    }
+}
+
+fn field_body(
+    field: &Field,
+    error_ty: &syn::Ident,
+) -> Result<Option<proc_macro2::TokenStream>, syn::Error> {
+    let Some((raw_rules, span)) = get_holy_string_value(&field.attrs, "validate") else {
+        return Ok(None);
+    };
+
+    let field_name = field.ident.as_ref().unwrap();
+    let path_str = field_name.to_string();
+    let path = quote! { #path_str.to_string() };
+    let type_kind = classify_type(&field.ty);
+    let rules = parse_validate_rules(&raw_rules, span)?;
+
+    // `required`, `custom` and `nested` see the field as declared. Every
+    // other rule runs on the value inside `Some(_)` for Option fields, so
+    // `None` is valid unless the field is also `required`.
+    let mut outer = Vec::new();
+    let mut inner = Vec::new();
+    let inner_kind = match &type_kind {
+        FieldTypeKind::Option(ty) => classify_type(ty),
+        _ => classify_type(&field.ty),
+    };
+    for rule in &rules {
+        let on_outer = matches!(
+            rule,
+            ValidateRule::Required | ValidateRule::Custom(_) | ValidateRule::Nested
+        ) || !matches!(type_kind, FieldTypeKind::Option(_));
+        if on_outer {
+            validate_rule_for_type(rule, &type_kind, field_name, span)?;
+            outer.push(rule_to_tokens(rule, &type_kind, &quote! { __value }, &path, error_ty));
+        } else {
+            validate_rule_for_type(rule, &inner_kind, field_name, span)?;
+            inner.push(rule_to_tokens(rule, &inner_kind, &quote! { __inner }, &path, error_ty));
+        }
+    }
+
+    let inner_block = (!inner.is_empty()).then(|| {
+        quote! {
+            if let Some(__inner) = __value.as_ref() {
+                #({ #inner })*
+            }
+        }
+    });
+
+    Ok(Some(quote! {
+        let __value = &self.#field_name;
+        #({ #outer })*
+        #inner_block
+    }))
+}
+
+pub fn impl_validate_macro(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let struct_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ast,
+                    "Validate macro only supports structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ast,
+                "Validate macro only supports structs",
+            ));
+        }
+    };
+
+    let error_ty = syn::Ident::new(&format!("{}ValidationError", struct_name), struct_name.span());
+    let error_vis = &ast.vis;
+
+    let mut per_field_methods = Vec::new();
+    let mut all_field_calls = Vec::new();
+
+    for field in fields.iter() {
+        let Some(body) = field_body(field, &error_ty)? else {
+            continue;
+        };
+        let field_name = field.ident.as_ref().unwrap();
+        let method_name = syn::Ident::new(&format!("validate_{}", field_name), field_name.span());
+
+        // Same visibility policy as the Sanitize per-field helpers; the
+        // aggregate `validate()` stays `pub`.
+        let method_vis = determine_visibility(&field.vis, &field.attrs)?;
+
+        per_field_methods.push(quote! {
+            #method_vis fn #method_name(&self) -> Vec<#error_ty> {
+                let mut __errors = Vec::new();
+                #body
+                __errors
+            }
+        });
+        all_field_calls.push(quote! {
+            __errors.extend(self.#method_name());
+        });
+    }
+
+    // `#[holy(validate_serde)]` on the struct opts into a `Serialize` error
+    // type; the deriving crate must depend on `serde`.
+    let serialize = if has_holy_argument(&ast.attrs, "validate_serde") {
+        quote! { #[derive(::serde::Serialize)] }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #serialize
+        #error_vis struct #error_ty {
+            pub path: String,
+            pub code: &'static str,
+            pub message: String,
+        }
+
+        impl ::std::fmt::Display for #error_ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}: {}", self.path, self.message)
+            }
+        }
+
+        impl ::std::error::Error for #error_ty {}
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            pub fn validate(&self) -> Result<(), Vec<#error_ty>> {
+                let mut __errors: Vec<#error_ty> = Vec::new();
+                #(#all_field_calls)*
+                if __errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(__errors)
+                }
+            }
+
+            #(#per_field_methods)*
+        }
+    };
+
+    Ok(TokenStream::from(expanded))
+}
@@ -1,6 +1,6 @@
 use holy::Getters;
 
 #[derive(Getters)]
-struct Nope(i32, i32);
+struct Nope;
 
 fn main() {}
@@ -2,7 +2,10 @@ use holy::Sanitize;
 
 #[derive(Sanitize)]
 enum Nope {
-	A,
+	A {
+		#[holy(sanitize = "trim")]
+		count: u32,
+	},
 	B,
 }
 
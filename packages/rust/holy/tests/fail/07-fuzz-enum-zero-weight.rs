@@ -2,7 +2,9 @@ use holy::Fuzz;
 
 #[derive(Fuzz)]
 enum Nope {
+	#[holy(weight = 0)]
 	A,
+	#[holy(weight = 0)]
 	B,
 }
 
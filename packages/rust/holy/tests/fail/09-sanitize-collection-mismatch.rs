@@ -0,0 +1,9 @@
+use holy::Sanitize;
+
+#[derive(Sanitize)]
+pub struct Bad {
+	#[holy(sanitize = "dedupe")]
+	pub name: String,
+}
+
+fn main() {}
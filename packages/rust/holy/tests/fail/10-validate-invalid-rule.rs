@@ -0,0 +1,9 @@
+use holy::Validate;
+
+#[derive(Validate)]
+pub struct Signup {
+	#[holy(validate = "required, postcode")]
+	pub zip: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use holy::Validate;
+
+#[derive(Validate)]
+pub struct Signup {
+	#[holy(validate = r#"regex("^[a-z+$")"#)]
+	pub handle: String,
+}
+
+fn main() {}
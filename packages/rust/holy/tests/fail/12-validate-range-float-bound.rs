@@ -0,0 +1,9 @@
+use holy::Validate;
+
+#[derive(Validate)]
+pub struct Signup {
+	#[holy(validate = "range(0, 1.5)")]
+	pub age: u8,
+}
+
+fn main() {}
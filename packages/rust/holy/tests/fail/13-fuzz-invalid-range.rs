@@ -0,0 +1,11 @@
+use holy::Fuzz;
+
+#[derive(Fuzz)]
+pub struct Stats {
+	#[holy(fuzz = "range(1, ten +)")]
+	pub level: u8,
+	#[holy(fuzz = "range(0, 2.5)")]
+	pub speed: u32,
+}
+
+fn main() {}
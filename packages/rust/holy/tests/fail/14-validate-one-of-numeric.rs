@@ -0,0 +1,9 @@
+use holy::Validate;
+
+#[derive(Validate)]
+pub struct Plan {
+	#[holy(validate = "one_of(\"a\", \"b\")")]
+	pub tier: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use holy::Sanitize;
+
+#[derive(Sanitize)]
+pub struct Bad {
+	#[holy(sanitize = "nested")]
+	pub tags: Vec<String>,
+}
+
+fn main() {}
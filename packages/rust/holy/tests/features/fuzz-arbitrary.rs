@@ -0,0 +1,49 @@
+use arbitrary::{Arbitrary, Unstructured};
+use holy::Fuzz;
+
+#[derive(Debug, Fuzz)]
+#[holy(fuzz_arbitrary)]
+pub struct Profile {
+	#[holy(fuzz = "alphanumeric(3, 12)")]
+	pub handle: String,
+	#[holy(fuzz = "range(13, 120)")]
+	pub age: u8,
+	#[holy(fuzz = "range(0.0, 5.0)")]
+	pub rating: f64,
+	pub verified: bool,
+}
+
+#[derive(Debug, Fuzz)]
+#[holy(fuzz_arbitrary)]
+pub enum Command {
+	#[holy(weight = 0)]
+	Disabled,
+	Move {
+		#[holy(fuzz = "range(-10, 10)")]
+		dx: i32,
+	},
+	Say(#[holy(fuzz = "ascii(1, 4)")] String),
+}
+
+fn main() {
+	let data: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+	let mut u = Unstructured::new(&data);
+
+	for _ in 0..32 {
+		let profile = Profile::arbitrary(&mut u).unwrap();
+		assert!((3..=12).contains(&profile.handle.len()));
+		assert!(profile.handle.chars().all(|c| c.is_ascii_alphanumeric()));
+		assert!((13..=120).contains(&profile.age));
+		assert!((0.0..=5.0).contains(&profile.rating));
+
+		match Command::arbitrary(&mut u).unwrap() {
+			Command::Disabled => panic!("zero-weight variant generated"),
+			Command::Move { dx } => assert!((-10..=10).contains(&dx)),
+			Command::Say(text) => assert!((1..=4).contains(&text.len())),
+		}
+	}
+
+	// An exhausted input still yields in-range values.
+	let profile = Profile::arbitrary(&mut Unstructured::new(&[])).unwrap();
+	assert!((13..=120).contains(&profile.age));
+}
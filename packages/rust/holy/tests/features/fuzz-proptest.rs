@@ -0,0 +1,64 @@
+use holy::Fuzz;
+use proptest::prelude::*;
+use proptest::test_runner::{TestError, TestRunner};
+
+#[derive(Debug, Clone, Fuzz)]
+#[holy(fuzz_proptest)]
+pub struct Profile {
+	#[holy(fuzz = "alphanumeric(3, 12)")]
+	pub handle: String,
+	#[holy(fuzz = "range(13, 120)")]
+	pub age: u8,
+	#[holy(fuzz = "range(0.0, 5.0)")]
+	pub rating: f32,
+	pub verified: bool,
+}
+
+#[derive(Debug, Clone, Fuzz)]
+#[holy(fuzz_proptest)]
+pub enum Command {
+	#[holy(weight = 0)]
+	Disabled,
+	Move {
+		#[holy(fuzz = "range(-10, 10)")]
+		dx: i32,
+	},
+	Say(#[holy(fuzz = "ascii(1, 4)")] String),
+	Quit,
+}
+
+fn main() {
+	let mut runner = TestRunner::default();
+
+	runner
+		.run(&any::<Profile>(), |profile| {
+			prop_assert!((3..=12).contains(&profile.handle.len()));
+			prop_assert!(profile.handle.chars().all(|c| c.is_ascii_alphanumeric()));
+			prop_assert!((13..=120).contains(&profile.age));
+			prop_assert!((0.0..=5.0).contains(&profile.rating));
+			Ok(())
+		})
+		.unwrap();
+
+	runner
+		.run(&any::<Command>(), |command| {
+			match command {
+				Command::Disabled => prop_assert!(false, "zero-weight variant generated"),
+				Command::Move { dx } => prop_assert!((-10..=10).contains(&dx)),
+				Command::Say(text) => prop_assert!((1..=4).contains(&text.len())),
+				Command::Quit => {}
+			}
+			Ok(())
+		})
+		.unwrap();
+
+	// Shrinking stays inside the declared constraints.
+	let failure = TestRunner::default().run(&any::<Profile>(), |profile| {
+		prop_assert!(profile.handle.len() > 5);
+		Ok(())
+	});
+	match failure {
+		Err(TestError::Fail(_, profile)) => assert!((3..=5).contains(&profile.handle.len())),
+		other => panic!("expected a shrunk failure, got {:?}", other),
+	}
+}
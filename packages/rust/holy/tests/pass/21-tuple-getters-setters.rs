@@ -0,0 +1,12 @@
+use holy::{Getters, Setters};
+
+#[derive(Getters, Setters)]
+pub struct Point(pub i32, pub i32, #[holy(skip)] pub String);
+
+fn main() {
+	let mut p = Point(1, 2, "origin".into());
+	let _x: &i32 = p._0();
+	p.set_1(5);
+	assert_eq!(*p._1(), 5);
+	assert_eq!(p.2, "origin");
+}
@@ -0,0 +1,50 @@
+use holy::Sanitize;
+
+#[derive(Sanitize)]
+pub struct Tag(#[holy(sanitize = "trim, lowercase")] pub String, pub u32);
+
+#[derive(Sanitize)]
+pub enum Message {
+	Chat {
+		#[holy(sanitize = "trim, control_strip, truncate(10)")]
+		body: String,
+		#[holy(sanitize = "clamp(0, 3)")]
+		priority: u8,
+		channel: String,
+	},
+	Rename(
+		u64,
+		#[holy(sanitize = "trim, slug")] String,
+		#[holy(sanitize = "trim")] Option<String>,
+	),
+	Ping,
+}
+
+fn main() {
+	let mut chat = Message::Chat {
+		body: "  hello\u{200B} world, again  ".into(),
+		priority: 9,
+		channel: "  general ".into(),
+	};
+	chat.sanitize();
+	let Message::Chat { body, priority, channel } = chat else {
+		unreachable!()
+	};
+	assert_eq!(body, "hello worl");
+	assert_eq!(priority, 3);
+	assert_eq!(channel, "  general ");
+
+	let mut rename = Message::Rename(7, " My Room ".into(), Some("  old ".into()));
+	rename.sanitize();
+	let Message::Rename(id, slug, previous) = rename else {
+		unreachable!()
+	};
+	assert_eq!((id, slug.as_str(), previous.as_deref()), (7, "my-room", Some("old")));
+
+	let mut ping = Message::Ping;
+	ping.sanitize();
+
+	let mut tag = Tag("  RUST ".into(), 1);
+	tag.sanitize_0();
+	assert_eq!(tag.0, "rust");
+}
@@ -0,0 +1,49 @@
+use holy::Fuzz;
+
+#[derive(Fuzz)]
+pub struct Pair(#[holy(fuzz = "range(1, 9)")] pub u8, pub bool);
+
+#[derive(Fuzz)]
+pub enum Command {
+	#[holy(weight = 0)]
+	Disabled,
+	Move {
+		#[holy(fuzz = "range(-10, 10)")]
+		dx: i32,
+		#[holy(fuzz = "range(-10, 10)")]
+		dy: i32,
+	},
+	#[holy(weight = 3)]
+	Say(#[holy(fuzz = "alphanumeric(1, 4)")] String),
+	Quit,
+}
+
+fn main() {
+	use rand::SeedableRng;
+
+	assert!(matches!(Command::fuzz_default(), Command::Move { dx: 0, dy: 0 }));
+	let pair = Pair::fuzz_default();
+	assert_eq!((pair.0, pair.1), (5, false));
+
+	let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+	let mut counts = [0usize; 4];
+	for _ in 0..2000 {
+		match Command::fuzz_with(&mut rng) {
+			Command::Disabled => counts[0] += 1,
+			Command::Move { dx, dy } => {
+				assert!((-10..=10).contains(&dx) && (-10..=10).contains(&dy));
+				counts[1] += 1;
+			}
+			Command::Say(text) => {
+				assert!((1..=4).contains(&text.len()));
+				counts[2] += 1;
+			}
+			Command::Quit => counts[3] += 1,
+		}
+	}
+	assert_eq!(counts[0], 0);
+	assert!(counts[2] > counts[1] && counts[2] > counts[3]);
+
+	let pair = Pair::fuzz_with(&mut rng);
+	assert!((1..=9).contains(&pair.0));
+}
@@ -0,0 +1,61 @@
+use holy::Sanitize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Sanitize)]
+pub struct Address {
+	#[holy(sanitize = "trim, truncate(8)")]
+	pub city: String,
+}
+
+#[derive(Sanitize)]
+pub struct Member {
+	#[holy(sanitize = "trim, lowercase")]
+	pub name: String,
+	#[holy(sanitize = "nested")]
+	pub address: Option<Address>,
+}
+
+#[derive(Sanitize)]
+pub struct Team {
+	#[holy(sanitize = "nested")]
+	pub lead: Member,
+	#[holy(sanitize = "nested, max_len(2)")]
+	pub members: Vec<Member>,
+	#[holy(sanitize = "each(trim, lowercase), dedupe, max_len(3)")]
+	pub tags: Vec<String>,
+	#[holy(sanitize = "each(trim)")]
+	pub aliases: Option<Vec<String>>,
+	#[holy(sanitize = "keys(trim, lowercase), values(trim, truncate(4))")]
+	pub labels: BTreeMap<String, String>,
+	#[holy(sanitize = "nested")]
+	pub by_role: HashMap<String, Vec<Member>>,
+}
+
+fn member(name: &str, city: Option<&str>) -> Member {
+	Member {
+		name: name.into(),
+		address: city.map(|c| Address { city: c.into() }),
+	}
+}
+
+fn main() {
+	let mut team = Team {
+		lead: member("  ADA ", Some("  Amsterdam  ")),
+		members: vec![member(" Bob", None), member("CAROL ", Some(" Oslo ")), member("dan", None)],
+		tags: vec![" Rust ".into(), "rust".into(), "GO".into(), " zig".into(), "c".into()],
+		aliases: Some(vec!["  a1 ".into()]),
+		labels: BTreeMap::from([("  Env ".to_string(), "  production ".to_string())]),
+		by_role: HashMap::from([("ops".to_string(), vec![member("  EVE ", None)])]),
+	};
+	team.sanitize();
+
+	assert_eq!(team.lead.name, "ada");
+	assert_eq!(team.lead.address.as_ref().unwrap().city, "Amsterda");
+	assert_eq!(team.members.len(), 2);
+	assert_eq!(team.members[0].name, "bob");
+	assert_eq!(team.members[1].address.as_ref().unwrap().city, "Oslo");
+	assert_eq!(team.tags, vec!["rust", "go", "zig"]);
+	assert_eq!(team.aliases, Some(vec!["a1".to_string()]));
+	assert_eq!(team.labels.get("env").map(String::as_str), Some("prod"));
+	assert_eq!(team.by_role["ops"][0].name, "eve");
+}
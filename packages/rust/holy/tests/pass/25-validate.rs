@@ -0,0 +1,98 @@
+use holy::Validate;
+
+fn not_reserved(name: &str) -> Result<(), String> {
+	if name == "admin" {
+		Err("is reserved".to_string())
+	} else {
+		Ok(())
+	}
+}
+
+#[derive(Validate)]
+pub struct Member {
+	#[holy(validate = "required, length(2, 16), custom(not_reserved)")]
+	pub name: String,
+	#[holy(validate = "email")]
+	pub email: Option<String>,
+}
+
+#[derive(Validate)]
+pub struct Signup {
+	#[holy(validate = r#"required, regex("^[a-z0-9_]+$")"#)]
+	pub handle: String,
+	#[holy(validate = "email")]
+	pub email: String,
+	#[holy(validate = "url")]
+	pub homepage: Option<String>,
+	#[holy(validate = "range(13, 120)")]
+	pub age: u8,
+	#[holy(validate = "range(0, 1)")]
+	pub score: f32,
+	#[holy(validate = "one_of(free, pro, team)")]
+	pub plan: String,
+	#[holy(validate = "length(1, 3)")]
+	pub tags: Vec<String>,
+	#[holy(validate = "required, nested")]
+	pub lead: Option<Member>,
+	#[holy(validate = "nested")]
+	pub members: Vec<Member>,
+}
+
+fn member(name: &str, email: Option<&str>) -> Member {
+	Member {
+		name: name.to_string(),
+		email: email.map(str::to_string),
+	}
+}
+
+fn main() {
+	let valid = Signup {
+		handle: "holy_user".to_string(),
+		email: "user@example.com".to_string(),
+		homepage: None,
+		age: 30,
+		score: 0.5,
+		plan: "pro".to_string(),
+		tags: vec!["rust".to_string()],
+		lead: Some(member("alice", Some("alice@example.com"))),
+		members: vec![member("bob", None)],
+	};
+	assert_eq!(valid.validate(), Ok(()));
+
+	let invalid = Signup {
+		handle: "Holy User".to_string(),
+		email: "not-an-email".to_string(),
+		homepage: Some("ftp://example.com".to_string()),
+		age: 9,
+		score: 1.5,
+		plan: "enterprise".to_string(),
+		tags: Vec::new(),
+		lead: None,
+		members: vec![member("bob", None), member("admin", Some("nope"))],
+	};
+	let errors = invalid.validate().unwrap_err();
+	let paths: Vec<(&str, &str)> = errors
+		.iter()
+		.map(|e| (e.path.as_str(), e.code))
+		.collect();
+	assert_eq!(
+		paths,
+		vec![
+			("handle", "regex"),
+			("email", "email"),
+			("homepage", "url"),
+			("age", "range"),
+			("score", "range"),
+			("plan", "one_of"),
+			("tags", "length"),
+			("lead", "required"),
+			("members[1].name", "custom"),
+			("members[1].email", "email"),
+		]
+	);
+	assert_eq!(errors[3].message, "must be between 13 and 120");
+	assert_eq!(errors[8].to_string(), "members[1].name: is reserved");
+
+	assert_eq!(invalid.validate_age().len(), 1);
+	assert!(valid.validate_members().is_empty());
+}
@@ -0,0 +1,45 @@
+use holy::Observer;
+use std::sync::{Arc, Mutex};
+
+#[derive(Observer)]
+pub struct Player {
+	#[holy(observe)]
+	pub health: u32,
+	#[holy(observe)]
+	pub name: String,
+}
+
+fn main() {
+	let mut player = Player {
+		health: 100,
+		name: "hero".into(),
+	};
+	let observers = PlayerObservers::new();
+	let seen = Arc::new(Mutex::new(Vec::new()));
+
+	let log = seen.clone();
+	let guard = observers.subscribe_health(move |old, new| {
+		log.lock().unwrap().push((*old, *new));
+	});
+
+	assert_eq!(player.update_health(80, &observers), 100);
+	assert_eq!(player.health, 80);
+	player.update_health(60, &observers);
+	assert_eq!(*seen.lock().unwrap(), vec![(100, 80), (80, 60)]);
+
+	drop(guard);
+	player.update_health(40, &observers);
+	assert_eq!(seen.lock().unwrap().len(), 2);
+
+	let renamed = Arc::new(Mutex::new(None));
+	let slot = renamed.clone();
+	observers
+		.subscribe_name(move |old: &String, new: &String| {
+			*slot.lock().unwrap() = Some(format!("{old} -> {new}"));
+		})
+		.detach();
+	player.update_name("champion".into(), &observers);
+	assert_eq!(renamed.lock().unwrap().as_deref(), Some("hero -> champion"));
+
+	observers.publish_health(&1, &2);
+}
@@ -0,0 +1,38 @@
+use holy::Observer;
+
+#[derive(Observer)]
+#[holy(observer_tokio)]
+pub struct Lobby {
+	#[holy(observe, capacity = 4)]
+	pub players: u32,
+}
+
+fn main() {
+	let mut lobby = Lobby { players: 0 };
+	let observers = LobbyObservers::new();
+
+	let mut changes = observers.changes_players();
+	let watch = observers.watch_players();
+	assert_eq!(*watch.borrow(), None);
+
+	lobby.update_players(1, &observers);
+	lobby.update_players(2, &observers);
+
+	assert_eq!(changes.try_recv().unwrap(), (0, 1));
+	assert_eq!(changes.try_recv().unwrap(), (1, 2));
+	assert!(changes.try_recv().is_err());
+	assert_eq!(*watch.borrow(), Some(2));
+	assert!(watch.has_changed().unwrap());
+
+	// Late subscribers see the latest value but only future changes.
+	let late = observers.watch_players();
+	assert_eq!(*late.borrow(), Some(2));
+
+	// With every receiver gone, `changes` is skipped but the watch still
+	// tracks the latest value for whoever subscribes next.
+	drop((changes, watch, late));
+	lobby.update_players(3, &observers);
+	assert_eq!(*observers.watch_players().borrow(), Some(3));
+	let mut changes = observers.changes_players();
+	assert!(changes.try_recv().is_err());
+}
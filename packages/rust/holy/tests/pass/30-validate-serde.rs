@@ -0,0 +1,41 @@
+use holy::Validate;
+
+#[derive(Validate)]
+pub struct Member {
+	#[holy(validate = "email")]
+	pub email: String,
+}
+
+#[derive(Validate)]
+#[holy(validate_serde)]
+pub struct Team {
+	#[holy(validate = "length(2, 16)")]
+	pub name: String,
+	#[holy(validate = "nested")]
+	pub members: Vec<Member>,
+}
+
+fn main() {
+	let team = Team {
+		name: "x".to_string(),
+		members: vec![
+			Member { email: "a@example.com".to_string() },
+			Member { email: "nope".to_string() },
+		],
+	};
+	let errors = team.validate().unwrap_err();
+
+	let json = serde_json::to_value(&errors).unwrap();
+	assert_eq!(json.as_array().unwrap().len(), 2);
+	assert_eq!(json[0]["path"], "name");
+	assert_eq!(json[0]["code"], "length");
+	assert_eq!(json[0]["message"], errors[0].message.as_str());
+	assert_eq!(
+		json[1],
+		serde_json::json!({
+			"path": "members[1].email",
+			"code": "email",
+			"message": "must be a valid email address",
+		})
+	);
+}
@@ -0,0 +1,28 @@
+use holy::Sanitize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Sanitize)]
+pub struct Labels {
+	#[holy(sanitize = "keys(trim, lowercase)")]
+	pub hashed: HashMap<String, u32>,
+	#[holy(sanitize = "keys(trim, lowercase)")]
+	pub sorted: BTreeMap<String, u32>,
+}
+
+fn main() {
+	// " env" < "ENV" < "Env" < "env ", so " env" wins however the map iterates.
+	let keys = ["Env", "env ", " env", "ENV"];
+	for rotation in 0..keys.len() {
+		let mut labels = Labels { hashed: HashMap::new(), sorted: BTreeMap::new() };
+		for i in (0..keys.len()).map(|i| (i + rotation) % keys.len()) {
+			labels.hashed.insert(keys[i].to_string(), i as u32);
+			labels.sorted.insert(keys[i].to_string(), i as u32);
+		}
+		labels.sanitize();
+
+		assert_eq!(labels.hashed.len(), 1);
+		assert_eq!(labels.hashed["env"], 2);
+		assert_eq!(labels.sorted.len(), 1);
+		assert_eq!(labels.sorted["env"], 2);
+	}
+}
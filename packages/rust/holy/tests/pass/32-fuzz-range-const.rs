@@ -0,0 +1,24 @@
+use holy::Fuzz;
+
+const MAX_LEVEL: u32 = 60;
+
+#[derive(Fuzz)]
+pub struct Character {
+	#[holy(fuzz = "range(1, MAX_LEVEL)")]
+	pub level: u32,
+	#[holy(fuzz = "range(0, u8::MAX)")]
+	pub luck: u8,
+	#[holy(fuzz = "range(-(MAX_LEVEL as i64), MAX_LEVEL as i64 * 2)")]
+	pub offset: i64,
+}
+
+fn main() {
+	use rand::SeedableRng;
+	let c = Character::fuzz_default();
+	assert_eq!(c.level, 30);
+	assert_eq!(c.luck, 127);
+	let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+	let c = Character::fuzz_with(&mut rng);
+	assert!((1..=MAX_LEVEL).contains(&c.level));
+	assert!((-60..=120).contains(&c.offset));
+}
@@ -0,0 +1,34 @@
+use holy::Sanitize;
+
+#[derive(Sanitize)]
+pub struct Marker {
+	pub id: u32,
+}
+
+#[derive(Sanitize)]
+pub enum Flag {
+	On,
+	Off,
+}
+
+#[derive(Sanitize)]
+pub struct Payload {
+	#[holy(sanitize = "nested")]
+	pub marker: Marker,
+	#[holy(sanitize = "nested")]
+	pub flags: Vec<Flag>,
+	#[holy(sanitize = "trim")]
+	pub name: String,
+}
+
+fn main() {
+	let mut payload = Payload {
+		marker: Marker { id: 7 },
+		flags: vec![Flag::On, Flag::Off],
+		name: " x ".into(),
+	};
+	payload.sanitize();
+	assert_eq!(payload.marker.id, 7);
+	assert!(matches!(payload.flags[..], [Flag::On, Flag::Off]));
+	assert_eq!(payload.name, "x");
+}
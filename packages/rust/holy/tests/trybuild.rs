@@ -3,4 +3,8 @@ fn tests() {
 	let t = trybuild::TestCases::new();
 	t.pass("tests/pass/*.rs");
 	t.compile_fail("tests/fail/*.rs");
+	#[cfg(feature = "proptest")]
+	t.pass("tests/features/fuzz-proptest.rs");
+	#[cfg(feature = "arbitrary")]
+	t.pass("tests/features/fuzz-arbitrary.rs");
 }
@@ -0,0 +1,181 @@
+//! Choosing an LLM provider from configuration.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::featherless::{FEATHERLESS_BASE_URL, FEATHERLESS_DEFAULT_MODEL};
+use super::groq::{GROQ_BASE_URL, GROQ_DEFAULT_MODEL};
+use super::openai::{DEFAULT_MAX_RETRIES, OpenAiCompatClient};
+use super::provider::LlmProvider;
+use crate::entity::error::JediError;
+use crate::state::sidecar::get_env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProviderKind {
+    Groq,
+    Featherless,
+    /// Any self-hosted OpenAI-compatible server.
+    Local,
+}
+
+impl std::str::FromStr for LlmProviderKind {
+    type Err = JediError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "groq" => Ok(LlmProviderKind::Groq),
+            "featherless" => Ok(LlmProviderKind::Featherless),
+            "local" | "openai" | "openai-compatible" => Ok(LlmProviderKind::Local),
+            other => Err(JediError::BadRequest(format!(
+                "unknown LLM provider '{other}'"
+            ))),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LlmConfig {
+    pub kind: LlmProviderKind,
+    /// Required for `Local`; overrides the hosted default otherwise.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub max_retries: u32,
+    pub max_concurrency: Option<usize>,
+}
+
+impl LlmConfig {
+    /// `LLM_PROVIDER` (`groq` | `featherless` | `local`, default `groq`),
+    /// `LLM_BASE_URL`, `LLM_API_KEY`, `LLM_MODEL`, `LLM_MAX_RETRIES` and
+    /// `LLM_MAX_CONCURRENCY` (each also readable via `*_FILE`). The key
+    /// falls back to `GROQ_API_KEY` / `FEATHERLESS_API_KEY`.
+    pub fn from_env() -> Result<Self, JediError> {
+        let kind: LlmProviderKind = get_env("LLM_PROVIDER", "groq").parse()?;
+        let set = |key: &str| Some(get_env(key, "")).filter(|v| !v.trim().is_empty());
+
+        let api_key = set("LLM_API_KEY").or_else(|| match kind {
+            LlmProviderKind::Groq => set("GROQ_API_KEY"),
+            LlmProviderKind::Featherless => set("FEATHERLESS_API_KEY"),
+            LlmProviderKind::Local => None,
+        });
+        let base_url = set("LLM_BASE_URL");
+
+        match kind {
+            LlmProviderKind::Local if base_url.is_none() => {
+                return Err(JediError::Internal("LLM_BASE_URL not set".into()));
+            }
+            LlmProviderKind::Groq | LlmProviderKind::Featherless if api_key.is_none() => {
+                return Err(JediError::Internal("LLM_API_KEY not set".into()));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            kind,
+            base_url,
+            api_key,
+            model: set("LLM_MODEL"),
+            max_retries: get_env("LLM_MAX_RETRIES", "")
+                .parse()
+                .unwrap_or(DEFAULT_MAX_RETRIES),
+            max_concurrency: get_env("LLM_MAX_CONCURRENCY", "").parse().ok(),
+        })
+    }
+
+    pub fn build(&self) -> Result<Arc<dyn LlmProvider>, JediError> {
+        let (name, base_url, model) = match self.kind {
+            LlmProviderKind::Groq => ("groq", GROQ_BASE_URL, GROQ_DEFAULT_MODEL),
+            LlmProviderKind::Featherless => (
+                "featherless",
+                FEATHERLESS_BASE_URL,
+                FEATHERLESS_DEFAULT_MODEL,
+            ),
+            LlmProviderKind::Local => {
+                let model = self.model.as_deref().ok_or_else(|| {
+                    JediError::BadRequest("local LLM provider requires a model".into())
+                })?;
+                ("local", "", model)
+            }
+        };
+
+        let base_url = self.base_url.as_deref().unwrap_or(base_url);
+        if base_url.is_empty() {
+            return Err(JediError::BadRequest(
+                "local LLM provider requires a base URL".into(),
+            ));
+        }
+
+        let mut client =
+            OpenAiCompatClient::new(name, base_url, self.model.as_deref().unwrap_or(model))
+                .with_retries(self.max_retries, Duration::from_millis(500));
+        if let Some(key) = &self.api_key {
+            client = client.with_api_key(key.clone());
+        }
+        if let Some(max) = self.max_concurrency {
+            client = client.with_max_concurrency(max);
+        }
+        Ok(Arc::new(client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(kind: LlmProviderKind) -> LlmConfig {
+        LlmConfig {
+            kind,
+            base_url: None,
+            api_key: Some("key".into()),
+            model: None,
+            max_retries: 0,
+            max_concurrency: None,
+        }
+    }
+
+    #[test]
+    fn builds_hosted_presets_with_defaults() {
+        let groq = config(LlmProviderKind::Groq).build().unwrap();
+        assert_eq!(groq.name(), "groq");
+        assert_eq!(groq.default_model(), GROQ_DEFAULT_MODEL);
+
+        let featherless = LlmConfig {
+            model: Some("Qwen/Qwen2.5-7B-Instruct".into()),
+            ..config(LlmProviderKind::Featherless)
+        }
+        .build()
+        .unwrap();
+        assert_eq!(featherless.default_model(), "Qwen/Qwen2.5-7B-Instruct");
+    }
+
+    #[test]
+    fn local_requires_url_and_model() {
+        let local = config(LlmProviderKind::Local);
+        assert!(matches!(local.build(), Err(JediError::BadRequest(_))));
+
+        let local = LlmConfig {
+            model: Some("qwen".into()),
+            ..local
+        };
+        assert!(matches!(local.build(), Err(JediError::BadRequest(_))));
+
+        let local = LlmConfig {
+            base_url: Some("http://127.0.0.1:8080/v1".into()),
+            ..local
+        };
+        assert_eq!(local.build().unwrap().name(), "local");
+    }
+
+    #[test]
+    fn provider_kind_parses() {
+        assert_eq!(
+            "Groq".parse::<LlmProviderKind>().unwrap(),
+            LlmProviderKind::Groq
+        );
+        assert_eq!(
+            "openai".parse::<LlmProviderKind>().unwrap(),
+            LlmProviderKind::Local
+        );
+        assert!("bard".parse::<LlmProviderKind>().is_err());
+    }
+}
@@ -1,186 +1,59 @@
-use crossbeam::queue::SegQueue;
-use reqwest::{Client, StatusCode};
-use serde::{Deserialize, Serialize};
-use std::error::Error;
-use std::sync::Arc;
-use tokio::time::{Duration, sleep};
-use tracing::{error, info, warn};
+//! Groq's OpenAI-compatible endpoint.
 
-// Constants
-const BASE_URL: &str = "https://api.groq.com/";
+use std::time::Duration;
 
-// Structs
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqMessage {
-    pub role: String,
-    pub content: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqRequestBody {
-    pub messages: Vec<GroqMessage>,
-    pub model: String,
-    pub response_format: Option<serde_json::Value>,
-}
-
-// Structs for Response
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqMessageContent {
-    role: String,
-    content: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqChoice {
-    index: u32,
-    message: GroqMessageContent,
-    finish_reason: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqUsage {
-    prompt_tokens: u32,
-    prompt_time: f64,
-    completion_tokens: u32,
-    completion_time: f64,
-    total_tokens: u32,
-    total_time: f64,
-}
+use super::openai::{OpenAiCompatClient, delegate_llm_provider};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GroqResponse {
-    id: String,
-    object: String,
-    created: u64,
-    model: String,
-    choices: Vec<GroqChoice>,
-    usage: GroqUsage,
-    system_fingerprint: String,
-    x_groq: Option<serde_json::Value>, // Generic JSON value for unknown or optional fields
-}
-
-#[derive(Debug)]
-pub enum GroqError {
-    ClientError(String),
-    NoAvailableClients,
-}
-
-impl std::fmt::Display for GroqError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GroqError::ClientError(msg) => write!(f, "Client Error: {}", msg),
-            GroqError::NoAvailableClients => write!(f, "No available clients"),
-        }
-    }
-}
-
-impl Error for GroqError {}
+pub const GROQ_BASE_URL: &str = "https://api.groq.com/openai/v1";
+pub const GROQ_DEFAULT_MODEL: &str = "llama-3.3-70b-versatile";
 
 #[derive(Clone)]
 pub struct GroqClient {
-    client: Arc<SegQueue<Client>>,
-    api_key: String,
-    rate_limit_delay: Duration,
-    max_retries: usize,
+    inner: OpenAiCompatClient,
 }
 
 impl GroqClient {
-    pub fn new(
-        api_key: String,
-        num_clients: usize,
-        rate_limit_delay: Duration,
-        max_retries: usize,
-    ) -> Self {
-        let queue = Arc::new(SegQueue::new());
-        for _ in 0..num_clients {
-            queue.push(
-                Client::builder()
-                    .use_rustls_tls()
-                    .build()
-                    .expect("Failed to build client within GroqClient"),
-            );
-        }
-        GroqClient {
-            client: queue,
-            api_key,
-            rate_limit_delay,
-            max_retries,
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            inner: OpenAiCompatClient::new("groq", GROQ_BASE_URL, GROQ_DEFAULT_MODEL)
+                .with_api_key(api_key),
         }
     }
 
-    pub fn pop_client(&self) -> Option<Client> {
-        self.client.pop()
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.inner = self.inner.with_model(model);
+        self
     }
 
-    pub fn push_client(&self, client: Client) {
-        self.client.push(client)
+    /// See [`OpenAiCompatClient::with_retries`]. Groq sends `Retry-After`
+    /// on `429`, which takes precedence over `backoff`.
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.inner = self.inner.with_retries(max_retries, backoff);
+        self
     }
 
-    pub async fn test_request(
-        &self,
-        body: &GroqRequestBody,
-    ) -> Result<GroqResponse, Box<dyn Error>> {
-        if let Some(client) = self.pop_client() {
-            let url = format!("{}/openai/v1/chat/completions", BASE_URL);
-            let mut attempts = 0;
-
-            while attempts < self.max_retries {
-                attempts += 1;
-                let response = client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", self.api_key))
-                    .header("Content-Type", "application/json")
-                    .json(body)
-                    .send()
-                    .await;
+    /// Override the base URL (useful for a proxy or testing).
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.inner = self.inner.with_base_url(url);
+        self
+    }
+}
 
-                match response {
-                    Ok(resp) => {
-                        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
-                            warn!(
-                                "Rate limited. Retrying in {} seconds...",
-                                self.rate_limit_delay.as_secs()
-                            );
-                            sleep(self.rate_limit_delay).await;
-                        } else if resp.status().is_success() {
-                            let groq_response: GroqResponse = resp.json().await?;
-                            self.push_client(client);
-                            return Ok(groq_response);
-                        } else {
-                            error!("Request failed with status: {}", resp.status());
-                            self.push_client(client);
-                            return Err(Box::new(GroqError::ClientError(
-                                resp.status().to_string(),
-                            )));
-                        }
-                    }
-                    Err(e) => {
-                        error!("Request failed: {}", e);
-                        if attempts >= self.max_retries {
-                            self.push_client(client);
-                            return Err(Box::new(e));
-                        } else {
-                            warn!(
-                                "Retrying request (attempt {}/{})",
-                                attempts, self.max_retries
-                            );
-                            sleep(self.rate_limit_delay).await;
-                        }
-                    }
-                }
-            }
+delegate_llm_provider!(GroqClient);
 
-            self.push_client(client);
-            Err(Box::new(GroqError::ClientError(
-                "Max retries exceeded".to_string(),
-            )))
-        } else {
-            Err(Box::new(GroqError::NoAvailableClients))
-        }
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::ai::LlmProvider;
 
-    pub fn shutdown(&self) {
-        info!("Shutting down GroqClient and releasing all clients.");
-        while self.client.pop().is_some() {}
+    #[test]
+    fn groq_defaults() {
+        let groq = GroqClient::new("gsk-test");
+        assert_eq!(groq.name(), "groq");
+        assert_eq!(groq.default_model(), GROQ_DEFAULT_MODEL);
+        assert_eq!(
+            groq.with_model("llama-3.1-8b-instant").default_model(),
+            "llama-3.1-8b-instant"
+        );
     }
 }
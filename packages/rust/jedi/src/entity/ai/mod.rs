@@ -1,5 +1,12 @@
+pub mod config;
 pub mod featherless;
 pub mod groq;
+pub mod openai;
+pub mod provider;
+mod sse;
 
+pub use config::*;
 pub use featherless::*;
 pub use groq::*;
+pub use openai::*;
+pub use provider::*;
@@ -0,0 +1,788 @@
+//! OpenAI-compatible chat-completions client, the HTTP implementation
+//! behind every [`LlmProvider`] in this module.
+//!
+//! ```ignore
+//! let llm = OpenAiCompatClient::local("http://127.0.0.1:8080/v1", "qwen2.5-7b-instruct");
+//! let mut stream = llm.chat_stream(&LlmRequest::new(vec![LlmMessage::user("hi")])).await?;
+//! while let Some(event) = stream.try_next().await? {
+//!     if let LlmStreamEvent::Delta(text) = event { print!("{text}"); }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
+
+use super::provider::*;
+use super::sse::SseDecoder;
+use crate::entity::error::JediError;
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone)]
+pub struct OpenAiCompatClient {
+    client: Client,
+    name: String,
+    base_url: String,
+    api_key: Option<String>,
+    default_model: String,
+    max_retries: u32,
+    backoff: Duration,
+    limiter: Option<Arc<Semaphore>>,
+    usage: Arc<UsageMeter>,
+}
+
+impl OpenAiCompatClient {
+    /// `base_url` is the root that `/chat/completions` hangs off, e.g.
+    /// `https://api.groq.com/openai/v1`.
+    pub fn new(name: &str, base_url: &str, default_model: &str) -> Self {
+        Self {
+            client: build_client(DEFAULT_TIMEOUT),
+            name: name.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+            default_model: default_model.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            limiter: None,
+            usage: Arc::new(UsageMeter::default()),
+        }
+    }
+
+    /// Any local OpenAI-compatible server (llama.cpp, mistral.rs, MLX,
+    /// vLLM, Ollama's `/v1`). No API key is sent unless one is set.
+    pub fn local(base_url: &str, model: &str) -> Self {
+        Self::new("local", base_url, model)
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into()).filter(|k| !k.is_empty());
+        self
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.default_model = model.to_string();
+        self
+    }
+
+    /// Retry `429`, `5xx` and transport errors up to `max_retries` times,
+    /// waiting `Retry-After` when the server sends it and otherwise
+    /// `backoff` doubled per attempt (capped at 60s).
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Cap in-flight requests; a streaming response holds its slot until
+    /// the stream ends or is dropped.
+    pub fn with_max_concurrency(mut self, max: usize) -> Self {
+        self.limiter = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Whole-request timeout, including the time to stream the body.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = build_client(timeout);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn body(&self, request: &LlmRequest, stream: bool) -> Value {
+        let mut body = json!({
+            "model": request.model.as_deref().unwrap_or(&self.default_model),
+            "messages": request.messages,
+        });
+        let fields = body.as_object_mut().expect("object literal");
+
+        if let Some(t) = request.temperature {
+            fields.insert("temperature".into(), json!(t));
+        }
+        if let Some(p) = request.top_p {
+            fields.insert("top_p".into(), json!(p));
+        }
+        if let Some(n) = request.max_tokens {
+            fields.insert("max_tokens".into(), json!(n));
+        }
+        if !request.stop.is_empty() {
+            fields.insert("stop".into(), json!(request.stop));
+        }
+        if !request.tools.is_empty() {
+            let tools = request.tools.iter().map(LlmTool::to_wire).collect();
+            fields.insert("tools".into(), Value::Array(tools));
+        }
+        if let Some(choice) = &request.tool_choice {
+            fields.insert("tool_choice".into(), choice.to_wire());
+        }
+        if let Some(format) = &request.response_format {
+            fields.insert("response_format".into(), format.to_wire());
+        }
+        if stream {
+            fields.insert("stream".into(), json!(true));
+            fields.insert("stream_options".into(), json!({ "include_usage": true }));
+        }
+        body
+    }
+
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1 << attempt.min(16))
+            .min(MAX_BACKOFF)
+    }
+
+    /// POST to `/chat/completions`, retrying transient failures. Returns
+    /// the successful response together with its concurrency slot.
+    async fn send(
+        &self,
+        body: &Value,
+    ) -> Result<(Response, Option<OwnedSemaphorePermit>), JediError> {
+        let permit = match &self.limiter {
+            Some(limiter) => Some(
+                limiter
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| JediError::Internal("LLM limiter closed".into()))?,
+            ),
+            None => None,
+        };
+
+        let url = format!("{}/chat/completions", self.base_url);
+        let mut attempt = 0;
+        loop {
+            let mut req = self.client.post(&url).json(body);
+            if let Some(key) = &self.api_key {
+                req = req.bearer_auth(key);
+            }
+
+            let delay = match req.send().await {
+                Ok(resp) if resp.status().is_success() => return Ok((resp, permit)),
+                Ok(resp) if is_transient(resp.status()) && attempt < self.max_retries => {
+                    retry_after(&resp).unwrap_or_else(|| self.backoff_for(attempt))
+                }
+                Ok(resp) => return Err(self.status_error(resp).await),
+                Err(e) if attempt < self.max_retries && !e.is_builder() => {
+                    self.backoff_for(attempt)
+                }
+                Err(e) if e.is_timeout() => return Err(JediError::Timeout),
+                Err(e) => {
+                    return Err(JediError::Internal(
+                        format!("{} request failed: {e}", self.name).into(),
+                    ));
+                }
+            };
+
+            attempt += 1;
+            warn!(
+                provider = %self.name,
+                attempt,
+                max_retries = self.max_retries,
+                delay_ms = delay.as_millis() as u64,
+                "[LLM] transient failure, retrying"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn status_error(&self, resp: Response) -> JediError {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        // OpenAI-style `{"error": {"message": ...}}`; fall back to the raw body.
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or(body);
+
+        match status {
+            StatusCode::UNAUTHORIZED => JediError::Unauthorized,
+            StatusCode::FORBIDDEN => JediError::Forbidden,
+            StatusCode::NOT_FOUND => JediError::NotFound,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                JediError::BadRequest(format!("{}: {message}", self.name))
+            }
+            StatusCode::TOO_MANY_REQUESTS => JediError::Internal(
+                format!(
+                    "{} rate limited after {} retries: {message}",
+                    self.name, self.max_retries
+                )
+                .into(),
+            ),
+            _ => JediError::Internal(format!("{} returned {status}: {message}", self.name).into()),
+        }
+    }
+}
+
+fn build_client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .expect("Failed to build reqwest client for OpenAiCompatClient")
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `Retry-After` in (possibly fractional) seconds; HTTP-date values are
+/// ignored in favour of the normal backoff.
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let secs: f64 = resp
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs).min(MAX_BACKOFF))
+}
+
+// ── Wire types ──────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct Completion {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    choices: Vec<CompletionChoice>,
+    usage: Option<LlmUsage>,
+    /// Groq reports usage here (and only here on streams).
+    x_groq: Option<GroqExtra>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChoice {
+    message: LlmMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GroqExtra {
+    usage: Option<LlmUsage>,
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<LlmUsage>,
+    x_groq: Option<GroqExtra>,
+    /// Some servers report mid-stream failures as an `error` event.
+    error: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    index: u32,
+    #[serde(default)]
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ChunkDelta {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Deserialize)]
+struct ToolCallDelta {
+    #[serde(default)]
+    index: usize,
+    id: Option<String>,
+    function: Option<FunctionDelta>,
+}
+
+#[derive(Deserialize)]
+struct FunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+// ── Streaming ───────────────────────────────────────────────────────
+
+/// How far past the calls seen so far a tool-call delta may index. Servers
+/// number calls densely from 0; the slack only tolerates one that skips a
+/// few, without letting a bogus index allocate millions of empty calls.
+const MAX_TOOL_CALL_INDEX_GAP: usize = 16;
+
+struct StreamState {
+    resp: Response,
+    decoder: SseDecoder,
+    pending: VecDeque<LlmStreamEvent>,
+    calls: Vec<LlmToolCall>,
+    finish_reason: Option<LlmFinishReason>,
+    usage: Option<LlmUsage>,
+    finished: bool,
+    meter: Arc<UsageMeter>,
+    provider: String,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl StreamState {
+    fn handle(&mut self, data: &str) -> Result<(), JediError> {
+        if self.finished {
+            return Ok(());
+        }
+        if data == "[DONE]" {
+            self.finish();
+            return Ok(());
+        }
+
+        let chunk: Chunk = serde_json::from_str(data)
+            .map_err(|e| JediError::Parse(format!("{} stream chunk: {e}", self.provider)))?;
+        if let Some(error) = chunk.error {
+            let message = error["message"]
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string);
+            return Err(JediError::Internal(
+                format!("{} stream error: {message}", self.provider).into(),
+            ));
+        }
+
+        // Only the first choice is surfaced; requests never ask for n > 1.
+        for choice in chunk.choices.into_iter().filter(|c| c.index == 0) {
+            if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+                self.pending.push_back(LlmStreamEvent::Delta(text));
+            }
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                if delta.index > self.calls.len() + MAX_TOOL_CALL_INDEX_GAP {
+                    return Err(JediError::Parse(format!(
+                        "{} stream: tool call index {} after {} calls",
+                        self.provider,
+                        delta.index,
+                        self.calls.len()
+                    )));
+                }
+                if self.calls.len() <= delta.index {
+                    self.calls.resize_with(delta.index + 1, || LlmToolCall {
+                        id: String::new(),
+                        name: String::new(),
+                        arguments: String::new(),
+                    });
+                }
+                let call = &mut self.calls[delta.index];
+                if let Some(id) = delta.id.filter(|id| !id.is_empty()) {
+                    call.id = id;
+                }
+                if let Some(function) = delta.function {
+                    call.name
+                        .push_str(function.name.as_deref().unwrap_or_default());
+                    call.arguments
+                        .push_str(function.arguments.as_deref().unwrap_or_default());
+                }
+            }
+            if let Some(reason) = choice.finish_reason {
+                self.finish_reason = Some(LlmFinishReason::from_api(&reason));
+            }
+        }
+
+        if let Some(usage) = chunk.usage.or(chunk.x_groq.and_then(|x| x.usage)) {
+            self.usage = Some(usage);
+        }
+        Ok(())
+    }
+
+    /// Emit assembled tool calls and `Done`, record usage, free the slot.
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        for call in self.calls.drain(..).filter(|c| !c.name.is_empty()) {
+            self.pending.push_back(LlmStreamEvent::ToolCall(call));
+        }
+        self.pending.push_back(LlmStreamEvent::Done {
+            finish_reason: self.finish_reason.take(),
+            usage: self.usage,
+        });
+        self.meter.record(self.usage.as_ref());
+        self.permit = None;
+    }
+}
+
+// ── LlmProvider ─────────────────────────────────────────────────────
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatClient {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    async fn chat(&self, request: &LlmRequest) -> Result<LlmResponse, JediError> {
+        let (resp, _permit) = self.send(&self.body(request, false)).await?;
+        let completion: Completion = resp
+            .json()
+            .await
+            .map_err(|e| JediError::Parse(format!("{} response: {e}", self.name)))?;
+
+        let usage = completion.usage.or(completion.x_groq.and_then(|x| x.usage));
+        self.usage.record(usage.as_ref());
+
+        let choice = completion
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| JediError::Parse(format!("{} returned no choices", self.name)))?;
+
+        Ok(LlmResponse {
+            id: completion.id,
+            model: completion.model,
+            message: choice.message,
+            finish_reason: choice
+                .finish_reason
+                .as_deref()
+                .map(LlmFinishReason::from_api),
+            usage,
+        })
+    }
+
+    async fn chat_stream(&self, request: &LlmRequest) -> Result<LlmStream, JediError> {
+        let (resp, permit) = self.send(&self.body(request, true)).await?;
+        let state = StreamState {
+            resp,
+            decoder: SseDecoder::default(),
+            pending: VecDeque::new(),
+            calls: Vec::new(),
+            finish_reason: None,
+            usage: None,
+            finished: false,
+            meter: self.usage.clone(),
+            provider: self.name.clone(),
+            permit,
+        };
+
+        Ok(stream::try_unfold(state, |mut st| async move {
+            loop {
+                if let Some(event) = st.pending.pop_front() {
+                    return Ok(Some((event, st)));
+                }
+                if st.finished {
+                    return Ok(None);
+                }
+                match st.resp.chunk().await {
+                    Ok(Some(bytes)) => {
+                        for data in st.decoder.push(&bytes) {
+                            st.handle(&data)?;
+                        }
+                    }
+                    Ok(None) => {
+                        if let Some(data) = st.decoder.finish() {
+                            st.handle(&data)?;
+                        }
+                        // Servers that skip `[DONE]` just close the body.
+                        st.finish();
+                    }
+                    Err(e) if e.is_timeout() => return Err(JediError::Timeout),
+                    Err(e) => {
+                        return Err(JediError::Internal(
+                            format!("{} stream: {e}", st.provider).into(),
+                        ));
+                    }
+                }
+            }
+        })
+        .boxed())
+    }
+
+    fn usage(&self) -> UsageTotals {
+        self.usage.totals()
+    }
+}
+
+/// Implement [`LlmProvider`] for a preset that wraps an
+/// [`OpenAiCompatClient`] in its `inner` field.
+macro_rules! delegate_llm_provider {
+    ($ty:ty) => {
+        #[async_trait::async_trait]
+        impl $crate::entity::ai::LlmProvider for $ty {
+            fn name(&self) -> &str {
+                $crate::entity::ai::LlmProvider::name(&self.inner)
+            }
+
+            fn default_model(&self) -> &str {
+                $crate::entity::ai::LlmProvider::default_model(&self.inner)
+            }
+
+            async fn chat(
+                &self,
+                request: &$crate::entity::ai::LlmRequest,
+            ) -> Result<$crate::entity::ai::LlmResponse, $crate::entity::error::JediError> {
+                $crate::entity::ai::LlmProvider::chat(&self.inner, request).await
+            }
+
+            async fn chat_stream(
+                &self,
+                request: &$crate::entity::ai::LlmRequest,
+            ) -> Result<$crate::entity::ai::LlmStream, $crate::entity::error::JediError> {
+                $crate::entity::ai::LlmProvider::chat_stream(&self.inner, request).await
+            }
+
+            fn usage(&self) -> $crate::entity::ai::UsageTotals {
+                $crate::entity::ai::LlmProvider::usage(&self.inner)
+            }
+        }
+    };
+}
+pub(crate) use delegate_llm_provider;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        http::{HeaderMap, StatusCode as AxumStatus, header},
+        response::IntoResponse,
+        routing::post,
+    };
+    use futures_util::TryStreamExt;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    async fn mock_server(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}/v1")
+    }
+
+    #[tokio::test]
+    async fn chat_sends_tools_and_schema_and_parses_tool_calls() {
+        let seen: Arc<Mutex<Option<(HeaderMap, Value)>>> = Arc::default();
+        let captured = seen.clone();
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move |headers: HeaderMap, Json(body): Json<Value>| {
+                *captured.lock().unwrap() = Some((headers, body));
+                async {
+                    Json(json!({
+                        "id": "cmpl-1",
+                        "model": "llama-3.3-70b-versatile",
+                        "choices": [{
+                            "index": 0,
+                            "message": {
+                                "role": "assistant",
+                                "content": null,
+                                "tool_calls": [{
+                                    "id": "call_1",
+                                    "type": "function",
+                                    "function": {"name": "lookup", "arguments": "{\"q\":\"kbve\"}"}
+                                }]
+                            },
+                            "finish_reason": "tool_calls"
+                        }],
+                        "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+                    }))
+                }
+            }),
+        );
+        let base = mock_server(app).await;
+        let llm = OpenAiCompatClient::new("test", &base, "default-model").with_api_key("sk-test");
+
+        let request = LlmRequest::new(vec![LlmMessage::user("find kbve")])
+            .temperature(0.5)
+            .tool(LlmTool::new(
+                "lookup",
+                "Search",
+                json!({"type": "object", "properties": {"q": {"type": "string"}}}),
+            ))
+            .tool_choice(LlmToolChoice::Auto)
+            .json_schema("reply", json!({"type": "object"}));
+        let response = llm.chat(&request).await.unwrap();
+
+        assert_eq!(response.finish_reason, Some(LlmFinishReason::ToolCalls));
+        assert_eq!(response.tool_calls()[0].name, "lookup");
+        assert_eq!(response.text(), "");
+        assert_eq!(response.usage.unwrap().total_tokens, 15);
+        assert_eq!(llm.usage().prompt_tokens, 12);
+        assert_eq!(llm.usage().requests, 1);
+
+        let (headers, body) = seen.lock().unwrap().take().unwrap();
+        assert_eq!(headers[header::AUTHORIZATION], "Bearer sk-test");
+        assert_eq!(body["model"], "default-model");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["tools"][0]["function"]["name"], "lookup");
+        assert_eq!(body["tool_choice"], "auto");
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert!(body.get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_yields_deltas_then_assembled_tool_calls_and_usage() {
+        let frames = [
+            json!({"choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]}),
+            json!({"choices": [{"index": 0, "delta": {"content": "lo"}}]}),
+            json!({"choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "id": "call_1", "type": "function", "function": {"name": "look", "arguments": "{\"q\""}}
+            ]}}]}),
+            json!({"choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "function": {"name": "up", "arguments": ":\"kbve\"}"}}
+            ]}}]}),
+            json!({"choices": [{"index": 0, "delta": {}, "finish_reason": "tool_calls"}],
+                   "x_groq": {"usage": {"prompt_tokens": 7, "completion_tokens": 4, "total_tokens": 11}}}),
+        ];
+        let mut sse: String = frames.iter().map(|f| format!("data: {f}\n\n")).collect();
+        sse.push_str("data: [DONE]\n\n");
+
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move |Json(body): Json<Value>| {
+                let sse = sse.clone();
+                async move {
+                    assert_eq!(body["stream"], true);
+                    ([(header::CONTENT_TYPE, "text/event-stream")], sse)
+                }
+            }),
+        );
+        let llm = OpenAiCompatClient::local(&mock_server(app).await, "local-model");
+
+        let events: Vec<LlmStreamEvent> = llm
+            .chat_stream(&LlmRequest::new(vec![LlmMessage::user("hi")]))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let usage = LlmUsage {
+            prompt_tokens: 7,
+            completion_tokens: 4,
+            total_tokens: 11,
+        };
+        assert_eq!(
+            events,
+            vec![
+                LlmStreamEvent::Delta("Hel".into()),
+                LlmStreamEvent::Delta("lo".into()),
+                LlmStreamEvent::ToolCall(LlmToolCall {
+                    id: "call_1".into(),
+                    name: "lookup".into(),
+                    arguments: r#"{"q":"kbve"}"#.into(),
+                }),
+                LlmStreamEvent::Done {
+                    finish_reason: Some(LlmFinishReason::ToolCalls),
+                    usage: Some(usage),
+                },
+            ]
+        );
+        assert_eq!(llm.usage().total_tokens, 11);
+    }
+
+    #[tokio::test]
+    async fn stream_rejects_a_runaway_tool_call_index() {
+        let frame = json!({"choices": [{"index": 0, "delta": {"tool_calls": [
+            {"index": 4_000_000_000_u64, "id": "call_1", "function": {"name": "lookup"}}
+        ]}}]});
+        let sse = format!("data: {frame}\n\ndata: [DONE]\n\n");
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move || {
+                let sse = sse.clone();
+                async move { ([(header::CONTENT_TYPE, "text/event-stream")], sse) }
+            }),
+        );
+        let llm = OpenAiCompatClient::local(&mock_server(app).await, "local-model");
+
+        let result: Result<Vec<LlmStreamEvent>, _> = llm
+            .chat_stream(&LlmRequest::new(vec![LlmMessage::user("hi")]))
+            .await
+            .unwrap()
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(JediError::Parse(m)) if m.contains("tool call index")));
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_then_succeeds() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if n == 0 {
+                        (AxumStatus::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")], "slow down")
+                            .into_response()
+                    } else {
+                        Json(json!({
+                            "choices": [{"message": {"role": "assistant", "content": "ok"}, "finish_reason": "stop"}]
+                        }))
+                        .into_response()
+                    }
+                }
+            }),
+        );
+        let llm = OpenAiCompatClient::local(&mock_server(app).await, "m")
+            .with_retries(2, Duration::from_millis(1));
+
+        let answer = llm.ask("be brief", "ping").await.unwrap();
+        assert_eq!(answer, "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        // No usage reported: the request still counts.
+        assert_eq!(llm.usage().requests, 1);
+        assert_eq!(llm.usage().total_tokens, 0);
+    }
+
+    #[tokio::test]
+    async fn maps_error_statuses() {
+        let app = Router::new()
+            .route(
+                "/bad/chat/completions",
+                post(|| async {
+                    (
+                        AxumStatus::BAD_REQUEST,
+                        Json(json!({"error": {"message": "model does not support tools"}})),
+                    )
+                }),
+            )
+            .route(
+                "/auth/chat/completions",
+                post(|| async { AxumStatus::UNAUTHORIZED }),
+            );
+        let base = mock_server(app).await;
+        let root = base.trim_end_matches("/v1");
+        let request = LlmRequest::new(vec![LlmMessage::user("hi")]);
+
+        let bad = OpenAiCompatClient::local(&format!("{root}/bad"), "m");
+        match bad.chat(&request).await {
+            Err(JediError::BadRequest(msg)) => assert!(msg.contains("does not support tools")),
+            other => panic!("expected BadRequest, got {other:?}"),
+        }
+
+        let auth = OpenAiCompatClient::local(&format!("{root}/auth"), "m");
+        assert!(matches!(
+            auth.chat(&request).await,
+            Err(JediError::Unauthorized)
+        ));
+    }
+}
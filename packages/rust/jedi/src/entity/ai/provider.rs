@@ -0,0 +1,589 @@
+//! Provider-agnostic chat types and the [`LlmProvider`] trait.
+//!
+//! The types serialize to the OpenAI chat-completions wire format, which
+//! Groq, Featherless, llama.cpp, mistral.rs, vLLM and Ollama all accept,
+//! so a provider only has to pick a base URL and a default model.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::entity::error::JediError;
+
+/// Stream of events from [`LlmProvider::chat_stream`].
+pub type LlmStream = BoxStream<'static, Result<LlmStreamEvent, JediError>>;
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short provider name, e.g. `"groq"`; used in logs and metrics.
+    fn name(&self) -> &str;
+
+    /// Model used when [`LlmRequest::model`] is `None`.
+    fn default_model(&self) -> &str;
+
+    async fn chat(&self, request: &LlmRequest) -> Result<LlmResponse, JediError>;
+
+    /// Token-by-token response. Tool calls are assembled from their deltas
+    /// and emitted whole, after the content, followed by a single `Done`.
+    async fn chat_stream(&self, request: &LlmRequest) -> Result<LlmStream, JediError>;
+
+    /// Tokens consumed through this provider since it was built.
+    fn usage(&self) -> UsageTotals;
+
+    /// One-shot helper: a system prompt and a user message, answer as text.
+    async fn ask(&self, system: &str, user: &str) -> Result<String, JediError> {
+        let request = LlmRequest::new(vec![LlmMessage::system(system), LlmMessage::user(user)]);
+        Ok(self.chat(&request).await?.text().to_string())
+    }
+}
+
+// ── Messages ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmMessage {
+    pub role: LlmRole,
+    /// `None` on assistant turns that only carry tool calls.
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "null_as_empty",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tool_calls: Vec<LlmToolCall>,
+    /// Set on `Tool` messages: the [`LlmToolCall::id`] being answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl LlmMessage {
+    fn text(role: LlmRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::text(LlmRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::text(LlmRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::text(LlmRole::Assistant, content)
+    }
+
+    /// The result of running a tool, to send back after the assistant turn
+    /// that requested it.
+    pub fn tool_result(call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(call_id.into()),
+            ..Self::text(LlmRole::Tool, content)
+        }
+    }
+}
+
+// ── Tools ───────────────────────────────────────────────────────────
+
+/// A function the model may call. `parameters` is a JSON Schema object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmTool {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: Value,
+}
+
+impl LlmTool {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            parameters,
+        }
+    }
+
+    pub(crate) fn to_wire(&self) -> Value {
+        let mut function = json!({ "name": self.name, "parameters": self.parameters });
+        if let Some(description) = &self.description {
+            function["description"] = json!(description);
+        }
+        json!({ "type": "function", "function": function })
+    }
+}
+
+/// A tool invocation requested by the model. `arguments` is the raw JSON
+/// string the model produced; it is not guaranteed to be valid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "WireToolCall", from = "WireToolCall")]
+pub struct LlmToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+impl LlmToolCall {
+    pub fn parse_arguments<T: DeserializeOwned>(&self) -> Result<T, JediError> {
+        serde_json::from_str(&self.arguments)
+            .map_err(|e| JediError::Parse(format!("tool call '{}' arguments: {e}", self.name)))
+    }
+}
+
+// Local servers are looser than the spec: Ollama omits `id` and sends
+// `arguments` as an object, llama.cpp sends `tool_calls: null`.
+#[derive(Serialize, Deserialize)]
+struct WireToolCall {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type", default = "function_type")]
+    kind: String,
+    function: WireFunction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireFunction {
+    name: String,
+    #[serde(default, deserialize_with = "arguments_string")]
+    arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+fn null_as_empty<'de, D, T>(de: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(de)?.unwrap_or_default())
+}
+
+fn arguments_string<'de, D>(de: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(de)? {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+impl From<LlmToolCall> for WireToolCall {
+    fn from(call: LlmToolCall) -> Self {
+        Self {
+            id: call.id,
+            kind: function_type(),
+            function: WireFunction {
+                name: call.name,
+                arguments: call.arguments,
+            },
+        }
+    }
+}
+
+impl From<WireToolCall> for LlmToolCall {
+    fn from(wire: WireToolCall) -> Self {
+        Self {
+            id: wire.id,
+            name: wire.function.name,
+            arguments: wire.function.arguments,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmToolChoice {
+    Auto,
+    None,
+    /// The model must call at least one tool.
+    Required,
+    /// The model must call this tool.
+    Function(String),
+}
+
+impl LlmToolChoice {
+    pub(crate) fn to_wire(&self) -> Value {
+        match self {
+            LlmToolChoice::Auto => json!("auto"),
+            LlmToolChoice::None => json!("none"),
+            LlmToolChoice::Required => json!("required"),
+            LlmToolChoice::Function(name) => {
+                json!({ "type": "function", "function": { "name": name } })
+            }
+        }
+    }
+}
+
+// ── Response format ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmResponseFormat {
+    Text,
+    /// Any valid JSON object.
+    JsonObject,
+    /// JSON matching `schema`. With `strict`, providers that support it
+    /// constrain decoding to the schema; others treat it as a hint.
+    JsonSchema {
+        name: String,
+        schema: Value,
+        strict: bool,
+    },
+}
+
+impl LlmResponseFormat {
+    pub(crate) fn to_wire(&self) -> Value {
+        match self {
+            LlmResponseFormat::Text => json!({ "type": "text" }),
+            LlmResponseFormat::JsonObject => json!({ "type": "json_object" }),
+            LlmResponseFormat::JsonSchema {
+                name,
+                schema,
+                strict,
+            } => json!({
+                "type": "json_schema",
+                "json_schema": { "name": name, "schema": schema, "strict": strict },
+            }),
+        }
+    }
+}
+
+// ── Request ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default)]
+pub struct LlmRequest {
+    /// `None` uses the provider's default model.
+    pub model: Option<String>,
+    pub messages: Vec<LlmMessage>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<u32>,
+    pub stop: Vec<String>,
+    pub tools: Vec<LlmTool>,
+    pub tool_choice: Option<LlmToolChoice>,
+    pub response_format: Option<LlmResponseFormat>,
+}
+
+impl LlmRequest {
+    pub fn new(messages: Vec<LlmMessage>) -> Self {
+        Self {
+            messages,
+            ..Self::default()
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn tool(mut self, tool: LlmTool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn tool_choice(mut self, choice: LlmToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
+    pub fn json_object(mut self) -> Self {
+        self.response_format = Some(LlmResponseFormat::JsonObject);
+        self
+    }
+
+    /// Ask for JSON matching `schema`, decoded strictly where supported.
+    pub fn json_schema(mut self, name: impl Into<String>, schema: Value) -> Self {
+        self.response_format = Some(LlmResponseFormat::JsonSchema {
+            name: name.into(),
+            schema,
+            strict: true,
+        });
+        self
+    }
+}
+
+// ── Response ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmFinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+    ContentFilter,
+    Other(String),
+}
+
+impl LlmFinishReason {
+    pub(crate) fn from_api(s: &str) -> Self {
+        match s {
+            "stop" | "eos" => LlmFinishReason::Stop,
+            "length" => LlmFinishReason::Length,
+            "tool_calls" | "function_call" => LlmFinishReason::ToolCalls,
+            "content_filter" => LlmFinishReason::ContentFilter,
+            other => LlmFinishReason::Other(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    #[serde(default)]
+    pub total_tokens: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmResponse {
+    pub id: String,
+    /// The model that actually answered (may differ from the requested alias).
+    pub model: String,
+    pub message: LlmMessage,
+    pub finish_reason: Option<LlmFinishReason>,
+    pub usage: Option<LlmUsage>,
+}
+
+impl LlmResponse {
+    pub fn text(&self) -> &str {
+        self.message.content.as_deref().unwrap_or_default()
+    }
+
+    pub fn tool_calls(&self) -> &[LlmToolCall] {
+        &self.message.tool_calls
+    }
+
+    /// Decode the content of a JSON-mode response.
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T, JediError> {
+        serde_json::from_str(strip_code_fence(self.text()))
+            .map_err(|e| JediError::Parse(format!("LLM JSON response: {e}")))
+    }
+}
+
+/// Smaller models wrap JSON in a Markdown fence even in JSON mode.
+fn strip_code_fence(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix("```json")
+        .or_else(|| s.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(s)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmStreamEvent {
+    /// A piece of assistant content.
+    Delta(String),
+    /// A completed tool call.
+    ToolCall(LlmToolCall),
+    /// End of the response. `usage` is `None` when the server does not
+    /// report it for streams.
+    Done {
+        finish_reason: Option<LlmFinishReason>,
+        usage: Option<LlmUsage>,
+    },
+}
+
+// ── Usage accounting ────────────────────────────────────────────────
+
+/// Running token totals, shared by every clone of a provider.
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    requests: AtomicU64,
+    prompt_tokens: AtomicU64,
+    completion_tokens: AtomicU64,
+}
+
+impl UsageMeter {
+    /// Count one completed request, with its usage when the server reported it.
+    pub fn record(&self, usage: Option<&LlmUsage>) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(u) = usage {
+            self.prompt_tokens
+                .fetch_add(u.prompt_tokens as u64, Ordering::Relaxed);
+            self.completion_tokens
+                .fetch_add(u.completion_tokens as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub fn totals(&self) -> UsageTotals {
+        let prompt_tokens = self.prompt_tokens.load(Ordering::Relaxed);
+        let completion_tokens = self.completion_tokens.load(Ordering::Relaxed);
+        UsageTotals {
+            requests: self.requests.load(Ordering::Relaxed),
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_serialize_to_openai_shape() {
+        let assistant = LlmMessage {
+            role: LlmRole::Assistant,
+            content: None,
+            tool_calls: vec![LlmToolCall {
+                id: "call_1".into(),
+                name: "lookup".into(),
+                arguments: r#"{"q":"kbve"}"#.into(),
+            }],
+            tool_call_id: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&assistant).unwrap(),
+            json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "lookup", "arguments": "{\"q\":\"kbve\"}"}
+                }]
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(LlmMessage::tool_result("call_1", "ok")).unwrap(),
+            json!({"role": "tool", "content": "ok", "tool_call_id": "call_1"})
+        );
+
+        let back: LlmMessage =
+            serde_json::from_value(serde_json::to_value(&assistant).unwrap()).unwrap();
+        assert_eq!(back, assistant);
+    }
+
+    #[test]
+    fn lenient_local_server_messages_deserialize() {
+        let msg: LlmMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": "hi",
+            "tool_calls": null
+        }))
+        .unwrap();
+        assert!(msg.tool_calls.is_empty());
+
+        let msg: LlmMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "tool_calls": [{"function": {"name": "lookup", "arguments": {"q": "kbve"}}}]
+        }))
+        .unwrap();
+        assert_eq!(msg.content, None);
+        assert_eq!(msg.tool_calls[0].arguments, r#"{"q":"kbve"}"#);
+    }
+
+    #[test]
+    fn tool_call_arguments_parse() {
+        #[derive(Deserialize)]
+        struct Args {
+            q: String,
+        }
+        let call = LlmToolCall {
+            id: "c".into(),
+            name: "lookup".into(),
+            arguments: r#"{"q":"kbve"}"#.into(),
+        };
+        assert_eq!(call.parse_arguments::<Args>().unwrap().q, "kbve");
+
+        let bad = LlmToolCall {
+            arguments: "{".into(),
+            ..call
+        };
+        assert!(matches!(
+            bad.parse_arguments::<Args>(),
+            Err(JediError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn response_format_and_tool_choice_wire() {
+        let schema = json!({"type": "object"});
+        assert_eq!(
+            LlmResponseFormat::JsonSchema {
+                name: "reply".into(),
+                schema: schema.clone(),
+                strict: true
+            }
+            .to_wire(),
+            json!({"type": "json_schema", "json_schema": {"name": "reply", "schema": schema, "strict": true}})
+        );
+        assert_eq!(
+            LlmToolChoice::Function("lookup".into()).to_wire(),
+            json!({"type": "function", "function": {"name": "lookup"}})
+        );
+    }
+
+    #[test]
+    fn parse_json_tolerates_code_fences() {
+        let response = LlmResponse {
+            id: String::new(),
+            model: String::new(),
+            message: LlmMessage::assistant("```json\n{\"ok\": true}\n```"),
+            finish_reason: Some(LlmFinishReason::Stop),
+            usage: None,
+        };
+        let v: Value = response.parse_json().unwrap();
+        assert_eq!(v["ok"], true);
+    }
+
+    #[test]
+    fn usage_meter_accumulates() {
+        let meter = UsageMeter::default();
+        meter.record(Some(&LlmUsage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+        }));
+        meter.record(None);
+        assert_eq!(
+            meter.totals(),
+            UsageTotals {
+                requests: 2,
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+            }
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Minimal `text/event-stream` decoder: only `data:` fields matter for
+//! chat completions, so event names, ids and retry hints are dropped.
+
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buf: Vec<u8>,
+    data: String,
+}
+
+impl SseDecoder {
+    /// Feed raw bytes; returns the `data` payload of every event completed
+    /// by this chunk. Events may span chunk boundaries.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.buf.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(std::mem::take(&mut self.data));
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !self.data.is_empty() {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+
+        events
+    }
+
+    /// Flush an event left unterminated when the body ended.
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        if !self.buf.is_empty() {
+            let mut tail = self.push(b"\n");
+            tail.extend(self.push(b"\n"));
+            return tail.pop();
+        }
+        (!self.data.is_empty()).then(|| std::mem::take(&mut self.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_events_across_chunks() {
+        let mut d = SseDecoder::default();
+        assert!(d.push(b"data: {\"a\"").is_empty());
+        assert_eq!(
+            d.push(b":1}\n\ndata: [DONE]\n\n"),
+            vec!["{\"a\":1}", "[DONE]"]
+        );
+    }
+
+    #[test]
+    fn handles_crlf_comments_and_multiline_data() {
+        let mut d = SseDecoder::default();
+        let events = d.push(b": keep-alive\r\n\r\nevent: x\r\ndata: one\r\ndata:two\r\n\r\n");
+        assert_eq!(events, vec!["one\ntwo"]);
+    }
+
+    #[test]
+    fn finish_flushes_unterminated_event() {
+        let mut d = SseDecoder::default();
+        assert!(d.push(b"data: tail").is_empty());
+        assert_eq!(d.finish().as_deref(), Some("tail"));
+        assert_eq!(d.finish(), None);
+    }
+}
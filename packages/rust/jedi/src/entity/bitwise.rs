@@ -116,9 +116,34 @@ define_multi_flag_checks!(
     (xread, XREAD, [Redis, Stream, Read]),
     (watch, WATCH, [Redis, Heartbeat, Read, Info]),
     (unwatch, UNWATCH, [Redis, Heartbeat, Del, Info]),
+    (pwatch, PWATCH, [Redis, Heartbeat, Read, List]),
+    (punwatch, PUNWATCH, [Redis, Heartbeat, Del, List]),
+    (wresume, WRESUME, [Redis, Heartbeat, Read, Action]),
     (publish, PUBLISH, [Redis, Message, Action]),
     (subscribe, SUBSCRIBE, [Redis, Message, Read]),
     (ch_insert, CH_INSERT, [Clickhouse, Add]),
     (ch_select, CH_SELECT, [Clickhouse, Read]),
     (ch_ddl, CH_DDL, [Clickhouse, Action, Set]),
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_kinds_do_not_match_each_other() {
+        type Check = fn(i32) -> bool;
+        let kinds: [(i32, Check); 5] = [
+            (MessageKind::WATCH, MessageKind::watch),
+            (MessageKind::UNWATCH, MessageKind::unwatch),
+            (MessageKind::PWATCH, MessageKind::pwatch),
+            (MessageKind::PUNWATCH, MessageKind::punwatch),
+            (MessageKind::WRESUME, MessageKind::wresume),
+        ];
+        for (i, &(kind, _)) in kinds.iter().enumerate() {
+            for (j, &(_, check)) in kinds.iter().enumerate() {
+                assert_eq!(check(kind), i == j, "kind #{i} vs check #{j}");
+            }
+        }
+    }
+}
@@ -463,7 +463,7 @@ impl JediEnvelope {
         }
 
         let payload = try_unwrap_payload::<KeyValueInput>(self)?;
-        if watch_manager.is_watching(conn_id, &*payload.key) {
+        if watch_manager.is_subscribed(conn_id, &payload.key) {
             Ok(Some(payload.key))
         } else {
             Ok(None)
@@ -0,0 +1,238 @@
+//! The [`Forge`] trait and the pagination helper behind its streams.
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde_json::Value;
+
+use super::types::*;
+use crate::entity::error::JediError;
+
+/// Page size used by the streaming helpers (the maximum both forges allow
+/// on list endpoints without server-side overrides).
+pub const STREAM_PAGE_SIZE: u32 = 50;
+
+/// Operations shared by GitHub and Forgejo. Page numbers start at 1.
+///
+/// Reads go through the client's ETag cache when one is attached, so
+/// polling an unchanged listing costs a `304` instead of a full body.
+#[async_trait]
+pub trait Forge: Send + Sync {
+    fn kind(&self) -> ForgeKind;
+
+    // ── Repository ──────────────────────────────────────────────────
+
+    async fn get_repo(&self, repo: &RepoRef) -> Result<ForgeRepo, JediError>;
+
+    // ── Issues ──────────────────────────────────────────────────────
+
+    /// One page of issues, pull requests excluded.
+    async fn list_issues(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeIssue>, JediError>;
+
+    async fn get_issue(&self, repo: &RepoRef, number: u64) -> Result<ForgeIssue, JediError>;
+
+    async fn create_issue(&self, repo: &RepoRef, issue: &NewIssue)
+    -> Result<ForgeIssue, JediError>;
+
+    async fn set_issue_state(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        state: IssueState,
+    ) -> Result<(), JediError>;
+
+    // ── Labels ──────────────────────────────────────────────────────
+
+    async fn list_labels(&self, repo: &RepoRef) -> Result<Vec<ForgeLabel>, JediError>;
+
+    /// Add labels by name, returning the issue's labels afterwards.
+    async fn add_labels(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        labels: &[&str],
+    ) -> Result<Vec<ForgeLabel>, JediError>;
+
+    async fn remove_label(&self, repo: &RepoRef, number: u64, label: &str)
+    -> Result<(), JediError>;
+
+    // ── Comments ────────────────────────────────────────────────────
+
+    async fn list_comments(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeComment>, JediError>;
+
+    async fn create_comment(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<ForgeComment, JediError>;
+
+    // ── Pull requests ───────────────────────────────────────────────
+
+    async fn list_pulls(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgePull>, JediError>;
+
+    async fn get_pull(&self, repo: &RepoRef, number: u64) -> Result<ForgePull, JediError>;
+
+    async fn merge_pull(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        method: MergeMethod,
+        message: Option<&str>,
+    ) -> Result<(), JediError>;
+
+    // ── Workflows ───────────────────────────────────────────────────
+
+    async fn list_workflows(&self, repo: &RepoRef) -> Result<Vec<ForgeWorkflow>, JediError>;
+
+    async fn list_workflow_runs(
+        &self,
+        repo: &RepoRef,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeWorkflowRun>, JediError>;
+
+    /// Trigger a `workflow_dispatch`. `workflow` is a [`ForgeWorkflow::id`].
+    async fn dispatch_workflow(
+        &self,
+        repo: &RepoRef,
+        workflow: &str,
+        git_ref: &str,
+        inputs: Option<&Value>,
+    ) -> Result<(), JediError>;
+
+    // ── Streams ─────────────────────────────────────────────────────
+
+    /// Every issue matching `state`, fetched page by page on demand.
+    fn issues<'a>(
+        &'a self,
+        repo: &'a RepoRef,
+        state: StateFilter,
+    ) -> BoxStream<'a, Result<ForgeIssue, JediError>> {
+        paginate(STREAM_PAGE_SIZE, move |page, per_page| {
+            self.list_issues(repo, state, page, per_page)
+        })
+    }
+
+    fn pulls<'a>(
+        &'a self,
+        repo: &'a RepoRef,
+        state: StateFilter,
+    ) -> BoxStream<'a, Result<ForgePull, JediError>> {
+        paginate(STREAM_PAGE_SIZE, move |page, per_page| {
+            self.list_pulls(repo, state, page, per_page)
+        })
+    }
+
+    fn comments<'a>(
+        &'a self,
+        repo: &'a RepoRef,
+        number: u64,
+    ) -> BoxStream<'a, Result<ForgeComment, JediError>> {
+        paginate(STREAM_PAGE_SIZE, move |page, per_page| {
+            self.list_comments(repo, number, page, per_page)
+        })
+    }
+
+    /// Workflow runs, newest first.
+    fn workflow_runs<'a>(
+        &'a self,
+        repo: &'a RepoRef,
+    ) -> BoxStream<'a, Result<ForgeWorkflowRun, JediError>> {
+        paginate(STREAM_PAGE_SIZE, move |page, per_page| {
+            self.list_workflow_runs(repo, page, per_page)
+        })
+    }
+}
+
+/// Flatten a page-fetching function into a stream of items, starting at
+/// page 1 and following `next_page` until it is absent (or fails to move
+/// forward, which guards against a server echoing the same page back).
+pub fn paginate<'a, T, F>(per_page: u32, fetch: F) -> BoxStream<'a, Result<T, JediError>>
+where
+    T: Send + 'a,
+    F: Fn(u32, u32) -> BoxFuture<'a, Result<Page<T>, JediError>> + Send + 'a,
+{
+    stream::try_unfold(Some(1u32), move |page| {
+        let pending = page.map(|p| (p, fetch(p, per_page)));
+        async move {
+            let Some((current, fut)) = pending else {
+                return Ok::<_, JediError>(None);
+            };
+            let Page { items, next_page } = fut.await?;
+            let next = next_page.filter(|n| *n > current);
+            Ok(Some((stream::iter(items.into_iter().map(Ok)), next)))
+        }
+    })
+    .try_flatten()
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn paginate_follows_next_page_until_exhausted() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let items: Vec<u32> = paginate(2, move |page, per_page| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                let start = (page - 1) * per_page;
+                Ok(Page {
+                    items: (start..(start + per_page).min(5)).collect(),
+                    next_page: (page < 3).then_some(page + 1),
+                })
+            })
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn paginate_stops_when_next_page_does_not_advance() {
+        let items: Vec<u32> = paginate(10, |page, _| {
+            Box::pin(async move {
+                Ok(Page {
+                    items: vec![page],
+                    next_page: Some(page),
+                })
+            })
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn paginate_surfaces_errors() {
+        let mut s = paginate::<u32, _>(10, |_, _| Box::pin(async { Err(JediError::Forbidden) }));
+        assert!(matches!(s.next().await, Some(Err(JediError::Forbidden))));
+    }
+}
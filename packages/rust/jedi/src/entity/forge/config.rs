@@ -0,0 +1,103 @@
+//! Choosing a forge backend from configuration.
+
+use std::sync::Arc;
+
+use super::api::Forge;
+use super::etag::EtagCache;
+use super::types::ForgeKind;
+use crate::entity::error::JediError;
+use crate::entity::github::GitHubClient;
+use crate::state::sidecar::get_env;
+
+#[derive(Clone)]
+pub struct ForgeConfig {
+    pub kind: ForgeKind,
+    /// API root. GitHub defaults to `https://api.github.com`; required for Forgejo.
+    pub base_url: Option<String>,
+    pub token: String,
+}
+
+impl ForgeConfig {
+    pub fn github(token: impl Into<String>) -> Self {
+        Self {
+            kind: ForgeKind::GitHub,
+            base_url: None,
+            token: token.into(),
+        }
+    }
+
+    pub fn forgejo(base_url: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            kind: ForgeKind::Forgejo,
+            base_url: Some(base_url.into()),
+            token: token.into(),
+        }
+    }
+
+    /// `FORGE_KIND` (`github` | `forgejo`, default `github`), `FORGE_URL` and
+    /// `FORGE_TOKEN` (each also readable via `*_FILE`). When the generic keys
+    /// are unset, falls back to the per-forge keys the services already use:
+    /// `GITHUB_TOKEN` / `GITHUB_API_BASE_URL` and `FORGEJO_AUTH_TOKEN` /
+    /// `FORGEJO_UPSTREAM_URL`.
+    pub fn from_env() -> Result<Self, JediError> {
+        let kind: ForgeKind = get_env("FORGE_KIND", "github").parse()?;
+        let (url_key, token_key) = match kind {
+            ForgeKind::GitHub => ("GITHUB_API_BASE_URL", "GITHUB_TOKEN"),
+            ForgeKind::Forgejo => ("FORGEJO_UPSTREAM_URL", "FORGEJO_AUTH_TOKEN"),
+        };
+
+        let first_set = |keys: [&str; 2]| {
+            keys.iter()
+                .map(|k| get_env(k, ""))
+                .find(|v| !v.trim().is_empty())
+        };
+
+        let token = first_set(["FORGE_TOKEN", token_key]).ok_or_else(|| {
+            JediError::Internal(format!("FORGE_TOKEN (or {token_key}) not set").into())
+        })?;
+        let base_url = first_set(["FORGE_URL", url_key]);
+        if kind == ForgeKind::Forgejo && base_url.is_none() {
+            return Err(JediError::Internal(
+                format!("FORGE_URL (or {url_key}) not set").into(),
+            ));
+        }
+
+        Ok(Self {
+            kind,
+            base_url,
+            token,
+        })
+    }
+
+    /// Build the configured backend. `etags` is shared with the client so
+    /// conditional requests survive across rebuilt handles.
+    pub fn build(&self, etags: Option<Arc<EtagCache>>) -> Result<Arc<dyn Forge>, JediError> {
+        match self.kind {
+            ForgeKind::GitHub => {
+                let mut client = GitHubClient::new(&self.token);
+                if let Some(url) = &self.base_url {
+                    client = client.with_base_url(url);
+                }
+                if let Some(cache) = etags {
+                    client = client.with_etag_cache(cache);
+                }
+                Ok(Arc::new(client))
+            }
+            #[cfg(feature = "forgejo")]
+            ForgeKind::Forgejo => {
+                let url = self.base_url.as_deref().ok_or_else(|| {
+                    JediError::BadRequest("Forgejo forge requires a base URL".into())
+                })?;
+                let mut client = crate::entity::forgejo::ForgejoClient::new(url, &self.token);
+                if let Some(cache) = etags {
+                    client = client.with_etag_cache(cache);
+                }
+                Ok(Arc::new(client))
+            }
+            #[cfg(not(feature = "forgejo"))]
+            ForgeKind::Forgejo => Err(JediError::BadRequest(
+                "Forgejo support requires the `forgejo` feature".into(),
+            )),
+        }
+    }
+}
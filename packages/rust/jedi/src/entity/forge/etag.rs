@@ -0,0 +1,252 @@
+//! ETag-aware GET support shared by the GitHub and Forgejo clients.
+//!
+//! Both forges return an `ETag` on list/get endpoints and answer a matching
+//! `If-None-Match` with `304 Not Modified` — on GitHub those replies don't
+//! count against the rate limit, which is what makes polling boards cheap.
+//! The cache keeps the last body per URL so a 304 can be served locally.
+//!
+//! Clients are usually built per command, so the cache is meant to be held
+//! by the caller (`Arc<EtagCache>`) and handed to each client via
+//! `with_etag_cache`.
+
+use reqwest::header::{ETAG, HeaderMap, IF_NONE_MATCH, LINK};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::entity::error::JediError;
+
+pub const DEFAULT_ETAG_CAPACITY: usize = 512;
+
+/// Bounded URL → (ETag, body) cache. Oldest entry is evicted when full.
+pub struct EtagCache {
+    entries: Mutex<HashMap<String, EtagEntry>>,
+    capacity: usize,
+    tick: AtomicU64,
+    not_modified: AtomicU64,
+    fetched: AtomicU64,
+}
+
+struct EtagEntry {
+    etag: String,
+    page: CachedPage,
+    stored_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EtagStats {
+    pub entries: usize,
+    /// Requests answered with `304 Not Modified` and served from the cache.
+    pub not_modified: u64,
+    /// Requests that returned a fresh body.
+    pub fetched: u64,
+}
+
+impl Default for EtagCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_ETAG_CAPACITY)
+    }
+}
+
+impl EtagCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+            tick: AtomicU64::new(0),
+            not_modified: AtomicU64::new(0),
+            fetched: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> EtagStats {
+        EtagStats {
+            entries: self.entries.lock().map(|e| e.len()).unwrap_or(0),
+            not_modified: self.not_modified.load(Ordering::Relaxed),
+            fetched: self.fetched.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<(String, CachedPage)> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(key).map(|e| (e.etag.clone(), e.page.clone()))
+    }
+
+    fn store(&self, key: &str, etag: String, page: CachedPage) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, e)| e.stored_at)
+                .map(|(k, _)| k.clone());
+            if let Some(k) = oldest {
+                entries.remove(&k);
+            }
+        }
+        let stored_at = self.tick.fetch_add(1, Ordering::Relaxed);
+        entries.insert(
+            key.to_string(),
+            EtagEntry {
+                etag,
+                page,
+                stored_at,
+            },
+        );
+    }
+}
+
+/// A successful GET body plus the `rel="next"` page from its `Link` header.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedPage {
+    pub body: Arc<str>,
+    pub next_page: Option<u32>,
+}
+
+pub(crate) enum GetOutcome {
+    Page(CachedPage),
+    /// Non-2xx, non-304 response, left for the client's own error mapping.
+    Failed(Response),
+}
+
+/// Cache key for a GET — the URL plus its query pairs in call order.
+pub(crate) fn cache_key(url: &str, query: &[(&str, String)]) -> String {
+    let mut key = url.to_string();
+    for (i, (k, v)) in query.iter().enumerate() {
+        key.push(if i == 0 { '?' } else { '&' });
+        key.push_str(k);
+        key.push('=');
+        key.push_str(v);
+    }
+    key
+}
+
+/// Attach `If-None-Match` when the cache holds an entry for `key`. The
+/// cached page is returned so a 304 can be answered even if the entry is
+/// evicted while the request is in flight.
+pub(crate) fn prepare(
+    req: RequestBuilder,
+    key: &str,
+    cache: Option<&EtagCache>,
+) -> (RequestBuilder, Option<CachedPage>) {
+    match cache.and_then(|c| c.lookup(key)) {
+        Some((etag, page)) => (req.header(IF_NONE_MATCH, etag), Some(page)),
+        None => (req, None),
+    }
+}
+
+/// Resolve a sent GET into a page, serving 304s from `cached` and storing
+/// fresh bodies that carry an ETag.
+pub(crate) async fn settle(
+    resp: Response,
+    key: &str,
+    cached: Option<CachedPage>,
+    cache: Option<&EtagCache>,
+) -> Result<GetOutcome, JediError> {
+    let status = resp.status();
+    if status == StatusCode::NOT_MODIFIED {
+        if let (Some(page), Some(cache)) = (cached, cache) {
+            cache.not_modified.fetch_add(1, Ordering::Relaxed);
+            return Ok(GetOutcome::Page(page));
+        }
+        return Err(JediError::Internal(
+            "304 Not Modified without a cached body".into(),
+        ));
+    }
+    if !status.is_success() {
+        return Ok(GetOutcome::Failed(resp));
+    }
+
+    let headers = resp.headers().clone();
+    let body = resp
+        .text()
+        .await
+        .map_err(|e| JediError::Parse(format!("Failed to read response body: {e}")))?;
+    let page = CachedPage {
+        body: Arc::from(body),
+        next_page: next_page(&headers),
+    };
+
+    if let Some(cache) = cache {
+        cache.fetched.fetch_add(1, Ordering::Relaxed);
+        if let Some(etag) = headers.get(ETAG).and_then(|v| v.to_str().ok()) {
+            cache.store(key, etag.to_string(), page.clone());
+        }
+    }
+    Ok(GetOutcome::Page(page))
+}
+
+/// Page number of the `rel="next"` entry in an RFC 8288 `Link` header —
+/// the format both GitHub and Forgejo use for list endpoints.
+pub(crate) fn next_page(headers: &HeaderMap) -> Option<u32> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let mut pieces = part.split(';');
+        let target = pieces.next()?.trim();
+        if !pieces.any(|p| p.trim() == "rel=\"next\"") {
+            return None;
+        }
+        let url = reqwest::Url::parse(target.strip_prefix('<')?.strip_suffix('>')?).ok()?;
+        url.query_pairs()
+            .find(|(k, _)| k == "page")
+            .and_then(|(_, v)| v.parse().ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn parses_next_page_from_link_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(
+                "<https://api.github.com/repos/a/b/issues?per_page=2&page=3>; rel=\"next\", \
+                 <https://api.github.com/repos/a/b/issues?per_page=2&page=9>; rel=\"last\"",
+            ),
+        );
+        assert_eq!(next_page(&headers), Some(3));
+
+        headers.insert(
+            LINK,
+            HeaderValue::from_static("<https://x/api/v1/repos/a/b/issues?page=1>; rel=\"prev\""),
+        );
+        assert_eq!(next_page(&headers), None);
+        assert_eq!(next_page(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn evicts_oldest_entry_when_full() {
+        let cache = EtagCache::new(2);
+        let page = |s: &str| CachedPage {
+            body: Arc::from(s),
+            next_page: None,
+        };
+        cache.store("a", "\"1\"".into(), page("a"));
+        cache.store("b", "\"2\"".into(), page("b"));
+        cache.store("c", "\"3\"".into(), page("c"));
+        assert!(cache.lookup("a").is_none());
+        assert_eq!(cache.lookup("c").unwrap().0, "\"3\"");
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn cache_key_keeps_query_order() {
+        let key = cache_key(
+            "https://h/repos/a/b/issues",
+            &[("state", "open".into()), ("page", "2".into())],
+        );
+        assert_eq!(key, "https://h/repos/a/b/issues?state=open&page=2");
+    }
+}
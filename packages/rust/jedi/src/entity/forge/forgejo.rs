@@ -0,0 +1,530 @@
+//! [`Forge`] for [`ForgejoClient`].
+//!
+//! Differences from GitHub that are papered over here:
+//! - issue creation only takes label ids, so labels are attached by name
+//!   in a second call (a failure there is logged, not returned, since the
+//!   issue already exists);
+//! - label removal is by id, resolved from the issue's current labels;
+//! - there is no workflow listing endpoint — workflow files are read from
+//!   `.forgejo/workflows` (falling back to `.gitea/` and `.github/`);
+//! - action tasks report a single status, split into status + conclusion;
+//! - drafts are marked by a `WIP:` / `[WIP]` title prefix.
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use serde_json::{Value, json};
+use tracing::warn;
+
+use super::api::{Forge, paginate};
+use super::types::*;
+use crate::entity::error::JediError;
+use crate::entity::forgejo::{
+    ForgejoActionTask, ForgejoActionTasks, ForgejoClient, ForgejoComment, ForgejoIssue,
+    ForgejoLabel, ForgejoOwner, ForgejoPull, ForgejoPullBranch, ForgejoRepo,
+};
+
+/// Server-side cap on `limit` for most Forgejo list endpoints.
+const MAX_LIMIT: u32 = 50;
+
+const WORKFLOW_DIRS: [&str; 3] = [
+    ".forgejo/workflows",
+    ".gitea/workflows",
+    ".github/workflows",
+];
+
+fn non_empty(s: String) -> Option<String> {
+    (!s.is_empty()).then_some(s)
+}
+
+fn user(o: ForgejoOwner) -> ForgeUser {
+    ForgeUser { login: o.login }
+}
+
+fn label(l: ForgejoLabel) -> ForgeLabel {
+    ForgeLabel {
+        name: l.name,
+        color: non_empty(l.color),
+        description: non_empty(l.description),
+    }
+}
+
+fn issue(i: ForgejoIssue) -> ForgeIssue {
+    ForgeIssue {
+        number: i.number,
+        title: i.title,
+        body: non_empty(i.body),
+        state: IssueState::from_api(&i.state),
+        author: i.user.map(user),
+        labels: i.labels.into_iter().map(label).collect(),
+        assignees: i
+            .assignees
+            .unwrap_or_default()
+            .into_iter()
+            .map(user)
+            .collect(),
+        comments: i.comments,
+        created_at: i.created_at,
+        updated_at: i.updated_at,
+        html_url: i.html_url,
+    }
+}
+
+fn is_wip(title: &str) -> bool {
+    let t = title.trim_start().to_ascii_uppercase();
+    t.starts_with("WIP:") || t.starts_with("[WIP]")
+}
+
+fn pull(p: ForgejoPull) -> ForgePull {
+    let state = if p.merged {
+        PullState::Merged
+    } else if p.state.eq_ignore_ascii_case("closed") {
+        PullState::Closed
+    } else {
+        PullState::Open
+    };
+    let branch = |b: ForgejoPullBranch| ForgeBranchRef {
+        ref_name: b.ref_name,
+        sha: b.sha,
+    };
+    ForgePull {
+        number: p.number,
+        draft: is_wip(&p.title),
+        title: p.title,
+        body: non_empty(p.body),
+        state,
+        author: p.user.map(user),
+        head: p.head.map(branch),
+        base: p.base.map(branch),
+        labels: p.labels.into_iter().map(label).collect(),
+        created_at: p.created_at,
+        updated_at: p.updated_at,
+        html_url: p.html_url,
+    }
+}
+
+fn comment(c: ForgejoComment) -> ForgeComment {
+    ForgeComment {
+        id: c.id,
+        body: c.body,
+        author: c.user.map(user),
+        created_at: c.created_at,
+        updated_at: c.updated_at,
+        html_url: c.html_url,
+    }
+}
+
+/// Map a Forgejo task status onto GitHub's `status` / `conclusion` pair.
+fn run_status(status: &str) -> (&'static str, Option<String>) {
+    match status {
+        "" | "waiting" | "blocked" => ("queued", None),
+        "running" => ("in_progress", None),
+        done => ("completed", Some(done.to_string())),
+    }
+}
+
+fn workflow_run(t: ForgejoActionTask) -> ForgeWorkflowRun {
+    let (status, conclusion) = run_status(&t.status);
+    ForgeWorkflowRun {
+        id: t.id,
+        name: non_empty(t.name).or_else(|| non_empty(t.workflow_id)),
+        status: status.to_string(),
+        conclusion,
+        run_number: t.run_number,
+        head_branch: non_empty(t.head_branch),
+        created_at: t.created_at,
+        updated_at: t.updated_at,
+        html_url: t.url,
+    }
+}
+
+fn repo_path(repo: &RepoRef, rest: &str) -> String {
+    format!("/api/v1/repos/{}/{}{}", repo.owner, repo.name, rest)
+}
+
+fn page_query(page: u32, per_page: u32) -> Vec<(&'static str, String)> {
+    vec![
+        ("page", page.to_string()),
+        ("limit", per_page.min(MAX_LIMIT).to_string()),
+    ]
+}
+
+#[async_trait]
+impl Forge for ForgejoClient {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Forgejo
+    }
+
+    async fn get_repo(&self, repo: &RepoRef) -> Result<ForgeRepo, JediError> {
+        let (r, _): (ForgejoRepo, _) = self.get_page(&repo_path(repo, ""), &[]).await?;
+        Ok(ForgeRepo {
+            owner: r.owner.login,
+            name: r.name,
+            full_name: r.full_name,
+            description: non_empty(r.description),
+            default_branch: r.default_branch,
+            html_url: r.html_url,
+            private: r.private,
+            archived: r.archived,
+            open_issues: r.open_issues_count,
+        })
+    }
+
+    async fn list_issues(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeIssue>, JediError> {
+        let mut query = page_query(page, per_page);
+        query.push(("state", state.as_str().to_string()));
+        query.push(("type", "issues".to_string()));
+        let (issues, next_page): (Vec<ForgejoIssue>, _) =
+            self.get_page(&repo_path(repo, "/issues"), &query).await?;
+        Ok(Page {
+            items: issues.into_iter().map(issue).collect(),
+            next_page,
+        })
+    }
+
+    async fn get_issue(&self, repo: &RepoRef, number: u64) -> Result<ForgeIssue, JediError> {
+        let (i, _) = self
+            .get_page(&repo_path(repo, &format!("/issues/{number}")), &[])
+            .await?;
+        Ok(issue(i))
+    }
+
+    async fn create_issue(&self, repo: &RepoRef, new: &NewIssue) -> Result<ForgeIssue, JediError> {
+        let mut body = json!({ "title": new.title, "assignees": new.assignees });
+        if let Some(text) = &new.body {
+            body["body"] = json!(text);
+        }
+        let mut created =
+            issue(ForgejoClient::create_issue(self, &repo.owner, &repo.name, &body).await?);
+        if !new.labels.is_empty() {
+            // Failing here would make callers retry and open a duplicate.
+            match self
+                .add_issue_labels(
+                    &repo.owner,
+                    &repo.name,
+                    created.number,
+                    &json!({ "labels": new.labels }),
+                )
+                .await
+            {
+                Ok(labels) => created.labels = labels.into_iter().map(label).collect(),
+                Err(e) => warn!(
+                    "Forgejo issue {}/{}#{} created without labels {:?}: {e}",
+                    repo.owner, repo.name, created.number, new.labels
+                ),
+            }
+        }
+        Ok(created)
+    }
+
+    async fn set_issue_state(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        state: IssueState,
+    ) -> Result<(), JediError> {
+        ForgejoClient::set_issue_state(
+            self,
+            &repo.owner,
+            &repo.name,
+            number,
+            &json!({ "state": state.as_str() }),
+        )
+        .await
+    }
+
+    /// Walks every page: Forgejo caps `limit` at 50.
+    async fn list_labels(&self, repo: &RepoRef) -> Result<Vec<ForgeLabel>, JediError> {
+        let path = repo_path(repo, "/labels");
+        paginate(MAX_LIMIT, |page, per_page| {
+            let path = path.clone();
+            Box::pin(async move {
+                let (labels, next_page): (Vec<ForgejoLabel>, _) =
+                    self.get_page(&path, &page_query(page, per_page)).await?;
+                Ok(Page {
+                    items: labels.into_iter().map(label).collect(),
+                    next_page,
+                })
+            })
+        })
+        .try_collect()
+        .await
+    }
+
+    async fn add_labels(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        labels: &[&str],
+    ) -> Result<Vec<ForgeLabel>, JediError> {
+        let labels = self
+            .add_issue_labels(
+                &repo.owner,
+                &repo.name,
+                number,
+                &json!({ "labels": labels }),
+            )
+            .await?;
+        Ok(labels.into_iter().map(label).collect())
+    }
+
+    async fn remove_label(&self, repo: &RepoRef, number: u64, name: &str) -> Result<(), JediError> {
+        let current: ForgejoIssue =
+            ForgejoClient::get_issue(self, &repo.owner, &repo.name, number).await?;
+        let id = current
+            .labels
+            .iter()
+            .find(|l| l.name == name)
+            .map(|l| l.id)
+            .ok_or(JediError::NotFound)?;
+        self.remove_issue_label(&repo.owner, &repo.name, number, id)
+            .await
+    }
+
+    async fn list_comments(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeComment>, JediError> {
+        let path = repo_path(repo, &format!("/issues/{number}/comments"));
+        let (comments, next_page): (Vec<ForgejoComment>, _) =
+            self.get_page(&path, &page_query(page, per_page)).await?;
+        Ok(Page {
+            items: comments.into_iter().map(comment).collect(),
+            next_page,
+        })
+    }
+
+    async fn create_comment(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<ForgeComment, JediError> {
+        let c = self
+            .create_issue_comment(&repo.owner, &repo.name, number, &json!({ "body": body }))
+            .await?;
+        Ok(comment(c))
+    }
+
+    async fn list_pulls(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgePull>, JediError> {
+        let mut query = page_query(page, per_page);
+        query.push(("state", state.as_str().to_string()));
+        let (pulls, next_page): (Vec<ForgejoPull>, _) =
+            self.get_page(&repo_path(repo, "/pulls"), &query).await?;
+        Ok(Page {
+            items: pulls.into_iter().map(pull).collect(),
+            next_page,
+        })
+    }
+
+    async fn get_pull(&self, repo: &RepoRef, number: u64) -> Result<ForgePull, JediError> {
+        let (p, _) = self
+            .get_page(&repo_path(repo, &format!("/pulls/{number}")), &[])
+            .await?;
+        Ok(pull(p))
+    }
+
+    async fn merge_pull(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        method: MergeMethod,
+        message: Option<&str>,
+    ) -> Result<(), JediError> {
+        let mut body = json!({ "Do": method.as_str() });
+        if let Some(m) = message {
+            body["MergeMessageField"] = json!(m);
+        }
+        ForgejoClient::merge_pull(self, &repo.owner, &repo.name, number, &body).await
+    }
+
+    async fn list_workflows(&self, repo: &RepoRef) -> Result<Vec<ForgeWorkflow>, JediError> {
+        for dir in WORKFLOW_DIRS {
+            let entries: Value = match self
+                .get_page(&repo_path(repo, &format!("/contents/{dir}")), &[])
+                .await
+            {
+                Ok((entries, _)) => entries,
+                Err(JediError::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            return Ok(entries
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|e| e.get("type").and_then(Value::as_str) == Some("file"))
+                .filter_map(|e| {
+                    let name = e.get("name")?.as_str()?;
+                    (name.ends_with(".yml") || name.ends_with(".yaml")).then(|| ForgeWorkflow {
+                        id: name.to_string(),
+                        name: name.to_string(),
+                        path: e
+                            .get("path")
+                            .and_then(Value::as_str)
+                            .unwrap_or(name)
+                            .to_string(),
+                        state: "active".to_string(),
+                    })
+                })
+                .collect());
+        }
+        Ok(Vec::new())
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        repo: &RepoRef,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeWorkflowRun>, JediError> {
+        let query = page_query(page, per_page);
+        let (tasks, link_next): (ForgejoActionTasks, _) = self
+            .get_page(&repo_path(repo, "/actions/tasks"), &query)
+            .await?;
+        // Older Forgejo releases omit the Link header here; fall back to the
+        // body's total_count.
+        let limit = u64::from(per_page.min(MAX_LIMIT));
+        let next_page =
+            link_next.or_else(|| (u64::from(page) * limit < tasks.total_count).then_some(page + 1));
+        Ok(Page {
+            items: tasks.workflow_runs.into_iter().map(workflow_run).collect(),
+            next_page,
+        })
+    }
+
+    async fn dispatch_workflow(
+        &self,
+        repo: &RepoRef,
+        workflow: &str,
+        git_ref: &str,
+        inputs: Option<&Value>,
+    ) -> Result<(), JediError> {
+        let body = json!({
+            "ref": git_ref,
+            "inputs": inputs.cloned().unwrap_or_else(|| json!({})),
+        });
+        ForgejoClient::dispatch_workflow(self, &repo.owner, &repo.name, workflow, &body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        extract::{Path, Query},
+        http::StatusCode,
+        routing::{delete, get},
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    async fn mock_server(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn task_status_maps_to_github_vocabulary() {
+        assert_eq!(run_status("waiting"), ("queued", None));
+        assert_eq!(run_status("running"), ("in_progress", None));
+        assert_eq!(
+            run_status("failure"),
+            ("completed", Some("failure".to_string()))
+        );
+        assert!(is_wip("[WIP] forge trait"));
+        assert!(!is_wip("forge trait"));
+    }
+
+    #[tokio::test]
+    async fn workflow_runs_paginate_by_total_count() {
+        let app = Router::new().route(
+            "/api/v1/repos/{owner}/{repo}/actions/tasks",
+            get(|Query(q): Query<HashMap<String, String>>| async move {
+                let page: u64 = q["page"].parse().unwrap();
+                axum::Json(json!({
+                    "total_count": 3,
+                    "workflow_runs": (0..2u64)
+                        .map(|i| (page - 1) * 2 + i + 1)
+                        .filter(|id| *id <= 3)
+                        .map(|id| json!({
+                            "id": id,
+                            "name": "build",
+                            "status": if id == 3 { "running" } else { "success" },
+                            "run_number": id,
+                            "head_branch": "dev",
+                            "url": format!("https://git.kbve.com/KBVE/kbve/actions/runs/{id}")
+                        }))
+                        .collect::<Vec<_>>()
+                }))
+            }),
+        );
+        let base = mock_server(app).await;
+        let fj = ForgejoClient::new(&base, "t");
+        let repo = RepoRef::new("KBVE", "kbve");
+
+        let runs: Vec<ForgeWorkflowRun> = paginate(2, |page, per_page| {
+            fj.list_workflow_runs(&repo, page, per_page)
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(runs[0].conclusion.as_deref(), Some("success"));
+        assert_eq!(runs[2].status, "in_progress");
+    }
+
+    #[tokio::test]
+    async fn remove_label_resolves_name_to_id() {
+        let removed = Arc::new(Mutex::new(None));
+        let seen = removed.clone();
+        let app = Router::new()
+            .route(
+                "/api/v1/repos/{owner}/{repo}/issues/{index}",
+                get(|| async {
+                    axum::Json(json!({
+                        "id": 100,
+                        "number": 4,
+                        "labels": [{"id": 11, "name": "bug"}, {"id": 12, "name": "ci"}]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v1/repos/{owner}/{repo}/issues/{index}/labels/{id}",
+                delete(
+                    move |Path((_, _, _, id)): Path<(String, String, u64, u64)>| async move {
+                        *seen.lock().unwrap() = Some(id);
+                        StatusCode::NO_CONTENT
+                    },
+                ),
+            );
+        let base = mock_server(app).await;
+        let fj = ForgejoClient::new(&base, "t");
+        let repo = RepoRef::new("KBVE", "kbve");
+
+        Forge::remove_label(&fj, &repo, 4, "ci").await.unwrap();
+        assert_eq!(*removed.lock().unwrap(), Some(12));
+        assert!(matches!(
+            Forge::remove_label(&fj, &repo, 4, "missing").await,
+            Err(JediError::NotFound)
+        ));
+    }
+}
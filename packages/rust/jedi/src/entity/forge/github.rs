@@ -0,0 +1,534 @@
+//! [`Forge`] for [`GitHubClient`].
+//!
+//! Reads go through `GitHubClient::get_page` so they pick up ETags and the
+//! `Link` header; writes reuse the client's existing methods. Wire structs
+//! here cover fields the public `GitHub*` types don't carry.
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::api::{Forge, paginate};
+use super::types::*;
+use crate::entity::error::JediError;
+use crate::entity::github::{
+    CreateIssueRequest, GitHubClient, GitHubComment, GitHubIssue, GitHubLabel,
+    GitHubWorkflowRunsResponse, GitHubWorkflowsResponse, MergePullRequest, UpdateIssueRequest,
+};
+
+#[derive(Deserialize)]
+struct RepoWire {
+    name: String,
+    full_name: String,
+    #[serde(default)]
+    description: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    default_branch: String,
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    open_issues_count: u64,
+    owner: ForgeUser,
+}
+
+#[derive(Deserialize)]
+struct PullWire {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    user: Option<ForgeUser>,
+    #[serde(default)]
+    head: Option<BranchWire>,
+    #[serde(default)]
+    base: Option<BranchWire>,
+    #[serde(default)]
+    labels: Vec<ForgeLabel>,
+    created_at: String,
+    updated_at: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct BranchWire {
+    #[serde(rename = "ref")]
+    ref_name: String,
+    sha: String,
+}
+
+fn label(l: GitHubLabel) -> ForgeLabel {
+    ForgeLabel {
+        name: l.name,
+        color: l.color,
+        description: None,
+    }
+}
+
+fn issue(i: GitHubIssue) -> ForgeIssue {
+    ForgeIssue {
+        number: i.number,
+        title: i.title,
+        body: i.body,
+        state: IssueState::from_api(&i.state),
+        author: Some(ForgeUser {
+            login: i.user.login,
+        }),
+        labels: i.labels.into_iter().map(label).collect(),
+        assignees: i
+            .assignees
+            .into_iter()
+            .map(|u| ForgeUser { login: u.login })
+            .collect(),
+        comments: i.comments,
+        created_at: i.created_at,
+        updated_at: i.updated_at,
+        html_url: i.html_url,
+    }
+}
+
+fn pull(p: PullWire) -> ForgePull {
+    let state = if p.merged_at.is_some() {
+        PullState::Merged
+    } else if p.state.eq_ignore_ascii_case("closed") {
+        PullState::Closed
+    } else {
+        PullState::Open
+    };
+    let branch = |b: BranchWire| ForgeBranchRef {
+        ref_name: b.ref_name,
+        sha: b.sha,
+    };
+    ForgePull {
+        number: p.number,
+        title: p.title,
+        body: p.body,
+        state,
+        draft: p.draft,
+        author: p.user,
+        head: p.head.map(branch),
+        base: p.base.map(branch),
+        labels: p.labels,
+        created_at: p.created_at,
+        updated_at: p.updated_at,
+        html_url: p.html_url,
+    }
+}
+
+fn comment(c: GitHubComment) -> ForgeComment {
+    ForgeComment {
+        id: c.id,
+        body: c.body,
+        author: Some(ForgeUser {
+            login: c.user.login,
+        }),
+        created_at: c.created_at,
+        updated_at: c.updated_at,
+        html_url: c.html_url,
+    }
+}
+
+fn page_query(page: u32, per_page: u32) -> Vec<(&'static str, String)> {
+    vec![
+        ("per_page", per_page.to_string()),
+        ("page", page.to_string()),
+    ]
+}
+
+#[async_trait]
+impl Forge for GitHubClient {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitHub
+    }
+
+    async fn get_repo(&self, repo: &RepoRef) -> Result<ForgeRepo, JediError> {
+        let (r, _): (RepoWire, _) = self.get_page(&repo.owner, &repo.name, "", &[]).await?;
+        Ok(ForgeRepo {
+            owner: r.owner.login,
+            name: r.name,
+            full_name: r.full_name,
+            description: r.description,
+            default_branch: r.default_branch,
+            html_url: r.html_url,
+            private: r.private,
+            archived: r.archived,
+            open_issues: r.open_issues_count,
+        })
+    }
+
+    async fn list_issues(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeIssue>, JediError> {
+        let mut query = page_query(page, per_page);
+        query.push(("state", state.as_str().to_string()));
+        let (issues, next_page): (Vec<GitHubIssue>, _) = self
+            .get_page(&repo.owner, &repo.name, "/issues", &query)
+            .await?;
+        // The issues endpoint interleaves PRs; a page may come back short.
+        Ok(Page {
+            items: issues
+                .into_iter()
+                .filter(|i| !i.is_pull_request())
+                .map(issue)
+                .collect(),
+            next_page,
+        })
+    }
+
+    async fn get_issue(&self, repo: &RepoRef, number: u64) -> Result<ForgeIssue, JediError> {
+        let path = format!("/issues/{number}");
+        let (i, _) = self.get_page(&repo.owner, &repo.name, &path, &[]).await?;
+        Ok(issue(i))
+    }
+
+    async fn create_issue(&self, repo: &RepoRef, new: &NewIssue) -> Result<ForgeIssue, JediError> {
+        let request = CreateIssueRequest {
+            title: new.title.clone(),
+            body: new.body.clone(),
+            labels: new.labels.clone(),
+            assignees: new.assignees.clone(),
+            issue_type: None,
+        };
+        let created = GitHubClient::create_issue(self, &repo.owner, &repo.name, &request).await?;
+        Ok(issue(created))
+    }
+
+    async fn set_issue_state(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        state: IssueState,
+    ) -> Result<(), JediError> {
+        let request = UpdateIssueRequest {
+            state: Some(state.as_str().to_string()),
+            ..Default::default()
+        };
+        self.update_issue(&repo.owner, &repo.name, number, &request)
+            .await?;
+        Ok(())
+    }
+
+    /// Walks every page: GitHub caps `per_page` at 100.
+    async fn list_labels(&self, repo: &RepoRef) -> Result<Vec<ForgeLabel>, JediError> {
+        paginate(100, |page, per_page| {
+            Box::pin(async move {
+                let (labels, next_page) = self
+                    .get_page(&repo.owner, &repo.name, "/labels", &page_query(page, per_page))
+                    .await?;
+                Ok(Page {
+                    items: labels,
+                    next_page,
+                })
+            })
+        })
+        .try_collect()
+        .await
+    }
+
+    async fn add_labels(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        labels: &[&str],
+    ) -> Result<Vec<ForgeLabel>, JediError> {
+        let labels =
+            GitHubClient::add_labels(self, &repo.owner, &repo.name, number, labels).await?;
+        Ok(labels.into_iter().map(label).collect())
+    }
+
+    async fn remove_label(&self, repo: &RepoRef, number: u64, name: &str) -> Result<(), JediError> {
+        GitHubClient::remove_label(self, &repo.owner, &repo.name, number, name).await
+    }
+
+    async fn list_comments(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeComment>, JediError> {
+        let path = format!("/issues/{number}/comments");
+        let (comments, next_page): (Vec<GitHubComment>, _) = self
+            .get_page(&repo.owner, &repo.name, &path, &page_query(page, per_page))
+            .await?;
+        Ok(Page {
+            items: comments.into_iter().map(comment).collect(),
+            next_page,
+        })
+    }
+
+    async fn create_comment(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<ForgeComment, JediError> {
+        let c = GitHubClient::create_comment(self, &repo.owner, &repo.name, number, body).await?;
+        Ok(comment(c))
+    }
+
+    async fn list_pulls(
+        &self,
+        repo: &RepoRef,
+        state: StateFilter,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgePull>, JediError> {
+        let mut query = page_query(page, per_page);
+        query.push(("state", state.as_str().to_string()));
+        let (pulls, next_page): (Vec<PullWire>, _) = self
+            .get_page(&repo.owner, &repo.name, "/pulls", &query)
+            .await?;
+        Ok(Page {
+            items: pulls.into_iter().map(pull).collect(),
+            next_page,
+        })
+    }
+
+    async fn get_pull(&self, repo: &RepoRef, number: u64) -> Result<ForgePull, JediError> {
+        let path = format!("/pulls/{number}");
+        let (p, _) = self.get_page(&repo.owner, &repo.name, &path, &[]).await?;
+        Ok(pull(p))
+    }
+
+    async fn merge_pull(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        method: MergeMethod,
+        message: Option<&str>,
+    ) -> Result<(), JediError> {
+        let request = MergePullRequest {
+            commit_title: None,
+            commit_message: message.map(String::from),
+            merge_method: Some(method.as_str().to_string()),
+        };
+        let result =
+            GitHubClient::merge_pull(self, &repo.owner, &repo.name, number, &request).await?;
+        if result.merged {
+            Ok(())
+        } else {
+            Err(JediError::BadRequest(result.message))
+        }
+    }
+
+    async fn list_workflows(&self, repo: &RepoRef) -> Result<Vec<ForgeWorkflow>, JediError> {
+        let (wrapper, _): (GitHubWorkflowsResponse, _) = self
+            .get_page(
+                &repo.owner,
+                &repo.name,
+                "/actions/workflows",
+                &page_query(1, 100),
+            )
+            .await?;
+        Ok(wrapper
+            .workflows
+            .into_iter()
+            .map(|w| ForgeWorkflow {
+                id: w.id.to_string(),
+                name: w.name,
+                path: w.path,
+                state: w.state,
+            })
+            .collect())
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        repo: &RepoRef,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Page<ForgeWorkflowRun>, JediError> {
+        let (wrapper, next_page): (GitHubWorkflowRunsResponse, _) = self
+            .get_page(
+                &repo.owner,
+                &repo.name,
+                "/actions/runs",
+                &page_query(page, per_page),
+            )
+            .await?;
+        Ok(Page {
+            items: wrapper
+                .workflow_runs
+                .into_iter()
+                .map(|r| ForgeWorkflowRun {
+                    id: r.id,
+                    name: r.name,
+                    status: r.status,
+                    conclusion: r.conclusion,
+                    run_number: r.run_number,
+                    head_branch: r.head_branch,
+                    created_at: r.created_at,
+                    updated_at: r.updated_at,
+                    html_url: r.html_url,
+                })
+                .collect(),
+            next_page,
+        })
+    }
+
+    async fn dispatch_workflow(
+        &self,
+        repo: &RepoRef,
+        workflow: &str,
+        git_ref: &str,
+        inputs: Option<&Value>,
+    ) -> Result<(), JediError> {
+        GitHubClient::dispatch_workflow(self, &repo.owner, &repo.name, workflow, git_ref, inputs)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::forge::EtagCache;
+    use axum::{
+        Router,
+        extract::Query,
+        http::{HeaderMap, StatusCode, header},
+        response::IntoResponse,
+        routing::get,
+    };
+    use futures_util::TryStreamExt;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    async fn mock_server(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn issue_json(number: u64, pr: bool) -> Value {
+        let mut v = serde_json::json!({
+            "number": number,
+            "title": format!("Issue {number}"),
+            "state": "open",
+            "user": {"login": "h0lybyte"},
+            "labels": [{"name": "bug", "color": "d73a4a"}],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-02T00:00:00Z",
+            "html_url": format!("https://github.com/KBVE/kbve/issues/{number}"),
+            "body": "text",
+            "comments": 2
+        });
+        if pr {
+            v["pull_request"] = serde_json::json!({"url": "x"});
+        }
+        v
+    }
+
+    #[tokio::test]
+    async fn issues_stream_follows_link_and_skips_pulls() {
+        let app = Router::new().route(
+            "/repos/{owner}/{repo}/issues",
+            get(|Query(q): Query<HashMap<String, String>>| async move {
+                let page: u32 = q.get("page").and_then(|p| p.parse().ok()).unwrap_or(1);
+                let mut headers = HeaderMap::new();
+                let body = if page == 1 {
+                    headers.insert(
+                        header::LINK,
+                        "<http://h/repos/KBVE/kbve/issues?page=2>; rel=\"next\""
+                            .parse()
+                            .unwrap(),
+                    );
+                    serde_json::json!([issue_json(1, false), issue_json(2, true)])
+                } else {
+                    serde_json::json!([issue_json(3, false)])
+                };
+                (headers, axum::Json(body))
+            }),
+        );
+        let base = mock_server(app).await;
+        let gh = GitHubClient::new("t").with_base_url(&base);
+        let repo = RepoRef::new("KBVE", "kbve");
+
+        let issues: Vec<ForgeIssue> = gh
+            .issues(&repo, StateFilter::Open)
+            .try_collect()
+            .await
+            .unwrap();
+        let numbers: Vec<u64> = issues.iter().map(|i| i.number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(issues[0].labels[0].name, "bug");
+        assert_eq!(issues[0].author.as_ref().unwrap().login, "h0lybyte");
+        assert_eq!(issues[0].state, IssueState::Open);
+    }
+
+    #[tokio::test]
+    async fn etag_304_serves_cached_body() {
+        let app = Router::new().route(
+            "/repos/{owner}/{repo}/pulls/{n}",
+            get(|headers: HeaderMap| async move {
+                if headers
+                    .get(header::IF_NONE_MATCH)
+                    .is_some_and(|v| v == "\"v1\"")
+                {
+                    return StatusCode::NOT_MODIFIED.into_response();
+                }
+                (
+                    [(header::ETAG, "\"v1\"")],
+                    axum::Json(serde_json::json!({
+                        "number": 7,
+                        "title": "Add forge",
+                        "state": "closed",
+                        "merged_at": "2026-01-03T00:00:00Z",
+                        "user": {"login": "dev"},
+                        "head": {"ref": "feature", "sha": "abc"},
+                        "base": {"ref": "dev", "sha": "def"},
+                        "created_at": "2026-01-01T00:00:00Z",
+                        "updated_at": "2026-01-03T00:00:00Z",
+                        "html_url": "https://github.com/KBVE/kbve/pull/7"
+                    })),
+                )
+                    .into_response()
+            }),
+        );
+        let base = mock_server(app).await;
+        let cache = Arc::new(EtagCache::default());
+        let gh = GitHubClient::new("t")
+            .with_base_url(&base)
+            .with_etag_cache(cache.clone());
+        let repo = RepoRef::new("KBVE", "kbve");
+
+        let first = gh.get_pull(&repo, 7).await.unwrap();
+        let second = gh.get_pull(&repo, 7).await.unwrap();
+        assert_eq!(first.state, PullState::Merged);
+        assert_eq!(second.base.unwrap().ref_name, "dev");
+
+        let stats = cache.stats();
+        assert_eq!(stats.fetched, 1);
+        assert_eq!(stats.not_modified, 1);
+    }
+
+    #[tokio::test]
+    async fn not_found_maps_through_get_page() {
+        let app = Router::new().route(
+            "/repos/{owner}/{repo}",
+            get(|| async { StatusCode::NOT_FOUND }),
+        );
+        let base = mock_server(app).await;
+        let gh = GitHubClient::new("t").with_base_url(&base);
+        let err = Forge::get_repo(&gh, &RepoRef::new("KBVE", "nope"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JediError::NotFound));
+    }
+}
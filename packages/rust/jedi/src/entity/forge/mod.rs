@@ -0,0 +1,24 @@
+//! Forge-agnostic access to issues, pull requests, labels, comments and
+//! workflows, implemented for [`GitHubClient`](super::github::GitHubClient)
+//! and (with the `forgejo` feature) `ForgejoClient`.
+//!
+//! ```ignore
+//! let etags = Arc::new(EtagCache::default());
+//! let forge = ForgeConfig::from_env()?.build(Some(etags))?;
+//! let repo = RepoRef::parse("KBVE/kbve")?;
+//! let mut issues = forge.issues(&repo, StateFilter::Open);
+//! while let Some(issue) = issues.try_next().await? { /* ... */ }
+//! ```
+
+mod api;
+mod config;
+pub(crate) mod etag;
+#[cfg(feature = "forgejo")]
+mod forgejo;
+mod github;
+mod types;
+
+pub use api::*;
+pub use config::*;
+pub use etag::{DEFAULT_ETAG_CAPACITY, EtagCache, EtagStats};
+pub use types::*;
@@ -0,0 +1,282 @@
+//! Forge-neutral types returned by [`Forge`](super::Forge).
+//!
+//! Field names follow GitHub's vocabulary; the Forgejo backend maps onto it
+//! (e.g. Forgejo action task states become `queued` / `in_progress` /
+//! `completed` + a conclusion).
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::entity::error::JediError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    Forgejo,
+}
+
+impl ForgeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github",
+            ForgeKind::Forgejo => "forgejo",
+        }
+    }
+}
+
+impl std::str::FromStr for ForgeKind {
+    type Err = JediError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "github" | "gh" => Ok(ForgeKind::GitHub),
+            "forgejo" | "gitea" => Ok(ForgeKind::Forgejo),
+            other => Err(JediError::BadRequest(format!(
+                "unknown forge kind '{other}'"
+            ))),
+        }
+    }
+}
+
+/// `owner/name` of a repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RepoRef {
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepoRef {
+    pub fn new(owner: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            owner: owner.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Parse `"owner/name"`.
+    pub fn parse(full_name: &str) -> Result<Self, JediError> {
+        match full_name.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(Self::new(owner, name))
+            }
+            _ => Err(JediError::BadRequest(format!(
+                "expected owner/name, got '{full_name}'"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for RepoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+/// One page of a list endpoint. `next_page` is `None` on the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateFilter {
+    Open,
+    Closed,
+    All,
+}
+
+impl StateFilter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StateFilter::Open => "open",
+            StateFilter::Closed => "closed",
+            StateFilter::All => "all",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    Open,
+    Closed,
+}
+
+impl IssueState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+        }
+    }
+
+    pub(crate) fn from_api(state: &str) -> Self {
+        if state.eq_ignore_ascii_case("closed") {
+            IssueState::Closed
+        } else {
+            IssueState::Open
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl MergeMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgeUser {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgeLabel {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeRepo {
+    pub owner: String,
+    pub name: String,
+    pub full_name: String,
+    pub description: Option<String>,
+    pub default_branch: String,
+    pub html_url: String,
+    pub private: bool,
+    pub archived: bool,
+    pub open_issues: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeIssue {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub state: IssueState,
+    pub author: Option<ForgeUser>,
+    pub labels: Vec<ForgeLabel>,
+    pub assignees: Vec<ForgeUser>,
+    pub comments: u64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub html_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeBranchRef {
+    pub ref_name: String,
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgePull {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub state: PullState,
+    pub draft: bool,
+    pub author: Option<ForgeUser>,
+    pub head: Option<ForgeBranchRef>,
+    pub base: Option<ForgeBranchRef>,
+    pub labels: Vec<ForgeLabel>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub html_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeComment {
+    pub id: u64,
+    pub body: String,
+    pub author: Option<ForgeUser>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub html_url: String,
+}
+
+/// A workflow definition. `id` is what `dispatch_workflow` accepts — the
+/// numeric id on GitHub, the file name on Forgejo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeWorkflow {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeWorkflowRun {
+    pub id: u64,
+    pub name: Option<String>,
+    /// `queued`, `in_progress` or `completed`.
+    pub status: String,
+    /// Set once `completed`: `success`, `failure`, `cancelled`, `skipped`, ...
+    pub conclusion: Option<String>,
+    pub run_number: u64,
+    pub head_branch: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub html_url: String,
+}
+
+/// Input for `Forge::create_issue`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewIssue {
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignees: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_ref_parses_owner_and_name() {
+        let r = RepoRef::parse("KBVE/kbve").unwrap();
+        assert_eq!(r, RepoRef::new("KBVE", "kbve"));
+        assert_eq!(r.to_string(), "KBVE/kbve");
+        for bad in ["", "kbve", "/kbve", "KBVE/", "a/b/c"] {
+            assert!(RepoRef::parse(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn forge_kind_from_str() {
+        assert_eq!("GitHub".parse::<ForgeKind>().unwrap(), ForgeKind::GitHub);
+        assert_eq!("gitea".parse::<ForgeKind>().unwrap(), ForgeKind::Forgejo);
+        assert!("gitlab".parse::<ForgeKind>().is_err());
+    }
+}
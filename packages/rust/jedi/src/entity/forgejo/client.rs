@@ -10,12 +10,15 @@ use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, warn};
 
 use super::policy::ForgejoPolicy;
 use super::types::*;
 use crate::entity::error::JediError;
+use crate::entity::forge::EtagCache;
+use crate::entity::forge::etag::{self, GetOutcome};
 
 const USER_AGENT: &str = "kbve-jedi/1.0";
 
@@ -25,6 +28,7 @@ pub struct ForgejoClient {
     token: String,
     base_url: String,
     policy: ForgejoPolicy,
+    etags: Option<Arc<EtagCache>>,
 }
 
 impl ForgejoClient {
@@ -41,6 +45,7 @@ impl ForgejoClient {
             token: token.to_string(),
             base_url: base_url.trim_end_matches('/').to_string(),
             policy: ForgejoPolicy::open(),
+            etags: None,
         }
     }
 
@@ -51,6 +56,12 @@ impl ForgejoClient {
         self
     }
 
+    /// Share an ETag cache across clients; see [`EtagCache`].
+    pub fn with_etag_cache(mut self, cache: Arc<EtagCache>) -> Self {
+        self.etags = Some(cache);
+        self
+    }
+
     // ── Core helpers ─────────────────────────────────────────────────
 
     fn url(&self, path: &str) -> String {
@@ -115,6 +126,30 @@ impl ForgejoClient {
         self.parse_response(resp).await
     }
 
+    /// Conditional GET returning the parsed body and the next page from the
+    /// `Link` header. Used by the forge layer for paginated listings.
+    pub(crate) async fn get_page<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(T, Option<u32>), JediError> {
+        let url = self.url(path);
+        let key = etag::cache_key(&url, query);
+        let cache = self.etags.as_deref();
+
+        let req = self.client.get(&url).bearer_auth(&self.token).query(query);
+        let (req, cached) = etag::prepare(req, &key, cache);
+        let resp = self.send(req).await?;
+        match etag::settle(resp, &key, cached, cache).await? {
+            GetOutcome::Page(page) => {
+                let parsed = serde_json::from_str(&page.body)
+                    .map_err(|e| JediError::Parse(format!("JSON parse error: {e}")))?;
+                Ok((parsed, page.next_page))
+            }
+            GetOutcome::Failed(resp) => Err(self.error_for(resp).await),
+        }
+    }
+
     async fn write<T: serde::de::DeserializeOwned>(
         &self,
         method: Method,
@@ -760,6 +795,31 @@ impl ForgejoClient {
         .await
     }
 
+    pub async fn get_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        index: u64,
+    ) -> Result<ForgejoIssue, JediError> {
+        self.get(&format!("/api/v1/repos/{owner}/{repo}/issues/{index}"), &[])
+            .await
+    }
+
+    pub async fn create_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        body: &Value,
+    ) -> Result<ForgejoIssue, JediError> {
+        self.policy.check_owner(owner)?;
+        self.write(
+            Method::POST,
+            &format!("/api/v1/repos/{owner}/{repo}/issues"),
+            Some(body),
+        )
+        .await
+    }
+
     pub async fn set_issue_state(
         &self,
         owner: &str,
@@ -1010,6 +1070,39 @@ impl ForgejoClient {
         .await
     }
 
+    /// Append labels to an issue. `body.labels` may hold label ids or names.
+    pub async fn add_issue_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        index: u64,
+        body: &Value,
+    ) -> Result<Vec<ForgejoLabel>, JediError> {
+        self.policy.check_owner(owner)?;
+        self.write(
+            Method::POST,
+            &format!("/api/v1/repos/{owner}/{repo}/issues/{index}/labels"),
+            Some(body),
+        )
+        .await
+    }
+
+    pub async fn remove_issue_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        index: u64,
+        label_id: u64,
+    ) -> Result<(), JediError> {
+        self.policy.check_owner(owner)?;
+        self.write_empty(
+            Method::DELETE,
+            &format!("/api/v1/repos/{owner}/{repo}/issues/{index}/labels/{label_id}"),
+            None,
+        )
+        .await
+    }
+
     // ── Milestones ───────────────────────────────────────────────────
 
     pub async fn list_milestones(
@@ -1595,4 +1688,38 @@ impl ForgejoClient {
         self.get("/api/v1/admin/runners/registration-token", &[])
             .await
     }
+
+    // ── Actions runs ─────────────────────────────────────────────────
+
+    pub async fn list_action_tasks(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: u32,
+        limit: u32,
+    ) -> Result<ForgejoActionTasks, JediError> {
+        self.get(
+            &format!("/api/v1/repos/{owner}/{repo}/actions/tasks"),
+            &Self::page_query(page, limit),
+        )
+        .await
+    }
+
+    /// Trigger a `workflow_dispatch` run. `workflow` is the file name under
+    /// `.forgejo/workflows` (e.g. `"ci.yml"`); `body` is `{"ref", "inputs"}`.
+    pub async fn dispatch_workflow(
+        &self,
+        owner: &str,
+        repo: &str,
+        workflow: &str,
+        body: &Value,
+    ) -> Result<(), JediError> {
+        self.policy.check_owner(owner)?;
+        self.write_empty(
+            Method::POST,
+            &format!("/api/v1/repos/{owner}/{repo}/actions/workflows/{workflow}/dispatches"),
+            Some(body),
+        )
+        .await
+    }
 }
@@ -35,6 +35,10 @@ pub struct ForgejoRepo {
     pub updated_at: String,
     #[serde(default)]
     pub language: String,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub open_issues_count: u64,
     pub owner: ForgejoOwner,
 }
 
@@ -256,16 +260,24 @@ pub struct ForgejoIssue {
     #[serde(default)]
     pub state: String,
     #[serde(default)]
+    pub body: String,
+    #[serde(default)]
     pub is_locked: bool,
     #[serde(default)]
     pub comments: u64,
     #[serde(default)]
     pub created_at: String,
     #[serde(default)]
+    pub updated_at: String,
+    #[serde(default)]
     pub html_url: String,
     #[serde(default)]
     pub user: Option<ForgejoOwner>,
     #[serde(default)]
+    pub labels: Vec<ForgejoLabel>,
+    #[serde(default)]
+    pub assignees: Option<Vec<ForgejoOwner>>,
+    #[serde(default)]
     pub pull_request: Option<ForgejoPullMeta>,
 }
 
@@ -299,6 +311,8 @@ pub struct ForgejoPull {
     pub html_url: String,
     #[serde(default)]
     pub user: Option<ForgejoOwner>,
+    #[serde(default)]
+    pub labels: Vec<ForgejoLabel>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -545,6 +559,43 @@ pub struct ForgejoRegistrationToken {
     pub token: String,
 }
 
+/// One Forgejo Actions task (a job run), from `GET /repos/{o}/{r}/actions/tasks`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgejoActionTask {
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub display_title: String,
+    #[serde(default)]
+    pub head_branch: String,
+    #[serde(default)]
+    pub head_sha: String,
+    #[serde(default)]
+    pub run_number: u64,
+    #[serde(default)]
+    pub event: String,
+    /// `waiting`, `running`, `blocked`, `success`, `failure`, `cancelled`, `skipped`, `unknown`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub workflow_id: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgejoActionTasks {
+    #[serde(default)]
+    pub workflow_runs: Vec<ForgejoActionTask>,
+    #[serde(default)]
+    pub total_count: u64,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct ForgejoSearchResults<T> {
     #[serde(default = "Vec::new")]
@@ -11,11 +11,14 @@
 
 use reqwest::{Client, Response, StatusCode};
 use std::borrow::Cow;
+use std::sync::Arc;
 use tracing::warn;
 
 use super::policy::RepoPolicy;
 use super::types::*;
 use crate::entity::error::JediError;
+use crate::entity::forge::EtagCache;
+use crate::entity::forge::etag::{self, GetOutcome};
 
 const DEFAULT_BASE_URL: &str = "https://api.github.com";
 const USER_AGENT: &str = "kbve-jedi/1.0";
@@ -26,6 +29,7 @@ pub struct GitHubClient {
     token: String,
     base_url: String,
     policy: RepoPolicy,
+    etags: Option<Arc<EtagCache>>,
 }
 
 impl GitHubClient {
@@ -41,6 +45,7 @@ impl GitHubClient {
             token: token.to_string(),
             base_url: DEFAULT_BASE_URL.to_string(),
             policy: RepoPolicy::open(),
+            etags: None,
         }
     }
 
@@ -57,6 +62,14 @@ impl GitHubClient {
         self
     }
 
+    /// Share an ETag cache across clients. GETs made through the forge
+    /// layer send `If-None-Match` and reuse the cached body on `304`, which
+    /// GitHub does not count against the rate limit.
+    pub fn with_etag_cache(mut self, cache: Arc<EtagCache>) -> Self {
+        self.etags = Some(cache);
+        self
+    }
+
     // ── API Methods ─────────────────────────────────────────────────
 
     /// Fetch issues for a repository. Excludes pull requests by default.
@@ -804,6 +817,38 @@ impl GitHubClient {
 
     // ── Internal ────────────────────────────────────────────────────
 
+    /// Conditional GET of `/repos/{owner}/{repo}{path}` returning the parsed
+    /// body and the next page from the `Link` header.
+    pub(crate) async fn get_page<T: serde::de::DeserializeOwned>(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(T, Option<u32>), JediError> {
+        self.policy.check(owner, repo)?;
+        let url = format!("{}/repos/{}/{}{}", self.base_url, owner, repo, path);
+        let key = etag::cache_key(&url, query);
+        let cache = self.etags.as_deref();
+
+        let req = self.client.get(&url).bearer_auth(&self.token).query(query);
+        let (req, cached) = etag::prepare(req, &key, cache);
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| JediError::Internal(Cow::Owned(format!("GitHub request failed: {e}"))))?;
+
+        let resp = self.check_rate_limit(resp);
+        match etag::settle(resp, &key, cached, cache).await? {
+            GetOutcome::Page(page) => {
+                let parsed = serde_json::from_str(&page.body)
+                    .map_err(|e| JediError::Parse(format!("JSON parse error: {e}")))?;
+                Ok((parsed, page.next_page))
+            }
+            GetOutcome::Failed(resp) => self.parse_response(resp).await,
+        }
+    }
+
     /// Check rate limit headers and log warnings.
     fn check_rate_limit(&self, resp: Response) -> Response {
         if let Some(remaining) = resp
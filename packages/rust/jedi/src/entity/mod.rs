@@ -4,6 +4,7 @@ pub mod bitwise;
 pub mod envelope;
 pub mod error;
 pub mod flex;
+pub mod forge;
 #[cfg(feature = "forgejo")]
 pub mod forgejo;
 pub mod github;
@@ -21,6 +22,8 @@ pub mod regex;
 pub mod serde_arc_str;
 pub mod serde_bytes_map;
 pub mod ulid;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 pub use ai::*;
 pub use bitwise::*;
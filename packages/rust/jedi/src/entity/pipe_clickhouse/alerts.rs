@@ -2,7 +2,10 @@ use serde::{Deserialize, Serialize};
 
 use super::super::super::error::JediError;
 use super::super::super::state::sidecar::ClickHouseConfig;
-use super::logs::{LogsResult, clamped_minutes};
+use super::logs::{LogsResult, clamped_minutes, run};
+use super::query::{
+    Alerts, AlertsColumn, Alias, Cond, Expr, Interval, Order, Query, Select, Table,
+};
 
 pub const MAX_ALERT_ROWS: u32 = 500;
 pub const DEFAULT_ALERT_ROWS: u32 = 100;
@@ -46,134 +49,142 @@ pub struct AlertsTopParams {
     pub limit: Option<u32>,
 }
 
-pub fn build_alerts_recent_sql(params: &AlertsRecentParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let limit = clamped_alert_rows(params.limit);
-    format!(
-        "SELECT \
-            timestamp, status, alertname, severity, namespace, pod, service, \
-            summary, description, fingerprint, starts_at, ends_at, source \
-         FROM alerts_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-         ORDER BY timestamp DESC \
-         LIMIT {limit}"
+fn recent(minutes: Option<u32>) -> Cond {
+    Cond::within(
+        AlertsColumn::Timestamp,
+        Interval::Minutes(clamped_minutes(minutes)),
     )
 }
 
-pub fn build_alerts_firing_sql(params: &AlertsFiringParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    format!(
-        "SELECT \
-            last_seen AS timestamp, \
-            last_status AS status, \
-            last_alertname AS alertname, \
-            last_severity AS severity, \
-            last_namespace AS namespace, \
-            last_pod AS pod, \
-            last_service AS service, \
-            last_summary AS summary, \
-            last_starts_at AS starts_at, \
-            fingerprint \
-         FROM ( \
-            SELECT \
-                fingerprint, \
-                argMax(timestamp, timestamp) AS last_seen, \
-                argMax(status, timestamp) AS last_status, \
-                argMax(alertname, timestamp) AS last_alertname, \
-                argMax(severity, timestamp) AS last_severity, \
-                argMax(namespace, timestamp) AS last_namespace, \
-                argMax(pod, timestamp) AS last_pod, \
-                argMax(service, timestamp) AS last_service, \
-                argMax(summary, timestamp) AS last_summary, \
-                argMax(starts_at, timestamp) AS last_starts_at \
-             FROM alerts_distributed \
-             WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-             GROUP BY fingerprint \
-         ) \
-         WHERE last_status = 'firing' \
-         ORDER BY last_starts_at DESC"
-    )
+fn firing_events() -> Expr {
+    Expr::raw("sumIf(1, status = 'firing')").alias("firing_events")
 }
 
-pub fn build_alerts_by_severity_sql(params: &AlertsBySeverityParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    format!(
-        "SELECT \
-            severity, \
-            uniqExact(fingerprint) AS distinct_alerts, \
-            count() AS total_events, \
-            sumIf(1, status = 'firing') AS firing_events, \
-            sumIf(1, status = 'resolved') AS resolved_events \
-         FROM alerts_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-         GROUP BY severity \
-         ORDER BY firing_events DESC, total_events DESC"
-    )
+pub fn build_alerts_recent_sql(params: &AlertsRecentParams) -> Query {
+    let limit = clamped_alert_rows(params.limit);
+    Alerts::select()
+        .columns([
+            AlertsColumn::Timestamp,
+            AlertsColumn::Status,
+            AlertsColumn::Alertname,
+            AlertsColumn::Severity,
+            AlertsColumn::Namespace,
+            AlertsColumn::Pod,
+            AlertsColumn::Service,
+            AlertsColumn::Summary,
+            AlertsColumn::Description,
+            AlertsColumn::Fingerprint,
+            AlertsColumn::StartsAt,
+            AlertsColumn::EndsAt,
+            AlertsColumn::Source,
+        ])
+        .filter(recent(params.minutes))
+        .order_by(AlertsColumn::Timestamp, Order::Desc)
+        .limit(limit)
+        .build()
 }
 
-pub fn build_alerts_top_sql(params: &AlertsTopParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
+/// Latest state per fingerprint (argMax over timestamp), keeping only the
+/// alerts whose most recent event is still `firing`.
+pub fn build_alerts_firing_sql(params: &AlertsFiringParams) -> Query {
+    let latest = |column: AlertsColumn, alias: &'static str| {
+        Expr::func(
+            "argMax",
+            [Expr::col(column), Expr::col(AlertsColumn::Timestamp)],
+        )
+        .alias(alias)
+    };
+    let inner = Alerts::select()
+        .column(AlertsColumn::Fingerprint)
+        .expr(latest(AlertsColumn::Timestamp, "last_seen"))
+        .expr(latest(AlertsColumn::Status, "last_status"))
+        .expr(latest(AlertsColumn::Alertname, "last_alertname"))
+        .expr(latest(AlertsColumn::Severity, "last_severity"))
+        .expr(latest(AlertsColumn::Namespace, "last_namespace"))
+        .expr(latest(AlertsColumn::Pod, "last_pod"))
+        .expr(latest(AlertsColumn::Service, "last_service"))
+        .expr(latest(AlertsColumn::Summary, "last_summary"))
+        .expr(latest(AlertsColumn::StartsAt, "last_starts_at"))
+        .filter(recent(params.minutes))
+        .group_by(AlertsColumn::Fingerprint);
+
+    let renamed = |from: &'static str, to: &'static str| Expr::col(Alias(from)).alias(to);
+    Select::over(inner)
+        .expr(renamed("last_seen", "timestamp"))
+        .expr(renamed("last_status", "status"))
+        .expr(renamed("last_alertname", "alertname"))
+        .expr(renamed("last_severity", "severity"))
+        .expr(renamed("last_namespace", "namespace"))
+        .expr(renamed("last_pod", "pod"))
+        .expr(renamed("last_service", "service"))
+        .expr(renamed("last_summary", "summary"))
+        .expr(renamed("last_starts_at", "starts_at"))
+        .column(Alias("fingerprint"))
+        .filter(Cond::is(Alias("last_status"), "firing"))
+        .order_by(Alias("last_starts_at"), Order::Desc)
+        .build()
+}
+
+pub fn build_alerts_by_severity_sql(params: &AlertsBySeverityParams) -> Query {
+    Alerts::select()
+        .column(AlertsColumn::Severity)
+        .expr(
+            Expr::func("uniqExact", [Expr::col(AlertsColumn::Fingerprint)])
+                .alias("distinct_alerts"),
+        )
+        .expr(Expr::count().alias("total_events"))
+        .expr(firing_events())
+        .expr(Expr::raw("sumIf(1, status = 'resolved')").alias("resolved_events"))
+        .filter(recent(params.minutes))
+        .group_by(AlertsColumn::Severity)
+        .order_by(Expr::ident("firing_events"), Order::Desc)
+        .order_by(Expr::ident("total_events"), Order::Desc)
+        .build()
+}
+
+pub fn build_alerts_top_sql(params: &AlertsTopParams) -> Query {
     let limit = clamped_top_alertnames(params.limit);
-    format!(
-        "SELECT \
-            alertname, \
-            uniqExact(fingerprint) AS distinct_instances, \
-            count() AS total_events, \
-            sumIf(1, status = 'firing') AS firing_events \
-         FROM alerts_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-         GROUP BY alertname \
-         ORDER BY total_events DESC \
-         LIMIT {limit}"
-    )
+    Alerts::select()
+        .column(AlertsColumn::Alertname)
+        .expr(
+            Expr::func("uniqExact", [Expr::col(AlertsColumn::Fingerprint)])
+                .alias("distinct_instances"),
+        )
+        .expr(Expr::count().alias("total_events"))
+        .expr(firing_events())
+        .filter(recent(params.minutes))
+        .group_by(AlertsColumn::Alertname)
+        .order_by(Expr::ident("total_events"), Order::Desc)
+        .limit(limit)
+        .build()
 }
 
 pub async fn run_alerts_recent(
     config: &ClickHouseConfig,
     params: &AlertsRecentParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_alerts_recent_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_alerts_recent_sql(params)).await
 }
 
 pub async fn run_alerts_firing(
     config: &ClickHouseConfig,
     params: &AlertsFiringParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_alerts_firing_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_alerts_firing_sql(params)).await
 }
 
 pub async fn run_alerts_by_severity(
     config: &ClickHouseConfig,
     params: &AlertsBySeverityParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_alerts_by_severity_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_alerts_by_severity_sql(params)).await
 }
 
 pub async fn run_alerts_top(
     config: &ClickHouseConfig,
     params: &AlertsTopParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_alerts_top_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_alerts_top_sql(params)).await
 }
 
 #[cfg(test)]
@@ -199,7 +210,8 @@ mod tests {
         let sql = build_alerts_recent_sql(&AlertsRecentParams {
             minutes: Some(30),
             limit: Some(50),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 30 MINUTE"));
         assert!(sql.contains("FROM alerts_distributed"));
         assert!(sql.contains("ORDER BY timestamp DESC"));
@@ -208,11 +220,12 @@ mod tests {
 
     #[test]
     fn firing_sql_uses_argmax_dedupe() {
-        let sql = build_alerts_firing_sql(&AlertsFiringParams { minutes: Some(60) });
+        let sql = build_alerts_firing_sql(&AlertsFiringParams { minutes: Some(60) }).sql;
         assert!(sql.contains("INTERVAL 60 MINUTE"));
         assert!(sql.contains("argMax(status, timestamp) AS last_status"));
         assert!(sql.contains("GROUP BY fingerprint"));
         assert!(sql.contains("WHERE last_status = 'firing'"));
+        assert!(sql.starts_with("SELECT last_seen AS timestamp"));
         assert!(sql.contains("ORDER BY last_starts_at DESC"));
         assert!(sql.contains("last_seen AS timestamp"));
     }
@@ -221,7 +234,8 @@ mod tests {
     fn by_severity_sql_shape() {
         let sql = build_alerts_by_severity_sql(&AlertsBySeverityParams {
             minutes: Some(1440),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 1440 MINUTE"));
         assert!(sql.contains("uniqExact(fingerprint)"));
         assert!(sql.contains("GROUP BY severity"));
@@ -232,7 +246,8 @@ mod tests {
         let sql = build_alerts_top_sql(&AlertsTopParams {
             minutes: Some(120),
             limit: Some(15),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 120 MINUTE"));
         assert!(sql.contains("GROUP BY alertname"));
         assert!(sql.contains("LIMIT 15"));
@@ -1,33 +1,79 @@
 // Typed query builders for the gameops.factorio_* Distributed tables.
 //
 // Mirrors logs.rs: build SQL inside Rust so axum-kbve can hit ClickHouse
-// directly via ClickHouseConfig::execute_select. Tables are the Distributed
+// directly via ClickHouseConfig::execute_select_with_params. Tables are the Distributed
 // twins defined in packages/data/ch/schemas/factorio.sql and fed by the relay
 // sidecar (apps/agones/factorio/relay). Fully-qualified `gameops.*` names so
 // the query is independent of the connection's default database.
 //
 // Bounds match logs.rs (minutes 1..=10080, limit 1..=500). The optional
-// server_id filter is escaped + inlined the same way.
+// server_id filter is bound as a query parameter (see query.rs).
 
 use serde::{Deserialize, Serialize};
 
 use super::super::super::error::JediError;
 use super::super::super::state::sidecar::ClickHouseConfig;
-use super::logs::{LogsResult, MAX_LIMIT, MAX_MINUTES};
+use super::logs::{LogsResult, MAX_LIMIT, MAX_MINUTES, run};
+use super::query::{Column, Cond, Expr, Interval, Order, Query, Table, clickhouse_table};
 
 const DEFAULT_MINUTES: u32 = 60;
 const DEFAULT_LIMIT: u32 = 200;
 
-fn escape(input: &str) -> String {
-    let mut out = String::with_capacity(input.len());
-    for ch in input.chars() {
-        match ch {
-            '\\' => out.push_str("\\\\"),
-            '\'' => out.push_str("\\'"),
-            c => out.push(c),
-        }
+clickhouse_table! {
+    pub FactorioSnapshots("gameops.factorio_snapshots") => SnapshotsColumn {
+        Ts => "ts",
+        ServerId => "server_id",
+        Scenario => "scenario",
+        RotationId => "rotation_id",
+        Seed => "seed",
+        Players => "players",
+        Ups => "ups",
+        MapAgeGameS => "map_age_game_s",
+        MapAgeWallS => "map_age_wall_s",
+        AutoPauseEnabled => "auto_pause_enabled",
+        GameTick => "game_tick",
+    }
+}
+
+clickhouse_table! {
+    pub FactorioPlayerEvents("gameops.factorio_player_events") => PlayerEventsColumn {
+        Ts => "ts",
+        ServerId => "server_id",
+        Player => "player",
+        Event => "event",
+        GameTick => "game_tick",
+    }
+}
+
+clickhouse_table! {
+    pub FactorioChatLog("gameops.factorio_chat_log") => ChatLogColumn {
+        Ts => "ts",
+        ServerId => "server_id",
+        Player => "player",
+        Mode => "mode",
+        Message => "message",
+        GameTick => "game_tick",
+    }
+}
+
+clickhouse_table! {
+    pub FactorioRotations("gameops.factorio_rotations") => RotationsColumn {
+        RotationId => "rotation_id",
+        ServerId => "server_id",
+        Scenario => "scenario",
+        Seed => "seed",
+        StartedAt => "started_at",
+        EndedAt => "ended_at",
+        EndReason => "end_reason",
+        AutoPauseEnabled => "auto_pause_enabled",
+        PeakPlayers => "peak_players",
+        TimeToPeakS => "time_to_peak_s",
+        JoinsFirst15m => "joins_first_15m",
+        JoinsFirst60m => "joins_first_60m",
+        TotalPlayerSeconds => "total_player_seconds",
+        WallAgeS => "wall_age_s",
+        GameAgeS => "game_age_s",
     }
-    out
 }
 
 fn clamped_minutes(raw: Option<u32>) -> u32 {
@@ -38,11 +84,15 @@ fn clamped_limit(raw: Option<u32>) -> u32 {
     raw.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
 }
 
-fn server_filter(server_id: &Option<String>) -> String {
-    match server_id.as_deref().filter(|s| !s.is_empty()) {
-        Some(id) => format!(" AND server_id = '{}'", escape(id)),
-        None => String::new(),
-    }
+fn server_filter<C: Column + Into<Expr>>(column: C, server_id: &Option<String>) -> Option<Cond> {
+    server_id
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .map(|id| Cond::eq(column, id))
+}
+
+fn window<C: Column + Into<Expr>>(column: C, minutes: Option<u32>) -> Cond {
+    Cond::within(column, Interval::Minutes(clamped_minutes(minutes)))
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -56,97 +106,105 @@ pub struct FactorioParams {
 }
 
 /// Latest snapshot per server — the "current status" view.
-pub fn build_current_sql(params: &FactorioParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    format!(
-        "SELECT server_id, scenario, toString(rotation_id) AS rotation_id, \
-		 seed, players, ups, map_age_game_s, map_age_wall_s, \
-		 auto_pause_enabled, game_tick, ts \
-		 FROM gameops.factorio_snapshots \
-		 WHERE ts > now() - INTERVAL {minutes} MINUTE{server} \
-		 ORDER BY ts DESC \
-		 LIMIT 1 BY server_id",
-        minutes = minutes,
-        server = server_filter(&params.server_id),
-    )
+pub fn build_current_sql(params: &FactorioParams) -> Query {
+    use SnapshotsColumn as C;
+    FactorioSnapshots::select()
+        .columns([C::ServerId, C::Scenario])
+        .expr(Expr::func("toString", [Expr::col(C::RotationId)]).alias("rotation_id"))
+        .columns([
+            C::Seed,
+            C::Players,
+            C::Ups,
+            C::MapAgeGameS,
+            C::MapAgeWallS,
+            C::AutoPauseEnabled,
+            C::GameTick,
+            C::Ts,
+        ])
+        .filter(window(C::Ts, params.minutes))
+        .filter_opt(server_filter(C::ServerId, &params.server_id))
+        .order_by(C::Ts, Order::Desc)
+        .limit_by(1, C::ServerId)
+        .build()
 }
 
 /// Raw snapshot time-series (player count / UPS / map age over time).
-pub fn build_snapshots_sql(params: &FactorioParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let limit = clamped_limit(params.limit);
-    format!(
-        "SELECT ts, server_id, players, ups, map_age_game_s, map_age_wall_s \
-		 FROM gameops.factorio_snapshots \
-		 WHERE ts > now() - INTERVAL {minutes} MINUTE{server} \
-		 ORDER BY ts DESC \
-		 LIMIT {limit}",
-        minutes = minutes,
-        server = server_filter(&params.server_id),
-        limit = limit,
-    )
+pub fn build_snapshots_sql(params: &FactorioParams) -> Query {
+    use SnapshotsColumn as C;
+    FactorioSnapshots::select()
+        .columns([
+            C::Ts,
+            C::ServerId,
+            C::Players,
+            C::Ups,
+            C::MapAgeGameS,
+            C::MapAgeWallS,
+        ])
+        .filter(window(C::Ts, params.minutes))
+        .filter_opt(server_filter(C::ServerId, &params.server_id))
+        .order_by(C::Ts, Order::Desc)
+        .limit(clamped_limit(params.limit))
+        .build()
 }
 
 /// Recent player join / leave / kick events.
-pub fn build_players_sql(params: &FactorioParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let limit = clamped_limit(params.limit);
-    format!(
-        "SELECT ts, server_id, player, event, game_tick \
-		 FROM gameops.factorio_player_events \
-		 WHERE ts > now() - INTERVAL {minutes} MINUTE{server} \
-		 ORDER BY ts DESC \
-		 LIMIT {limit}",
-        minutes = minutes,
-        server = server_filter(&params.server_id),
-        limit = limit,
-    )
+pub fn build_players_sql(params: &FactorioParams) -> Query {
+    use PlayerEventsColumn as C;
+    FactorioPlayerEvents::select()
+        .columns([C::Ts, C::ServerId, C::Player, C::Event, C::GameTick])
+        .filter(window(C::Ts, params.minutes))
+        .filter_opt(server_filter(C::ServerId, &params.server_id))
+        .order_by(C::Ts, Order::Desc)
+        .limit(clamped_limit(params.limit))
+        .build()
 }
 
 /// Recent chat / command / system lines (moderation buffer).
-pub fn build_chat_sql(params: &FactorioParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let limit = clamped_limit(params.limit);
-    format!(
-        "SELECT ts, server_id, player, mode, message, game_tick \
-		 FROM gameops.factorio_chat_log \
-		 WHERE ts > now() - INTERVAL {minutes} MINUTE{server} \
-		 ORDER BY ts DESC \
-		 LIMIT {limit}",
-        minutes = minutes,
-        server = server_filter(&params.server_id),
-        limit = limit,
-    )
+pub fn build_chat_sql(params: &FactorioParams) -> Query {
+    use ChatLogColumn as C;
+    FactorioChatLog::select()
+        .columns([
+            C::Ts,
+            C::ServerId,
+            C::Player,
+            C::Mode,
+            C::Message,
+            C::GameTick,
+        ])
+        .filter(window(C::Ts, params.minutes))
+        .filter_opt(server_filter(C::ServerId, &params.server_id))
+        .order_by(C::Ts, Order::Desc)
+        .limit(clamped_limit(params.limit))
+        .build()
 }
 
 /// Map rotation history. `FINAL` collapses the ReplacingMergeTree so the
 /// latest upsert of each rotation_id wins. No time window — bounded by limit.
-pub fn build_rotations_sql(params: &FactorioParams) -> String {
-    let limit = clamped_limit(params.limit);
-    let server = match params.server_id.as_deref().filter(|s| !s.is_empty()) {
-        Some(id) => format!("WHERE server_id = '{}' ", escape(id)),
-        None => String::new(),
-    };
-    format!(
-        "SELECT toString(rotation_id) AS rotation_id, server_id, scenario, seed, \
-		 started_at, ended_at, end_reason, auto_pause_enabled, peak_players, \
-		 time_to_peak_s, joins_first_15m, joins_first_60m, total_player_seconds, \
-		 wall_age_s, game_age_s \
-		 FROM gameops.factorio_rotations FINAL \
-		 {server}\
-		 ORDER BY started_at DESC \
-		 LIMIT {limit}",
-        server = server,
-        limit = limit,
-    )
-}
-
-async fn run(config: &ClickHouseConfig, sql: String) -> Result<LogsResult, JediError> {
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+pub fn build_rotations_sql(params: &FactorioParams) -> Query {
+    use RotationsColumn as C;
+    FactorioRotations::select()
+        .expr(Expr::func("toString", [Expr::col(C::RotationId)]).alias("rotation_id"))
+        .columns([
+            C::ServerId,
+            C::Scenario,
+            C::Seed,
+            C::StartedAt,
+            C::EndedAt,
+            C::EndReason,
+            C::AutoPauseEnabled,
+            C::PeakPlayers,
+            C::TimeToPeakS,
+            C::JoinsFirst15m,
+            C::JoinsFirst60m,
+            C::TotalPlayerSeconds,
+            C::WallAgeS,
+            C::GameAgeS,
+        ])
+        .final_rows()
+        .filter_opt(server_filter(C::ServerId, &params.server_id))
+        .order_by(C::StartedAt, Order::Desc)
+        .limit(clamped_limit(params.limit))
+        .build()
 }
 
 pub async fn run_current(
@@ -189,19 +247,40 @@ mod tests {
     use super::*;
 
     #[test]
-    fn server_filter_escapes_and_omits() {
+    fn server_filter_binds_and_omits() {
         let none = FactorioParams::default();
-        assert!(!build_snapshots_sql(&none).contains("server_id ="));
+        assert!(!build_snapshots_sql(&none).sql.contains("server_id ="));
         let some = FactorioParams {
             server_id: Some("factorio-1".into()),
             ..Default::default()
         };
-        assert!(build_snapshots_sql(&some).contains("server_id = 'factorio-1'"));
+        let q = build_snapshots_sql(&some);
+        assert!(q.sql.contains("server_id = {p0:String}"));
+        assert_eq!(q.param("p0"), Some("factorio-1"));
         let inject = FactorioParams {
             server_id: Some("x' OR '1'='1".into()),
             ..Default::default()
         };
-        assert!(build_snapshots_sql(&inject).contains("\\' OR"));
+        let q = build_snapshots_sql(&inject);
+        assert!(!q.sql.contains("'1'='1"));
+        assert_eq!(q.param("p0"), Some("x' OR '1'='1"));
+    }
+
+    #[test]
+    fn current_and_rotations_shape() {
+        let current = build_current_sql(&FactorioParams::default()).sql;
+        assert!(current.contains("toString(rotation_id) AS rotation_id"));
+        assert!(current.ends_with("ORDER BY ts DESC LIMIT 1 BY server_id"));
+        let rotations = build_rotations_sql(&FactorioParams {
+            server_id: Some("factorio-1".into()),
+            ..Default::default()
+        })
+        .sql;
+        assert!(
+            rotations
+                .contains("FROM gameops.factorio_rotations FINAL WHERE server_id = {p0:String}")
+        );
+        assert!(!rotations.contains("INTERVAL"));
     }
 
     #[test]
@@ -211,7 +290,7 @@ mod tests {
             limit: Some(999_999),
             ..Default::default()
         };
-        let sql = build_snapshots_sql(&p);
+        let sql = build_snapshots_sql(&p).sql;
         assert!(sql.contains(&format!("INTERVAL {MAX_MINUTES} MINUTE")));
         assert!(sql.contains(&format!("LIMIT {MAX_LIMIT}")));
     }
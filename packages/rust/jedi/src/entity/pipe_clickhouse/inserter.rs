@@ -0,0 +1,957 @@
+// Batched JSONEachRow inserter shared by relays and services.
+//
+// Rows are serialized on the caller's task and queued on a bounded channel;
+// a full channel is the backpressure (`write` waits, `try_write` drops).
+// One background task keeps a buffer per table and flushes it once it
+// holds `max_rows` / `max_bytes` or its oldest row is `flush_interval` old.
+//
+// A failed flush is retried with exponential backoff. When retries run out
+// the batch is appended to `<spill_dir>/spill.jsonl` (`table\trow` lines)
+// and the inserter turns degraded: later batches go straight to disk
+// instead of stalling writers on retries, until a replay of the spill file
+// gets everything into ClickHouse. Replays stream the file from a separate
+// task, so a large spill doesn't stall the flusher. Spill files left by a
+// previous process are replayed on start. Without a spill dir an
+// undeliverable batch is dropped and counted.
+//
+// A batch ClickHouse rejects outright (a 4xx: bad rows, unknown table) is
+// never retried and never degrades the inserter; it is set aside in
+// `<spill_dir>/rejected.jsonl` for inspection, on flush and on replay alike,
+// so one poison batch can't hold up the rest. Auth failures (401/403) are
+// an outage, not a rejection: they spill and replay like a 5xx.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter, Lines};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::super::super::error::JediError;
+use super::super::super::state::sidecar::ClickHouseConfig;
+
+const SPILL_FILE: &str = "spill.jsonl";
+const REPLAY_FILE: &str = "spill.jsonl.replay";
+const REPLAY_REST_FILE: &str = "spill.jsonl.rest";
+const REJECTED_FILE: &str = "rejected.jsonl";
+
+#[derive(Clone, Debug)]
+pub struct InserterConfig {
+    pub max_rows: usize,
+    pub max_bytes: usize,
+    pub flush_interval: Duration,
+    /// Rows in flight between writers and the flush task.
+    pub queue_capacity: usize,
+    pub max_retries: u32,
+    pub backoff_initial: Duration,
+    pub backoff_max: Duration,
+    /// Where undeliverable and rejected batches go; `None` drops them.
+    pub spill_dir: Option<PathBuf>,
+    /// Cap on the spill file (and, separately, the rejected file); batches
+    /// past it are dropped.
+    pub spill_max_bytes: u64,
+    pub replay_interval: Duration,
+}
+
+impl Default for InserterConfig {
+    fn default() -> Self {
+        Self {
+            max_rows: 1000,
+            max_bytes: 1 << 20,
+            flush_interval: Duration::from_secs(1),
+            queue_capacity: 10_000,
+            max_retries: 3,
+            backoff_initial: Duration::from_millis(200),
+            backoff_max: Duration::from_secs(5),
+            spill_dir: None,
+            spill_max_bytes: 256 << 20,
+            replay_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Row counters since start.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InserterStats {
+    pub inserted: u64,
+    pub spilled: u64,
+    pub replayed: u64,
+    pub dropped: u64,
+    pub retries: u64,
+    /// Rows in batches ClickHouse refused with a 4xx.
+    pub rejected: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    inserted: AtomicU64,
+    spilled: AtomicU64,
+    replayed: AtomicU64,
+    dropped: AtomicU64,
+    retries: AtomicU64,
+    rejected: AtomicU64,
+}
+
+enum Command {
+    Row { table: Arc<str>, line: String },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Cheap to clone. The flush task drains and exits once every clone (and
+/// every [`TableWriter`]) is dropped; await its `JoinHandle` to be sure
+/// the tail was delivered or spilled.
+#[derive(Clone)]
+pub struct ClickHouseInserter {
+    tx: mpsc::Sender<Command>,
+    counters: Arc<Counters>,
+}
+
+impl ClickHouseInserter {
+    pub fn spawn(ch: ClickHouseConfig, cfg: InserterConfig) -> (Self, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(cfg.queue_capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        let flusher = Flusher {
+            ch: Arc::new(ch),
+            cfg,
+            counters: counters.clone(),
+            buffers: HashMap::new(),
+            degraded: false,
+        };
+        let task = tokio::spawn(flusher.run(rx));
+        (Self { tx, counters }, task)
+    }
+
+    /// Typed handle for one table.
+    pub fn table<T: Serialize>(&self, table: impl Into<Arc<str>>) -> TableWriter<T> {
+        TableWriter {
+            inserter: self.clone(),
+            table: table.into(),
+            _row: std::marker::PhantomData,
+        }
+    }
+
+    /// Queue a row, waiting while the queue is full.
+    pub async fn write<T: Serialize>(&self, table: &str, row: &T) -> Result<(), JediError> {
+        self.send(Arc::from(table), row).await
+    }
+
+    /// Queue a row without waiting. Returns `Ok(false)` and counts a drop
+    /// when the queue is full.
+    pub fn try_write<T: Serialize>(&self, table: &str, row: &T) -> Result<bool, JediError> {
+        self.try_send(Arc::from(table), row)
+    }
+
+    /// Flush every buffered row (delivered, spilled or dropped) and wait
+    /// for it.
+    pub async fn flush(&self) -> Result<(), JediError> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.tx
+            .send(Command::Flush(done_tx))
+            .await
+            .map_err(|_| JediError::Internal("ClickHouse inserter stopped".into()))?;
+        done_rx
+            .await
+            .map_err(|_| JediError::Internal("ClickHouse inserter stopped".into()))
+    }
+
+    pub fn stats(&self) -> InserterStats {
+        let c = &self.counters;
+        InserterStats {
+            inserted: c.inserted.load(Ordering::Relaxed),
+            spilled: c.spilled.load(Ordering::Relaxed),
+            replayed: c.replayed.load(Ordering::Relaxed),
+            dropped: c.dropped.load(Ordering::Relaxed),
+            retries: c.retries.load(Ordering::Relaxed),
+            rejected: c.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Rows waiting in the queue, not yet in a table buffer.
+    pub fn queued(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    async fn send<T: Serialize>(&self, table: Arc<str>, row: &T) -> Result<(), JediError> {
+        let line = encode_row(row)?;
+        self.tx
+            .send(Command::Row { table, line })
+            .await
+            .map_err(|_| JediError::Internal("ClickHouse inserter stopped".into()))
+    }
+
+    fn try_send<T: Serialize>(&self, table: Arc<str>, row: &T) -> Result<bool, JediError> {
+        let line = encode_row(row)?;
+        match self.tx.try_send(Command::Row { table, line }) {
+            Ok(()) => Ok(true),
+            Err(mpsc::error::TrySendError::Full(Command::Row { table, .. })) => {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                metric_rows("kbve_ch_inserter_dropped_rows", &table, 1);
+                Ok(false)
+            }
+            Err(_) => Err(JediError::Internal("ClickHouse inserter stopped".into())),
+        }
+    }
+}
+
+pub struct TableWriter<T> {
+    inserter: ClickHouseInserter,
+    table: Arc<str>,
+    _row: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<T: Serialize> TableWriter<T> {
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    pub async fn write(&self, row: &T) -> Result<(), JediError> {
+        self.inserter.send(self.table.clone(), row).await
+    }
+
+    pub fn try_write(&self, row: &T) -> Result<bool, JediError> {
+        self.inserter.try_send(self.table.clone(), row)
+    }
+}
+
+impl<T> Clone for TableWriter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inserter: self.inserter.clone(),
+            table: self.table.clone(),
+            _row: std::marker::PhantomData,
+        }
+    }
+}
+
+struct Buffer {
+    body: String,
+    rows: usize,
+    oldest: Instant,
+}
+
+/// Outcome of pushing one batch.
+enum Delivery {
+    Inserted,
+    /// Refused with a 4xx; resending can't help.
+    Rejected(String),
+    /// Still failing after the retries.
+    Failed,
+}
+
+struct Flusher {
+    ch: Arc<ClickHouseConfig>,
+    cfg: InserterConfig,
+    counters: Arc<Counters>,
+    buffers: HashMap<Arc<str>, Buffer>,
+    /// Set after a batch exhausted its retries; cleared by a full replay.
+    degraded: bool,
+}
+
+impl Flusher {
+    async fn run(mut self, mut rx: mpsc::Receiver<Command>) {
+        let tick_every = (self.cfg.flush_interval / 4).max(Duration::from_millis(10));
+        let mut tick = tokio::time::interval(tick_every);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut replay_tick = tokio::time::interval(self.cfg.replay_interval);
+        replay_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut replaying: Option<JoinHandle<bool>> = None;
+
+        loop {
+            tokio::select! {
+                cmd = rx.recv() => match cmd {
+                    Some(Command::Row { table, line }) => self.push(table, line).await,
+                    Some(Command::Flush(done)) => {
+                        self.flush_all().await;
+                        let _ = done.send(());
+                    }
+                    None => break,
+                },
+                _ = tick.tick() => self.flush_due().await,
+                _ = replay_tick.tick(), if replaying.is_none() => replaying = self.start_replay(),
+                done = async { replaying.as_mut().expect("guarded by is_some").await },
+                    if replaying.is_some() =>
+                {
+                    replaying = None;
+                    if matches!(done, Ok(true)) {
+                        self.set_healthy();
+                    }
+                }
+            }
+        }
+
+        if let Some(task) = replaying {
+            let _ = task.await;
+        }
+        self.flush_all().await;
+        tracing::info!("[ChInserter] stopped");
+    }
+
+    async fn push(&mut self, table: Arc<str>, line: String) {
+        let buffer = self.buffers.entry(table.clone()).or_insert_with(|| Buffer {
+            body: String::new(),
+            rows: 0,
+            oldest: Instant::now(),
+        });
+        if buffer.rows > 0 {
+            buffer.body.push('\n');
+        }
+        buffer.body.push_str(&line);
+        buffer.rows += 1;
+
+        if buffer.rows >= self.cfg.max_rows || buffer.body.len() >= self.cfg.max_bytes {
+            self.flush_table(&table).await;
+        }
+    }
+
+    async fn flush_due(&mut self) {
+        let due: Vec<Arc<str>> = self
+            .buffers
+            .iter()
+            .filter(|(_, b)| b.oldest.elapsed() >= self.cfg.flush_interval)
+            .map(|(t, _)| t.clone())
+            .collect();
+        for table in due {
+            self.flush_table(&table).await;
+        }
+    }
+
+    async fn flush_all(&mut self) {
+        let tables: Vec<Arc<str>> = self.buffers.keys().cloned().collect();
+        for table in tables {
+            self.flush_table(&table).await;
+        }
+    }
+
+    async fn flush_table(&mut self, table: &Arc<str>) {
+        let Some(buffer) = self.buffers.remove(table) else {
+            return;
+        };
+        metric_lag(table, buffer.oldest.elapsed());
+
+        if !self.degraded {
+            match insert_with_retry(&self.ch, &self.cfg, &self.counters, table, &buffer.body).await
+            {
+                Delivery::Inserted => {
+                    self.counters
+                        .inserted
+                        .fetch_add(buffer.rows as u64, Ordering::Relaxed);
+                    metric_rows("kbve_ch_inserter_inserted_rows", table, buffer.rows as u64);
+                    return;
+                }
+                Delivery::Rejected(reason) => {
+                    reject(&self.cfg, &self.counters, table, &buffer.body, buffer.rows, &reason)
+                        .await;
+                    return;
+                }
+                Delivery::Failed => {}
+            }
+            self.degraded = true;
+            metric_degraded(true);
+            tracing::warn!(table = %table, "[ChInserter] ClickHouse unreachable, spilling");
+        }
+        self.spill(table, &buffer.body, buffer.rows).await;
+    }
+
+    async fn spill(&self, table: &str, body: &str, rows: usize) {
+        let spilled = match &self.cfg.spill_dir {
+            Some(dir) => append_lines(dir, SPILL_FILE, table, body, self.cfg.spill_max_bytes).await,
+            None => Err("no spill dir".to_string()),
+        };
+        match spilled {
+            Ok(()) => {
+                self.counters
+                    .spilled
+                    .fetch_add(rows as u64, Ordering::Relaxed);
+                metric_rows("kbve_ch_inserter_spilled_rows", table, rows as u64);
+            }
+            Err(reason) => {
+                self.counters
+                    .dropped
+                    .fetch_add(rows as u64, Ordering::Relaxed);
+                metric_rows("kbve_ch_inserter_dropped_rows", table, rows as u64);
+                tracing::warn!(table, rows, reason, "[ChInserter] batch dropped");
+            }
+        }
+    }
+
+    /// Hand the spill file to a background replay. `None` when there is
+    /// nowhere to replay from.
+    fn start_replay(&mut self) -> Option<JoinHandle<bool>> {
+        if self.cfg.spill_dir.is_none() {
+            // Nothing to replay; just let the next flush probe again.
+            self.set_healthy();
+            return None;
+        }
+        Some(tokio::spawn(replay_spill(
+            self.ch.clone(),
+            self.cfg.clone(),
+            self.counters.clone(),
+        )))
+    }
+
+    fn set_healthy(&mut self) {
+        if self.degraded {
+            self.degraded = false;
+            metric_degraded(false);
+        }
+    }
+}
+
+/// Push one batch, retrying with exponential backoff. Used by flushes and
+/// replays alike.
+async fn insert_with_retry(
+    ch: &ClickHouseConfig,
+    cfg: &InserterConfig,
+    counters: &Counters,
+    table: &str,
+    body: &str,
+) -> Delivery {
+    let mut backoff = cfg.backoff_initial;
+    for attempt in 0..=cfg.max_retries {
+        match ch.execute_insert_raw(table, body).await {
+            Ok(()) => return Delivery::Inserted,
+            Err(JediError::BadRequest(reason)) => return Delivery::Rejected(reason),
+            Err(e) if attempt == cfg.max_retries => {
+                tracing::warn!(table, error = %e, "[ChInserter] insert failed, retries exhausted");
+            }
+            Err(e) => {
+                counters.retries.fetch_add(1, Ordering::Relaxed);
+                metric_rows("kbve_ch_inserter_retries", table, 1);
+                tracing::debug!(table, error = %e, attempt, "[ChInserter] insert failed, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(cfg.backoff_max);
+            }
+        }
+    }
+    Delivery::Failed
+}
+
+/// Set a rejected batch aside in the rejected file (or drop it without a
+/// spill dir). Either way it is counted as rejected, not retried.
+async fn reject(
+    cfg: &InserterConfig,
+    counters: &Counters,
+    table: &str,
+    body: &str,
+    rows: usize,
+    reason: &str,
+) {
+    counters.rejected.fetch_add(rows as u64, Ordering::Relaxed);
+    metric_rows("kbve_ch_inserter_rejected_rows", table, rows as u64);
+    let kept = match &cfg.spill_dir {
+        Some(dir) => append_lines(dir, REJECTED_FILE, table, body, cfg.spill_max_bytes).await,
+        None => Err("no spill dir".to_string()),
+    };
+    match kept {
+        Ok(()) => tracing::warn!(table, rows, reason, "[ChInserter] batch rejected, set aside"),
+        Err(e) => tracing::warn!(table, rows, reason, kept = %e, "[ChInserter] batch rejected, dropped"),
+    }
+}
+
+/// Append `body` to `<dir>/<file>` as `table\trow` lines, refusing to grow
+/// the file past `max_bytes`.
+async fn append_lines(
+    dir: &Path,
+    file: &str,
+    table: &str,
+    body: &str,
+    max_bytes: u64,
+) -> Result<(), String> {
+    let path = dir.join(file);
+    let current = tokio::fs::metadata(&path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let mut chunk = String::with_capacity(body.len() + table.len() * 8);
+    for line in body.lines() {
+        chunk.push_str(table);
+        chunk.push('\t');
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
+    if current + chunk.len() as u64 > max_bytes {
+        return Err(format!("{file} full"));
+    }
+
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut out = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    out.write_all(chunk.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    out.flush().await.map_err(|e| e.to_string())
+}
+
+/// Move the spill file aside and push it to ClickHouse table by table,
+/// streaming it line by line. Each batch gets the same retries and backoff
+/// as a flush. Rejected batches are set aside and skipped; at the first
+/// batch that still fails once its retries run out, it and everything
+/// after it are written back for the next round. Returns whether nothing
+/// is left.
+async fn replay_spill(
+    ch: Arc<ClickHouseConfig>,
+    cfg: InserterConfig,
+    counters: Arc<Counters>,
+) -> bool {
+    let Some(dir) = cfg.spill_dir.as_deref() else {
+        return true;
+    };
+    let replay_path = dir.join(REPLAY_FILE);
+    let spill_path = dir.join(SPILL_FILE);
+    if tokio::fs::metadata(&replay_path).await.is_err()
+        && tokio::fs::rename(&spill_path, &replay_path).await.is_err()
+    {
+        // No spill file: nothing pending.
+        return true;
+    }
+    let mut lines = match tokio::fs::File::open(&replay_path).await {
+        Ok(file) => BufReader::new(file).lines(),
+        Err(e) => {
+            tracing::warn!(error = %e, "[ChInserter] cannot read spill file");
+            return false;
+        }
+    };
+
+    let mut table = String::new();
+    let mut rows: Vec<String> = Vec::new();
+    loop {
+        let next = match lines.next_line().await {
+            Ok(Some(line)) => match line.split_once('\t') {
+                Some((t, row)) => Some((t.to_string(), row.to_string())),
+                None => continue,
+            },
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "[ChInserter] cannot read spill file");
+                return false;
+            }
+        };
+        let batch_done = match &next {
+            Some((t, _)) => *t != table || rows.len() >= cfg.max_rows,
+            None => true,
+        };
+        if batch_done && !rows.is_empty() {
+            let body = rows.join("\n");
+            match insert_with_retry(&ch, &cfg, &counters, &table, &body).await {
+                Delivery::Inserted => {
+                    counters
+                        .replayed
+                        .fetch_add(rows.len() as u64, Ordering::Relaxed);
+                    metric_rows("kbve_ch_inserter_replayed_rows", &table, rows.len() as u64);
+                }
+                Delivery::Rejected(reason) => {
+                    reject(&cfg, &counters, &table, &body, rows.len(), &reason).await;
+                }
+                Delivery::Failed => {
+                    tracing::warn!(table, "[ChInserter] replay failed");
+                    if let Err(e) = write_back(dir, &table, &rows, next, lines).await {
+                        tracing::warn!(error = %e, "[ChInserter] cannot rewrite spill file");
+                    }
+                    return false;
+                }
+            }
+            rows.clear();
+        }
+        let Some((t, row)) = next else { break };
+        if rows.is_empty() {
+            table = t;
+        }
+        rows.push(row);
+    }
+
+    let _ = tokio::fs::remove_file(&replay_path).await;
+    tracing::info!("[ChInserter] spill file replayed");
+    true
+}
+
+/// Replace the replay file with the unsent batch, the line read after it
+/// and whatever `lines` has left, without loading the rest into memory.
+async fn write_back(
+    dir: &Path,
+    table: &str,
+    rows: &[String],
+    next: Option<(String, String)>,
+    mut lines: Lines<BufReader<tokio::fs::File>>,
+) -> std::io::Result<()> {
+    let rest_path = dir.join(REPLAY_REST_FILE);
+    let mut out = BufWriter::new(tokio::fs::File::create(&rest_path).await?);
+    for row in rows {
+        out.write_all(format!("{table}\t{row}\n").as_bytes())
+            .await?;
+    }
+    if let Some((t, row)) = next {
+        out.write_all(format!("{t}\t{row}\n").as_bytes()).await?;
+    }
+    while let Some(line) = lines.next_line().await? {
+        out.write_all(line.as_bytes()).await?;
+        out.write_all(b"\n").await?;
+    }
+    out.flush().await?;
+    tokio::fs::rename(&rest_path, dir.join(REPLAY_FILE)).await
+}
+
+fn encode_row<T: Serialize>(row: &T) -> Result<String, JediError> {
+    serde_json::to_string(row)
+        .map_err(|e| JediError::Parse(format!("ClickHouse JSON serialize error: {}", e)))
+}
+
+#[cfg(feature = "prometheus")]
+fn metric_rows(name: &'static str, table: &str, n: u64) {
+    metrics::counter!(name, "table" => table.to_string()).increment(n);
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn metric_rows(_: &'static str, _: &str, _: u64) {}
+
+#[cfg(feature = "prometheus")]
+fn metric_lag(table: &str, lag: Duration) {
+    metrics::histogram!("kbve_ch_inserter_flush_lag_seconds", "table" => table.to_string())
+        .record(lag.as_secs_f64());
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn metric_lag(_: &str, _: Duration) {}
+
+#[cfg(feature = "prometheus")]
+fn metric_degraded(on: bool) {
+    metrics::gauge!("kbve_ch_inserter_degraded").set(if on { 1.0 } else { 0.0 });
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn metric_degraded(_: bool) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[derive(Serialize)]
+    struct Snapshot {
+        server_id: &'static str,
+        players: u32,
+    }
+
+    /// Minimal ClickHouse HTTP stand-in: counts JSONEachRow lines, or
+    /// answers 500 while `failing` is set and 403 (ACCESS_DENIED) while
+    /// `denied` is. Inserts into a table named `bad_*` always get a 400.
+    struct FakeClickHouse {
+        url: String,
+        rows: Arc<AtomicU64>,
+        failing: Arc<AtomicBool>,
+        denied: Arc<AtomicBool>,
+    }
+
+    async fn fake_clickhouse() -> FakeClickHouse {
+        use axum::{
+            Router,
+            http::{StatusCode, Uri},
+            response::IntoResponse,
+            routing::post,
+        };
+
+        let rows = Arc::new(AtomicU64::new(0));
+        let failing = Arc::new(AtomicBool::new(false));
+        let denied = Arc::new(AtomicBool::new(false));
+        let (r, f, d) = (rows.clone(), failing.clone(), denied.clone());
+        let app = Router::new().route(
+            "/",
+            post(move |uri: Uri, body: String| {
+                let (r, f, d) = (r.clone(), f.clone(), d.clone());
+                async move {
+                    if uri.query().is_some_and(|q| q.contains("bad_")) {
+                        return StatusCode::BAD_REQUEST.into_response();
+                    }
+                    if d.load(Ordering::SeqCst) {
+                        let code = [("X-ClickHouse-Exception-Code", "497")];
+                        return (StatusCode::FORBIDDEN, code).into_response();
+                    }
+                    if f.load(Ordering::SeqCst) {
+                        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                    }
+                    r.fetch_add(body.lines().count() as u64, Ordering::SeqCst);
+                    StatusCode::OK.into_response()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        FakeClickHouse {
+            url,
+            rows,
+            failing,
+            denied,
+        }
+    }
+
+    fn ch(url: &str) -> ClickHouseConfig {
+        ClickHouseConfig {
+            url: url.to_string(),
+            user: String::new(),
+            password: String::new(),
+            database: "default".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn flushes_on_size_and_on_demand() {
+        let server = fake_clickhouse().await;
+        let cfg = InserterConfig {
+            max_rows: 2,
+            flush_interval: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let (inserter, _task) = ClickHouseInserter::spawn(ch(&server.url), cfg);
+        let writer = inserter.table::<Snapshot>("gameops.snapshots");
+
+        for players in 0..3 {
+            writer
+                .write(&Snapshot {
+                    server_id: "pw-1",
+                    players,
+                })
+                .await
+                .unwrap();
+        }
+        inserter.flush().await.unwrap();
+
+        assert_eq!(server.rows.load(Ordering::SeqCst), 3);
+        assert_eq!(inserter.stats().inserted, 3);
+    }
+
+    #[tokio::test]
+    async fn spills_while_down_and_replays_after() {
+        let server = fake_clickhouse().await;
+        server.failing.store(true, Ordering::SeqCst);
+        let dir = spill_dir();
+        let cfg = InserterConfig {
+            max_retries: 1,
+            backoff_initial: Duration::from_millis(1),
+            spill_dir: Some(dir.clone()),
+            replay_interval: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let (inserter, _task) = ClickHouseInserter::spawn(ch(&server.url), cfg);
+
+        for players in 0..3 {
+            inserter
+                .write(
+                    "gameops.snapshots",
+                    &Snapshot {
+                        server_id: "pw-1",
+                        players,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        inserter.flush().await.unwrap();
+        let stats = inserter.stats();
+        assert_eq!((stats.spilled, stats.retries), (3, 1));
+        assert_eq!(server.rows.load(Ordering::SeqCst), 0);
+
+        server.failing.store(false, Ordering::SeqCst);
+        for _ in 0..100 {
+            if inserter.stats().replayed == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(inserter.stats().replayed, 3);
+        assert_eq!(server.rows.load(Ordering::SeqCst), 3);
+        assert!(!dir.join(REPLAY_FILE).exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn auth_failure_spills_instead_of_rejecting() {
+        let server = fake_clickhouse().await;
+        server.denied.store(true, Ordering::SeqCst);
+        let dir = spill_dir();
+        let cfg = InserterConfig {
+            max_retries: 0,
+            spill_dir: Some(dir.clone()),
+            replay_interval: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let (inserter, _task) = ClickHouseInserter::spawn(ch(&server.url), cfg);
+        let row = Snapshot {
+            server_id: "pw-1",
+            players: 1,
+        };
+
+        inserter.write("gameops.snapshots", &row).await.unwrap();
+        inserter.write("gameops.snapshots", &row).await.unwrap();
+        inserter.flush().await.unwrap();
+        let stats = inserter.stats();
+        assert_eq!((stats.spilled, stats.rejected), (2, 0));
+
+        server.denied.store(false, Ordering::SeqCst);
+        for _ in 0..100 {
+            if inserter.stats().replayed == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(inserter.stats().replayed, 2);
+        assert_eq!(server.rows.load(Ordering::SeqCst), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn spill_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("jedi-ch-spill-{}", ulid::Ulid::new()))
+    }
+
+    #[tokio::test]
+    async fn rejected_batch_is_set_aside_without_degrading() {
+        let server = fake_clickhouse().await;
+        let dir = spill_dir();
+        let cfg = InserterConfig {
+            max_retries: 3,
+            backoff_initial: Duration::from_millis(1),
+            spill_dir: Some(dir.clone()),
+            replay_interval: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let (inserter, _task) = ClickHouseInserter::spawn(ch(&server.url), cfg);
+        let row = Snapshot {
+            server_id: "pw-1",
+            players: 1,
+        };
+
+        inserter.write("bad_table", &row).await.unwrap();
+        inserter.write("bad_table", &row).await.unwrap();
+        inserter.flush().await.unwrap();
+        let stats = inserter.stats();
+        assert_eq!((stats.rejected, stats.retries, stats.spilled), (2, 0, 0));
+        let rejected = std::fs::read_to_string(dir.join(REJECTED_FILE)).unwrap();
+        assert_eq!(rejected.lines().count(), 2);
+        assert!(rejected.starts_with("bad_table\t"));
+
+        inserter.write("gameops.snapshots", &row).await.unwrap();
+        inserter.flush().await.unwrap();
+        assert_eq!(inserter.stats().inserted, 1, "a rejection doesn't degrade");
+        assert_eq!(server.rows.load(Ordering::SeqCst), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn replay_skips_past_a_rejected_batch() {
+        let server = fake_clickhouse().await;
+        let dir = spill_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let row = r#"{"server_id":"pw-1","players":1}"#;
+        std::fs::write(
+            dir.join(SPILL_FILE),
+            format!("good\t{row}\nbad_table\t{row}\ngood\t{row}\ngood\t{row}\n"),
+        )
+        .unwrap();
+        let cfg = InserterConfig {
+            spill_dir: Some(dir.clone()),
+            replay_interval: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let (inserter, _task) = ClickHouseInserter::spawn(ch(&server.url), cfg);
+
+        for _ in 0..100 {
+            if inserter.stats().replayed == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let stats = inserter.stats();
+        assert_eq!((stats.replayed, stats.rejected), (3, 1));
+        assert_eq!(server.rows.load(Ordering::SeqCst), 3);
+        assert!(!dir.join(REPLAY_FILE).exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn failed_replay_writes_back_the_unsent_tail() {
+        let server = fake_clickhouse().await;
+        server.failing.store(true, Ordering::SeqCst);
+        let dir = spill_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let body: String = (0..5).map(|i| format!("t{}\t{{\"n\":{i}}}\n", i % 2)).collect();
+        std::fs::write(dir.join(REPLAY_FILE), &body).unwrap();
+
+        let counters = Arc::new(Counters::default());
+        let replayed = replay_spill(
+            Arc::new(ch(&server.url)),
+            InserterConfig {
+                spill_dir: Some(dir.clone()),
+                max_retries: 1,
+                backoff_initial: Duration::from_millis(1),
+                ..Default::default()
+            },
+            counters.clone(),
+        )
+        .await;
+        assert!(!replayed);
+        assert_eq!(counters.retries.load(Ordering::SeqCst), 1);
+        assert_eq!(std::fs::read_to_string(dir.join(REPLAY_FILE)).unwrap(), body);
+        assert!(!dir.join(REPLAY_REST_FILE).exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn replay_rides_out_a_transient_failure() {
+        let server = fake_clickhouse().await;
+        server.failing.store(true, Ordering::SeqCst);
+        let dir = spill_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let body: String = (0..4).map(|i| format!("t\t{{\"n\":{i}}}\n")).collect();
+        std::fs::write(dir.join(REPLAY_FILE), &body).unwrap();
+
+        let failing = server.failing.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            failing.store(false, Ordering::SeqCst);
+        });
+        let counters = Arc::new(Counters::default());
+        let replayed = replay_spill(
+            Arc::new(ch(&server.url)),
+            InserterConfig {
+                spill_dir: Some(dir.clone()),
+                max_retries: 5,
+                backoff_initial: Duration::from_millis(20),
+                ..Default::default()
+            },
+            counters.clone(),
+        )
+        .await;
+        assert!(replayed);
+        assert!(counters.retries.load(Ordering::SeqCst) >= 1);
+        assert_eq!(counters.replayed.load(Ordering::SeqCst), 4);
+        assert!(!dir.join(REPLAY_FILE).exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn try_write_drops_when_queue_is_full() {
+        // No flush task: the single queue slot stays occupied.
+        let (tx, _rx) = mpsc::channel(1);
+        let inserter = ClickHouseInserter {
+            tx,
+            counters: Arc::new(Counters::default()),
+        };
+        let row = Snapshot {
+            server_id: "pw-1",
+            players: 1,
+        };
+        assert!(inserter.try_write("t", &row).unwrap());
+        assert!(!inserter.try_write("t", &row).unwrap());
+        assert_eq!(inserter.stats().dropped, 1);
+        assert_eq!(inserter.queued(), 1);
+    }
+}
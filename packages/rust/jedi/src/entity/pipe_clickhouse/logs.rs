@@ -12,16 +12,18 @@
 //   - limit:          1..=500
 //   - search length:  <=100 chars
 //
-// All user-supplied strings go through `escape_clickhouse_string` to neutralize
-// `\` and `'` before being inlined into the SQL. ClickHouse parameterized
-// queries (`{name:Type}`) only work over the native protocol, not over the
-// HTTP+JSONEachRow path ClickHouseConfig::execute_select uses, so escape +
-// inline is the simpler approach.
+// SQL is composed with the typed builder in query.rs: user-supplied strings
+// are bound as `{pN:String}` parameters and sent alongside the query, never
+// inlined. Fixed labels (`level = 'error'`, the ROWS trace filter) still
+// render inline since they come from this file, not from the request.
 
 use serde::{Deserialize, Serialize};
 
 use super::super::super::error::JediError;
 use super::super::super::state::sidecar::ClickHouseConfig;
+use super::query::{
+    CURSOR_TIE, Cond, Cursor, Expr, Interval, Logs, LogsColumn, Order, Query, Select, Table,
+};
 
 pub const MAX_MINUTES: u32 = 10_080; // 7 days
 pub const DEFAULT_MINUTES: u32 = 60;
@@ -45,6 +47,9 @@ pub struct LogsQueryParams {
     pub minutes: Option<u32>,
     #[serde(default)]
     pub limit: Option<u32>,
+    /// `next_cursor` from the previous page; `None` starts at the newest row.
+    #[serde(default)]
+    pub cursor: Option<Cursor>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -67,18 +72,33 @@ pub struct ErrorGroupsParams {
 pub struct LogsResult {
     pub rows: Vec<serde_json::Value>,
     pub count: usize,
-}
-
-fn escape_clickhouse_string(input: &str) -> String {
-    let mut out = String::with_capacity(input.len() + 2);
-    for ch in input.chars() {
-        match ch {
-            '\\' => out.push_str("\\\\"),
-            '\'' => out.push_str("\\'"),
-            c => out.push(c),
+    /// Set by paginated commands when a full page came back; pass it as
+    /// `cursor` to fetch the next one.
+    ///
+    /// Paging is lossy for exact duplicates: log lines identical in every
+    /// column and logged in the same millisecond share one sort key, and the
+    /// next page resumes strictly below the key of this page's last row. If
+    /// such a run straddles a page boundary, its copies that didn't fit on
+    /// this page are skipped. Distinct lines are never skipped or repeated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Cursor>,
+}
+
+impl LogsResult {
+    fn from_rows(rows: Vec<serde_json::Value>) -> Self {
+        Self {
+            count: rows.len(),
+            rows,
+            next_cursor: None,
         }
     }
-    out
+}
+
+/// Execute a built query and wrap the rows. Shared by every `run_*` in
+/// pipe_clickhouse.
+pub(crate) async fn run(config: &ClickHouseConfig, query: Query) -> Result<LogsResult, JediError> {
+    let rows = query.fetch(config).await?;
+    Ok(LogsResult::from_rows(rows))
 }
 
 pub(crate) fn clamped_minutes(raw: Option<u32>) -> u32 {
@@ -88,12 +108,12 @@ pub(crate) fn clamped_minutes(raw: Option<u32>) -> u32 {
 /// Time-window condition for the query WHERE clause. `Some(0)` is the ALL
 /// sentinel and returns `None` (no timestamp filter), letting the dashboard
 /// scan the full retention window.
-pub(crate) fn time_condition(raw: Option<u32>) -> Option<String> {
+pub(crate) fn time_condition(raw: Option<u32>) -> Option<Cond> {
     match raw {
         Some(0) => None,
-        other => Some(format!(
-            "timestamp > now() - INTERVAL {} MINUTE",
-            clamped_minutes(other)
+        other => Some(Cond::within(
+            LogsColumn::Timestamp,
+            Interval::Minutes(clamped_minutes(other)),
         )),
     }
 }
@@ -103,93 +123,125 @@ fn clamped_limit(raw: Option<u32>) -> u32 {
 }
 
 fn clamped_search(raw: &str) -> String {
-    let trimmed: String = raw.chars().take(MAX_SEARCH_LENGTH).collect();
-    escape_clickhouse_string(&trimmed)
+    raw.chars().take(MAX_SEARCH_LENGTH).collect()
 }
 
 /// Level predicate — `warn` and `warning` are stored interchangeably by
 /// different emitters, so either input matches both spellings.
-fn level_condition(raw: &str) -> String {
+fn level_condition(raw: &str) -> Cond {
     let lvl = raw.to_lowercase();
     if lvl == "warn" || lvl == "warning" {
-        "level IN ('warn', 'warning')".to_string()
+        Cond::one_of(LogsColumn::Level, &["warn", "warning"])
     } else {
-        format!("level = '{}'", escape_clickhouse_string(&lvl))
+        Cond::eq(LogsColumn::Level, lvl)
     }
 }
 
+/// Non-empty optional string filter as a bound equality.
+fn eq_filter(column: LogsColumn, raw: &Option<String>) -> Option<Cond> {
+    raw.as_deref()
+        .filter(|s| !s.is_empty())
+        .map(|v| Cond::eq(column, v))
+}
+
+/// Tie-breaker for `query` pagination — rows sharing a millisecond are
+/// ordered by a hash of their content, so the order is the same on every
+/// replica and pages never overlap. Byte-identical rows in the same
+/// millisecond still tie, so a run of them cut by a page boundary loses
+/// the copies past it (see `LogsResult::next_cursor`).
+fn query_tie() -> Expr {
+    Expr::func(
+        "cityHash64",
+        [
+            Expr::col(LogsColumn::Service),
+            Expr::col(LogsColumn::PodNamespace),
+            Expr::col(LogsColumn::PodName),
+            Expr::col(LogsColumn::Level),
+            Expr::col(LogsColumn::Message),
+            Expr::col(LogsColumn::Metadata),
+        ],
+    )
+}
+
 /// Build the `query` SQL — filtered SELECT against logs_distributed,
-/// ordered by timestamp DESC.
-pub fn build_query_sql(params: &LogsQueryParams) -> String {
+/// newest first, resuming after `params.cursor` when set.
+pub fn build_query_sql(params: &LogsQueryParams) -> Query {
     let limit = clamped_limit(params.limit);
 
-    let mut conditions: Vec<String> = Vec::new();
-    if let Some(cond) = time_condition(params.minutes) {
-        conditions.push(cond);
-    }
-
-    if let Some(ns) = params.pod_namespace.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(format!(
-            "pod_namespace = '{}'",
-            escape_clickhouse_string(ns)
-        ));
-    }
-    if let Some(pn) = params.pod_name.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(format!("pod_name = '{}'", escape_clickhouse_string(pn)));
-    }
-    if let Some(svc) = params.service.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(format!("service = '{}'", escape_clickhouse_string(svc)));
-    }
-    if let Some(lvl) = params.level.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(level_condition(lvl));
-    }
-    if let Some(search) = params.search.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(format!("message ILIKE '%{}%'", clamped_search(search)));
-    }
-
-    let where_clause = if conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
-    };
-
-    format!(
-        "SELECT timestamp, pod_namespace, service, level, message, pod_name, metadata \
-		 FROM logs_distributed \
-		 {} \
-		 ORDER BY timestamp DESC \
-		 LIMIT {}",
-        where_clause, limit
-    )
+    Logs::select()
+        .columns([
+            LogsColumn::Timestamp,
+            LogsColumn::PodNamespace,
+            LogsColumn::Service,
+            LogsColumn::Level,
+            LogsColumn::Message,
+            LogsColumn::PodName,
+            LogsColumn::Metadata,
+        ])
+        .filter_opt(time_condition(params.minutes))
+        .filter_opt(eq_filter(LogsColumn::PodNamespace, &params.pod_namespace))
+        .filter_opt(eq_filter(LogsColumn::PodName, &params.pod_name))
+        .filter_opt(eq_filter(LogsColumn::Service, &params.service))
+        .filter_opt(
+            params
+                .level
+                .as_deref()
+                .filter(|s| !s.is_empty())
+                .map(level_condition),
+        )
+        .filter_opt(
+            params
+                .search
+                .as_deref()
+                .filter(|s| !s.is_empty())
+                .map(|s| Cond::contains(LogsColumn::Message, clamped_search(s))),
+        )
+        .paginate(LogsColumn::Timestamp, query_tie(), params.cursor.clone())
+        .limit(limit)
+        .build()
 }
 
 /// Build the `stats` SQL — GROUP BY namespace+service+level for the last
 /// `minutes` minutes.
-pub fn build_stats_sql(params: &LogsStatsParams) -> String {
-    let where_clause = match time_condition(params.minutes) {
-        Some(cond) => format!("WHERE {} ", cond),
-        None => String::new(),
-    };
-    format!(
-        "SELECT pod_namespace, service, level, count() AS cnt \
-		 FROM logs_distributed \
-		 {}\
-		 GROUP BY pod_namespace, service, level \
-		 ORDER BY cnt DESC \
-		 LIMIT 5000",
-        where_clause
-    )
+pub fn build_stats_sql(params: &LogsStatsParams) -> Query {
+    Logs::select()
+        .columns([
+            LogsColumn::PodNamespace,
+            LogsColumn::Service,
+            LogsColumn::Level,
+        ])
+        .expr(Expr::count().alias("cnt"))
+        .filter_opt(time_condition(params.minutes))
+        .group_by(LogsColumn::PodNamespace)
+        .group_by(LogsColumn::Service)
+        .group_by(LogsColumn::Level)
+        .order_by(Expr::ident("cnt"), Order::Desc)
+        .limit(5000)
+        .build()
 }
 
 pub async fn run_query(
     config: &ClickHouseConfig,
     params: &LogsQueryParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_query_sql(params);
-    let rows = config.execute_select(&sql).await?;
+    let limit = clamped_limit(params.limit) as usize;
+    let mut rows = build_query_sql(params).fetch(config).await?;
+
+    let next_cursor = if rows.len() == limit {
+        rows.last()
+            .and_then(|row| Cursor::from_row(row, LogsColumn::Timestamp))
+    } else {
+        None
+    };
+    for row in &mut rows {
+        if let Some(obj) = row.as_object_mut() {
+            obj.remove(CURSOR_TIE);
+        }
+    }
+
     Ok(LogsResult {
-        count: rows.len(),
-        rows,
+        next_cursor,
+        ..LogsResult::from_rows(rows)
     })
 }
 
@@ -197,12 +249,7 @@ pub async fn run_stats(
     config: &ClickHouseConfig,
     params: &LogsStatsParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_stats_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_stats_sql(params)).await
 }
 
 pub const MAX_ERROR_GROUPS: u32 = 100;
@@ -219,54 +266,57 @@ fn clamped_error_groups(raw: Option<u32>) -> u32 {
 /// by frequency. Turns a flat error stream into "this error ×1840, last seen
 /// 2m ago" so callers can triage the dominant failure first. Optionally scoped
 /// to a single namespace.
-pub fn build_error_groups_sql(params: &ErrorGroupsParams) -> String {
+pub fn build_error_groups_sql(params: &ErrorGroupsParams) -> Query {
     let limit = clamped_error_groups(params.limit);
 
-    let mut conditions: Vec<String> = Vec::new();
-    if let Some(cond) = time_condition(params.minutes) {
-        conditions.push(cond);
-    }
-    conditions.push("level = 'error'".to_string());
-    if let Some(ns) = params.pod_namespace.as_deref().filter(|s| !s.is_empty()) {
-        conditions.push(format!(
-            "pod_namespace = '{}'",
-            escape_clickhouse_string(ns)
-        ));
-    }
-    let where_clause = conditions.join(" AND ");
-
     // Bracket-class regexes only — no backslash escapes to thread through both
-    // the Rust string literal and the ClickHouse SQL literal. Braces in the
-    // quantifier are doubled for `format!`.
-    format!(
-        "SELECT \
-            pod_namespace, \
-            any(service) AS service, \
-            substring(replaceRegexpAll(replaceRegexpAll(message, '[0-9a-fA-F-]{{16,}}', '<id>'), '[0-9]+', 'N'), 1, {sig_len}) AS signature, \
-            count() AS cnt, \
-            max(timestamp) AS last_seen, \
-            any(message) AS sample \
-         FROM logs_distributed \
-         WHERE {where_clause} \
-         GROUP BY pod_namespace, signature \
-         ORDER BY cnt DESC \
-         LIMIT {limit}",
-        sig_len = ERROR_GROUP_SIGNATURE_LENGTH,
-        where_clause = where_clause,
-        limit = limit,
-    )
+    // the Rust string literal and the ClickHouse SQL literal.
+    let masked = Expr::func(
+        "replaceRegexpAll",
+        [
+            Expr::func(
+                "replaceRegexpAll",
+                [
+                    Expr::col(LogsColumn::Message),
+                    Expr::lit("[0-9a-fA-F-]{16,}"),
+                    Expr::lit("<id>"),
+                ],
+            ),
+            Expr::lit("[0-9]+"),
+            Expr::lit("N"),
+        ],
+    );
+    let signature = Expr::func(
+        "substring",
+        [
+            masked,
+            Expr::int(1),
+            Expr::int(i64::from(ERROR_GROUP_SIGNATURE_LENGTH)),
+        ],
+    );
+
+    Logs::select()
+        .column(LogsColumn::PodNamespace)
+        .expr(Expr::func("any", [Expr::col(LogsColumn::Service)]).alias("service"))
+        .expr(signature.alias("signature"))
+        .expr(Expr::count().alias("cnt"))
+        .expr(Expr::func("max", [Expr::col(LogsColumn::Timestamp)]).alias("last_seen"))
+        .expr(Expr::func("any", [Expr::col(LogsColumn::Message)]).alias("sample"))
+        .filter_opt(time_condition(params.minutes))
+        .filter(Cond::is(LogsColumn::Level, "error"))
+        .filter_opt(eq_filter(LogsColumn::PodNamespace, &params.pod_namespace))
+        .group_by(LogsColumn::PodNamespace)
+        .group_by(Expr::ident("signature"))
+        .order_by(Expr::ident("cnt"), Order::Desc)
+        .limit(limit)
+        .build()
 }
 
 pub async fn run_error_groups(
     config: &ClickHouseConfig,
     params: &ErrorGroupsParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_error_groups_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_error_groups_sql(params)).await
 }
 
 // ─── ROWS-specific aggregates ─────────────────────────────────────────
@@ -333,137 +383,114 @@ fn clamped_error_rows(raw: Option<u32>) -> u32 {
     raw.unwrap_or(DEFAULT_ERROR_ROWS).clamp(1, MAX_ERROR_ROWS)
 }
 
-const ROWS_REQUEST_FILTER: &str = "pod_namespace = 'rows' \
-     AND JSONExtractString(message, 'target') = 'rows::trace' \
-     AND JSONExtractString(message, 'fields', 'message') = 'request completed'";
+/// `pod_namespace = 'rows'` plus the trace target / completion message
+/// match shared by every ROWS panel, on top of the time window.
+fn rows_requests(minutes: Option<u32>) -> Select<Logs> {
+    Logs::select()
+        .filter(Cond::within(
+            LogsColumn::Timestamp,
+            Interval::Minutes(clamped_minutes(minutes)),
+        ))
+        .filter(Cond::is(LogsColumn::PodNamespace, "rows"))
+        .filter(Cond::is(
+            Expr::json_string(LogsColumn::Message, &["target"]),
+            "rows::trace",
+        ))
+        .filter(Cond::is(
+            Expr::json_string(LogsColumn::Message, &["fields", "message"]),
+            "request completed",
+        ))
+}
+
+fn span_string(key: &'static str) -> Expr {
+    Expr::json_string(LogsColumn::Message, &["span", key])
+}
 
-pub fn build_rows_request_rate_sql(params: &RowsRequestRateParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let bucket = clamped_bucket_seconds(params.bucket_seconds);
-    format!(
-        "SELECT \
-            toStartOfInterval(timestamp, INTERVAL {bucket} SECOND) AS bucket, \
-            count() AS reqs \
-         FROM logs_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-            AND {ROWS_REQUEST_FILTER} \
-         GROUP BY bucket \
-         ORDER BY bucket"
-    )
+fn span_int(key: &'static str) -> Expr {
+    Expr::json_int(LogsColumn::Message, &["span", key])
 }
 
-pub fn build_rows_status_histogram_sql(params: &RowsStatusHistogramParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    format!(
-        "SELECT \
-            JSONExtractInt(message, 'span', 'status') AS status, \
-            count() AS n \
-         FROM logs_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-            AND {ROWS_REQUEST_FILTER} \
-         GROUP BY status \
-         ORDER BY status"
-    )
+pub fn build_rows_request_rate_sql(params: &RowsRequestRateParams) -> Query {
+    let bucket = clamped_bucket_seconds(params.bucket_seconds);
+    rows_requests(params.minutes)
+        .expr(Expr::bucket(LogsColumn::Timestamp, Interval::Seconds(bucket)).alias("bucket"))
+        .expr(Expr::count().alias("reqs"))
+        .group_by(Expr::ident("bucket"))
+        .order_by(Expr::ident("bucket"), Order::Asc)
+        .build()
 }
 
-pub fn build_rows_top_endpoints_sql(params: &RowsTopEndpointsParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
-    let limit = clamped_top_endpoints(params.limit);
-    format!(
-        "SELECT \
-            JSONExtractString(message, 'span', 'path') AS path, \
-            count() AS n, \
-            quantile(0.5)(JSONExtractInt(message, 'span', 'latency_ms')) AS p50, \
-            quantile(0.95)(JSONExtractInt(message, 'span', 'latency_ms')) AS p95, \
-            quantile(0.99)(JSONExtractInt(message, 'span', 'latency_ms')) AS p99 \
-         FROM logs_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-            AND {ROWS_REQUEST_FILTER} \
-         GROUP BY path \
-         ORDER BY n DESC \
-         LIMIT {limit}"
-    )
+pub fn build_rows_status_histogram_sql(params: &RowsStatusHistogramParams) -> Query {
+    rows_requests(params.minutes)
+        .expr(span_int("status").alias("status"))
+        .expr(Expr::count().alias("n"))
+        .group_by(Expr::ident("status"))
+        .order_by(Expr::ident("status"), Order::Asc)
+        .build()
 }
 
-pub fn build_rows_errors_sql(params: &RowsErrorsParams) -> String {
-    let minutes = clamped_minutes(params.minutes);
+pub fn build_rows_top_endpoints_sql(params: &RowsTopEndpointsParams) -> Query {
+    let limit = clamped_top_endpoints(params.limit);
+    rows_requests(params.minutes)
+        .expr(span_string("path").alias("path"))
+        .expr(Expr::count().alias("n"))
+        .expr(Expr::quantile(0.5, span_int("latency_ms")).alias("p50"))
+        .expr(Expr::quantile(0.95, span_int("latency_ms")).alias("p95"))
+        .expr(Expr::quantile(0.99, span_int("latency_ms")).alias("p99"))
+        .group_by(Expr::ident("path"))
+        .order_by(Expr::ident("n"), Order::Desc)
+        .limit(limit)
+        .build()
+}
+
+pub fn build_rows_errors_sql(params: &RowsErrorsParams) -> Query {
     let limit = clamped_error_rows(params.limit);
-    format!(
-        "SELECT \
-            timestamp, \
-            JSONExtractString(message, 'span', 'method') AS method, \
-            JSONExtractString(message, 'span', 'path') AS path, \
-            JSONExtractInt(message, 'span', 'status') AS status, \
-            JSONExtractInt(message, 'span', 'latency_ms') AS latency_ms, \
-            JSONExtractString(message, 'span', 'customer') AS customer, \
-            JSONExtractString(message, 'span', 'id') AS request_id \
-         FROM logs_distributed \
-         WHERE timestamp > now() - INTERVAL {minutes} MINUTE \
-            AND {ROWS_REQUEST_FILTER} \
-            AND JSONExtractInt(message, 'span', 'status') >= 400 \
-         ORDER BY timestamp DESC \
-         LIMIT {limit}"
-    )
+    rows_requests(params.minutes)
+        .column(LogsColumn::Timestamp)
+        .expr(span_string("method").alias("method"))
+        .expr(span_string("path").alias("path"))
+        .expr(span_int("status").alias("status"))
+        .expr(span_int("latency_ms").alias("latency_ms"))
+        .expr(span_string("customer").alias("customer"))
+        .expr(span_string("id").alias("request_id"))
+        .filter(Cond::ge(span_int("status"), 400u32))
+        .order_by(LogsColumn::Timestamp, Order::Desc)
+        .limit(limit)
+        .build()
 }
 
 pub async fn run_rows_request_rate(
     config: &ClickHouseConfig,
     params: &RowsRequestRateParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_rows_request_rate_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_rows_request_rate_sql(params)).await
 }
 
 pub async fn run_rows_status_histogram(
     config: &ClickHouseConfig,
     params: &RowsStatusHistogramParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_rows_status_histogram_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_rows_status_histogram_sql(params)).await
 }
 
 pub async fn run_rows_top_endpoints(
     config: &ClickHouseConfig,
     params: &RowsTopEndpointsParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_rows_top_endpoints_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_rows_top_endpoints_sql(params)).await
 }
 
 pub async fn run_rows_errors(
     config: &ClickHouseConfig,
     params: &RowsErrorsParams,
 ) -> Result<LogsResult, JediError> {
-    let sql = build_rows_errors_sql(params);
-    let rows = config.execute_select(&sql).await?;
-    Ok(LogsResult {
-        count: rows.len(),
-        rows,
-    })
+    run(config, build_rows_errors_sql(params)).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn escapes_quotes_and_backslashes() {
-        assert_eq!(escape_clickhouse_string("he'llo"), "he\\'llo");
-        assert_eq!(escape_clickhouse_string("a\\b"), "a\\\\b");
-    }
-
     #[test]
     fn clamps_minutes_and_limit() {
         assert_eq!(clamped_minutes(None), DEFAULT_MINUTES);
@@ -484,13 +511,18 @@ mod tests {
             limit: Some(50),
             ..Default::default()
         };
-        let sql = build_query_sql(&params);
+        let query = build_query_sql(&params);
+        let sql = &query.sql;
         assert!(sql.contains("INTERVAL 15 MINUTE"));
-        assert!(sql.contains("pod_namespace = 'kbve'"));
-        assert!(sql.contains("service = 'axum'"));
-        assert!(sql.contains("level = 'error'"));
-        assert!(sql.contains("message ILIKE '%panic%'"));
+        assert!(sql.contains("pod_namespace = {p0:String}"));
+        assert!(sql.contains("service = {p1:String}"));
+        assert!(sql.contains("level = {p2:String}"));
+        assert!(sql.contains("message ILIKE concat('%', {p3:String}, '%')"));
         assert!(sql.contains("LIMIT 50"));
+        assert_eq!(query.param("p0"), Some("kbve"));
+        assert_eq!(query.param("p1"), Some("axum"));
+        assert_eq!(query.param("p2"), Some("error"));
+        assert_eq!(query.param("p3"), Some("panic"));
     }
 
     #[test]
@@ -499,22 +531,24 @@ mod tests {
             let sql = build_query_sql(&LogsQueryParams {
                 level: Some(input.into()),
                 ..Default::default()
-            });
+            })
+            .sql;
             assert!(
                 sql.contains("level IN ('warn', 'warning')"),
                 "level={input} should match both spellings: {sql}"
             );
         }
-        let sql = build_query_sql(&LogsQueryParams {
+        let query = build_query_sql(&LogsQueryParams {
             level: Some("error".into()),
             ..Default::default()
         });
-        assert!(sql.contains("level = 'error'"));
+        assert!(query.sql.contains("level = {p0:String}"));
+        assert_eq!(query.param("p0"), Some("error"));
     }
 
     #[test]
     fn build_stats_sql_default_minutes() {
-        let sql = build_stats_sql(&LogsStatsParams::default());
+        let sql = build_stats_sql(&LogsStatsParams::default()).sql;
         assert!(sql.contains(&format!("INTERVAL {} MINUTE", DEFAULT_MINUTES)));
         assert!(sql.contains("GROUP BY pod_namespace, service, level"));
     }
@@ -527,10 +561,11 @@ mod tests {
         let query = build_query_sql(&LogsQueryParams {
             minutes: Some(0),
             ..Default::default()
-        });
+        })
+        .sql;
         assert!(!query.contains("INTERVAL"));
 
-        let stats = build_stats_sql(&LogsStatsParams { minutes: Some(0) });
+        let stats = build_stats_sql(&LogsStatsParams { minutes: Some(0) }).sql;
         assert!(!stats.contains("INTERVAL"));
         assert!(!stats.contains("WHERE"));
         assert!(stats.contains("GROUP BY pod_namespace, service, level"));
@@ -539,7 +574,8 @@ mod tests {
             pod_namespace: None,
             minutes: Some(0),
             limit: Some(10),
-        });
+        })
+        .sql;
         assert!(!groups.contains("INTERVAL"));
         assert!(groups.contains("level = 'error'"));
     }
@@ -550,10 +586,11 @@ mod tests {
             pod_namespace: Some("kbve".into()),
             minutes: Some(120),
             limit: Some(10),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 120 MINUTE"));
         assert!(sql.contains("level = 'error'"));
-        assert!(sql.contains("pod_namespace = 'kbve'"));
+        assert!(sql.contains("pod_namespace = {p0:String}"));
         assert!(sql.contains("AS signature"));
         assert!(sql.contains("count() AS cnt"));
         assert!(sql.contains("max(timestamp) AS last_seen"));
@@ -564,9 +601,9 @@ mod tests {
 
     #[test]
     fn build_error_groups_sql_unscoped_clamps_limit() {
-        let sql = build_error_groups_sql(&ErrorGroupsParams::default());
+        let sql = build_error_groups_sql(&ErrorGroupsParams::default()).sql;
         assert!(sql.contains(&format!("INTERVAL {} MINUTE", DEFAULT_MINUTES)));
-        assert!(!sql.contains("pod_namespace = '"));
+        assert!(!sql.contains("pod_namespace ="));
         assert!(sql.contains(&format!("LIMIT {}", DEFAULT_ERROR_GROUPS)));
         assert_eq!(clamped_error_groups(Some(0)), 1);
         assert_eq!(clamped_error_groups(Some(9_999)), MAX_ERROR_GROUPS);
@@ -578,9 +615,36 @@ mod tests {
             pod_namespace: Some("kbve' OR 1=1 --".into()),
             ..Default::default()
         };
-        let sql = build_query_sql(&params);
-        // Escaped single quote keeps the injection inside the literal.
-        assert!(sql.contains("pod_namespace = 'kbve\\' OR 1=1 --'"));
+        let query = build_query_sql(&params);
+        // The value travels as a bound parameter, never as SQL text.
+        assert!(query.sql.contains("pod_namespace = {p0:String}"));
+        assert!(!query.sql.contains("OR 1=1"));
+        assert_eq!(query.param("p0"), Some("kbve' OR 1=1 --"));
+    }
+
+    #[test]
+    fn build_query_sql_paginates_newest_first() {
+        let first = build_query_sql(&LogsQueryParams::default()).sql;
+        assert!(first.contains(
+            "cityHash64(service, pod_namespace, pod_name, level, message, metadata) AS cursor_tie"
+        ));
+        assert!(first.contains("ORDER BY timestamp DESC, cursor_tie DESC"));
+        assert!(!first.contains("toDateTime64"));
+
+        let next = build_query_sql(&LogsQueryParams {
+            pod_namespace: Some("kbve".into()),
+            cursor: Some(Cursor {
+                time: "2026-10-18 12:00:00.123".into(),
+                tie: 7,
+            }),
+            ..Default::default()
+        });
+        assert!(next.sql.contains(
+            "(timestamp, cityHash64(service, pod_namespace, pod_name, level, message, metadata)) < \
+             (toDateTime64({p1:String}, 3, 'UTC'), toUInt128({p2:String}))"
+        ));
+        assert_eq!(next.param("p1"), Some("2026-10-18 12:00:00.123"));
+        assert_eq!(next.param("p2"), Some("7"));
     }
 
     #[test]
@@ -595,7 +659,8 @@ mod tests {
         let sql = build_rows_request_rate_sql(&RowsRequestRateParams {
             minutes: Some(15),
             bucket_seconds: Some(60),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 60 SECOND"));
         assert!(sql.contains("INTERVAL 15 MINUTE"));
         assert!(sql.contains("pod_namespace = 'rows'"));
@@ -606,7 +671,8 @@ mod tests {
 
     #[test]
     fn rows_status_histogram_sql_shape() {
-        let sql = build_rows_status_histogram_sql(&RowsStatusHistogramParams { minutes: Some(30) });
+        let sql =
+            build_rows_status_histogram_sql(&RowsStatusHistogramParams { minutes: Some(30) }).sql;
         assert!(sql.contains("INTERVAL 30 MINUTE"));
         assert!(sql.contains("JSONExtractInt(message, 'span', 'status')"));
         assert!(sql.contains("GROUP BY status"));
@@ -617,7 +683,8 @@ mod tests {
         let sql = build_rows_top_endpoints_sql(&RowsTopEndpointsParams {
             minutes: Some(60),
             limit: Some(10),
-        });
+        })
+        .sql;
         assert!(sql.contains("JSONExtractString(message, 'span', 'path')"));
         assert!(sql.contains("quantile(0.5)"));
         assert!(sql.contains("quantile(0.95)"));
@@ -637,7 +704,8 @@ mod tests {
         let sql = build_rows_errors_sql(&RowsErrorsParams {
             minutes: Some(120),
             limit: Some(25),
-        });
+        })
+        .sql;
         assert!(sql.contains("INTERVAL 120 MINUTE"));
         assert!(sql.contains("JSONExtractInt(message, 'span', 'status') >= 400"));
         assert!(sql.contains("ORDER BY timestamp DESC"));
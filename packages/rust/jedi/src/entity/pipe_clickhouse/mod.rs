@@ -3,9 +3,13 @@ pub mod clickhouse_types;
 #[cfg(feature = "valkey")]
 pub mod core;
 pub mod factorio;
+pub mod inserter;
 pub mod logs;
+pub mod query;
 
 pub use clickhouse_types::*;
 #[cfg(feature = "valkey")]
 pub use core::*;
+pub use inserter::{ClickHouseInserter, InserterConfig, InserterStats, TableWriter};
 pub use logs::{LogsQueryParams, LogsResult, LogsStatsParams, run_query, run_stats};
+pub use query::{Cond, Cursor, Expr, Interval, Order, Query, Select, Table};
@@ -0,0 +1,951 @@
+// Typed SELECT builder for the pipe_clickhouse dashboards.
+//
+// logs.rs, alerts.rs and factorio.rs used to assemble their SQL with
+// `format!` and an escape-and-inline helper for every user string. This
+// module is the shared replacement:
+//
+//   - every table gets a column enum (`clickhouse_table!`), so selects,
+//     filters and sort keys name columns the compiler knows about;
+//   - user-supplied strings are *bound*, never interpolated — they render as
+//     `{p0:String}` placeholders and travel as `param_p0=...` on the HTTP
+//     request (ClickHouseConfig::execute_select_with_params);
+//   - integers (limits, windows, thresholds) are rendered inline since they
+//     can't carry SQL, which keeps the generated text readable in logs;
+//   - the only way to put raw SQL in a query is a `&'static str`, i.e. text
+//     that lives in the binary, not in a request.
+//
+// Time helpers cover the two shapes every panel needs — a trailing window
+// (`Cond::within`) and bucketing (`Expr::bucket`) — and `Select::paginate`
+// adds newest-first keyset pagination with an opaque `Cursor`.
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::super::super::error::JediError;
+use super::super::super::state::sidecar::ClickHouseConfig;
+
+/// A column of a ClickHouse table or subquery.
+pub trait Column: Copy {
+    fn name(self) -> &'static str;
+}
+
+/// Anything a `Select` can read from — a table or a derived subquery.
+pub trait Relation {
+    type Column: Column;
+}
+
+/// A physical (or Distributed) table.
+pub trait Table: Relation + Sized {
+    const NAME: &'static str;
+
+    fn select() -> Select<Self> {
+        Select::new()
+    }
+}
+
+/// Declare a table marker plus its column enum.
+///
+/// ```ignore
+/// clickhouse_table! {
+///     pub Logs("logs_distributed") => LogsColumn {
+///         Timestamp => "timestamp",
+///         Message => "message",
+///     }
+/// }
+/// ```
+macro_rules! clickhouse_table {
+    (
+        $(#[$meta:meta])*
+        $vis:vis $table:ident($name:literal) => $column:ident {
+            $($(#[$vmeta:meta])* $variant:ident => $col:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        $vis struct $table;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $column {
+            $($(#[$vmeta])* $variant),+
+        }
+
+        impl $crate::entity::pipe_clickhouse::query::Column for $column {
+            fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $col),+
+                }
+            }
+        }
+
+        impl $crate::entity::pipe_clickhouse::query::Relation for $table {
+            type Column = $column;
+        }
+
+        impl $crate::entity::pipe_clickhouse::query::Table for $table {
+            const NAME: &'static str = $name;
+        }
+
+        impl From<$column> for $crate::entity::pipe_clickhouse::query::Expr {
+            fn from(c: $column) -> Self {
+                $crate::entity::pipe_clickhouse::query::Expr::col(c)
+            }
+        }
+    };
+}
+
+pub(crate) use clickhouse_table;
+
+clickhouse_table! {
+    /// `observability.logs_distributed`.
+    pub Logs("logs_distributed") => LogsColumn {
+        Timestamp => "timestamp",
+        PodNamespace => "pod_namespace",
+        PodName => "pod_name",
+        Service => "service",
+        Level => "level",
+        Message => "message",
+        Metadata => "metadata",
+    }
+}
+
+clickhouse_table! {
+    /// `observability.alerts_distributed`.
+    pub Alerts("alerts_distributed") => AlertsColumn {
+        Timestamp => "timestamp",
+        Status => "status",
+        Alertname => "alertname",
+        Severity => "severity",
+        Namespace => "namespace",
+        Pod => "pod",
+        Service => "service",
+        Summary => "summary",
+        Description => "description",
+        Fingerprint => "fingerprint",
+        StartsAt => "starts_at",
+        EndsAt => "ends_at",
+        Source => "source",
+    }
+}
+
+/// Marker for a `Select` over a subquery. Its columns are the subquery's
+/// aliases, named with `Alias`.
+#[derive(Debug, Clone, Copy)]
+pub struct Derived;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Alias(pub &'static str);
+
+impl Column for Alias {
+    fn name(self) -> &'static str {
+        self.0
+    }
+}
+
+impl Relation for Derived {
+    type Column = Alias;
+}
+
+impl From<Alias> for Expr {
+    fn from(a: Alias) -> Self {
+        Expr::col(a)
+    }
+}
+
+/// Quote a trusted literal. Only ever fed `&'static str`s from this crate,
+/// but escaped anyway so a stray quote can't change the statement.
+fn quote_literal(input: &str) -> String {
+    let mut out = String::with_capacity(input.len() + 2);
+    out.push('\'');
+    for ch in input.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// Encode a bound value for the HTTP `param_<name>` argument. ClickHouse
+/// parses these in the Escaped (TSV) text format, so backslashes and control
+/// characters need escaping; quotes don't.
+fn escape_param(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ─── Values, intervals, expressions ───────────────────────────────────
+
+/// A filter operand. Strings are bound as parameters; integers render inline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Str(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Str(v)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(v: u32) -> Self {
+        Value::Int(i64::from(v))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Seconds(u32),
+    Minutes(u32),
+    Hours(u32),
+    Days(u32),
+}
+
+impl Interval {
+    fn sql(self) -> String {
+        let (n, unit) = match self {
+            Interval::Seconds(n) => (n, "SECOND"),
+            Interval::Minutes(n) => (n, "MINUTE"),
+            Interval::Hours(n) => (n, "HOUR"),
+            Interval::Days(n) => (n, "DAY"),
+        };
+        format!("INTERVAL {} {}", n, unit)
+    }
+}
+
+/// A SELECT-list / GROUP BY / ORDER BY expression. Built only from columns,
+/// trusted static SQL and integers — there is no constructor taking a
+/// runtime string.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    sql: Cow<'static, str>,
+    alias: Option<&'static str>,
+}
+
+impl Expr {
+    pub fn col<C: Column>(c: C) -> Self {
+        Self::from_sql(Cow::Borrowed(c.name()))
+    }
+
+    /// Refer to an alias defined elsewhere in the query (`GROUP BY bucket`).
+    pub fn ident(name: &'static str) -> Self {
+        Self::from_sql(Cow::Borrowed(name))
+    }
+
+    /// Trusted SQL fragment, for the odd expression the helpers don't cover.
+    pub fn raw(sql: &'static str) -> Self {
+        Self::from_sql(Cow::Borrowed(sql))
+    }
+
+    /// Quoted string literal (regex patterns, JSON keys, fixed labels).
+    pub fn lit(value: &'static str) -> Self {
+        Self::from_sql(Cow::Owned(quote_literal(value)))
+    }
+
+    pub fn int(value: i64) -> Self {
+        Self::from_sql(Cow::Owned(value.to_string()))
+    }
+
+    pub fn count() -> Self {
+        Self::raw("count()")
+    }
+
+    /// `name(arg, ...)` — aggregate or scalar function call.
+    pub fn func(name: &'static str, args: impl IntoIterator<Item = Expr>) -> Self {
+        let args: Vec<String> = args.into_iter().map(|a| a.sql.into_owned()).collect();
+        Self::from_sql(Cow::Owned(format!("{}({})", name, args.join(", "))))
+    }
+
+    /// `quantile(level)(arg)`.
+    pub fn quantile(level: f64, arg: Expr) -> Self {
+        Self::from_sql(Cow::Owned(format!("quantile({})({})", level, arg.sql)))
+    }
+
+    /// `JSONExtractString(column, 'k1', 'k2', ...)`.
+    pub fn json_string<C: Column>(column: C, path: &[&'static str]) -> Self {
+        Self::json_extract("JSONExtractString", column, path)
+    }
+
+    /// `JSONExtractInt(column, 'k1', 'k2', ...)`.
+    pub fn json_int<C: Column>(column: C, path: &[&'static str]) -> Self {
+        Self::json_extract("JSONExtractInt", column, path)
+    }
+
+    /// `toStartOfInterval(column, INTERVAL n UNIT)` — time bucket for
+    /// series panels.
+    pub fn bucket<C: Column>(column: C, width: Interval) -> Self {
+        Self::from_sql(Cow::Owned(format!(
+            "toStartOfInterval({}, {})",
+            column.name(),
+            width.sql()
+        )))
+    }
+
+    pub fn alias(mut self, alias: &'static str) -> Self {
+        self.alias = Some(alias);
+        self
+    }
+
+    fn json_extract<C: Column>(func: &str, column: C, path: &[&'static str]) -> Self {
+        let mut sql = format!("{}({}", func, column.name());
+        for key in path {
+            sql.push_str(", ");
+            sql.push_str(&quote_literal(key));
+        }
+        sql.push(')');
+        Self::from_sql(Cow::Owned(sql))
+    }
+
+    fn from_sql(sql: Cow<'static, str>) -> Self {
+        Self { sql, alias: None }
+    }
+
+    fn select_sql(&self) -> String {
+        match self.alias {
+            Some(alias) if alias != self.sql => format!("{} AS {}", self.sql, alias),
+            _ => self.sql.to_string(),
+        }
+    }
+}
+
+// ─── Conditions ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn sql(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+}
+
+/// One WHERE predicate. Conditions on a `Select` are AND-ed.
+#[derive(Debug, Clone)]
+pub struct Cond(CondKind);
+
+#[derive(Debug, Clone)]
+enum CondKind {
+    Cmp(Expr, CmpOp, Value),
+    Static(Expr, Vec<&'static str>),
+    Contains(Expr, Value),
+    Within(Expr, Interval),
+    Before(Expr, Expr, Cursor),
+    Raw(&'static str),
+}
+
+impl Cond {
+    pub fn cmp(expr: impl Into<Expr>, op: CmpOp, value: impl Into<Value>) -> Self {
+        Self(CondKind::Cmp(expr.into(), op, value.into()))
+    }
+
+    pub fn eq(expr: impl Into<Expr>, value: impl Into<Value>) -> Self {
+        Self::cmp(expr, CmpOp::Eq, value)
+    }
+
+    pub fn ge(expr: impl Into<Expr>, value: impl Into<Value>) -> Self {
+        Self::cmp(expr, CmpOp::Ge, value)
+    }
+
+    /// `expr = 'value'` against a fixed label; rendered inline because the
+    /// value is part of the binary, which keeps the SQL self-documenting.
+    pub fn is(expr: impl Into<Expr>, value: &'static str) -> Self {
+        Self(CondKind::Static(expr.into(), vec![value]))
+    }
+
+    /// `expr IN ('a', 'b', ...)` against fixed labels.
+    pub fn one_of(expr: impl Into<Expr>, values: &[&'static str]) -> Self {
+        Self(CondKind::Static(expr.into(), values.to_vec()))
+    }
+
+    /// Case-insensitive substring match with the needle bound as a parameter.
+    pub fn contains(expr: impl Into<Expr>, needle: impl Into<String>) -> Self {
+        Self(CondKind::Contains(expr.into(), Value::Str(needle.into())))
+    }
+
+    /// `expr > now() - INTERVAL n UNIT` — trailing time window.
+    pub fn within(expr: impl Into<Expr>, window: Interval) -> Self {
+        Self(CondKind::Within(expr.into(), window))
+    }
+
+    /// Trusted predicate text (`Expr::raw` for WHERE clauses).
+    pub fn raw(sql: &'static str) -> Self {
+        Self(CondKind::Raw(sql))
+    }
+}
+
+// ─── Pagination ───────────────────────────────────────────────────────
+
+/// Alias of the tie-breaker key `Select::paginate` adds to the select list.
+pub const CURSOR_TIE: &str = "cursor_tie";
+
+/// DateTime64 precision of the cursor's time key — matches the
+/// `DateTime64(3, 'UTC')` timestamps in packages/data/ch/schemas.
+const CURSOR_TIME_PRECISION: u8 = 3;
+
+/// Keyset position for newest-first pagination: the `(time, tie)` sort key
+/// of the last row of the previous page. `tie` is wide enough for any
+/// unsigned integer tie expression up to `UInt128`. Serializes as an opaque
+/// hex token so clients just echo it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub time: String,
+    pub tie: u128,
+}
+
+impl Cursor {
+    /// Read the sort key of a result row produced by a paginated `Select`.
+    /// 64-bit and wider integers come back quoted in JSONEachRow by default,
+    /// so both a JSON number and a numeric string are accepted for the
+    /// tie-breaker.
+    pub fn from_row<C: Column>(row: &serde_json::Value, time: C) -> Option<Self> {
+        let time = row.get(time.name())?.as_str()?.to_string();
+        let tie = match row.get(CURSOR_TIE)? {
+            serde_json::Value::Number(n) => n.as_u64()?.into(),
+            serde_json::Value::String(s) => s.parse().ok()?,
+            _ => return None,
+        };
+        Some(Self { time, tie })
+    }
+
+    pub fn encode(&self) -> String {
+        let mut out = format!("{:032x}", self.tie);
+        for b in self.time.as_bytes() {
+            out.push_str(&format!("{:02x}", b));
+        }
+        out
+    }
+
+    pub fn decode(token: &str) -> Result<Self, JediError> {
+        let invalid = || JediError::BadRequest("invalid cursor".into());
+        if token.len() < 32 || !token.len().is_multiple_of(2) || !token.is_ascii() {
+            return Err(invalid());
+        }
+        let tie = u128::from_str_radix(&token[..32], 16).map_err(|_| invalid())?;
+        let bytes = (32..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&token[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid())?;
+        let time = String::from_utf8(bytes).map_err(|_| invalid())?;
+        if time.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { time, tie })
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        Cursor::decode(&token).map_err(serde::de::Error::custom)
+    }
+}
+
+// ─── Select ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn sql(self) -> &'static str {
+        match self {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Table(&'static str),
+    Subquery(Box<SelectCore>),
+}
+
+#[derive(Debug, Clone)]
+struct SelectCore {
+    source: Source,
+    final_: bool,
+    columns: Vec<Expr>,
+    conds: Vec<Cond>,
+    group_by: Vec<Expr>,
+    order_by: Vec<(Expr, Order)>,
+    limit_by: Option<(u32, Vec<Expr>)>,
+    limit: Option<u32>,
+}
+
+/// SELECT over relation `R`. Columns of `R` are accepted directly; computed
+/// expressions go through `expr`.
+#[derive(Debug, Clone)]
+pub struct Select<R: Relation> {
+    core: SelectCore,
+    _relation: PhantomData<R>,
+}
+
+impl<T: Table> Select<T> {
+    pub fn new() -> Self {
+        Self::with_source(Source::Table(T::NAME))
+    }
+}
+
+impl<T: Table> Default for Select<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Select<Derived> {
+    /// `SELECT ... FROM (inner)` — outer query addresses the inner aliases
+    /// through `Alias`.
+    pub fn over<R: Relation>(inner: Select<R>) -> Self {
+        Self::with_source(Source::Subquery(Box::new(inner.core)))
+    }
+}
+
+impl<R: Relation> Select<R> {
+    fn with_source(source: Source) -> Self {
+        Self {
+            core: SelectCore {
+                source,
+                final_: false,
+                columns: Vec::new(),
+                conds: Vec::new(),
+                group_by: Vec::new(),
+                order_by: Vec::new(),
+                limit_by: None,
+                limit: None,
+            },
+            _relation: PhantomData,
+        }
+    }
+
+    pub fn column(mut self, column: R::Column) -> Self {
+        self.core.columns.push(Expr::col(column));
+        self
+    }
+
+    pub fn columns(mut self, columns: impl IntoIterator<Item = R::Column>) -> Self {
+        self.core.columns.extend(columns.into_iter().map(Expr::col));
+        self
+    }
+
+    pub fn expr(mut self, expr: Expr) -> Self {
+        self.core.columns.push(expr);
+        self
+    }
+
+    /// `FROM table FINAL` — collapse ReplacingMergeTree versions at read time.
+    pub fn final_rows(mut self) -> Self {
+        self.core.final_ = true;
+        self
+    }
+
+    pub fn filter(mut self, cond: Cond) -> Self {
+        self.core.conds.push(cond);
+        self
+    }
+
+    pub fn filter_opt(self, cond: Option<Cond>) -> Self {
+        match cond {
+            Some(cond) => self.filter(cond),
+            None => self,
+        }
+    }
+
+    pub fn group_by(mut self, expr: impl Into<Expr>) -> Self {
+        self.core.group_by.push(expr.into());
+        self
+    }
+
+    pub fn order_by(mut self, expr: impl Into<Expr>, order: Order) -> Self {
+        self.core.order_by.push((expr.into(), order));
+        self
+    }
+
+    /// `LIMIT n BY expr` — top-n rows per group.
+    pub fn limit_by(mut self, n: u32, expr: impl Into<Expr>) -> Self {
+        match &mut self.core.limit_by {
+            Some((_, exprs)) => exprs.push(expr.into()),
+            None => self.core.limit_by = Some((n, vec![expr.into()])),
+        }
+        self
+    }
+
+    pub fn limit(mut self, n: u32) -> Self {
+        self.core.limit = Some(n);
+        self
+    }
+
+    /// Newest-first keyset pagination on `(time, tie)`. `tie` disambiguates
+    /// rows sharing a timestamp and is selected as `CURSOR_TIE` so
+    /// `Cursor::from_row` can read it back off the last row of a page.
+    /// Supplies the ORDER BY, so don't order by `time` separately.
+    pub fn paginate(mut self, time: R::Column, tie: Expr, after: Option<Cursor>) -> Self {
+        let time = Expr::col(time);
+        if let Some(cursor) = after {
+            self.core
+                .conds
+                .push(Cond(CondKind::Before(time.clone(), tie.clone(), cursor)));
+        }
+        self.core.columns.push(tie.alias(CURSOR_TIE));
+        self.core.order_by.push((time, Order::Desc));
+        self.core
+            .order_by
+            .push((Expr::ident(CURSOR_TIE), Order::Desc));
+        self
+    }
+
+    pub fn build(&self) -> Query {
+        let mut binder = Binder::default();
+        let sql = self.core.render(&mut binder);
+        Query {
+            sql,
+            params: binder.params,
+        }
+    }
+}
+
+/// Hands out `{pN:String}` placeholders in render order, so subqueries and
+/// outer queries share one parameter namespace.
+#[derive(Default)]
+struct Binder {
+    params: Vec<(String, String)>,
+}
+
+impl Binder {
+    fn bind(&mut self, value: &Value) -> String {
+        match value {
+            Value::Int(n) => n.to_string(),
+            Value::Str(s) => {
+                let name = format!("p{}", self.params.len());
+                let placeholder = format!("{{{}:String}}", name);
+                self.params.push((name, escape_param(s)));
+                placeholder
+            }
+        }
+    }
+}
+
+impl Cond {
+    fn render(&self, binder: &mut Binder) -> String {
+        match &self.0 {
+            CondKind::Cmp(expr, op, value) => {
+                format!("{} {} {}", expr.sql, op.sql(), binder.bind(value))
+            }
+            CondKind::Static(expr, values) if values.len() == 1 => {
+                format!("{} = {}", expr.sql, quote_literal(values[0]))
+            }
+            CondKind::Static(expr, values) => {
+                let values: Vec<String> = values.iter().map(|v| quote_literal(v)).collect();
+                format!("{} IN ({})", expr.sql, values.join(", "))
+            }
+            CondKind::Contains(expr, needle) => {
+                format!(
+                    "{} ILIKE concat('%', {}, '%')",
+                    expr.sql,
+                    binder.bind(needle)
+                )
+            }
+            CondKind::Within(expr, window) => {
+                format!("{} > now() - {}", expr.sql, window.sql())
+            }
+            // The tie travels as a string: ClickHouse reads integer literals
+            // past UInt64 as Float64, which would round a UInt128 key.
+            CondKind::Before(time, tie, cursor) => format!(
+                "({}, {}) < (toDateTime64({}, {}, 'UTC'), toUInt128({}))",
+                time.sql,
+                tie.sql,
+                binder.bind(&Value::Str(cursor.time.clone())),
+                CURSOR_TIME_PRECISION,
+                binder.bind(&Value::Str(cursor.tie.to_string()))
+            ),
+            CondKind::Raw(sql) => sql.to_string(),
+        }
+    }
+}
+
+impl SelectCore {
+    fn render(&self, binder: &mut Binder) -> String {
+        let columns: Vec<String> = self.columns.iter().map(Expr::select_sql).collect();
+        let mut sql = format!("SELECT {} FROM ", columns.join(", "));
+
+        match &self.source {
+            Source::Table(name) => sql.push_str(name),
+            Source::Subquery(inner) => {
+                sql.push('(');
+                sql.push_str(&inner.render(binder));
+                sql.push(')');
+            }
+        }
+        if self.final_ {
+            sql.push_str(" FINAL");
+        }
+        if !self.conds.is_empty() {
+            let conds: Vec<String> = self.conds.iter().map(|c| c.render(binder)).collect();
+            sql.push_str(" WHERE ");
+            sql.push_str(&conds.join(" AND "));
+        }
+        if !self.group_by.is_empty() {
+            let exprs: Vec<&str> = self.group_by.iter().map(|e| e.sql.as_ref()).collect();
+            sql.push_str(" GROUP BY ");
+            sql.push_str(&exprs.join(", "));
+        }
+        if !self.order_by.is_empty() {
+            let keys: Vec<String> = self
+                .order_by
+                .iter()
+                .map(|(e, o)| format!("{} {}", e.sql, o.sql()))
+                .collect();
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&keys.join(", "));
+        }
+        if let Some((n, exprs)) = &self.limit_by {
+            let exprs: Vec<&str> = exprs.iter().map(|e| e.sql.as_ref()).collect();
+            sql.push_str(&format!(" LIMIT {} BY {}", n, exprs.join(", ")));
+        }
+        if let Some(n) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", n));
+        }
+        sql
+    }
+}
+
+/// Rendered SQL plus its bound parameters, ready for
+/// `ClickHouseConfig::execute_select_with_params`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub sql: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl Query {
+    /// Value bound to placeholder `name` (as sent on the wire).
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub async fn fetch(
+        &self,
+        config: &ClickHouseConfig,
+    ) -> Result<Vec<serde_json::Value>, JediError> {
+        config
+            .execute_select_with_params(&self.sql, &self.params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_literals_and_escapes_params() {
+        assert_eq!(quote_literal("he'llo"), "'he\\'llo'");
+        assert_eq!(quote_literal("a\\b"), "'a\\\\b'");
+        assert_eq!(escape_param("a\\b\tc\nd'e"), "a\\\\b\\tc\\nd'e");
+    }
+
+    #[test]
+    fn strings_are_bound_and_integers_inlined() {
+        let q = Logs::select()
+            .column(LogsColumn::Message)
+            .filter(Cond::eq(LogsColumn::PodNamespace, "kbve' OR 1=1 --"))
+            .filter(Cond::ge(
+                Expr::json_int(LogsColumn::Message, &["span", "status"]),
+                400u32,
+            ))
+            .limit(10)
+            .build();
+        assert_eq!(
+            q.sql,
+            "SELECT message FROM logs_distributed \
+             WHERE pod_namespace = {p0:String} \
+             AND JSONExtractInt(message, 'span', 'status') >= 400 LIMIT 10"
+        );
+        assert_eq!(q.param("p0"), Some("kbve' OR 1=1 --"));
+        assert!(!q.sql.contains("OR 1=1"));
+    }
+
+    #[test]
+    fn static_labels_and_windows_render_inline() {
+        let q = Logs::select()
+            .column(LogsColumn::Level)
+            .filter(Cond::within(LogsColumn::Timestamp, Interval::Minutes(15)))
+            .filter(Cond::one_of(LogsColumn::Level, &["warn", "warning"]))
+            .filter(Cond::is(LogsColumn::Level, "error"))
+            .build();
+        assert!(q.sql.contains("timestamp > now() - INTERVAL 15 MINUTE"));
+        assert!(q.sql.contains("level IN ('warn', 'warning')"));
+        assert!(q.sql.contains("level = 'error'"));
+        assert!(q.params.is_empty());
+    }
+
+    #[test]
+    fn bucket_group_and_aliases() {
+        let q = Logs::select()
+            .expr(Expr::bucket(LogsColumn::Timestamp, Interval::Seconds(60)).alias("bucket"))
+            .expr(Expr::count().alias("n"))
+            .expr(Expr::quantile(0.95, Expr::col(LogsColumn::Level)).alias("p95"))
+            .group_by(Expr::ident("bucket"))
+            .order_by(Expr::ident("bucket"), Order::Asc)
+            .build();
+        assert_eq!(
+            q.sql,
+            "SELECT toStartOfInterval(timestamp, INTERVAL 60 SECOND) AS bucket, \
+             count() AS n, quantile(0.95)(level) AS p95 FROM logs_distributed \
+             GROUP BY bucket ORDER BY bucket ASC"
+        );
+    }
+
+    #[test]
+    fn subquery_shares_parameter_namespace() {
+        let inner = Alerts::select()
+            .column(AlertsColumn::Fingerprint)
+            .expr(
+                Expr::func(
+                    "argMax",
+                    [
+                        Expr::col(AlertsColumn::Status),
+                        Expr::col(AlertsColumn::Timestamp),
+                    ],
+                )
+                .alias("last_status"),
+            )
+            .filter(Cond::eq(AlertsColumn::Namespace, "kbve"))
+            .group_by(AlertsColumn::Fingerprint);
+        let q = Select::over(inner)
+            .column(Alias("fingerprint"))
+            .filter(Cond::eq(Alias("last_status"), "firing"))
+            .build();
+        assert_eq!(
+            q.sql,
+            "SELECT fingerprint FROM (SELECT fingerprint, \
+             argMax(status, timestamp) AS last_status FROM alerts_distributed \
+             WHERE namespace = {p0:String} GROUP BY fingerprint) \
+             WHERE last_status = {p1:String}"
+        );
+        assert_eq!(q.param("p0"), Some("kbve"));
+        assert_eq!(q.param("p1"), Some("firing"));
+    }
+
+    #[test]
+    fn final_and_limit_by() {
+        let q = Logs::select()
+            .column(LogsColumn::Service)
+            .final_rows()
+            .limit_by(1, LogsColumn::Service)
+            .build();
+        assert_eq!(
+            q.sql,
+            "SELECT service FROM logs_distributed FINAL LIMIT 1 BY service"
+        );
+    }
+
+    #[test]
+    fn paginate_orders_and_seeks() {
+        let tie = || Expr::func("cityHash64", [Expr::col(LogsColumn::Message)]);
+        let first = Logs::select()
+            .column(LogsColumn::Timestamp)
+            .paginate(LogsColumn::Timestamp, tie(), None)
+            .limit(2)
+            .build();
+        assert_eq!(
+            first.sql,
+            "SELECT timestamp, cityHash64(message) AS cursor_tie FROM logs_distributed \
+             ORDER BY timestamp DESC, cursor_tie DESC LIMIT 2"
+        );
+
+        let row = serde_json::json!({
+            "timestamp": "2026-10-18 12:00:00.123",
+            "cursor_tie": "42",
+        });
+        let cursor = Cursor::from_row(&row, LogsColumn::Timestamp).unwrap();
+        let next = Logs::select()
+            .column(LogsColumn::Timestamp)
+            .paginate(LogsColumn::Timestamp, tie(), Some(cursor))
+            .build();
+        assert!(next.sql.contains(
+            "WHERE (timestamp, cityHash64(message)) < \
+             (toDateTime64({p0:String}, 3, 'UTC'), toUInt128({p1:String}))"
+        ));
+        assert_eq!(next.param("p0"), Some("2026-10-18 12:00:00.123"));
+        assert_eq!(next.param("p1"), Some("42"));
+    }
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = Cursor {
+            time: "2026-10-18 12:00:00.123".into(),
+            tie: u128::MAX,
+        };
+        let token = cursor.encode();
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(serde_json::from_str::<Cursor>(&json).unwrap(), cursor);
+
+        for bad in [
+            "",
+            "zz",
+            "00000000000000000000000000000000",
+            "000000000000000000000000000000000",
+            "ffffffffffffffffffffffffffffffffzz",
+        ] {
+            assert!(Cursor::decode(bad).is_err(), "{bad:?} should be rejected");
+        }
+        assert!(serde_json::from_str::<Cursor>("\"nope\"").is_err());
+    }
+}
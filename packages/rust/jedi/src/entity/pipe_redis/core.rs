@@ -14,11 +14,20 @@ use std::sync::Arc;
 use crate::entity::envelope::{try_unwrap_payload, wrap_hybrid};
 
 use super::extract_redis_bytes;
-use super::redis_types::{KeyValueInput, RedisResult, StreamEntry, StreamMessages, XAddInput};
+use super::redis_types::{
+    KeyValueInput, RedisResult, StreamEntry, StreamMessages, WatchResumeInput, XAddInput,
+};
 
 macro_rules! match_redis_handlers_flex {
     ($kind:expr, $env:expr, $ctx:expr) => {{
-        if MessageKind::get($kind) {
+        // Pattern watches carry the DEL/READ bits too, so match them first.
+        if MessageKind::pwatch($kind) {
+            handle_redis_pwatch_flex($env, $ctx).await
+        } else if MessageKind::punwatch($kind) {
+            handle_redis_punwatch_flex($env, $ctx).await
+        } else if MessageKind::wresume($kind) {
+            handle_redis_wresume_flex($env, $ctx).await
+        } else if MessageKind::get($kind) {
             handle_redis_get_flex($env, $ctx).await
         } else if MessageKind::set($kind) {
             handle_redis_set_flex($env, $ctx).await
@@ -44,7 +53,14 @@ macro_rules! match_redis_handlers_flex {
 
 macro_rules! match_redis_handlers_json {
     ($kind:expr, $env:expr, $ctx:expr) => {{
-        if MessageKind::get($kind) {
+        // Pattern watches carry the DEL/READ bits too, so match them first.
+        if MessageKind::pwatch($kind) {
+            handle_redis_pwatch_json($env, $ctx).await
+        } else if MessageKind::punwatch($kind) {
+            handle_redis_punwatch_json($env, $ctx).await
+        } else if MessageKind::wresume($kind) {
+            handle_redis_wresume_json($env, $ctx).await
+        } else if MessageKind::get($kind) {
             handle_redis_get_json($env, $ctx).await
         } else if MessageKind::set($kind) {
             handle_redis_set_json($env, $ctx).await
@@ -285,6 +301,55 @@ async fn handle_redis_unwatch_flex(
     Ok(env.clone())
 }
 
+async fn handle_redis_pwatch_flex(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<KeyValueInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let connection_id = crate::entity::ulid::extract_connection_id_bytes(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    ctx.watch_manager
+        .watch_pattern(connection_id, input.key, PayloadFormat::Flex)?;
+    Ok(env.clone())
+}
+
+async fn handle_redis_punwatch_flex(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<KeyValueInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let conn_id = crate::entity::ulid::extract_connection_id_bytes(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    ctx.watch_manager
+        .unwatch_pattern(&conn_id, input.key, PayloadFormat::Flex)?;
+    Ok(env.clone())
+}
+
+async fn handle_redis_wresume_flex(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<WatchResumeInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let conn_id = crate::entity::ulid::extract_connection_id_bytes(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    let resumed = ctx.resume_watches(&conn_id, input.token.as_deref()).await?;
+    Ok(wrap_hybrid(
+        MessageKind::WRESUME,
+        PayloadFormat::Flex,
+        &resumed,
+        Some(env.metadata.clone()),
+    ))
+}
+
 async fn handle_redis_pub_flex(
     env: &JediEnvelope,
     ctx: &TempleState,
@@ -506,6 +571,55 @@ async fn handle_redis_unwatch_json(
     Ok(env.clone())
 }
 
+async fn handle_redis_pwatch_json(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<KeyValueInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let conn_id = crate::entity::ulid::extract_connection_id_json(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    ctx.watch_manager
+        .watch_pattern(conn_id, input.key, PayloadFormat::Json)?;
+    Ok(env.clone())
+}
+
+async fn handle_redis_punwatch_json(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<KeyValueInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let conn_id = crate::entity::ulid::extract_connection_id_json(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    ctx.watch_manager
+        .unwatch_pattern(&conn_id, input.key, PayloadFormat::Json)?;
+    Ok(env.clone())
+}
+
+async fn handle_redis_wresume_json(
+    env: &JediEnvelope,
+    ctx: &TempleState,
+) -> Result<JediEnvelope, JediError> {
+    let input = try_unwrap_payload::<WatchResumeInput>(env)?;
+    let metadata = env.metadata_or_empty();
+
+    let conn_id = crate::entity::ulid::extract_connection_id_json(&metadata)
+        .ok_or_else(|| JediError::BadRequest("Missing connection ID in metadata".into()))?;
+
+    let resumed = ctx.resume_watches(&conn_id, input.token.as_deref()).await?;
+    Ok(wrap_hybrid(
+        MessageKind::WRESUME,
+        PayloadFormat::Json,
+        &resumed,
+        Some(env.metadata.clone()),
+    ))
+}
+
 async fn handle_redis_pub_json(
     env: &JediEnvelope,
     ctx: &TempleState,
@@ -1,4 +1,8 @@
-use fred::{clients::SubscriberClient, prelude::*, types::Message as RedisMessage};
+use fred::{
+    clients::SubscriberClient,
+    prelude::*,
+    types::{Message as RedisMessage, MessageKind as RedisMessageKind},
+};
 use std::sync::Arc;
 use tokio::sync::{broadcast::Sender as BroadcastSender, mpsc::UnboundedReceiver};
 use tokio::task::JoinHandle;
@@ -8,6 +12,7 @@ use crate::error::JediError;
 use crate::pipe_redis::KeyValueInput;
 use crate::proto::jedi::{JediEnvelope, MessageKind, PayloadFormat};
 use crate::temple::TempleState;
+use crate::watchmaster::WatchManager;
 
 pub async fn create_pubsub_connection_fred(
     config: Config,
@@ -50,6 +55,7 @@ fn parse_pubsub_message_to_envelope(msg: &RedisMessage) -> Option<JediEnvelope>
           "key": key,
           "value": payload,
           "timestamp": chrono::Utc::now().timestamp_millis(),
+          "via_pattern": msg.kind == RedisMessageKind::PMessage,
         }),
         None,
     );
@@ -79,6 +85,65 @@ pub fn spawn_pubsub_listener_task(
     })
 }
 
+/// Like [`spawn_pubsub_listener_task`], but records each key update in the
+/// watch log and stamps `version`, `seq` and `token` onto the payload so
+/// clients can resume after a reconnect. A key matched by several watches
+/// (an exact one, overlapping patterns) arrives once per subscription;
+/// [`WatchManager::observe`] keeps a single copy.
+pub fn spawn_watched_pubsub_listener_task(
+    mut rx: UnboundedReceiver<JediEnvelope>,
+    event_tx: BroadcastSender<JediEnvelope>,
+    watch_manager: WatchManager,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(env) = rx.recv().await {
+            let env = match stamp_watch_update(env, &watch_manager) {
+                Some(env) => env,
+                None => continue,
+            };
+
+            if let Err(e) = event_tx.send(env) {
+                tracing::warn!("[Redis] Failed to broadcast pubsub event: {}", e);
+            }
+        }
+
+        tracing::warn!("[Redis] PubSub listener exited");
+    })
+}
+
+fn stamp_watch_update(env: JediEnvelope, watch_manager: &WatchManager) -> Option<JediEnvelope> {
+    if !MessageKind::config_update(env.kind) {
+        return Some(env);
+    }
+    let Ok(mut update) = try_unwrap_payload::<serde_json::Value>(&env) else {
+        return Some(env);
+    };
+    let Some(key) = update
+        .get("key")
+        .and_then(|k| k.as_str())
+        .map(Arc::<str>::from)
+    else {
+        return Some(env);
+    };
+
+    let via_pattern = update
+        .get("via_pattern")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let value = update
+        .get("value")
+        .and_then(|v| v.as_str())
+        .map(Arc::<str>::from);
+    let delta = watch_manager.observe(key, value, via_pattern)?;
+    update["version"] = delta.version.into();
+    update["seq"] = delta.seq.into();
+    update["token"] = watch_manager.log.token_at(delta.seq).into();
+
+    let format = PayloadFormat::try_from(env.format).unwrap_or(PayloadFormat::Json);
+    let metadata = (!env.metadata.is_empty()).then(|| env.metadata.clone());
+    Some(wrap_hybrid(env.kind, format, &update, metadata))
+}
+
 pub fn spawn_watch_event_listener(
     mut rx: UnboundedReceiver<JediEnvelope>,
     client: SubscriberClient,
@@ -92,7 +157,29 @@ pub fn spawn_watch_event_listener(
                 continue;
             }
 
-            if MessageKind::watch(kind) || MessageKind::unwatch(kind) {
+            // PWATCH/PUNWATCH carry the DEL/READ bits too, so test them first.
+            if MessageKind::pwatch(kind) || MessageKind::punwatch(kind) {
+                let pattern = match try_unwrap_payload::<KeyValueInput>(&env) {
+                    Ok(kv) => kv.key,
+                    Err(e) => {
+                        tracing::warn!("[Redis] Failed to parse pwatch/punwatch payload: {}", e);
+                        continue;
+                    }
+                };
+
+                let channel = format!("key:{}", pattern);
+                let result = if MessageKind::pwatch(kind) {
+                    tracing::info!("[Redis] Pattern-subscribing to {}", channel);
+                    client.psubscribe(channel).await
+                } else {
+                    tracing::info!("[Redis] Pattern-unsubscribing from {}", channel);
+                    client.punsubscribe(channel).await
+                };
+
+                if let Err(e) = result {
+                    tracing::warn!("[Redis] Failed to process pwatch/punwatch: {}", e);
+                }
+            } else if MessageKind::watch(kind) || MessageKind::unwatch(kind) {
                 let payload = try_unwrap_payload::<KeyValueInput>(&env);
 
                 let key = match payload {
@@ -13,6 +13,21 @@ pub struct KeyValueInput {
     pub ttl: Option<usize>,
 }
 
+// * WRESUME: `token` is the last one the client saw; omit it for a snapshot.
+#[derive(Debug, Default, Deserialize)]
+pub struct WatchResumeInput {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchResumeOutput {
+    pub token: String,
+    /// `deltas` hold current values rather than missed updates.
+    pub snapshot: bool,
+    pub deltas: Vec<crate::state::watchmaster::WatchDelta>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct RedisResult {
     #[serde(with = "serde_arc_str")]
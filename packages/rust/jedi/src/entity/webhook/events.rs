@@ -0,0 +1,389 @@
+//! Typed webhook payloads.
+//!
+//! Forgejo's payloads follow GitHub's shape, so one set of structs covers
+//! both; fields only one side sends are `#[serde(default)]`. Anything not
+//! listed in [`WebhookEvent`] is kept in `Other` as raw JSON, or as a JSON
+//! string holding the body when it isn't JSON (a form-encoded GitHub hook),
+//! so an event nobody handles can't fail the delivery.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::entity::error::JediError;
+use crate::entity::forge::{ForgeKind, ForgeLabel, ForgeUser, RepoRef};
+
+/// One verified, de-duplicated delivery.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub source: ForgeKind,
+    /// `X-GitHub-Delivery` / `X-Forgejo-Delivery`.
+    pub delivery_id: String,
+    /// Event header value, e.g. `"pull_request"`.
+    pub event_name: String,
+    pub repo: Option<RepoRef>,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+    pub event: WebhookEvent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Push(PushEvent),
+    Issues(IssuesEvent),
+    PullRequest(PullRequestEvent),
+    WorkflowRun(WorkflowRunEvent),
+    Release(ReleaseEvent),
+    Ping,
+    Other(Value),
+}
+
+impl WebhookEvent {
+    /// Parse `body` according to the event header.
+    pub fn parse(event_name: &str, body: &[u8]) -> Result<Self, JediError> {
+        fn de<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T, JediError> {
+            serde_json::from_slice(body)
+                .map_err(|e| JediError::Parse(format!("webhook payload: {e}")))
+        }
+
+        Ok(match event_name {
+            "push" => WebhookEvent::Push(de(body)?),
+            "issues" => WebhookEvent::Issues(de(body)?),
+            "pull_request" => WebhookEvent::PullRequest(de(body)?),
+            "workflow_run" => WebhookEvent::WorkflowRun(de(body)?),
+            "release" => WebhookEvent::Release(de(body)?),
+            "ping" => WebhookEvent::Ping,
+            _ => WebhookEvent::Other(
+                serde_json::from_slice(body)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned())),
+            ),
+        })
+    }
+
+    pub fn repository(&self) -> Option<&WebhookRepository> {
+        match self {
+            WebhookEvent::Push(e) => Some(&e.repository),
+            WebhookEvent::Issues(e) => Some(&e.repository),
+            WebhookEvent::PullRequest(e) => Some(&e.repository),
+            WebhookEvent::WorkflowRun(e) => Some(&e.repository),
+            WebhookEvent::Release(e) => Some(&e.repository),
+            WebhookEvent::Ping | WebhookEvent::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookRepository {
+    pub name: String,
+    pub full_name: String,
+    pub owner: ForgeUser,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub default_branch: String,
+    #[serde(default)]
+    pub private: bool,
+}
+
+impl WebhookRepository {
+    pub fn repo_ref(&self) -> RepoRef {
+        RepoRef::new(&self.owner.login, &self.name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookBranch {
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+    #[serde(default)]
+    pub sha: String,
+}
+
+// ── push ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushEvent {
+    /// Full ref, e.g. `refs/heads/main`.
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+    pub before: String,
+    pub after: String,
+    /// GitHub sends `compare`, Forgejo `compare_url`.
+    #[serde(default, alias = "compare_url")]
+    pub compare: String,
+    #[serde(default)]
+    pub created: bool,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub forced: bool,
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+    pub repository: WebhookRepository,
+    #[serde(default)]
+    pub sender: Option<ForgeUser>,
+}
+
+impl PushEvent {
+    /// Branch name when this push targets `refs/heads/*`.
+    pub fn branch(&self) -> Option<&str> {
+        self.ref_name.strip_prefix("refs/heads/")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushCommit {
+    pub id: String,
+    pub message: String,
+    #[serde(default)]
+    pub url: String,
+    pub author: CommitAuthor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitAuthor {
+    pub name: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+// ── issues ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuesEvent {
+    /// `opened`, `closed`, `reopened`, `edited`, `labeled`, ...
+    pub action: String,
+    pub issue: WebhookIssue,
+    /// The label added/removed, for `labeled` / `unlabeled` (GitHub only).
+    #[serde(default)]
+    pub label: Option<ForgeLabel>,
+    pub repository: WebhookRepository,
+    #[serde(default)]
+    pub sender: Option<ForgeUser>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookIssue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    pub state: String,
+    #[serde(default)]
+    pub user: Option<ForgeUser>,
+    #[serde(default)]
+    pub labels: Vec<ForgeLabel>,
+    #[serde(default)]
+    pub assignees: Option<Vec<ForgeUser>>,
+    #[serde(default)]
+    pub html_url: String,
+}
+
+// ── pull_request ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestEvent {
+    /// `opened`, `closed`, `synchronize` (GitHub) / `synchronized` (Forgejo), ...
+    pub action: String,
+    pub number: u64,
+    pub pull_request: WebhookPull,
+    pub repository: WebhookRepository,
+    #[serde(default)]
+    pub sender: Option<ForgeUser>,
+}
+
+impl PullRequestEvent {
+    /// `closed` with the PR merged.
+    pub fn is_merge(&self) -> bool {
+        self.action == "closed" && self.pull_request.merged
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPull {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    pub state: String,
+    #[serde(default)]
+    pub merged: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub user: Option<ForgeUser>,
+    pub head: WebhookBranch,
+    pub base: WebhookBranch,
+    #[serde(default)]
+    pub labels: Vec<ForgeLabel>,
+    #[serde(default)]
+    pub html_url: String,
+}
+
+// ── workflow_run ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRunEvent {
+    /// `requested`, `in_progress`, `completed`.
+    pub action: String,
+    pub workflow_run: WebhookWorkflowRun,
+    pub repository: WebhookRepository,
+    #[serde(default)]
+    pub sender: Option<ForgeUser>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookWorkflowRun {
+    pub id: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+    #[serde(default)]
+    pub run_number: u64,
+    #[serde(default)]
+    pub event: String,
+    #[serde(default)]
+    pub head_branch: Option<String>,
+    #[serde(default)]
+    pub head_sha: String,
+    #[serde(default)]
+    pub html_url: String,
+}
+
+// ── release ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseEvent {
+    /// `published`, `created`, `updated`, `deleted`, ...
+    pub action: String,
+    pub release: WebhookRelease,
+    pub repository: WebhookRepository,
+    #[serde(default)]
+    pub sender: Option<ForgeUser>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookRelease {
+    pub id: u64,
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub author: Option<ForgeUser>,
+    #[serde(default)]
+    pub html_url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repository() -> Value {
+        json!({
+            "name": "kbve",
+            "full_name": "KBVE/kbve",
+            "owner": {"login": "KBVE"},
+            "html_url": "https://github.com/KBVE/kbve"
+        })
+    }
+
+    #[test]
+    fn parses_github_push() {
+        let body = json!({
+            "ref": "refs/heads/dev",
+            "before": "a",
+            "after": "b",
+            "compare": "https://github.com/KBVE/kbve/compare/a...b",
+            "commits": [{
+                "id": "b",
+                "message": "fix",
+                "url": "u",
+                "author": {"name": "dev", "email": "d@kbve.com", "username": "dev"}
+            }],
+            "repository": repository(),
+            "sender": {"login": "dev"}
+        });
+        let WebhookEvent::Push(push) =
+            WebhookEvent::parse("push", body.to_string().as_bytes()).unwrap()
+        else {
+            panic!("expected push");
+        };
+        assert_eq!(push.branch(), Some("dev"));
+        assert_eq!(push.commits[0].author.username.as_deref(), Some("dev"));
+        assert_eq!(push.repository.repo_ref(), RepoRef::new("KBVE", "kbve"));
+    }
+
+    #[test]
+    fn parses_forgejo_push_compare_url() {
+        let body = json!({
+            "ref": "refs/tags/v1.0.0",
+            "before": "0000",
+            "after": "b",
+            "compare_url": "https://git.kbve.com/KBVE/kbve/compare/0000...b",
+            "commits": [],
+            "repository": repository()
+        });
+        let WebhookEvent::Push(push) =
+            WebhookEvent::parse("push", body.to_string().as_bytes()).unwrap()
+        else {
+            panic!("expected push");
+        };
+        assert!(push.compare.ends_with("0000...b"));
+        assert_eq!(push.branch(), None);
+    }
+
+    #[test]
+    fn parses_merged_pull_request() {
+        let body = json!({
+            "action": "closed",
+            "number": 9,
+            "pull_request": {
+                "number": 9,
+                "title": "forge trait",
+                "state": "closed",
+                "merged": true,
+                "head": {"ref": "feature", "sha": "abc"},
+                "base": {"ref": "dev", "sha": "def"}
+            },
+            "repository": repository()
+        });
+        let WebhookEvent::PullRequest(pr) =
+            WebhookEvent::parse("pull_request", body.to_string().as_bytes()).unwrap()
+        else {
+            panic!("expected pull_request");
+        };
+        assert!(pr.is_merge());
+        assert_eq!(pr.pull_request.base.ref_name, "dev");
+    }
+
+    #[test]
+    fn unknown_events_keep_raw_json_and_bad_payloads_fail() {
+        let event = WebhookEvent::parse("star", br#"{"action":"created"}"#).unwrap();
+        assert!(matches!(event, WebhookEvent::Other(v) if v["action"] == "created"));
+        let event = WebhookEvent::parse("star", b"payload=%7B%7D").unwrap();
+        assert!(matches!(event, WebhookEvent::Other(Value::String(s)) if s == "payload=%7B%7D"));
+        assert!(matches!(
+            WebhookEvent::parse("star", b"[1,2]"),
+            Ok(WebhookEvent::Other(Value::Array(_)))
+        ));
+        assert!(matches!(
+            WebhookEvent::parse("issues", br#"{"action":"opened"}"#),
+            Err(JediError::Parse(_))
+        ));
+        assert!(matches!(
+            WebhookEvent::parse("ping", b"{}"),
+            Ok(WebhookEvent::Ping)
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+//! Inbound GitHub / Forgejo webhooks: HMAC-SHA256 signature checks, typed
+//! events, delivery-ID replay protection and fan-out to a tokio broadcast
+//! channel plus any number of [`WebhookSink`]s (with `valkey`, the
+//! [`EnvelopeSink`] feeds the envelope pipeline).
+//!
+//! ```ignore
+//! let receiver = WebhookReceiver::new(WebhookConfig::from_env())
+//!     .with_guard(Arc::new(ValkeyDeliveryGuard::new(pool, "kbve:webhook", ttl)))
+//!     .with_sink(Arc::new(EnvelopeSink::from_temple(&temple, "kbve:webhooks")));
+//! let mut events = receiver.subscribe();
+//! let app = Router::new().nest("/webhooks", receiver.router());
+//!
+//! while let Ok(delivery) = events.recv().await {
+//!     if let WebhookEvent::PullRequest(pr) = &delivery.event && pr.is_merge() { /* ... */ }
+//! }
+//! ```
+
+mod events;
+mod replay;
+mod router;
+mod signature;
+mod sink;
+
+pub use events::*;
+pub use replay::*;
+pub use router::*;
+pub use signature::*;
+pub use sink::*;
@@ -0,0 +1,291 @@
+//! Delivery-ID replay protection.
+//!
+//! Both forges redeliver on timeouts and let operators hit "Redeliver" by
+//! hand, re-sending the same `X-*-Delivery` ID. A claim first takes a short
+//! in-progress lease on the ID, which is only turned into a "done" record
+//! for the full replay window once the delivery has been handled. A crash
+//! mid-delivery therefore blocks redeliveries for the lease, not the window.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::entity::error::JediError;
+
+/// Default window during which a delivery ID is remembered.
+pub const DEFAULT_REPLAY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default lifetime of an in-progress claim. Outlives any sane fan-out;
+/// after it a crashed attempt's delivery can be claimed again.
+pub const DEFAULT_CLAIM_LEASE: Duration = Duration::from_secs(2 * 60);
+
+/// What a [`DeliveryGuard::claim`] found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Claim {
+    /// First sight (or the previous lease lapsed): the caller now holds it.
+    New,
+    /// Another attempt holds the lease and hasn't finished yet.
+    InProgress,
+    /// Already handled inside the replay window.
+    Done,
+}
+
+#[async_trait]
+pub trait DeliveryGuard: Send + Sync {
+    /// Lease `delivery_id` for the in-progress window, unless another
+    /// attempt holds it or it is already done.
+    async fn claim(&self, delivery_id: &str) -> Result<Claim, JediError>;
+
+    /// Turn a lease into a "done" record for the full replay window.
+    async fn complete(&self, delivery_id: &str) -> Result<(), JediError>;
+
+    /// Forget a claim, so a delivery that failed downstream can be retried.
+    async fn release(&self, delivery_id: &str) -> Result<(), JediError>;
+}
+
+/// Per-process guard. Fine for a single replica; use
+/// [`ValkeyDeliveryGuard`] when several pods share one webhook URL.
+///
+/// At most `capacity` IDs are remembered. Expired entries go first, but
+/// once every entry is still live the one closest to expiry is dropped,
+/// and that can be a "done" record: a redelivery of its ID is then handled
+/// again. Size `capacity` above the deliveries expected per `ttl`; each
+/// such eviction is logged.
+pub struct MemoryDeliveryGuard {
+    /// Expiry and whether the entry is done (vs. a lease).
+    seen: Mutex<HashMap<String, (Instant, bool)>>,
+    ttl: Duration,
+    lease: Duration,
+    capacity: usize,
+}
+
+impl MemoryDeliveryGuard {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            seen: Mutex::new(HashMap::new()),
+            ttl,
+            lease: DEFAULT_CLAIM_LEASE.min(ttl),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryDeliveryGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_TTL, 10_000)
+    }
+}
+
+#[async_trait]
+impl DeliveryGuard for MemoryDeliveryGuard {
+    async fn claim(&self, delivery_id: &str) -> Result<Claim, JediError> {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some((expires, done)) = seen.get(delivery_id)
+            && now < *expires
+        {
+            return Ok(if *done {
+                Claim::Done
+            } else {
+                Claim::InProgress
+            });
+        }
+
+        if seen.len() >= self.capacity {
+            seen.retain(|_, (expires, _)| now < *expires);
+        }
+        // Still full: drop the soonest to expire so the newest delivery is
+        // tracked.
+        if seen.len() >= self.capacity
+            && let Some(oldest) = seen
+                .iter()
+                .min_by_key(|(_, (expires, _))| *expires)
+                .map(|(k, _)| k.clone())
+        {
+            if let Some((_, true)) = seen.remove(&oldest) {
+                tracing::warn!(
+                    delivery_id = %oldest,
+                    capacity = self.capacity,
+                    "[Webhook] replay guard full; forgot a handled delivery before its ttl"
+                );
+            }
+        }
+
+        seen.insert(delivery_id.to_string(), (now + self.lease, false));
+        Ok(Claim::New)
+    }
+
+    async fn complete(&self, delivery_id: &str) -> Result<(), JediError> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(delivery_id.to_string(), (Instant::now() + self.ttl, true));
+        Ok(())
+    }
+
+    async fn release(&self, delivery_id: &str) -> Result<(), JediError> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(delivery_id);
+        Ok(())
+    }
+}
+
+/// Shared guard backed by `SET key pending NX EX lease`, overwritten with
+/// `done` for the replay window on completion, so every replica behind the
+/// same webhook URL agrees on what has been seen.
+#[cfg(feature = "valkey")]
+pub struct ValkeyDeliveryGuard {
+    pool: fred::clients::Pool,
+    prefix: String,
+    ttl: Duration,
+    lease: Duration,
+}
+
+#[cfg(feature = "valkey")]
+impl ValkeyDeliveryGuard {
+    pub fn new(pool: fred::clients::Pool, prefix: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            pool,
+            prefix: prefix.into(),
+            ttl,
+            lease: DEFAULT_CLAIM_LEASE.min(ttl),
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    fn key(&self, delivery_id: &str) -> String {
+        format!("{}:{}", self.prefix, delivery_id)
+    }
+}
+
+#[cfg(feature = "valkey")]
+#[async_trait]
+impl DeliveryGuard for ValkeyDeliveryGuard {
+    async fn claim(&self, delivery_id: &str) -> Result<Claim, JediError> {
+        use fred::prelude::*;
+        use fred::types::{Expiration, SetOptions};
+
+        let key = self.key(delivery_id);
+        // `SET NX` answers `OK` when it wrote and nil when the key existed.
+        let set = self
+            .pool
+            .set::<Option<String>, _, _>(
+                &key,
+                "pending",
+                Some(Expiration::EX(self.lease.as_secs().max(1) as i64)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await?;
+        if set.is_some() {
+            return Ok(Claim::New);
+        }
+        // A lease that lapsed between the two calls reads as in progress;
+        // the forge's next retry claims it.
+        let state = self.pool.get::<Option<String>, _>(&key).await?;
+        Ok(match state.as_deref() {
+            Some("done") => Claim::Done,
+            _ => Claim::InProgress,
+        })
+    }
+
+    async fn complete(&self, delivery_id: &str) -> Result<(), JediError> {
+        use fred::prelude::*;
+        use fred::types::Expiration;
+
+        self.pool
+            .set::<(), _, _>(
+                self.key(delivery_id),
+                "done",
+                Some(Expiration::EX(self.ttl.as_secs().max(1) as i64)),
+                None,
+                false,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn release(&self, delivery_id: &str) -> Result<(), JediError> {
+        use fred::prelude::*;
+
+        self.pool.del::<u64, _>(self.key(delivery_id)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn duplicate_claims_are_rejected_until_released() {
+        let guard = MemoryDeliveryGuard::default();
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::InProgress);
+        assert_eq!(guard.claim("b").await.unwrap(), Claim::New);
+
+        guard.release("a").await.unwrap();
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        guard.complete("a").await.unwrap();
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::Done);
+    }
+
+    #[tokio::test]
+    async fn lapsed_leases_and_expired_records_are_accepted_again() {
+        let guard = MemoryDeliveryGuard::new(Duration::from_millis(50), 16)
+            .with_lease(Duration::from_millis(10));
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        guard.complete("a").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::Done);
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+    }
+
+    #[tokio::test]
+    async fn capacity_evicts_oldest() {
+        let guard = MemoryDeliveryGuard::new(DEFAULT_REPLAY_TTL, 2);
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(guard.claim("b").await.unwrap(), Claim::New);
+        assert_eq!(guard.claim("c").await.unwrap(), Claim::New);
+        assert_eq!(guard.len(), 2);
+        assert_eq!(guard.claim("c").await.unwrap(), Claim::InProgress);
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+    }
+
+    #[tokio::test]
+    async fn capacity_can_evict_a_done_record() {
+        let guard = MemoryDeliveryGuard::new(Duration::from_secs(60), 1)
+            .with_lease(Duration::from_secs(30));
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+        guard.complete("a").await.unwrap();
+        assert_eq!(guard.claim("b").await.unwrap(), Claim::New);
+        // "a" was dropped to make room, so its redelivery runs again.
+        assert_eq!(guard.claim("a").await.unwrap(), Claim::New);
+    }
+}
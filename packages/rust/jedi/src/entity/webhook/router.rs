@@ -0,0 +1,506 @@
+//! The axum router and the accept path shared by both forges.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::HeaderMap,
+    routing::post,
+};
+use serde_json::{Value, json};
+use tokio::sync::broadcast;
+
+use super::events::{WebhookDelivery, WebhookEvent};
+use super::replay::{Claim, DEFAULT_REPLAY_TTL, DeliveryGuard, MemoryDeliveryGuard};
+use super::signature::{verify_forgejo, verify_github};
+use super::sink::WebhookSink;
+use crate::entity::error::JediError;
+use crate::entity::forge::ForgeKind;
+use crate::state::sidecar::get_env;
+
+/// GitHub caps payloads at 25 MB, but anything near that is a push with
+/// thousands of commits; 5 MiB covers real traffic.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+
+/// Secrets are per source; a source without one is not mounted (its route
+/// answers `404`). No `Debug`, so secrets can't end up in logs.
+#[derive(Clone)]
+pub struct WebhookConfig {
+    pub github_secret: Option<String>,
+    pub forgejo_secret: Option<String>,
+    /// How long a delivery ID is remembered by the default guard.
+    pub replay_ttl: Duration,
+    pub max_body_bytes: usize,
+    /// Capacity of the in-process broadcast channel.
+    pub channel_capacity: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            github_secret: None,
+            forgejo_secret: None,
+            replay_ttl: DEFAULT_REPLAY_TTL,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            channel_capacity: 256,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// `WEBHOOK_GITHUB_SECRET`, `WEBHOOK_FORGEJO_SECRET` (each also readable
+    /// via `*_FILE`), `WEBHOOK_REPLAY_TTL_S` and `WEBHOOK_MAX_BODY_BYTES`.
+    pub fn from_env() -> Self {
+        let secret = |key: &str| Some(get_env(key, "")).filter(|s| !s.trim().is_empty());
+        Self {
+            github_secret: secret("WEBHOOK_GITHUB_SECRET"),
+            forgejo_secret: secret("WEBHOOK_FORGEJO_SECRET"),
+            replay_ttl: get_env("WEBHOOK_REPLAY_TTL_S", "")
+                .parse()
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_REPLAY_TTL),
+            max_body_bytes: get_env("WEBHOOK_MAX_BODY_BYTES", "")
+                .parse()
+                .unwrap_or(DEFAULT_MAX_BODY_BYTES),
+            ..Self::default()
+        }
+    }
+
+    fn secret(&self, source: ForgeKind) -> Option<&[u8]> {
+        match source {
+            ForgeKind::GitHub => self.github_secret.as_deref(),
+            ForgeKind::Forgejo => self.forgejo_secret.as_deref(),
+        }
+        .map(str::as_bytes)
+    }
+}
+
+/// Header names per source, in lookup order. Forgejo still sends the
+/// `X-Gitea-*` names alongside its own, and plain Gitea only sends those.
+struct Headers {
+    event: &'static [&'static str],
+    delivery: &'static [&'static str],
+    signature: &'static [&'static str],
+}
+
+const GITHUB_HEADERS: Headers = Headers {
+    event: &["x-github-event"],
+    delivery: &["x-github-delivery"],
+    signature: &["x-hub-signature-256"],
+};
+
+const FORGEJO_HEADERS: Headers = Headers {
+    event: &["x-forgejo-event", "x-gitea-event"],
+    delivery: &["x-forgejo-delivery", "x-gitea-delivery"],
+    signature: &["x-forgejo-signature", "x-gitea-signature"],
+};
+
+fn header<'h>(headers: &'h HeaderMap, names: &[&str]) -> Option<&'h str> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Verifies, de-duplicates and fans out forge webhooks.
+///
+/// Accepted deliveries are handed to every [`WebhookSink`] in order, then
+/// broadcast to every [`subscribe`](Self::subscribe) receiver. Mount with
+/// [`router`](Self::router), e.g. under `/webhooks`:
+///
+/// ```ignore
+/// let receiver = WebhookReceiver::new(WebhookConfig::from_env());
+/// let mut events = receiver.subscribe();
+/// let app = Router::new().nest("/webhooks", receiver.router());
+/// ```
+pub struct WebhookReceiver {
+    config: WebhookConfig,
+    guard: Arc<dyn DeliveryGuard>,
+    sinks: Vec<Arc<dyn WebhookSink>>,
+    events: broadcast::Sender<Arc<WebhookDelivery>>,
+}
+
+impl WebhookReceiver {
+    /// Receiver with an in-memory replay guard and no sinks.
+    pub fn new(config: WebhookConfig) -> Self {
+        let (events, _) = broadcast::channel(config.channel_capacity.max(1));
+        Self {
+            guard: Arc::new(MemoryDeliveryGuard::new(config.replay_ttl, 10_000)),
+            sinks: Vec::new(),
+            events,
+            config,
+        }
+    }
+
+    pub fn with_guard(mut self, guard: Arc<dyn DeliveryGuard>) -> Self {
+        self.guard = guard;
+        self
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn WebhookSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Receive every accepted delivery. Slow subscribers lag (and miss
+    /// deliveries) rather than back-pressure the forge.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<WebhookDelivery>> {
+        self.events.subscribe()
+    }
+
+    /// `POST /github` and `POST /forgejo`.
+    pub fn router(self) -> Router {
+        let limit = self.config.max_body_bytes;
+        Router::new()
+            .route("/github", post(github_hook))
+            .route("/forgejo", post(forgejo_hook))
+            .layer(DefaultBodyLimit::max(limit))
+            .with_state(Arc::new(self))
+    }
+
+    /// Run one request through verification, replay protection and fan-out.
+    /// Returns the JSON body to answer with.
+    pub async fn accept(
+        &self,
+        source: ForgeKind,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Value, JediError> {
+        let Some(secret) = self.config.secret(source) else {
+            return Err(JediError::NotFound);
+        };
+        let names = match source {
+            ForgeKind::GitHub => &GITHUB_HEADERS,
+            ForgeKind::Forgejo => &FORGEJO_HEADERS,
+        };
+
+        let signature = header(headers, names.signature);
+        let verified = match source {
+            ForgeKind::GitHub => verify_github(secret, signature, body),
+            ForgeKind::Forgejo => verify_forgejo(secret, signature, body),
+        };
+        if !verified {
+            tracing::warn!(source = source.as_str(), "[Webhook] signature mismatch");
+            return Err(JediError::Unauthorized);
+        }
+
+        let event_name = header(headers, names.event)
+            .ok_or_else(|| JediError::BadRequest("missing event header".into()))?;
+        let delivery_id = header(headers, names.delivery)
+            .ok_or_else(|| JediError::BadRequest("missing delivery header".into()))?;
+
+        let event = WebhookEvent::parse(event_name, body)?;
+
+        // Namespaced by source so GitHub and Forgejo IDs can't collide.
+        let claim_key = format!("{}:{}", source.as_str(), delivery_id);
+        match self.guard.claim(&claim_key).await? {
+            Claim::New => {}
+            Claim::Done => {
+                tracing::debug!(delivery_id, "[Webhook] duplicate delivery ignored");
+                return Ok(json!({ "ok": true, "duplicate": true }));
+            }
+            // Fail instead of acknowledging: if the running attempt fails,
+            // the forge still has this one to retry.
+            Claim::InProgress => {
+                tracing::debug!(delivery_id, "[Webhook] delivery already in progress");
+                return Err(JediError::Internal(
+                    "webhook delivery already in progress".into(),
+                ));
+            }
+        }
+
+        let delivery = Arc::new(WebhookDelivery {
+            source,
+            delivery_id: delivery_id.to_string(),
+            event_name: event_name.to_string(),
+            repo: event.repository().map(|r| r.repo_ref()),
+            received_at: chrono::Utc::now(),
+            event,
+        });
+
+        if let Err(e) = self.fan_out(&claim_key, &delivery).await {
+            // Let the forge's retry through instead of dropping it as a duplicate.
+            self.release(&claim_key).await;
+            return Err(e);
+        }
+        self.guard.complete(&claim_key).await?;
+
+        // No subscribers is fine; the sinks already have it.
+        let _ = self.events.send(delivery);
+
+        Ok(json!({ "ok": true, "event": event_name }))
+    }
+
+    /// Hand `delivery` to each sink that hasn't already taken it. Completion
+    /// is recorded per sink in the same guard, keyed by position and name, so
+    /// a retry after a partial failure resumes at the sink that failed.
+    async fn fan_out(&self, claim_key: &str, delivery: &WebhookDelivery) -> Result<(), JediError> {
+        for (i, sink) in self.sinks.iter().enumerate() {
+            let done = format!("{claim_key}/sink/{i}/{}", sink.name());
+            match self.guard.claim(&done).await? {
+                Claim::New => {}
+                Claim::Done => continue,
+                Claim::InProgress => {
+                    return Err(JediError::Internal(
+                        "webhook sink delivery already in progress".into(),
+                    ));
+                }
+            }
+            if let Err(e) = sink.deliver(delivery).await {
+                self.release(&done).await;
+                return Err(e);
+            }
+            self.guard.complete(&done).await?;
+        }
+        Ok(())
+    }
+
+    async fn release(&self, id: &str) {
+        if let Err(e) = self.guard.release(id).await {
+            tracing::warn!(error = %e, id, "[Webhook] claim release failed");
+        }
+    }
+}
+
+async fn github_hook(
+    State(receiver): State<Arc<WebhookReceiver>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, JediError> {
+    receiver
+        .accept(ForgeKind::GitHub, &headers, &body)
+        .await
+        .map(Json)
+}
+
+async fn forgejo_hook(
+    State(receiver): State<Arc<WebhookReceiver>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, JediError> {
+    receiver
+        .accept(ForgeKind::Forgejo, &headers, &body)
+        .await
+        .map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::webhook::signature::{sign_github, sign_hex};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "hush";
+
+    fn config() -> WebhookConfig {
+        WebhookConfig {
+            github_secret: Some(SECRET.into()),
+            forgejo_secret: Some(SECRET.into()),
+            ..WebhookConfig::default()
+        }
+    }
+
+    fn issues_body() -> Vec<u8> {
+        json!({
+            "action": "opened",
+            "issue": {"number": 3, "title": "bug", "state": "open", "labels": []},
+            "repository": {"name": "kbve", "full_name": "KBVE/kbve", "owner": {"login": "KBVE"}}
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn github_request(body: &[u8], delivery: &str, signature: &str) -> Request<Body> {
+        Request::post("/github")
+            .header("x-github-event", "issues")
+            .header("x-github-delivery", delivery)
+            .header("x-hub-signature-256", signature)
+            .body(Body::from(body.to_vec()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn github_delivery_is_broadcast_once() {
+        let receiver = WebhookReceiver::new(config());
+        let mut events = receiver.subscribe();
+        let app = receiver.router();
+        let body = issues_body();
+        let sig = sign_github(SECRET.as_bytes(), &body);
+
+        let res = app
+            .clone()
+            .oneshot(github_request(&body, "d-1", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let delivery = events.recv().await.unwrap();
+        assert_eq!(delivery.delivery_id, "d-1");
+        assert_eq!(delivery.repo.as_ref().unwrap().to_string(), "KBVE/kbve");
+        assert!(matches!(&delivery.event, WebhookEvent::Issues(e) if e.issue.number == 3));
+
+        // Redelivery: acknowledged, not re-broadcast.
+        let res = app
+            .oneshot(github_request(&body, "d-1", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn bad_signature_and_unconfigured_source_are_rejected() {
+        let body = issues_body();
+        let app = WebhookReceiver::new(config()).router();
+        let res = app
+            .oneshot(github_request(&body, "d-2", &sign_github(b"nope", &body)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let app = WebhookReceiver::new(WebhookConfig::default()).router();
+        let sig = sign_github(SECRET.as_bytes(), &body);
+        let res = app
+            .oneshot(github_request(&body, "d-3", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn forgejo_falls_back_to_gitea_headers() {
+        let receiver = WebhookReceiver::new(config());
+        let mut events = receiver.subscribe();
+        let body = issues_body();
+        let req = Request::post("/forgejo")
+            .header("x-gitea-event", "issues")
+            .header("x-gitea-delivery", "f-1")
+            .header("x-gitea-signature", sign_hex(SECRET.as_bytes(), &body))
+            .body(Body::from(body))
+            .unwrap();
+
+        let res = receiver.router().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(events.recv().await.unwrap().source, ForgeKind::Forgejo);
+    }
+
+    #[tokio::test]
+    async fn in_progress_delivery_is_not_acknowledged() {
+        let guard = Arc::new(MemoryDeliveryGuard::default());
+        let app = WebhookReceiver::new(config())
+            .with_guard(guard.clone())
+            .router();
+        let body = issues_body();
+        let sig = sign_github(SECRET.as_bytes(), &body);
+
+        // Another attempt holds the lease: the forge must keep its retry.
+        guard.claim("github:d-6").await.unwrap();
+        let res = app
+            .clone()
+            .oneshot(github_request(&body, "d-6", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        guard.release("github:d-6").await.unwrap();
+        let res = app
+            .oneshot(github_request(&body, "d-6", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(guard.claim("github:d-6").await.unwrap(), Claim::Done);
+        // Same ID from the other forge is a different delivery.
+        assert_eq!(guard.claim("forgejo:d-6").await.unwrap(), Claim::New);
+    }
+
+    struct FlakySink(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl WebhookSink for FlakySink {
+        async fn deliver(&self, _: &WebhookDelivery) -> Result<(), JediError> {
+            if self.0.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(JediError::Internal("down".into()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    struct CountingSink(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl WebhookSink for CountingSink {
+        async fn deliver(&self, _: &WebhookDelivery) -> Result<(), JediError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_skips_sinks_that_already_took_the_delivery() {
+        let first = Arc::new(CountingSink(AtomicUsize::new(0)));
+        let flaky = Arc::new(FlakySink(AtomicUsize::new(0)));
+        let app = WebhookReceiver::new(config())
+            .with_sink(first.clone())
+            .with_sink(flaky.clone())
+            .router();
+        let body = issues_body();
+        let sig = sign_github(SECRET.as_bytes(), &body);
+
+        let res = app
+            .clone()
+            .oneshot(github_request(&body, "d-5", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let res = app
+            .clone()
+            .oneshot(github_request(&body, "d-5", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(first.0.load(Ordering::SeqCst), 1);
+        assert_eq!(flaky.0.load(Ordering::SeqCst), 2);
+
+        // Fully delivered: a further redelivery reaches neither sink.
+        let res = app
+            .oneshot(github_request(&body, "d-5", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(first.0.load(Ordering::SeqCst), 1);
+        assert_eq!(flaky.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn sink_failure_releases_claim_for_retry() {
+        let sink = Arc::new(FlakySink(AtomicUsize::new(0)));
+        let app = WebhookReceiver::new(config())
+            .with_sink(sink.clone())
+            .router();
+        let body = issues_body();
+        let sig = sign_github(SECRET.as_bytes(), &body);
+
+        let res = app
+            .clone()
+            .oneshot(github_request(&body, "d-4", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let res = app
+            .oneshot(github_request(&body, "d-4", &sig))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(sink.0.load(Ordering::SeqCst), 2);
+    }
+}
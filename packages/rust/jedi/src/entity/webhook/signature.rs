@@ -0,0 +1,122 @@
+//! HMAC-SHA256 body signatures.
+//!
+//! GitHub sends `X-Hub-Signature-256: sha256=<hex>`; Forgejo (and Gitea)
+//! send `X-Forgejo-Signature` / `X-Gitea-Signature` as bare hex. Both are
+//! the HMAC of the raw request body keyed with the webhook secret.
+
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verify a GitHub `X-Hub-Signature-256` header value.
+pub fn verify_github(secret: &[u8], header: Option<&str>, body: &[u8]) -> bool {
+    header
+        .and_then(|h| h.trim().strip_prefix("sha256="))
+        .is_some_and(|hex| verify_hex(secret, hex, body))
+}
+
+/// Verify a Forgejo `X-Forgejo-Signature` header value. A `sha256=` prefix
+/// is tolerated so proxies that normalise to GitHub's format still pass.
+pub fn verify_forgejo(secret: &[u8], header: Option<&str>, body: &[u8]) -> bool {
+    header
+        .map(|h| {
+            let h = h.trim();
+            h.strip_prefix("sha256=").unwrap_or(h)
+        })
+        .is_some_and(|hex| verify_hex(secret, hex, body))
+}
+
+/// `sha256=<hex>` signature for `body`, as GitHub would send it.
+pub fn sign_github(secret: &[u8], body: &[u8]) -> String {
+    format!("sha256={}", sign_hex(secret, body))
+}
+
+/// Bare hex signature for `body`, as Forgejo would send it.
+pub fn sign_hex(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn verify_hex(secret: &[u8], hex: &str, body: &[u8]) -> bool {
+    let Some(expected) = decode_hex(hex) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    // Constant-time comparison.
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            u8::try_from(hi * 16 + lo).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example from GitHub's "Validating webhook deliveries" docs.
+    const SECRET: &[u8] = b"It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const DIGEST: &str = "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn github_reference_vector() {
+        assert_eq!(sign_hex(SECRET, BODY), DIGEST);
+        assert!(verify_github(
+            SECRET,
+            Some(&format!("sha256={DIGEST}")),
+            BODY
+        ));
+        assert!(verify_github(
+            SECRET,
+            Some(&format!("sha256={}", DIGEST.to_uppercase())),
+            BODY
+        ));
+    }
+
+    #[test]
+    fn github_rejects_bad_or_missing_signatures() {
+        assert!(!verify_github(SECRET, None, BODY));
+        assert!(!verify_github(SECRET, Some(DIGEST), BODY));
+        assert!(!verify_github(SECRET, Some("sha256=zz"), BODY));
+        assert!(!verify_github(
+            b"wrong",
+            Some(&sign_github(SECRET, BODY)),
+            BODY
+        ));
+        assert!(!verify_github(
+            SECRET,
+            Some(&sign_github(SECRET, BODY)),
+            b"Hello, World?"
+        ));
+    }
+
+    #[test]
+    fn forgejo_accepts_bare_hex() {
+        assert!(verify_forgejo(SECRET, Some(DIGEST), BODY));
+        assert!(verify_forgejo(
+            SECRET,
+            Some(&format!("sha256={DIGEST}")),
+            BODY
+        ));
+        assert!(!verify_forgejo(SECRET, Some(&DIGEST[..62]), BODY));
+        assert!(!verify_forgejo(SECRET, None, BODY));
+    }
+}
@@ -0,0 +1,156 @@
+//! Where accepted deliveries go after the in-process broadcast.
+
+use async_trait::async_trait;
+
+use super::events::WebhookDelivery;
+use crate::entity::error::JediError;
+
+/// Downstream consumer of verified deliveries. A sink error releases the
+/// delivery's replay claim and fails the request, so the forge retries it.
+/// Sinks that already took the delivery are skipped on that retry, so each
+/// sink sees a delivery once per replay window even when a later one fails.
+#[async_trait]
+pub trait WebhookSink: Send + Sync {
+    async fn deliver(&self, delivery: &WebhookDelivery) -> Result<(), JediError>;
+
+    /// Identifies the sink in its per-delivery completion record.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Forwards each delivery into the envelope pipeline as a JSON `XADD` on
+/// `stream`, with fields `source`, `event`, `delivery` and `payload`, so
+/// consumers on other replicas can pick it up. Waits for the worker's reply,
+/// so a failed `XADD` fails the delivery instead of being acknowledged.
+#[cfg(feature = "valkey")]
+pub struct EnvelopeSink {
+    envelope_tx: tokio::sync::mpsc::Sender<crate::entity::envelope::EnvelopeWorkItem>,
+    stream: String,
+}
+
+#[cfg(feature = "valkey")]
+impl EnvelopeSink {
+    pub fn new(
+        envelope_tx: tokio::sync::mpsc::Sender<crate::entity::envelope::EnvelopeWorkItem>,
+        stream: impl Into<String>,
+    ) -> Self {
+        Self {
+            envelope_tx,
+            stream: stream.into(),
+        }
+    }
+
+    /// Share the worker queue of an existing [`crate::state::temple::TempleState`].
+    pub fn from_temple(
+        temple: &crate::state::temple::TempleState,
+        stream: impl Into<String>,
+    ) -> Self {
+        Self::new(temple.envelope_tx.clone(), stream)
+    }
+}
+
+#[cfg(feature = "valkey")]
+#[async_trait]
+impl WebhookSink for EnvelopeSink {
+    async fn deliver(&self, delivery: &WebhookDelivery) -> Result<(), JediError> {
+        use crate::entity::envelope::{EnvelopeWorkItem, try_unwrap_payload, wrap_hybrid};
+        use crate::proto::jedi::{MessageKind, PayloadFormat};
+
+        let payload = serde_json::to_string(&delivery.event)
+            .map_err(|e| JediError::Internal(format!("webhook encode: {e}").into()))?;
+        let input = serde_json::json!({
+            "stream": self.stream,
+            "fields": {
+                "source": delivery.source.as_str(),
+                "event": delivery.event_name,
+                "delivery": delivery.delivery_id,
+                "payload": payload,
+            },
+        });
+        let envelope = wrap_hybrid(
+            MessageKind::Add as i32 | MessageKind::Redis as i32 | MessageKind::Stream as i32,
+            PayloadFormat::Json,
+            &input,
+            None,
+        );
+
+        let (item, reply) = EnvelopeWorkItem::with_response(envelope);
+        self.envelope_tx
+            .send(item)
+            .await
+            .map_err(|_| JediError::Internal("envelope worker channel closed".into()))?;
+        let reply = reply
+            .await
+            .map_err(|_| JediError::Internal("envelope worker dropped the reply".into()))?;
+
+        if MessageKind::has_flag(reply.kind, MessageKind::Error) {
+            let reason = try_unwrap_payload::<serde_json::Value>(&reply)
+                .ok()
+                .and_then(|v| v.get("error")?.as_str().map(str::to_owned))
+                .unwrap_or_else(|| "unknown error".into());
+            return Err(JediError::Internal(
+                format!("webhook XADD to {}: {reason}", self.stream).into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "valkey"))]
+mod tests {
+    use super::*;
+    use crate::entity::envelope::{EnvelopeWorkItem, try_unwrap_payload};
+    use crate::entity::forge::ForgeKind;
+    use crate::entity::webhook::WebhookEvent;
+    use crate::proto::jedi::{JediEnvelope, PayloadFormat};
+
+    fn delivery() -> WebhookDelivery {
+        WebhookDelivery {
+            source: ForgeKind::GitHub,
+            delivery_id: "d-1".into(),
+            event_name: "ping".into(),
+            repo: None,
+            received_at: chrono::Utc::now(),
+            event: WebhookEvent::parse("ping", b"{}").unwrap(),
+        }
+    }
+
+    /// Stand-in for the envelope worker: answers every item with `reply`.
+    fn worker(reply: fn(&JediEnvelope) -> JediEnvelope) -> EnvelopeSink {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<EnvelopeWorkItem>(4);
+        tokio::spawn(async move {
+            while let Some(item) = rx.recv().await {
+                if let Some(tx) = item.response_tx {
+                    let _ = tx.send(reply(&item.envelope));
+                }
+            }
+        });
+        EnvelopeSink::new(tx, "kbve:webhooks")
+    }
+
+    #[tokio::test]
+    async fn deliver_waits_for_the_xadd() {
+        let sink = worker(|env| {
+            let input: serde_json::Value = try_unwrap_payload(env).unwrap();
+            assert_eq!(input["stream"], "kbve:webhooks");
+            assert_eq!(input["fields"]["delivery"], "d-1");
+            env.clone()
+        });
+        sink.deliver(&delivery()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_xadd_fails_the_delivery() {
+        let sink = worker(|env| {
+            JediEnvelope::error_with_meta(
+                "EnvelopeWorker",
+                "READONLY You can't write against a read only replica.",
+                env.metadata_or_empty(),
+                PayloadFormat::Json,
+            )
+        });
+        let err = sink.deliver(&delivery()).await.unwrap_err().to_string();
+        assert!(err.contains("READONLY"), "{err}");
+    }
+}
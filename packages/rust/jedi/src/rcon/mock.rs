@@ -0,0 +1,198 @@
+//! In-process RCON server for deterministic tests.
+//!
+//! Speaks the same framing as a real server: password auth, one response
+//! per EXEC split into `split_size`-byte packets (4096 by default, like
+//! Minecraft), and an empty reply to an empty EXEC so the
+//! [`RconClient::exec_multi`](super::RconClient::exec_multi) sentinel
+//! works. Commands go through a caller-supplied handler.
+//!
+//! Available under `#[cfg(test)]` and, for downstream crates' tests, the
+//! `rcon-mock` feature.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use super::{
+    AUTH_FAILED_ID, PACKET_TYPE_AUTH, PACKET_TYPE_EXEC, RconEndpoint, recv_packet_bytes,
+    send_packet_bytes,
+};
+
+const PACKET_TYPE_RESPONSE: i32 = 0;
+const PACKET_TYPE_AUTH_RESPONSE: i32 = 2;
+const DEFAULT_SPLIT_SIZE: usize = 4096;
+
+type Handler = dyn Fn(&str) -> String + Send + Sync;
+
+struct MockState {
+    password: String,
+    handler: Box<Handler>,
+    split_size: AtomicUsize,
+    connections: AtomicUsize,
+    /// Non-empty commands with the index of the session they arrived on.
+    commands: Mutex<Vec<(usize, String)>>,
+    /// Set by `drop_after_next_command`; cleared by the session it hits.
+    drop_after_command: AtomicBool,
+    /// Bumped by `drop_connections`; every session exits on change.
+    generation: watch::Sender<u64>,
+}
+
+pub struct MockRconServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    task: JoinHandle<()>,
+}
+
+impl MockRconServer {
+    /// Bind on an ephemeral localhost port and start accepting sessions.
+    pub async fn start<F>(password: impl Into<String>, handler: F) -> std::io::Result<Self>
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState {
+            password: password.into(),
+            handler: Box::new(handler),
+            split_size: AtomicUsize::new(DEFAULT_SPLIT_SIZE),
+            connections: AtomicUsize::new(0),
+            commands: Mutex::new(Vec::new()),
+            drop_after_command: AtomicBool::new(false),
+            generation: watch::channel(0).0,
+        });
+
+        let accept_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let session = accept_state.connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(stream, accept_state.clone(), session));
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    pub fn endpoint(&self) -> RconEndpoint {
+        RconEndpoint::new(
+            self.addr.ip().to_string(),
+            self.addr.port(),
+            self.state.password.clone(),
+        )
+    }
+
+    /// Sessions accepted since start.
+    pub fn connections(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+
+    /// Non-empty commands received, in arrival order.
+    pub fn commands(&self) -> Vec<String> {
+        self.state
+            .commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(_, command)| command.clone())
+            .collect()
+    }
+
+    /// Non-empty commands received on the `session`-th accepted session
+    /// (counting from zero), in arrival order.
+    pub fn session_commands(&self, session: usize) -> Vec<String> {
+        self.state
+            .commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(s, _)| *s == session)
+            .map(|(_, command)| command.clone())
+            .collect()
+    }
+
+    /// Body bytes per response packet before the reply is split.
+    pub fn set_split_size(&self, bytes: usize) {
+        self.state.split_size.store(bytes.max(1), Ordering::SeqCst);
+    }
+
+    /// Record the next non-empty command, then close its session without
+    /// replying, as a server crashing mid-command would.
+    pub fn drop_after_next_command(&self) {
+        self.state.drop_after_command.store(true, Ordering::SeqCst);
+    }
+
+    /// Close every open session, as a server restart would. New
+    /// connections are still accepted.
+    pub fn drop_connections(&self) {
+        self.state.generation.send_modify(|g| *g += 1);
+    }
+}
+
+impl Drop for MockRconServer {
+    fn drop(&mut self) {
+        self.drop_connections();
+        self.task.abort();
+    }
+}
+
+async fn serve(mut stream: TcpStream, state: Arc<MockState>, session: usize) {
+    let mut generation = state.generation.subscribe();
+    let mut authed = false;
+    loop {
+        let packet = tokio::select! {
+            _ = generation.changed() => return,
+            packet = recv_packet_bytes(&mut stream) => packet,
+        };
+        let Ok((id, ptype, body)) = packet else {
+            return;
+        };
+        let body = String::from_utf8_lossy(&body).into_owned();
+
+        let sent = match ptype {
+            PACKET_TYPE_AUTH => {
+                authed = body == state.password;
+                let resp_id = if authed { id } else { AUTH_FAILED_ID };
+                send_packet_bytes(&mut stream, resp_id, PACKET_TYPE_AUTH_RESPONSE, b"").await
+            }
+            PACKET_TYPE_EXEC if authed => {
+                let reply = if body.is_empty() {
+                    String::new()
+                } else {
+                    state
+                        .commands
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push((session, body.clone()));
+                    if state.drop_after_command.swap(false, Ordering::SeqCst) {
+                        return;
+                    }
+                    (state.handler)(&body)
+                };
+                send_response(&mut stream, id, reply.as_bytes(), &state).await
+            }
+            _ => return,
+        };
+        if sent.is_err() {
+            return;
+        }
+    }
+}
+
+async fn send_response(
+    stream: &mut TcpStream,
+    id: i32,
+    reply: &[u8],
+    state: &MockState,
+) -> super::RconResult<()> {
+    let split = state.split_size.load(Ordering::SeqCst);
+    if reply.is_empty() {
+        return send_packet_bytes(stream, id, PACKET_TYPE_RESPONSE, b"").await;
+    }
+    for chunk in reply.chunks(split) {
+        send_packet_bytes(stream, id, PACKET_TYPE_RESPONSE, chunk).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,329 @@
+//! Source RCON client — game-agnostic implementation of the Valve Source
+//! protocol (Minecraft, Factorio, and every other game that speaks
+//! length-prefixed TCP RCON).
+//!
+//! Shared so axum-kbve, the discord bot, internal scripts, and future Rust
+//! services all hit the same wire implementation instead of re-rolling the
+//! packet framing.
+//!
+//! Higher layers stay in their own crates:
+//!   * command grammars (MC `list`, Factorio `/silent-command`, …)
+//!   * allowlist / auth gating
+//!   * per-endpoint env-var schemes
+//!   * audit logging
+//!
+//! Only the transport lives here, plus:
+//!   * [`RconPool`] — pooled, health-checked sessions with reconnect backoff
+//!   * [`mock`] — in-process RCON server for deterministic tests
+//!     (`#[cfg(test)]` or the `rcon-mock` feature)
+
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+mod pool;
+#[cfg(any(test, feature = "rcon-mock"))]
+pub mod mock;
+
+pub use pool::{RconPool, RconPoolConfig, RconPoolStatus};
+
+/// Source RCON packet types we send. Server-auth response also reuses these.
+const PACKET_TYPE_AUTH: i32 = 3;
+const PACKET_TYPE_EXEC: i32 = 2;
+
+/// Bounds on the packet `length` field. Source RCON caps a response body
+/// at 4096 bytes, so a full packet is 4096 + 10 bytes of framing; Factorio
+/// ignores the cap and sends large outputs in one packet. Anything outside
+/// [10, 1 MiB] is treated as a corrupt stream rather than allocated.
+const PACKET_MIN_LEN: usize = 10;
+const PACKET_MAX_LEN: usize = 1024 * 1024;
+
+/// Default ceiling on a reassembled multi-packet response.
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Failed-auth sentinel returned by the server in the `id` field per the
+/// Source RCON spec.
+const AUTH_FAILED_ID: i32 = -1;
+
+#[derive(Debug, Error)]
+pub enum RconError {
+    #[error("rcon connect to {addr} timed out after {timeout:?}")]
+    ConnectTimeout { addr: String, timeout: Duration },
+
+    #[error("rcon i/o: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("rcon auth rejected by server")]
+    AuthRejected,
+
+    #[error("rcon packet length {0} out of range [{PACKET_MIN_LEN}, {PACKET_MAX_LEN}]")]
+    PacketLength(usize),
+
+    #[error("rcon response exceeded {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+
+    #[error("rcon exec timed out after {timeout:?}")]
+    ExecTimeout { timeout: Duration },
+
+    #[error("rcon {addr} unavailable, reconnect backoff for {retry_in:?}")]
+    Backoff { addr: String, retry_in: Duration },
+}
+
+pub type RconResult<T> = Result<T, RconError>;
+
+/// Connection params for one RCON endpoint. Cheap to clone — meant to be
+/// built once at startup from env vars / config and reused per call.
+#[derive(Clone, Debug)]
+pub struct RconEndpoint {
+    pub host: String,
+    pub port: u16,
+    pub password: String,
+}
+
+impl RconEndpoint {
+    pub fn new(host: impl Into<String>, port: u16, password: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            password: password.into(),
+        }
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// Authenticated RCON session. Holds the TCP stream and a monotonically
+/// increasing request id. Drop the value to close the connection.
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    /// Connect and authenticate against an endpoint, returning a session
+    /// ready to `exec`.
+    pub async fn connect(ep: &RconEndpoint, connect_timeout: Duration) -> RconResult<Self> {
+        let stream = timeout(connect_timeout, TcpStream::connect(ep.addr()))
+            .await
+            .map_err(|_| RconError::ConnectTimeout {
+                addr: ep.addr(),
+                timeout: connect_timeout,
+            })??;
+
+        let mut client = Self { stream, next_id: 1 };
+        client.authenticate(&ep.password).await?;
+        Ok(client)
+    }
+
+    async fn authenticate(&mut self, password: &str) -> RconResult<()> {
+        let id = self.alloc_id();
+        send_packet(&mut self.stream, id, PACKET_TYPE_AUTH, password).await?;
+        let (resp_id, _, _) = recv_packet(&mut self.stream).await?;
+        if resp_id == AUTH_FAILED_ID {
+            return Err(RconError::AuthRejected);
+        }
+        Ok(())
+    }
+
+    /// Whether the server has already closed this session, checked with a
+    /// non-blocking peek so nothing is written or consumed.
+    pub fn is_closed(&self) -> bool {
+        let mut byte = [0u8; 1];
+        let mut buf = tokio::io::ReadBuf::new(&mut byte);
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        matches!(
+            self.stream.poll_peek(&mut cx, &mut buf),
+            std::task::Poll::Ready(Ok(0) | Err(_))
+        )
+    }
+
+    /// Send an EXEC packet and return the body of the server's response.
+    ///
+    /// Reads exactly one packet, so output the server splits across several
+    /// packets is truncated — use [`RconClient::exec_multi`] for commands
+    /// with long output.
+    pub async fn exec(&mut self, command: &str) -> RconResult<String> {
+        let id = self.alloc_id();
+        send_packet(&mut self.stream, id, PACKET_TYPE_EXEC, command).await?;
+        let (_, _, body) = recv_packet(&mut self.stream).await?;
+        Ok(body)
+    }
+
+    /// Send an EXEC packet and reassemble a response split across several
+    /// packets, up to `max_bytes` of body.
+    ///
+    /// Uses the empty-exec sentinel trick: the command is followed by an
+    /// empty EXEC with its own id. Servers answer in order, so every packet
+    /// carrying the command's id belongs to the response, and the first
+    /// packet carrying the sentinel id marks its end.
+    pub async fn exec_multi(&mut self, command: &str, max_bytes: usize) -> RconResult<String> {
+        let id = self.alloc_id();
+        let sentinel = self.alloc_id();
+        send_packet(&mut self.stream, id, PACKET_TYPE_EXEC, command).await?;
+        send_packet(&mut self.stream, sentinel, PACKET_TYPE_EXEC, "").await?;
+
+        // Bytes, not String: a multi-byte char can straddle a packet split.
+        let mut body = Vec::new();
+        loop {
+            let (resp_id, _, chunk) = recv_packet_bytes(&mut self.stream).await?;
+            if resp_id == sentinel {
+                break;
+            }
+            if resp_id != id {
+                // Stale reply from an earlier, abandoned exec.
+                continue;
+            }
+            if body.len() + chunk.len() > max_bytes {
+                return Err(RconError::ResponseTooLarge { limit: max_bytes });
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn alloc_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        id
+    }
+}
+
+/// Wire format: [length:4][req_id:4][type:4][body + \0][pad \0]
+///
+/// Buffered into one write so RCON servers that expect atomic reads don't
+/// race a TCP segmentation boundary.
+async fn send_packet<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    req_id: i32,
+    ptype: i32,
+    body: &str,
+) -> RconResult<()> {
+    send_packet_bytes(stream, req_id, ptype, body.as_bytes()).await
+}
+
+async fn send_packet_bytes<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    req_id: i32,
+    ptype: i32,
+    body_bytes: &[u8],
+) -> RconResult<()> {
+    let length = 4 + 4 + body_bytes.len() as i32 + 2;
+
+    let mut buf = Vec::with_capacity(4 + length as usize);
+    buf.extend_from_slice(&length.to_le_bytes());
+    buf.extend_from_slice(&req_id.to_le_bytes());
+    buf.extend_from_slice(&ptype.to_le_bytes());
+    buf.extend_from_slice(body_bytes);
+    buf.extend_from_slice(&[0, 0]);
+
+    stream.write_all(&buf).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+async fn recv_packet<S: AsyncRead + Unpin>(stream: &mut S) -> RconResult<(i32, i32, String)> {
+    let (req_id, ptype, body) = recv_packet_bytes(stream).await?;
+    Ok((req_id, ptype, String::from_utf8_lossy(&body).into_owned()))
+}
+
+async fn recv_packet_bytes<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> RconResult<(i32, i32, Vec<u8>)> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf).await?;
+    let length = i32::from_le_bytes(len_buf) as usize;
+
+    if !(PACKET_MIN_LEN..=PACKET_MAX_LEN).contains(&length) {
+        return Err(RconError::PacketLength(length));
+    }
+
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await?;
+
+    let req_id = i32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+    let ptype = i32::from_le_bytes([payload[4], payload[5], payload[6], payload[7]]);
+    let body_end = length.saturating_sub(2);
+    payload.truncate(body_end);
+    payload.drain(..8);
+
+    Ok((req_id, ptype, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_addr_formats_host_port() {
+        let ep = RconEndpoint::new("mc-lobby.kbve.svc.cluster.local", 25575, "hunter2");
+        assert_eq!(ep.addr(), "mc-lobby.kbve.svc.cluster.local:25575");
+    }
+
+    #[tokio::test]
+    async fn exec_multi_reassembles_split_packets() {
+        let server = mock::MockRconServer::start("pw", |_| "é".repeat(3000))
+            .await
+            .unwrap();
+        server.set_split_size(1001);
+        let mut client = RconClient::connect(&server.endpoint(), Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        let body = client.exec_multi("list", DEFAULT_MAX_RESPONSE_BYTES).await.unwrap();
+        assert_eq!(body, "é".repeat(3000));
+        // The sentinel is not recorded as a command.
+        assert_eq!(server.commands(), vec!["list".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn exec_reads_only_the_first_packet() {
+        let server = mock::MockRconServer::start("pw", |_| "a".repeat(5000))
+            .await
+            .unwrap();
+        let mut client = RconClient::connect(&server.endpoint(), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(client.exec("list").await.unwrap().len(), 4096);
+    }
+
+    #[tokio::test]
+    async fn exec_multi_enforces_response_limit() {
+        let server = mock::MockRconServer::start("pw", |_| "a".repeat(5000))
+            .await
+            .unwrap();
+        let mut client = RconClient::connect(&server.endpoint(), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.exec_multi("list", 4500).await,
+            Err(RconError::ResponseTooLarge { limit: 4500 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn connect_rejects_bad_password() {
+        let server = mock::MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let mut ep = server.endpoint();
+        ep.password = "nope".into();
+        assert!(matches!(
+            RconClient::connect(&ep, Duration::from_secs(1)).await,
+            Err(RconError::AuthRejected)
+        ));
+    }
+
+    #[test]
+    fn endpoint_clone_is_cheap_and_independent() {
+        let ep = RconEndpoint::new("a", 1, "p");
+        let cloned = ep.clone();
+        assert_eq!(cloned.host, "a");
+        assert_eq!(cloned.port, 1);
+        assert_eq!(cloned.password, "p");
+    }
+}
@@ -0,0 +1,496 @@
+//! Pooled RCON sessions for one endpoint.
+//!
+//! A bounded set of authenticated sessions, reused across `exec` calls,
+//! with:
+//!   * reconnect on demand — a dead session is dropped and the next call
+//!     dials a fresh one; idle sessions the server has closed are detected
+//!     before the command is written, so it is never sent twice unless
+//!     `retry_after_send` opts in
+//!   * exponential connect backoff so a down server isn't hammered
+//!   * idle eviction + a periodic health check (`spawn_health_check`)
+//!   * multi-packet reassembly via [`RconClient::exec_multi`] (opt out with
+//!     `multi_packet = false` for servers that choke on an empty EXEC)
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+
+use super::{DEFAULT_MAX_RESPONSE_BYTES, RconClient, RconEndpoint, RconError, RconResult};
+
+#[derive(Clone, Debug)]
+pub struct RconPoolConfig {
+    /// Max concurrent sessions; extra `exec` callers wait for a slot.
+    pub max_size: usize,
+    pub connect_timeout: Duration,
+    /// Ceiling on one exec round-trip. A session that times out is dropped,
+    /// since its stream may still carry the late reply.
+    pub exec_timeout: Duration,
+    /// Sessions idle longer than this are closed instead of reused.
+    pub idle_timeout: Duration,
+    pub health_check_interval: Duration,
+    /// Command sent by the health check. Empty is a no-op on Minecraft,
+    /// Factorio and Source servers.
+    pub health_check_command: String,
+    pub multi_packet: bool,
+    pub max_response_bytes: usize,
+    pub backoff_initial: Duration,
+    pub backoff_max: Duration,
+    /// Resend a command on a fresh session when a reused one drops after
+    /// the command was written. The server may already have run it, so
+    /// only enable this for idempotent commands.
+    pub retry_after_send: bool,
+}
+
+impl Default for RconPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 4,
+            connect_timeout: Duration::from_secs(5),
+            exec_timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(300),
+            health_check_interval: Duration::from_secs(30),
+            health_check_command: String::new(),
+            multi_packet: true,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(30),
+            retry_after_send: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RconPoolStatus {
+    pub idle: usize,
+    pub in_use: usize,
+    pub max_size: usize,
+    /// Remaining connect backoff, if the last dial failed.
+    pub backoff: Option<Duration>,
+}
+
+struct IdleSession {
+    client: RconClient,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct Backoff {
+    failures: u32,
+    until: Option<Instant>,
+}
+
+struct PoolInner {
+    endpoint: RconEndpoint,
+    config: RconPoolConfig,
+    idle: Mutex<Vec<IdleSession>>,
+    permits: Semaphore,
+    backoff: Mutex<Backoff>,
+}
+
+/// Cheap to clone; all clones share the same sessions.
+#[derive(Clone)]
+pub struct RconPool {
+    inner: Arc<PoolInner>,
+}
+
+impl RconPool {
+    pub fn new(endpoint: RconEndpoint, config: RconPoolConfig) -> Self {
+        let permits = Semaphore::new(config.max_size.max(1));
+        Self {
+            inner: Arc::new(PoolInner {
+                endpoint,
+                config,
+                idle: Mutex::new(Vec::new()),
+                permits,
+                backoff: Mutex::new(Backoff::default()),
+            }),
+        }
+    }
+
+    pub fn endpoint(&self) -> &RconEndpoint {
+        &self.inner.endpoint
+    }
+
+    pub fn config(&self) -> &RconPoolConfig {
+        &self.inner.config
+    }
+
+    /// Run a command on a pooled session, dialing one if none is idle.
+    pub async fn exec(&self, command: &str) -> RconResult<String> {
+        let _permit = self
+            .inner
+            .permits
+            .acquire()
+            .await
+            .expect("rcon pool semaphore is never closed");
+
+        // `take_idle` skips sessions the server closed while they sat idle.
+        // One that drops after the command is written may already have run
+        // it, so it is only retried when the caller opted in.
+        if let Some(client) = self.take_idle() {
+            match self.run(client, command).await {
+                Err(RconError::Io(e))
+                    if is_disconnect(&e) && self.inner.config.retry_after_send =>
+                {
+                    debug!(rcon = %self.inner.endpoint.addr(), error = %e, "rcon_pool stale session; redialing");
+                }
+                result => return result,
+            }
+        }
+
+        let client = self.connect().await?;
+        self.run(client, command).await
+    }
+
+    /// Ping every idle session once with `health_check_command`, dropping
+    /// the ones that fail or have sat past `idle_timeout`. A ping does not
+    /// count as use, so it never keeps a session from aging out. Returns
+    /// how many healthy sessions remain idle.
+    pub async fn health_check(&self) -> usize {
+        let sessions = std::mem::take(&mut *self.lock_idle());
+        let idle_timeout = self.inner.config.idle_timeout;
+        let mut healthy = 0;
+        for mut session in sessions {
+            if session.last_used.elapsed() >= idle_timeout || session.client.is_closed() {
+                continue;
+            }
+            let _permit = self
+                .inner
+                .permits
+                .acquire()
+                .await
+                .expect("rcon pool semaphore is never closed");
+            match self
+                .send(&mut session.client, &self.inner.config.health_check_command)
+                .await
+            {
+                Ok(_) => {
+                    if self.restore_idle(session) {
+                        healthy += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!(rcon = %self.inner.endpoint.addr(), error = %e, "rcon_pool health check failed; dropping session");
+                }
+            }
+        }
+        healthy
+    }
+
+    /// Run `health_check` every `health_check_interval` until the last
+    /// pool handle is dropped.
+    pub fn spawn_health_check(&self) -> tokio::task::JoinHandle<()> {
+        let weak = Arc::downgrade(&self.inner);
+        let interval = self.inner.config.health_check_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(inner) = weak.upgrade() else {
+                    break;
+                };
+                RconPool { inner }.health_check().await;
+            }
+        })
+    }
+
+    pub fn status(&self) -> RconPoolStatus {
+        let max_size = self.inner.config.max_size.max(1);
+        let backoff = self
+            .lock_backoff()
+            .until
+            .and_then(|until| until.checked_duration_since(Instant::now()));
+        RconPoolStatus {
+            idle: self.lock_idle().len(),
+            in_use: max_size - self.inner.permits.available_permits(),
+            max_size,
+            backoff,
+        }
+    }
+
+    async fn run(&self, mut client: RconClient, command: &str) -> RconResult<String> {
+        // Any failure leaves the stream in an unknown state; drop it.
+        let body = self.send(&mut client, command).await?;
+        self.lock_idle().push(IdleSession {
+            client,
+            last_used: Instant::now(),
+        });
+        Ok(body)
+    }
+
+    async fn send(&self, client: &mut RconClient, command: &str) -> RconResult<String> {
+        let cfg = &self.inner.config;
+        timeout(cfg.exec_timeout, async {
+            if cfg.multi_packet {
+                client.exec_multi(command, cfg.max_response_bytes).await
+            } else {
+                client.exec(command).await
+            }
+        })
+        .await
+        .unwrap_or(Err(RconError::ExecTimeout {
+            timeout: cfg.exec_timeout,
+        }))
+    }
+
+    /// Put a health-checked session back in `last_used` order. Sessions
+    /// dialed while the check held it may have filled the pool; the
+    /// checked one is closed then. Returns whether it was kept.
+    fn restore_idle(&self, session: IdleSession) -> bool {
+        let mut idle = self.lock_idle();
+        if idle.len() >= self.inner.config.max_size.max(1) {
+            return false;
+        }
+        let at = idle.partition_point(|s| s.last_used <= session.last_used);
+        idle.insert(at, session);
+        true
+    }
+
+    async fn connect(&self) -> RconResult<RconClient> {
+        let ep = &self.inner.endpoint;
+        if let Some(until) = self.lock_backoff().until {
+            let now = Instant::now();
+            if until > now {
+                return Err(RconError::Backoff {
+                    addr: ep.addr(),
+                    retry_in: until - now,
+                });
+            }
+        }
+
+        match RconClient::connect(ep, self.inner.config.connect_timeout).await {
+            Ok(client) => {
+                let mut backoff = self.lock_backoff();
+                if backoff.failures > 0 {
+                    info!(rcon = %ep.addr(), "rcon_pool reconnected");
+                }
+                *backoff = Backoff::default();
+                Ok(client)
+            }
+            Err(e) => {
+                let cfg = &self.inner.config;
+                let mut backoff = self.lock_backoff();
+                backoff.failures = backoff.failures.saturating_add(1);
+                let delay = cfg
+                    .backoff_initial
+                    .saturating_mul(1 << (backoff.failures - 1).min(16))
+                    .min(cfg.backoff_max);
+                backoff.until = Some(Instant::now() + delay);
+                warn!(rcon = %ep.addr(), error = %e, retry_in = ?delay, "rcon_pool connect failed");
+                Err(e)
+            }
+        }
+    }
+
+    /// Most recently used first, so the pool shrinks naturally: the
+    /// sessions left at the bottom age out via `idle_timeout`.
+    fn take_idle(&self) -> Option<RconClient> {
+        let idle_timeout = self.inner.config.idle_timeout;
+        let mut idle = self.lock_idle();
+        while let Some(session) = idle.pop() {
+            if session.last_used.elapsed() >= idle_timeout {
+                continue;
+            }
+            if session.client.is_closed() {
+                debug!(rcon = %self.inner.endpoint.addr(), "rcon_pool dropping session closed by server");
+                continue;
+            }
+            return Some(session.client);
+        }
+        None
+    }
+
+    fn lock_idle(&self) -> std::sync::MutexGuard<'_, Vec<IdleSession>> {
+        self.inner.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_backoff(&self) -> std::sync::MutexGuard<'_, Backoff> {
+        self.inner.backoff.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn is_disconnect(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        e.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rcon::mock::MockRconServer;
+
+    fn config() -> RconPoolConfig {
+        RconPoolConfig {
+            max_size: 2,
+            connect_timeout: Duration::from_secs(1),
+            exec_timeout: Duration::from_secs(1),
+            ..RconPoolConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reuses_sessions_across_execs() {
+        let server = MockRconServer::start("pw", |cmd| format!("ok {cmd}"))
+            .await
+            .unwrap();
+        let pool = RconPool::new(server.endpoint(), config());
+
+        for i in 0..5 {
+            assert_eq!(pool.exec(&format!("c{i}")).await.unwrap(), format!("ok c{i}"));
+        }
+        assert_eq!(server.connections(), 1);
+        assert_eq!(pool.status().idle, 1);
+        assert_eq!(pool.status().in_use, 0);
+    }
+
+    #[tokio::test]
+    async fn reassembles_split_responses() {
+        let server = MockRconServer::start("pw", |_| "x".repeat(10_000))
+            .await
+            .unwrap();
+        let pool = RconPool::new(server.endpoint(), config());
+        assert_eq!(pool.exec("list").await.unwrap().len(), 10_000);
+    }
+
+    #[tokio::test]
+    async fn redials_after_server_drops_sessions() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let pool = RconPool::new(server.endpoint(), config());
+
+        assert_eq!(pool.exec("a").await.unwrap(), "a");
+        server.drop_connections();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(pool.exec("b").await.unwrap(), "b");
+        assert_eq!(server.connections(), 2);
+        assert_eq!(server.commands(), vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn does_not_resend_a_command_the_server_may_have_run() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let pool = RconPool::new(server.endpoint(), config());
+
+        assert_eq!(pool.exec("a").await.unwrap(), "a");
+        server.drop_after_next_command();
+        assert!(matches!(pool.exec("give").await, Err(RconError::Io(_))));
+        assert_eq!(server.commands(), vec!["a".to_string(), "give".to_string()]);
+        assert_eq!(server.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_after_send_resends_on_a_fresh_session() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let pool = RconPool::new(
+            server.endpoint(),
+            RconPoolConfig {
+                retry_after_send: true,
+                ..config()
+            },
+        );
+
+        assert_eq!(pool.exec("a").await.unwrap(), "a");
+        server.drop_after_next_command();
+        assert_eq!(pool.exec("list").await.unwrap(), "list");
+        assert_eq!(server.connections(), 2);
+        assert_eq!(server.commands(), vec!["a", "list", "list"]);
+    }
+
+    #[tokio::test]
+    async fn backs_off_after_failed_connect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let pool = RconPool::new(RconEndpoint::new("127.0.0.1", port, "pw"), config());
+        assert!(matches!(pool.exec("a").await, Err(RconError::Io(_))));
+        assert!(matches!(pool.exec("a").await, Err(RconError::Backoff { .. })));
+        assert!(pool.status().backoff.is_some());
+    }
+
+    #[tokio::test]
+    async fn auth_failure_is_not_pooled() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let mut ep = server.endpoint();
+        ep.password = "wrong".into();
+        let pool = RconPool::new(ep, config());
+        assert!(matches!(pool.exec("a").await, Err(RconError::AuthRejected)));
+        assert_eq!(pool.status().idle, 0);
+    }
+
+    #[tokio::test]
+    async fn health_check_drops_dead_sessions() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let pool = RconPool::new(
+            server.endpoint(),
+            RconPoolConfig {
+                health_check_command: "ping".into(),
+                ..config()
+            },
+        );
+
+        let (a, b) = tokio::join!(pool.exec("a"), pool.exec("b"));
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(server.connections(), 2);
+        assert_eq!(pool.health_check().await, 2);
+        for session in 0..2 {
+            let pings = server.session_commands(session);
+            assert_eq!(
+                pings.iter().filter(|c| *c == "ping").count(),
+                1,
+                "session {session}"
+            );
+        }
+
+        server.drop_after_next_command();
+        assert_eq!(pool.health_check().await, 1);
+        assert_eq!(pool.status().idle, 1);
+
+        server.drop_connections();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(pool.health_check().await, 0);
+        assert_eq!(pool.status().idle, 0);
+    }
+
+    #[tokio::test]
+    async fn health_check_does_not_keep_idle_sessions_alive() {
+        let server = MockRconServer::start("pw", |cmd| cmd.to_string())
+            .await
+            .unwrap();
+        let pool = RconPool::new(
+            server.endpoint(),
+            RconPoolConfig {
+                idle_timeout: Duration::from_millis(300),
+                ..config()
+            },
+        );
+
+        pool.exec("a").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(pool.health_check().await, 1);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(pool.health_check().await, 0);
+        pool.exec("b").await.unwrap();
+        assert_eq!(server.connections(), 2);
+    }
+}
@@ -13,6 +13,31 @@
 //! so on a hot-key miss only one task hits the fetch fn; the rest park
 //! on the broadcast.
 //!
+//! Per-call [`CachePolicy`] (the `*_with` entry points) layers on:
+//!
+//!   - soft TTL: past it the entry is served stale while one background
+//!     task refreshes it; only the hard TTL forces a blocking fetch
+//!   - negative caching: a fetch returning `JediError::NotFound` is cached
+//!     for `negative_ttl` and replayed as `NotFound`
+//!   - jitter: TTLs are shortened by a fraction hashed from the key, so
+//!     keys written together don't all expire in the same instant while
+//!     every write of one key gets the same TTLs
+//!
+//! Cross-pod invalidation: `invalidate`, `invalidate_prefix` and
+//! `invalidate_tag` publish on `<namespace>:__inval`; every replica
+//! subscribed via [`KvCache::start_invalidation_listener`] drops the
+//! matching L1 entries and re-deletes the L2 copies it knows about. A
+//! tag invalidation also carries the tag's L2 member keys, since a
+//! replica that promoted an entry from L2 never learned its tags. An
+//! invalidation is also recorded against every in-flight fill it
+//! matches (by key, prefix or tag), and a fetch that raced one is
+//! returned to its caller but not cached, so a pre-invalidation read
+//! can't repopulate L1/L2 after the purge. Every invalidation also bumps
+//! a generation counter; an L1 write is dropped if the counter moved
+//! since its read left the fill (or L2), and an L2 write is deleted
+//! again if the counter moved while it was in flight, which closes the
+//! window between a fill finishing and its result landing in the tiers.
+//!
 //! Environment:
 //!
 //!   - `KBVE_KV_NAMESPACE`         (default `"kbve:cache"`)
@@ -20,20 +45,25 @@
 //!   - `KBVE_KV_L1_TTL_MS`         (default 500ms)
 //!   - `KBVE_KV_L2_TTL_S`          (default 5s)
 //!   - `KBVE_KV_L2_ENABLED`        (default `true`)
+//!   - `KBVE_KV_INVALIDATION`      (default `true`; pub/sub fan-out, needs L2)
 
 use std::{
     num::NonZeroUsize,
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
 };
 
-use dashmap::{DashMap, mapref::entry::Entry};
+use dashmap::{DashMap, DashSet, mapref::entry::Entry};
 use fred::clients::Pool as ValkeyPool;
 use fred::prelude::*;
 use fred::types::Expiration;
 use lru::LruCache;
-use serde::{Serialize, de::DeserializeOwned};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio::sync::{Mutex, broadcast};
+use tokio::time::Instant;
 
 use crate::entity::error::JediError;
 
@@ -49,6 +79,7 @@ pub struct KvCacheConfig {
     pub l1_default_ttl: Duration,
     pub l2_default_ttl: Duration,
     pub l2_enabled: bool,
+    pub invalidation_enabled: bool,
 }
 
 impl KvCacheConfig {
@@ -62,31 +93,212 @@ impl KvCacheConfig {
             Duration::from_millis(parse_env::<u64>("KBVE_KV_L1_TTL_MS").unwrap_or(500));
         let l2_default_ttl = Duration::from_secs(parse_env::<u64>("KBVE_KV_L2_TTL_S").unwrap_or(5));
         let l2_enabled = parse_bool_env("KBVE_KV_L2_ENABLED").unwrap_or(true);
+        let invalidation_enabled = parse_bool_env("KBVE_KV_INVALIDATION").unwrap_or(true);
         Self {
             namespace,
             l1_capacity,
             l1_default_ttl,
             l2_default_ttl,
             l2_enabled,
+            invalidation_enabled,
+        }
+    }
+}
+
+/// Per-call cache policy. `CachePolicy::default()` (what the plain
+/// `ttl: Option<Duration>` entry points use) keeps the historic
+/// behaviour: per-tier default TTLs, nothing served stale, errors never
+/// cached.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CachePolicy {
+    /// Entry lifetime in both tiers. `None` → `l1_default_ttl` /
+    /// `l2_default_ttl`.
+    pub hard_ttl: Option<Duration>,
+    /// Age after which a hit is served stale and refreshed in the
+    /// background. `None` → never stale, only the hard TTL applies.
+    pub soft_ttl: Option<Duration>,
+    /// Lifetime of a cached `NotFound`. `None` → not-found is not cached.
+    pub negative_ttl: Option<Duration>,
+    /// Fraction in `[0, 1]`; each write shortens its TTLs by up to this
+    /// much, by a share derived from the key (the same key always gets
+    /// the same share).
+    pub jitter: f64,
+}
+
+impl CachePolicy {
+    pub fn ttl(ttl: Duration) -> Self {
+        Self {
+            hard_ttl: Some(ttl),
+            ..Self::default()
+        }
+    }
+
+    /// Fresh for `soft`, then served stale (with one background refresh)
+    /// until `hard`.
+    pub fn stale_while_revalidate(soft: Duration, hard: Duration) -> Self {
+        Self {
+            hard_ttl: Some(hard.max(soft)),
+            soft_ttl: Some(soft),
+            ..Self::default()
         }
     }
+
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// TTLs for one write of `key`, jitter applied.
+    fn resolve(&self, cfg: &KvCacheConfig, key: &str) -> ResolvedTtls {
+        let factor = jitter_factor(self.jitter, key);
+        let scale = |d: Duration| d.mul_f64(factor);
+        ResolvedTtls {
+            l1: scale(self.hard_ttl.unwrap_or(cfg.l1_default_ttl)),
+            l2: scale(self.hard_ttl.unwrap_or(cfg.l2_default_ttl)),
+            soft: self.soft_ttl.map(scale),
+            negative: self.negative_ttl.map(scale),
+        }
+    }
+}
+
+impl From<Option<Duration>> for CachePolicy {
+    fn from(ttl: Option<Duration>) -> Self {
+        Self {
+            hard_ttl: ttl,
+            ..Self::default()
+        }
+    }
+}
+
+struct ResolvedTtls {
+    l1: Duration,
+    l2: Duration,
+    soft: Option<Duration>,
+    negative: Option<Duration>,
 }
 
+/// `None` body = cached `NotFound`.
 struct L1Entry {
-    body: Arc<[u8]>,
+    body: Option<Arc<[u8]>>,
     tags: Arc<[Arc<str>]>,
     expires_at: Instant,
+    stale_at: Option<Instant>,
+}
+
+struct CacheHit {
+    body: Option<Arc<[u8]>>,
+    stale: bool,
+}
+
+/// Single-flight broadcast payload; `Ok(None)` relays a `NotFound`.
+type FlightResult = Result<Option<Arc<[u8]>>, String>;
+
+/// One in-progress fill: waiters subscribe to `tx`, invalidations that
+/// land mid-fetch are recorded in `fill`.
+struct Flight {
+    tx: broadcast::Sender<FlightResult>,
+    fill: Arc<FillState>,
+}
+
+#[derive(Default)]
+struct FillState {
+    /// A key or prefix invalidation (or an L1 clear) matched this fill.
+    invalidated: AtomicBool,
+    /// Qualified tags invalidated mid-fetch. The fetched value's tags
+    /// aren't known until it lands, so they're checked then.
+    tags: std::sync::Mutex<Vec<Arc<str>>>,
+}
+
+impl FillState {
+    fn cacheable(&self, qualified_tags: &[String]) -> bool {
+        if self.invalidated.load(Ordering::SeqCst) {
+            return false;
+        }
+        let hit = self.tags.lock().unwrap_or_else(|e| e.into_inner());
+        !qualified_tags
+            .iter()
+            .any(|t| hit.iter().any(|h| **h == **t))
+    }
+}
+
+/// Drops the `inflight` entry if a fill unwinds or is cancelled, so
+/// waiters see a closed channel instead of parking forever.
+struct FlightGuard<'a> {
+    inflight: &'a DashMap<String, Flight>,
+    key: &'a str,
+    done: bool,
+}
+
+impl FlightGuard<'_> {
+    fn finish(mut self) -> Option<Flight> {
+        self.done = true;
+        self.inflight.remove(self.key).map(|(_, f)| f)
+    }
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.inflight.remove(self.key);
+        }
+    }
+}
+
+/// Clears a key's `refreshing` mark when its refresh task ends, panics
+/// included, so a later stale hit can start another.
+struct RefreshGuard {
+    cache: Arc<KvCache>,
+    key: String,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.cache.refreshing.remove(&self.key);
+    }
+}
+
+/// Pub/sub payload on `<namespace>:__inval`. Targets are already
+/// namespace-qualified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct InvalidationMessage {
+    origin: String,
+    #[serde(flatten)]
+    op: InvalidationOp,
+    /// Tag ops only: the L2 member set the publisher purged. Entries a
+    /// receiver promoted from L2 carry no tags in its L1, so it drops
+    /// these keys directly.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "target", rename_all = "snake_case")]
+enum InvalidationOp {
+    Key(String),
+    Prefix(String),
+    Tag(String),
 }
 
 pub struct KvCache {
     cfg: KvCacheConfig,
     l1: Mutex<LruCache<String, L1Entry>>,
     l2: Option<ValkeyPool>,
-    #[allow(clippy::type_complexity)]
-    inflight: DashMap<String, broadcast::Sender<Result<Arc<[u8]>, String>>>,
+    inflight: DashMap<String, Flight>,
+    /// Keys with a background stale-while-revalidate refresh running.
+    refreshing: DashSet<String>,
     /// `tag → set<qualified_key>` reverse index for L1 invalidation.
     /// L2 mirrors this in `<namespace>:tag:<tag>` Redis SETs.
     tag_index: DashMap<Arc<str>, dashmap::DashSet<Arc<str>>>,
+    /// Identifies this instance's own pub/sub messages.
+    origin: String,
+    /// Bumped by every invalidation. Writes carry the value read before
+    /// their source was read and are dropped if it has moved since.
+    generation: AtomicU64,
 }
 
 impl KvCache {
@@ -98,7 +310,10 @@ impl KvCache {
             l1,
             l2,
             inflight: DashMap::new(),
+            refreshing: DashSet::new(),
             tag_index: DashMap::new(),
+            origin: ulid::Ulid::new().to_string(),
+            generation: AtomicU64::new(0),
         })
     }
 
@@ -106,6 +321,8 @@ impl KvCache {
     /// `KBVE_KV_URL` / `REDIS_URL` (size `KBVE_KV_POOL_SIZE`,
     /// default 4) when `KBVE_KV_L2_ENABLED` is on. On pool build
     /// failure the cache stays L1-only and a warning is logged.
+    /// With L2 up and `KBVE_KV_INVALIDATION` on, also starts the
+    /// cross-pod invalidation listener.
     pub async fn from_env() -> Arc<Self> {
         let cfg = KvCacheConfig::from_env();
         let l2 = if cfg.l2_enabled {
@@ -119,7 +336,133 @@ impl KvCache {
         } else {
             None
         };
-        Self::new(cfg, l2)
+        let cache = Self::new(cfg, l2);
+        if cache.l2.is_some() && cache.cfg.invalidation_enabled {
+            let started = match valkey_config_from_env() {
+                Ok(config) => cache.start_invalidation_listener(config).await.map(|_| ()),
+                Err(e) => Err(JediError::Internal(e.into())),
+            };
+            if let Err(e) = started {
+                tracing::warn!(error = %e, "[KvCache] cross-pod invalidation disabled");
+            }
+        }
+        cache
+    }
+
+    /// Subscribe to this namespace's invalidation channel on a dedicated
+    /// fred subscriber (see
+    /// [`create_subscriber_fred`](crate::wrapper::redis_wrapper::create_subscriber_fred))
+    /// and apply other pods' invalidations. The task exits once the cache
+    /// is dropped. Whenever messages may have been lost — the subscriber
+    /// lagged, or its connection dropped and came back — L1 is cleared
+    /// wholesale rather than risk serving a purged entry.
+    pub async fn start_invalidation_listener(
+        self: &Arc<Self>,
+        config: Config,
+    ) -> Result<tokio::task::JoinHandle<()>, JediError> {
+        let subscriber = crate::wrapper::redis_wrapper::create_subscriber_fred(config).await?;
+        subscriber
+            .subscribe(self.invalidation_channel())
+            .await
+            .map_err(|e| JediError::Internal(format!("kv invalidation subscribe: {e}").into()))?;
+
+        let mut rx = subscriber.message_rx();
+        // Subscribed after `init`, so the initial connect isn't replayed;
+        // every event here is a reconnect with a gap in delivery.
+        let mut reconnects = subscriber.reconnect_rx();
+        let weak = Arc::downgrade(self);
+        Ok(tokio::spawn(async move {
+            let _subscriber = subscriber;
+            loop {
+                let msg = tokio::select! {
+                    msg = rx.recv() => msg,
+                    event = reconnects.recv() => {
+                        let Some(cache) = weak.upgrade() else { break };
+                        if matches!(event, Err(broadcast::error::RecvError::Closed)) {
+                            break;
+                        }
+                        metrics_inc("kbve_kv_inval_reconnect");
+                        tracing::warn!("[KvCache] invalidation subscriber reconnected; clearing L1");
+                        cache.l1_clear().await;
+                        continue;
+                    }
+                };
+                let Some(cache) = weak.upgrade() else { break };
+                match msg {
+                    Ok(msg) => {
+                        let Ok(raw) = msg.value.convert::<String>() else {
+                            continue;
+                        };
+                        match serde_json::from_str::<InvalidationMessage>(&raw) {
+                            Ok(message) => cache.apply_invalidation(message).await,
+                            Err(e) => {
+                                tracing::warn!(error = %e, "[KvCache] bad invalidation message")
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        metrics_inc("kbve_kv_inval_lagged");
+                        tracing::warn!(
+                            missed,
+                            "[KvCache] invalidation listener lagged; clearing L1"
+                        );
+                        cache.l1_clear().await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }))
+    }
+
+    fn invalidation_channel(&self) -> String {
+        format!("{}:__inval", self.cfg.namespace)
+    }
+
+    async fn publish_invalidation(&self, op: InvalidationOp, keys: Vec<String>) {
+        if !self.cfg.invalidation_enabled {
+            return;
+        }
+        let Some(pool) = &self.l2 else { return };
+        let message = InvalidationMessage {
+            origin: self.origin.clone(),
+            op,
+            keys,
+        };
+        let Ok(payload) = serde_json::to_string(&message) else {
+            return;
+        };
+        let channel = self.invalidation_channel();
+        let publish = pool.next().publish::<i64, _, _>(&channel, payload);
+        match tokio::time::timeout(L2_OP_TIMEOUT, publish).await {
+            Ok(Ok(_)) => metrics_inc("kbve_kv_inval_published"),
+            Ok(Err(e)) => tracing::warn!(error = %e, "[KvCache] invalidation publish failed"),
+            Err(_) => {
+                metrics_inc("kbve_kv_l2_timeout");
+                tracing::warn!("[KvCache] invalidation publish timed out");
+            }
+        }
+    }
+
+    /// The publishing pod already purged L2, but a fill on this pod that
+    /// raced the purge may have written its copy back, so key and tag
+    /// invalidations delete from L2 again. Prefixes stay L1-only, as they
+    /// are on the publishing side.
+    async fn apply_invalidation(&self, message: InvalidationMessage) {
+        if message.origin == self.origin {
+            return;
+        }
+        metrics_inc("kbve_kv_inval_received");
+        match message.op {
+            InvalidationOp::Key(q) => {
+                let _ = self.invalidate_qualified(&q).await;
+            }
+            InvalidationOp::Prefix(q) => self.l1_invalidate_prefix(&q).await,
+            InvalidationOp::Tag(t) => {
+                let qualified_tag: Arc<str> = Arc::from(t);
+                let l1_keys = self.l1_invalidate_tag(&qualified_tag, &message.keys).await;
+                self.l2_invalidate_tag(&qualified_tag, &l1_keys).await;
+            }
+        }
     }
 
     pub fn config(&self) -> &KvCacheConfig {
@@ -162,28 +505,162 @@ impl KvCache {
         TagFn: FnOnce(&T) -> Vec<String> + Send,
     {
         let qualified = self.qualified(key);
-        let l1_ttl = ttl.unwrap_or(self.cfg.l1_default_ttl);
-        let l2_ttl = ttl.unwrap_or(self.cfg.l2_default_ttl);
+        let policy = CachePolicy::from(ttl);
+
+        // No `Arc<Self>` here to spawn a refresh from, so an entry a
+        // `*_with` caller left stale counts as a miss on this path.
+        if let Some(hit) = self.cached(&qualified, &policy).await
+            && !hit.stale
+        {
+            return decode_body(hit.body);
+        }
+
+        let bytes = self
+            .single_flight(&qualified, fetch, tag_fn, &policy)
+            .await?;
+        serde_json::from_slice(&bytes).map_err(json_err)
+    }
+
+    /// [`get_or_fetch_json`](Self::get_or_fetch_json) with a full
+    /// [`CachePolicy`].
+    pub async fn get_or_fetch_json_with<T, F, Fut>(
+        self: &Arc<Self>,
+        key: &str,
+        policy: CachePolicy,
+        fetch: F,
+    ) -> Result<T, JediError>
+    where
+        T: Serialize + DeserializeOwned + Send + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, JediError>> + Send + 'static,
+    {
+        self.get_or_fetch_json_tagged_with(key, policy, fetch, |_| Vec::new())
+            .await
+    }
+
+    /// [`get_or_fetch_json_tagged`](Self::get_or_fetch_json_tagged) with a
+    /// full [`CachePolicy`]. A stale hit is returned immediately and
+    /// `fetch` moves into a background refresh (at most one per key); a
+    /// failed refresh leaves the stale entry in place until its hard TTL.
+    pub async fn get_or_fetch_json_tagged_with<T, F, Fut, TagFn>(
+        self: &Arc<Self>,
+        key: &str,
+        policy: CachePolicy,
+        fetch: F,
+        tag_fn: TagFn,
+    ) -> Result<T, JediError>
+    where
+        T: Serialize + DeserializeOwned + Send + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, JediError>> + Send + 'static,
+        TagFn: FnOnce(&T) -> Vec<String> + Send + 'static,
+    {
+        let qualified = self.qualified(key);
 
-        if let Some(bytes) = self.l1_get(&qualified).await {
+        if let Some(hit) = self.cached(&qualified, &policy).await {
+            if hit.stale {
+                metrics_inc("kbve_kv_stale_served");
+                self.spawn_refresh(qualified, policy, fetch, tag_fn);
+            }
+            return decode_body(hit.body);
+        }
+
+        let bytes = self
+            .single_flight(&qualified, fetch, tag_fn, &policy)
+            .await?;
+        serde_json::from_slice(&bytes).map_err(json_err)
+    }
+
+    /// L1 → L2 lookup (including the negative marker when the policy
+    /// caches not-found). L2 hits are promoted into L1.
+    async fn cached(&self, qualified: &str, policy: &CachePolicy) -> Option<CacheHit> {
+        if let Some(hit) = self.l1_get(qualified).await {
             metrics_inc("kbve_kv_l1_hit");
-            return serde_json::from_slice(&bytes).map_err(json_err);
+            return Some(hit);
         }
         metrics_inc("kbve_kv_l1_miss");
 
-        if let Some(bytes) = self.l2_get(&qualified).await {
+        let ttls = policy.resolve(&self.cfg, qualified);
+        let generation = self.generation.load(Ordering::SeqCst);
+        if let Some(bytes) = self.l2_get(qualified).await {
             metrics_inc("kbve_kv_l2_hit");
+            let stale_at = match ttls.soft {
+                Some(soft) => Some(self.l2_stale_at(qualified, ttls.l2, soft).await),
+                None => None,
+            };
             let arc: Arc<[u8]> = Arc::from(bytes.into_boxed_slice());
-            self.l1_put(qualified.clone(), Arc::clone(&arc), Vec::new(), l1_ttl)
-                .await;
-            return serde_json::from_slice(&arc).map_err(json_err);
+            self.l1_put(
+                generation,
+                qualified.to_string(),
+                Some(Arc::clone(&arc)),
+                Vec::new(),
+                ttls.l1,
+                stale_at,
+            )
+            .await;
+            return Some(CacheHit {
+                body: Some(arc),
+                stale: stale_at.is_some_and(|at| at <= Instant::now()),
+            });
         }
         metrics_inc("kbve_kv_l2_miss");
 
-        let bytes = self
-            .single_flight(&qualified, fetch, tag_fn, l1_ttl, l2_ttl)
-            .await?;
-        serde_json::from_slice(&bytes).map_err(json_err)
+        if let Some(negative) = ttls.negative
+            && self.l2_negative_get(qualified).await
+        {
+            metrics_inc("kbve_kv_negative_hit");
+            self.l1_put(
+                generation,
+                qualified.to_string(),
+                None,
+                Vec::new(),
+                negative.min(ttls.l1),
+                None,
+            )
+            .await;
+            return Some(CacheHit {
+                body: None,
+                stale: false,
+            });
+        }
+        None
+    }
+
+    fn spawn_refresh<T, F, Fut, TagFn>(
+        self: &Arc<Self>,
+        qualified: String,
+        policy: CachePolicy,
+        fetch: F,
+        tag_fn: TagFn,
+    ) where
+        T: Serialize + Send + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, JediError>> + Send + 'static,
+        TagFn: FnOnce(&T) -> Vec<String> + Send + 'static,
+    {
+        if !self.refreshing.insert(qualified.clone()) {
+            return;
+        }
+        let guard = RefreshGuard {
+            cache: Arc::clone(self),
+            key: qualified,
+        };
+        tokio::spawn(async move {
+            let (cache, qualified) = (&guard.cache, &guard.key);
+            match cache.single_flight(qualified, fetch, tag_fn, &policy).await {
+                Ok(_) => metrics_inc("kbve_kv_refresh_ok"),
+                // Gone upstream: without a negative TTL to overwrite it,
+                // the stale value must not outlive the row.
+                Err(JediError::NotFound) if policy.negative_ttl.is_none() => {
+                    let _ = cache.invalidate_qualified(qualified).await;
+                }
+                Err(JediError::NotFound) => {}
+                Err(e) => {
+                    metrics_inc("kbve_kv_refresh_error");
+                    tracing::warn!(error = %e, key = %qualified, "[KvCache] background refresh failed; serving stale");
+                }
+            }
+        });
     }
 
     async fn single_flight<T, F, Fut, TagFn>(
@@ -191,8 +668,7 @@ impl KvCache {
         qualified: &str,
         fetch: F,
         tag_fn: TagFn,
-        l1_ttl: Duration,
-        l2_ttl: Duration,
+        policy: &CachePolicy,
     ) -> Result<Arc<[u8]>, JediError>
     where
         T: Serialize + Send,
@@ -200,21 +676,26 @@ impl KvCache {
         Fut: std::future::Future<Output = Result<T, JediError>> + Send,
         TagFn: FnOnce(&T) -> Vec<String> + Send,
     {
-        let receiver = match self.inflight.entry(qualified.to_string()) {
+        let (receiver, fill) = match self.inflight.entry(qualified.to_string()) {
             Entry::Occupied(o) => {
                 metrics_inc("kbve_kv_inflight_share");
-                Some(o.get().subscribe())
+                (Some(o.get().tx.subscribe()), None)
             }
             Entry::Vacant(v) => {
                 let (tx, _rx) = broadcast::channel(1);
-                v.insert(tx);
-                None
+                let fill = Arc::new(FillState::default());
+                v.insert(Flight {
+                    tx,
+                    fill: Arc::clone(&fill),
+                });
+                (None, Some(fill))
             }
         };
 
         if let Some(mut rx) = receiver {
             return match rx.recv().await {
-                Ok(Ok(bytes)) => Ok(bytes),
+                Ok(Ok(Some(bytes))) => Ok(bytes),
+                Ok(Ok(None)) => Err(JediError::NotFound),
                 Ok(Err(s)) => Err(JediError::Internal(
                     format!("kv single-flight upstream: {s}").into(),
                 )),
@@ -224,7 +705,14 @@ impl KvCache {
             };
         }
 
+        let fill = fill.expect("vacant entry registers a fill");
+        let flight = FlightGuard {
+            inflight: &self.inflight,
+            key: qualified,
+            done: false,
+        };
         let outcome = fetch().await;
+        let not_found = matches!(outcome, Err(JediError::NotFound));
         let (bytes, tags, err_str): (Option<Arc<[u8]>>, Vec<String>, Option<String>) =
             match &outcome {
                 Ok(v) => match serde_json::to_vec(v) {
@@ -233,10 +721,44 @@ impl KvCache {
                 },
                 Err(e) => (None, Vec::new(), Some(e.to_string())),
             };
+        // Read before leaving `inflight`: an invalidation that misses the
+        // fill below has to bump the counter after this load.
+        let generation = self.generation.load(Ordering::SeqCst);
+        let flight = flight.finish();
+
+        // An invalidation matching this key or one of its tags landed
+        // mid-fetch: the result may predate it, so hand it to the callers
+        // but keep it out of both tiers.
+        let qualified_tags: Vec<String> = tags.iter().map(|t| self.qualified(t)).collect();
+        let cacheable = fill.cacheable(&qualified_tags);
+        if !cacheable {
+            metrics_inc("kbve_kv_fill_skipped");
+        }
 
-        if let Some((_, tx)) = self.inflight.remove(qualified) {
+        let ttls = policy.resolve(&self.cfg, qualified);
+        if cacheable
+            && not_found
+            && let Some(negative) = ttls.negative
+            && self
+                .l1_put(
+                    generation,
+                    qualified.to_string(),
+                    None,
+                    Vec::new(),
+                    negative,
+                    None,
+                )
+                .await
+        {
+            self.l2_negative_put(qualified, negative).await;
+            self.l2_undo_if_invalidated(generation, &self.negative_key(qualified))
+                .await;
+        }
+
+        if let Some(Flight { tx, .. }) = flight {
             let payload = match (&bytes, &err_str) {
-                (Some(b), _) => Ok(Arc::clone(b)),
+                (Some(b), _) => Ok(Some(Arc::clone(b))),
+                _ if not_found => Ok(None),
                 (_, Some(s)) => Err(s.clone()),
                 _ => Err("kv: empty outcome".to_string()),
             };
@@ -248,36 +770,125 @@ impl KvCache {
             JediError::Internal(err_str.unwrap_or_else(|| "kv: missing body".into()).into())
         })?;
 
-        self.l1_put(
-            qualified.to_string(),
-            Arc::clone(&bytes),
-            tags.clone(),
-            l1_ttl,
-        )
-        .await;
-        self.l2_put(qualified, bytes.as_ref(), &tags, l2_ttl).await;
+        if !cacheable {
+            return Ok(bytes);
+        }
+
+        let stale_at = ttls.soft.map(|soft| Instant::now() + soft);
+        if self
+            .l1_put(
+                generation,
+                qualified.to_string(),
+                Some(Arc::clone(&bytes)),
+                tags.clone(),
+                ttls.l1,
+                stale_at,
+            )
+            .await
+        {
+            self.l2_put(qualified, bytes.as_ref(), &tags, ttls.l2).await;
+            self.l2_undo_if_invalidated(generation, qualified).await;
+        } else {
+            metrics_inc("kbve_kv_fill_skipped");
+        }
 
         Ok(bytes)
     }
 
     pub async fn invalidate(&self, key: &str) -> Result<(), JediError> {
         let q = self.qualified(key);
-        let tags = self.l1_pop(&q).await;
-        self.tag_index_remove_key(&q, &tags);
+        self.invalidate_qualified(&q).await?;
+        self.publish_invalidation(InvalidationOp::Key(q), Vec::new())
+            .await;
+        Ok(())
+    }
+
+    async fn invalidate_qualified(&self, q: &str) -> Result<(), JediError> {
+        self.l1_invalidate_key(q).await;
         if let Some(pool) = &self.l2 {
-            let _: Result<i64, _> = pool.del(&*q).await;
+            let _: Result<i64, _> = pool.del(q).await;
+            let _: Result<i64, _> = pool.del(&*self.negative_key(q)).await;
         }
         Ok(())
     }
 
     pub async fn invalidate_prefix(&self, prefix: &str) -> Result<(), JediError> {
         let q = self.qualified(prefix);
+        self.l1_invalidate_prefix(&q).await;
+        self.publish_invalidation(InvalidationOp::Prefix(q), Vec::new())
+            .await;
+        Ok(())
+    }
+
+    /// Drop every cached entry registered under `tag` from L1 + L2.
+    /// Returns the number of L1 keys purged (L2 deletes happen
+    /// best-effort and may exceed the L1 count when other pods have
+    /// also cached entries under the same tag).
+    ///
+    /// `tag` is namespaced internally — callers pass the bare tag
+    /// (e.g. `"wallet:account:<uuid>"`).
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<usize, JediError> {
+        let qualified_tag: Arc<str> = self.qualified(tag).into();
+        let l1_keys = self.l1_invalidate_tag(&qualified_tag, &[]).await;
+        let members = self.l2_invalidate_tag(&qualified_tag, &[]).await;
+
+        self.publish_invalidation(InvalidationOp::Tag(qualified_tag.to_string()), members)
+            .await;
+        Ok(l1_keys.len())
+    }
+
+    /// Deletes the tag's L2 member set and every body in it, plus
+    /// `extra` keys that may not have made it into the set. Returns the
+    /// set's members.
+    async fn l2_invalidate_tag(&self, qualified_tag: &str, extra: &[Arc<str>]) -> Vec<String> {
+        let Some(pool) = &self.l2 else {
+            return Vec::new();
+        };
+        let tag_set_key = self.tag_set_key(qualified_tag);
+        let members: Vec<String> = match pool.smembers::<Vec<String>, _>(&*tag_set_key).await {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::warn!(error = %e, tag = %qualified_tag, "[KvCache] L2 SMEMBERS failed");
+                Vec::new()
+            }
+        };
+        for m in members
+            .iter()
+            .map(String::as_str)
+            .chain(extra.iter().map(|k| &**k))
+        {
+            let _: Result<i64, _> = pool.del(m).await;
+        }
+        let _: Result<i64, _> = pool.del(&*tag_set_key).await;
+        members
+    }
+
+    /// Marks every in-flight fill whose key satisfies `matches` as
+    /// invalidated, then bumps the generation for writes already past
+    /// their fill.
+    fn invalidate_fills(&self, matches: impl Fn(&str) -> bool) {
+        for flight in self.inflight.iter() {
+            if matches(flight.key()) {
+                flight.fill.invalidated.store(true, Ordering::SeqCst);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn l1_invalidate_key(&self, q: &str) {
+        self.invalidate_fills(|k| k == q);
+        let tags = self.l1_pop(q).await;
+        self.tag_index_remove_key(q, &tags);
+    }
+
+    async fn l1_invalidate_prefix(&self, q: &str) {
+        self.invalidate_fills(|k| k.starts_with(q));
         let victims: Vec<(String, Arc<[Arc<str>]>)> = {
             let mut l1 = self.l1.lock().await;
             let keys: Vec<String> = l1
                 .iter()
                 .filter_map(|(k, _)| {
-                    if k.starts_with(&q) {
+                    if k.starts_with(q) {
                         Some(k.clone())
                     } else {
                         None
@@ -294,62 +905,59 @@ impl KvCache {
         for (k, tags) in victims {
             self.tag_index_remove_key(&k, &tags);
         }
-        Ok(())
     }
 
-    /// Drop every cached entry registered under `tag` from L1 + L2.
-    /// Returns the number of L1 keys purged (L2 deletes happen
-    /// best-effort and may exceed the L1 count when other pods have
-    /// also cached entries under the same tag).
-    ///
-    /// `tag` is namespaced internally — callers pass the bare tag
-    /// (e.g. `"wallet:account:<uuid>"`).
-    pub async fn invalidate_tag(&self, tag: &str) -> Result<usize, JediError> {
-        let qualified_tag: Arc<str> = self.qualified(tag).into();
-
-        let l1_keys: Vec<Arc<str>> = self
+    /// Drops the L1 keys that carried the tag, plus `extra` keys known to
+    /// carry it elsewhere, and returns them.
+    async fn l1_invalidate_tag(&self, qualified_tag: &Arc<str>, extra: &[String]) -> Vec<Arc<str>> {
+        for flight in self.inflight.iter() {
+            flight
+                .fill
+                .tags
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(Arc::clone(qualified_tag));
+        }
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let mut l1_keys: Vec<Arc<str>> = self
             .tag_index
-            .remove(&qualified_tag)
+            .remove(qualified_tag)
             .map(|(_, set)| set.into_iter().collect())
             .unwrap_or_default();
-        let l1_count = l1_keys.len();
-        {
-            let mut l1 = self.l1.lock().await;
-            for k in &l1_keys {
-                if let Some(entry) = l1.pop(&**k) {
-                    let other_tags: Vec<Arc<str>> = entry
-                        .tags
-                        .iter()
-                        .filter(|t| **t != qualified_tag)
-                        .cloned()
-                        .collect();
-                    drop(other_tags);
-                }
+        for k in extra {
+            if !l1_keys.iter().any(|known| **known == **k) {
+                l1_keys.push(Arc::from(k.as_str()));
             }
         }
-
-        if let Some(pool) = &self.l2 {
-            let tag_set_key = self.tag_set_key(&qualified_tag);
-            let members: Vec<String> = match pool.smembers::<Vec<String>, _>(&*tag_set_key).await {
-                Ok(m) => m,
-                Err(e) => {
-                    tracing::warn!(error = %e, tag = %tag, "[KvCache] L2 SMEMBERS failed");
-                    Vec::new()
-                }
-            };
-            for m in &members {
-                let _: Result<i64, _> = pool.del(m.as_str()).await;
-            }
-            let _: Result<i64, _> = pool.del(&*tag_set_key).await;
+        let popped: Vec<(&Arc<str>, Arc<[Arc<str>]>)> = {
+            let mut l1 = self.l1.lock().await;
+            l1_keys
+                .iter()
+                .filter_map(|k| Some((k, l1.pop(&**k)?.tags)))
+                .collect()
+        };
+        for (k, tags) in popped {
+            self.tag_index_remove_key(k, &tags);
         }
+        l1_keys
+    }
 
-        Ok(l1_count)
+    async fn l1_clear(&self) {
+        self.invalidate_fills(|_| true);
+        self.l1.lock().await.clear();
+        self.tag_index.clear();
     }
 
     fn tag_set_key(&self, qualified_tag: &str) -> String {
         format!("{}:__tag:{qualified_tag}", self.cfg.namespace)
     }
 
+    /// L2 marker for a cached `NotFound`. Kept beside the body key rather
+    /// than inside it so pods on older builds never read the marker as JSON.
+    fn negative_key(&self, qualified_key: &str) -> String {
+        format!("{}:__nf:{qualified_key}", self.cfg.namespace)
+    }
+
     fn tag_index_register(&self, qualified_key: &Arc<str>, tags: &[Arc<str>]) {
         for tag in tags {
             self.tag_index
@@ -371,17 +979,21 @@ impl KvCache {
         format!("{}:{}", self.cfg.namespace, key)
     }
 
-    async fn l1_get(&self, key: &str) -> Option<Arc<[u8]>> {
+    async fn l1_get(&self, key: &str) -> Option<CacheHit> {
         let mut l1 = self.l1.lock().await;
-        let body = match l1.peek(key) {
-            Some(e) if e.expires_at > Instant::now() => Some(Arc::clone(&e.body)),
+        let now = Instant::now();
+        let hit = match l1.peek(key) {
+            Some(e) if e.expires_at > now => Some(CacheHit {
+                body: e.body.clone(),
+                stale: e.stale_at.is_some_and(|at| at <= now),
+            }),
             Some(_) => None,
             None => return None,
         };
-        if body.is_none() {
+        if hit.is_none() {
             l1.pop(key);
         }
-        body
+        hit
     }
 
     async fn l1_pop(&self, key: &str) -> Arc<[Arc<str>]> {
@@ -391,20 +1003,38 @@ impl KvCache {
         }
     }
 
-    async fn l1_put(&self, key: String, body: Arc<[u8]>, tags: Vec<String>, ttl: Duration) {
+    /// Returns `false` without writing if an invalidation bumped the
+    /// generation since `generation` was read.
+    async fn l1_put(
+        &self,
+        generation: u64,
+        key: String,
+        body: Option<Arc<[u8]>>,
+        tags: Vec<String>,
+        ttl: Duration,
+        stale_at: Option<Instant>,
+    ) -> bool {
         let qualified_key: Arc<str> = key.clone().into();
         let tag_arcs: Vec<Arc<str>> = tags
             .into_iter()
             .map(|t| Arc::<str>::from(self.qualified(&t).into_boxed_str()))
             .collect();
         let tag_slice: Arc<[Arc<str>]> = Arc::from(tag_arcs.clone().into_boxed_slice());
-        self.tag_index_register(&qualified_key, &tag_arcs);
         let entry = L1Entry {
             body,
             tags: tag_slice,
             expires_at: Instant::now() + ttl,
+            stale_at,
         };
-        self.l1.lock().await.put(key, entry);
+        // Invalidations bump before they take the lock to purge, so a
+        // write that passes this check lands before the purge, not after.
+        let mut l1 = self.l1.lock().await;
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return false;
+        }
+        self.tag_index_register(&qualified_key, &tag_arcs);
+        l1.put(key, entry);
+        true
     }
 
     async fn l2_get(&self, key: &str) -> Option<Vec<u8>> {
@@ -422,6 +1052,61 @@ impl KvCache {
         }
     }
 
+    /// When an L2 copy turns stale, derived from its remaining PTTL. `hard`
+    /// and `soft` are the key's resolved TTLs — jitter is a function of
+    /// the key, so they match what the writer used — making
+    /// `age = hard - remaining`. Unknown PTTL counts as freshly written.
+    async fn l2_stale_at(&self, key: &str, hard: Duration, soft: Duration) -> Instant {
+        let now = Instant::now();
+        let Some(pool) = &self.l2 else {
+            return now + soft;
+        };
+        match tokio::time::timeout(L2_OP_TIMEOUT, pool.pttl::<i64, _>(key)).await {
+            Ok(Ok(remaining_ms)) if remaining_ms >= 0 => {
+                let age = hard.saturating_sub(Duration::from_millis(remaining_ms as u64));
+                now + soft.saturating_sub(age)
+            }
+            _ => now + soft,
+        }
+    }
+
+    async fn l2_negative_get(&self, qualified_key: &str) -> bool {
+        let Some(pool) = &self.l2 else { return false };
+        let key = self.negative_key(qualified_key);
+        matches!(
+            tokio::time::timeout(L2_OP_TIMEOUT, pool.exists::<i64, _>(&*key)).await,
+            Ok(Ok(n)) if n > 0
+        )
+    }
+
+    /// Stores the not-found marker and drops any body still in L2, so other
+    /// pods stop serving a value that no longer exists upstream.
+    async fn l2_negative_put(&self, qualified_key: &str, ttl: Duration) {
+        let Some(pool) = &self.l2 else { return };
+        let key = self.negative_key(qualified_key);
+        let ttl_ms = (ttl.as_millis() as i64).max(1);
+        let set_fut = pool.set::<(), _, _>(&*key, "1", Some(Expiration::PX(ttl_ms)), None, false);
+        if !matches!(
+            tokio::time::timeout(L2_OP_TIMEOUT, set_fut).await,
+            Ok(Ok(()))
+        ) {
+            tracing::warn!(key = %qualified_key, "[KvCache] L2 negative put failed");
+        }
+        let _: Result<i64, _> = pool.del(qualified_key).await;
+    }
+
+    /// An invalidation that bumped the generation after `l1_put` checked it
+    /// may have deleted `key` before the write above landed; delete it
+    /// again so the pre-invalidation bytes don't outlive the purge in L2.
+    async fn l2_undo_if_invalidated(&self, generation: u64, key: &str) {
+        if self.generation.load(Ordering::SeqCst) == generation {
+            return;
+        }
+        let Some(pool) = &self.l2 else { return };
+        metrics_inc("kbve_kv_fill_skipped");
+        let _: Result<i64, _> = pool.del(key).await;
+    }
+
     async fn l2_put(&self, key: &str, body: &[u8], tags: &[String], ttl: Duration) {
         let Some(pool) = &self.l2 else { return };
         let ttl_ms = ttl.as_millis() as i64;
@@ -507,17 +1192,20 @@ impl KvCache {
     }
 }
 
-async fn build_valkey_pool() -> Result<ValkeyPool, String> {
+fn valkey_config_from_env() -> Result<Config, String> {
     let url = std::env::var("KBVE_KV_URL")
         .or_else(|_| std::env::var("REDIS_URL"))
         .map_err(|_| "KBVE_KV_URL / REDIS_URL not set".to_string())?;
+    Config::from_url(&url).map_err(|e| format!("invalid KV URL: {e}"))
+}
 
+async fn build_valkey_pool() -> Result<ValkeyPool, String> {
     let pool_size: usize = std::env::var("KBVE_KV_POOL_SIZE")
         .ok()
         .and_then(|s| s.parse().ok())
         .unwrap_or(4);
 
-    let config = Config::from_url(&url).map_err(|e| format!("invalid KV URL: {e}"))?;
+    let config = valkey_config_from_env()?;
     let pool = Builder::from_config(config)
         .set_policy(ReconnectPolicy::default())
         // Global ceiling on every command so a silently-broken connection
@@ -534,6 +1222,28 @@ async fn build_valkey_pool() -> Result<ValkeyPool, String> {
     Ok(pool)
 }
 
+fn decode_body<T: DeserializeOwned>(body: Option<Arc<[u8]>>) -> Result<T, JediError> {
+    match body {
+        Some(bytes) => serde_json::from_slice(&bytes).map_err(json_err),
+        None => Err(JediError::NotFound),
+    }
+}
+
+/// `1 - jitter * r` for an `r` in `[0, 1)` hashed from `key` (FNV-1a).
+/// Different keys still spread out, and since every pod and build
+/// derives the same factor for a key, a reader can recover the TTLs an
+/// L2 entry was written with.
+fn jitter_factor(jitter: f64, key: &str) -> f64 {
+    if jitter <= 0.0 {
+        return 1.0;
+    }
+    let bits = key.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    let r = (bits >> 11) as f64 / (1u64 << 53) as f64;
+    1.0 - jitter.min(1.0) * r
+}
+
 fn json_err(e: serde_json::Error) -> JediError {
     JediError::Internal(format!("kv json: {e}").into())
 }
@@ -571,6 +1281,7 @@ mod tests {
             l1_default_ttl: Duration::from_secs(60),
             l2_default_ttl: Duration::from_secs(60),
             l2_enabled: false,
+            invalidation_enabled: true,
         }
     }
 
@@ -739,6 +1450,463 @@ mod tests {
         assert_eq!(v, 7);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn stale_hit_is_served_while_refreshing() {
+        let cache = KvCache::new(cfg(), None);
+        let policy = CachePolicy::stale_while_revalidate(
+            Duration::from_millis(100),
+            Duration::from_secs(60),
+        );
+
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Ok::<u32, JediError>(1) })
+            .await
+            .unwrap();
+        assert_eq!(v, 1);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<u32, JediError>(2)
+            })
+            .await
+            .unwrap();
+        assert_eq!(v, 1, "stale value served without waiting on fetch");
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy, || async { panic!("refreshed entry is fresh") })
+            .await
+            .unwrap();
+        assert_eq!(v, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stale_hits_share_one_refresh() {
+        let cache = KvCache::new(cfg(), None);
+        let policy =
+            CachePolicy::stale_while_revalidate(Duration::from_millis(10), Duration::from_secs(60));
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Ok::<u32, JediError>(1) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        for _ in 0..5 {
+            let calls = Arc::clone(&calls);
+            let _: u32 = cache
+                .get_or_fetch_json_with("k", policy.clone(), move || async move {
+                    calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    Ok::<u32, JediError>(2)
+                })
+                .await
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn panicked_refresh_does_not_wedge_the_key() {
+        let cache = KvCache::new(cfg(), None);
+        let policy =
+            CachePolicy::stale_while_revalidate(Duration::from_millis(10), Duration::from_secs(60));
+
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Ok::<u32, JediError>(1) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { panic!("refresh blew up") })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Ok::<u32, JediError>(2) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy, || async { panic!("refreshed entry is fresh") })
+            .await
+            .unwrap();
+        assert_eq!(v, 2);
+    }
+
+    #[tokio::test]
+    async fn refresh_not_found_evicts_stale_entry() {
+        let cache = KvCache::new(cfg(), None);
+        let policy =
+            CachePolicy::stale_while_revalidate(Duration::from_millis(10), Duration::from_secs(60));
+
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Ok::<u32, JediError>(1) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let _: u32 = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async {
+                Err::<u32, _>(JediError::NotFound)
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy, || async { Ok::<u32, JediError>(3) })
+            .await
+            .unwrap();
+        assert_eq!(v, 3);
+    }
+
+    #[tokio::test]
+    async fn not_found_is_cached_with_negative_ttl() {
+        let cache = KvCache::new(cfg(), None);
+        let policy =
+            CachePolicy::ttl(Duration::from_secs(60)).with_negative_ttl(Duration::from_millis(30));
+
+        let r: Result<u32, _> = cache
+            .get_or_fetch_json_with("missing", policy.clone(), || async {
+                Err(JediError::NotFound)
+            })
+            .await;
+        assert!(matches!(r, Err(JediError::NotFound)));
+
+        let r: Result<u32, _> = cache
+            .get_or_fetch_json_with("missing", policy.clone(), || async {
+                panic!("negative entry should answer")
+            })
+            .await;
+        assert!(matches!(r, Err(JediError::NotFound)));
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let v: u32 = cache
+            .get_or_fetch_json_with("missing", policy, || async { Ok::<u32, JediError>(5) })
+            .await
+            .unwrap();
+        assert_eq!(v, 5);
+    }
+
+    #[tokio::test]
+    async fn invalidate_clears_negative_entry() {
+        let cache = KvCache::new(cfg(), None);
+        let policy = CachePolicy::default().with_negative_ttl(Duration::from_secs(60));
+        let _: Result<u32, _> = cache
+            .get_or_fetch_json_with("k", policy.clone(), || async { Err(JediError::NotFound) })
+            .await;
+        cache.invalidate("k").await.unwrap();
+        let v: u32 = cache
+            .get_or_fetch_json_with("k", policy, || async { Ok::<u32, JediError>(1) })
+            .await
+            .unwrap();
+        assert_eq!(v, 1);
+    }
+
+    fn remote(op: InvalidationOp) -> InvalidationMessage {
+        InvalidationMessage {
+            origin: "other-pod".into(),
+            op,
+            keys: Vec::new(),
+        }
+    }
+
+    async fn fill(cache: &Arc<KvCache>, key: &str, tag: &str, v: u32) {
+        let tag = tag.to_string();
+        let _: u32 = cache
+            .get_or_fetch_json_tagged(
+                key,
+                None,
+                move || async move { Ok::<u32, JediError>(v) },
+                move |_| vec![tag.clone()],
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn is_cached(cache: &Arc<KvCache>, key: &str) -> bool {
+        cache
+            .get_or_fetch_json::<u32, _, _>(key, None, || async { Err(JediError::NotFound) })
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn remote_invalidations_apply_to_l1() {
+        let cache = KvCache::new(cfg(), None);
+        fill(&cache, "a", "t1", 1).await;
+        fill(&cache, "p:1", "t2", 2).await;
+        fill(&cache, "p:2", "t2", 3).await;
+        fill(&cache, "b", "t3", 4).await;
+
+        cache
+            .apply_invalidation(remote(InvalidationOp::Key(cache.qualified("a"))))
+            .await;
+        assert!(!is_cached(&cache, "a").await);
+
+        cache
+            .apply_invalidation(remote(InvalidationOp::Prefix(cache.qualified("p:"))))
+            .await;
+        assert!(!is_cached(&cache, "p:1").await);
+        assert!(!is_cached(&cache, "p:2").await);
+
+        cache
+            .apply_invalidation(remote(InvalidationOp::Tag(cache.qualified("t3"))))
+            .await;
+        assert!(!is_cached(&cache, "b").await);
+    }
+
+    #[tokio::test]
+    async fn remote_tag_invalidation_drops_listed_keys() {
+        let cache = KvCache::new(cfg(), None);
+        // Shaped like an L2 promotion: in L1, but without its tags.
+        let body: Arc<[u8]> = Arc::from(&b"1"[..]);
+        let q = cache.qualified("k");
+        let ttl = Duration::from_secs(60);
+        assert!(
+            cache
+                .l1_put(0, q.clone(), Some(body), Vec::new(), ttl, None)
+                .await
+        );
+
+        let message = InvalidationMessage {
+            keys: vec![q],
+            ..remote(InvalidationOp::Tag(cache.qualified("t")))
+        };
+        cache.apply_invalidation(message).await;
+        assert!(!is_cached(&cache, "k").await);
+    }
+
+    #[tokio::test]
+    async fn own_invalidation_messages_are_ignored() {
+        let cache = KvCache::new(cfg(), None);
+        fill(&cache, "a", "t", 1).await;
+        let own = InvalidationMessage {
+            origin: cache.origin.clone(),
+            op: InvalidationOp::Key(cache.qualified("a")),
+            keys: Vec::new(),
+        };
+        cache.apply_invalidation(own).await;
+        assert!(is_cached(&cache, "a").await);
+    }
+
+    #[tokio::test]
+    async fn fill_racing_an_invalidation_is_not_cached() {
+        let cache = KvCache::new(cfg(), None);
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let c = cache.clone();
+        let fetch = tokio::spawn(async move {
+            c.get_or_fetch_json("k", None, || async move {
+                let _ = started_tx.send(());
+                let _ = release_rx.await;
+                Ok::<u32, JediError>(1)
+            })
+            .await
+        });
+        started_rx.await.unwrap();
+        cache.invalidate("k").await.unwrap();
+        release_tx.send(()).unwrap();
+
+        assert_eq!(fetch.await.unwrap().unwrap(), 1);
+        assert!(!is_cached(&cache, "k").await);
+    }
+
+    #[tokio::test]
+    async fn cancelled_fill_releases_waiters() {
+        let cache = KvCache::new(cfg(), None);
+        let c = cache.clone();
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let stuck = tokio::spawn(async move {
+            c.get_or_fetch_json("k", None, || async move {
+                let _ = started_tx.send(());
+                std::future::pending::<Result<u32, JediError>>().await
+            })
+            .await
+        });
+        started_rx.await.unwrap();
+        stuck.abort();
+        let _ = stuck.await;
+
+        let v: u32 = cache
+            .get_or_fetch_json("k", None, || async { Ok::<u32, JediError>(4) })
+            .await
+            .unwrap();
+        assert_eq!(v, 4);
+    }
+
+    /// Starts a fill of `key` tagged `tag` that blocks until released.
+    async fn blocked_fill(
+        cache: &Arc<KvCache>,
+        key: &'static str,
+        tag: &'static str,
+    ) -> (
+        tokio::task::JoinHandle<Result<u32, JediError>>,
+        tokio::sync::oneshot::Sender<()>,
+    ) {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+        let c = cache.clone();
+        let fetch = tokio::spawn(async move {
+            c.get_or_fetch_json_tagged(
+                key,
+                None,
+                || async move {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.await;
+                    Ok::<u32, JediError>(1)
+                },
+                move |_| vec![tag.to_string()],
+            )
+            .await
+        });
+        started_rx.await.unwrap();
+        (fetch, release_tx)
+    }
+
+    #[tokio::test]
+    async fn unrelated_invalidations_do_not_block_a_fill() {
+        let cache = KvCache::new(cfg(), None);
+        let (fetch, release) = blocked_fill(&cache, "k", "t").await;
+        cache.invalidate("other").await.unwrap();
+        cache.invalidate_prefix("x:").await.unwrap();
+        cache.invalidate_tag("u").await.unwrap();
+        release.send(()).unwrap();
+
+        assert_eq!(fetch.await.unwrap().unwrap(), 1);
+        assert!(is_cached(&cache, "k").await);
+    }
+
+    #[tokio::test]
+    async fn fill_racing_its_tag_or_prefix_invalidation_is_not_cached() {
+        let cache = KvCache::new(cfg(), None);
+        let (fetch, release) = blocked_fill(&cache, "k", "t").await;
+        cache.invalidate_tag("t").await.unwrap();
+        release.send(()).unwrap();
+        assert_eq!(fetch.await.unwrap().unwrap(), 1);
+        assert!(!is_cached(&cache, "k").await);
+
+        let (fetch, release) = blocked_fill(&cache, "p:1", "t").await;
+        cache
+            .apply_invalidation(remote(InvalidationOp::Prefix(cache.qualified("p:"))))
+            .await;
+        release.send(()).unwrap();
+        assert_eq!(fetch.await.unwrap().unwrap(), 1);
+        assert!(!is_cached(&cache, "p:1").await);
+    }
+
+    #[tokio::test]
+    async fn invalidation_after_a_fill_finishes_still_beats_its_write() {
+        let cache = KvCache::new(cfg(), None);
+        let (fetch, release) = blocked_fill(&cache, "k", "t").await;
+        // Park the fill between leaving `inflight` and writing L1, where
+        // the per-fill state no longer sees invalidations.
+        let l1 = cache.l1.lock().await;
+        release.send(()).unwrap();
+        while cache.inflight.contains_key("test:k") {
+            tokio::task::yield_now().await;
+        }
+        cache.invalidate_fills(|k| k == "test:k");
+        drop(l1);
+
+        assert_eq!(fetch.await.unwrap().unwrap(), 1);
+        assert!(!is_cached(&cache, "k").await);
+    }
+
+    async fn valkey_cache(config: &Config, namespace: &str) -> Arc<KvCache> {
+        let pool = Builder::from_config(config.clone())
+            .build_pool(1)
+            .expect("valkey pool");
+        pool.init().await.expect("valkey init");
+        KvCache::new(
+            KvCacheConfig {
+                namespace: namespace.into(),
+                l2_enabled: true,
+                ..cfg()
+            },
+            Some(pool),
+        )
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Valkey"]
+    async fn tag_invalidation_reaches_entries_promoted_from_l2() {
+        let url = std::env::var("JEDI_TEST_VALKEY_URL")
+            .unwrap_or_else(|_| panic!("JEDI_TEST_VALKEY_URL must point at a Valkey server"));
+        let config = Config::from_url(&url).expect("valkey url");
+        let namespace = format!("test:kv:{}", ulid::Ulid::new());
+        let a = valkey_cache(&config, &namespace).await;
+        let b = valkey_cache(&config, &namespace).await;
+        let _listener = b.start_invalidation_listener(config.clone()).await.unwrap();
+
+        fill(&a, "k", "t", 1).await;
+        let v: u32 = b
+            .get_or_fetch_json("k", None, || async { Ok::<u32, JediError>(2) })
+            .await
+            .unwrap();
+        assert_eq!(v, 1, "B reads A's value through L2");
+
+        a.invalidate_tag("t").await.unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while is_cached(&b, "k").await {
+            assert!(Instant::now() < deadline, "B still serves the purged entry");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[test]
+    fn invalidation_message_wire_format() {
+        let msg = remote(InvalidationOp::Tag("test:wallet".into()));
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"origin": "other-pod", "op": "tag", "target": "test:wallet"})
+        );
+        let back: InvalidationMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(back, msg);
+
+        let msg = InvalidationMessage {
+            keys: vec!["test:k".into()],
+            ..msg
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["keys"], serde_json::json!(["test:k"]));
+        let back: InvalidationMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(back, msg);
+    }
+
+    #[test]
+    fn jitter_only_shortens_ttls() {
+        let policy =
+            CachePolicy::stale_while_revalidate(Duration::from_secs(10), Duration::from_secs(100))
+                .with_jitter(0.2);
+        let mut distinct = std::collections::HashSet::new();
+        for i in 0..100 {
+            let key = format!("test:k{i}");
+            let ttls = policy.resolve(&cfg(), &key);
+            assert!(ttls.l1 <= Duration::from_secs(100) && ttls.l1 >= Duration::from_secs(80));
+            let soft = ttls.soft.unwrap();
+            assert!(soft <= Duration::from_secs(10) && soft >= Duration::from_secs(8));
+            assert_eq!(
+                policy.resolve(&cfg(), &key).l2,
+                ttls.l2,
+                "same key, same jitter"
+            );
+            distinct.insert(ttls.l2);
+        }
+        assert!(distinct.len() > 50, "keys spread out: {}", distinct.len());
+        assert_eq!(
+            CachePolicy::default().resolve(&cfg(), "test:k").l1,
+            cfg().l1_default_ttl
+        );
+    }
+
     #[test]
     #[serial]
     fn from_env_defaults() {
@@ -748,6 +1916,7 @@ mod tests {
             std::env::remove_var("KBVE_KV_L1_TTL_MS");
             std::env::remove_var("KBVE_KV_L2_TTL_S");
             std::env::remove_var("KBVE_KV_L2_ENABLED");
+            std::env::remove_var("KBVE_KV_INVALIDATION");
         }
         let c = KvCacheConfig::from_env();
         assert_eq!(c.namespace, "kbve:cache");
@@ -755,6 +1924,7 @@ mod tests {
         assert_eq!(c.l1_default_ttl, Duration::from_millis(500));
         assert_eq!(c.l2_default_ttl, Duration::from_secs(5));
         assert!(c.l2_enabled);
+        assert!(c.invalidation_enabled);
     }
 
     #[test]
@@ -766,6 +1936,7 @@ mod tests {
             std::env::set_var("KBVE_KV_L1_TTL_MS", "1500");
             std::env::set_var("KBVE_KV_L2_TTL_S", "30");
             std::env::set_var("KBVE_KV_L2_ENABLED", "false");
+            std::env::set_var("KBVE_KV_INVALIDATION", "false");
         }
         let c = KvCacheConfig::from_env();
         assert_eq!(c.namespace, "custom");
@@ -773,12 +1944,14 @@ mod tests {
         assert_eq!(c.l1_default_ttl, Duration::from_millis(1500));
         assert_eq!(c.l2_default_ttl, Duration::from_secs(30));
         assert!(!c.l2_enabled);
+        assert!(!c.invalidation_enabled);
         unsafe {
             std::env::remove_var("KBVE_KV_NAMESPACE");
             std::env::remove_var("KBVE_KV_L1_CAPACITY");
             std::env::remove_var("KBVE_KV_L1_TTL_MS");
             std::env::remove_var("KBVE_KV_L2_TTL_S");
             std::env::remove_var("KBVE_KV_L2_ENABLED");
+            std::env::remove_var("KBVE_KV_INVALIDATION");
         }
     }
 }
@@ -9,3 +9,6 @@ pub mod pg;
 
 #[cfg(feature = "valkey")]
 pub mod kv;
+
+#[cfg(feature = "valkey")]
+pub mod queue;
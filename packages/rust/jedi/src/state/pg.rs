@@ -501,10 +501,42 @@ impl PgCluster {
             + Send
             + 'static,
     {
-        self.cached_caller_read_tagged(cache, cache_key, ttl, sub, auth_role, f, |_: &T| Vec::new())
+        self.cached_caller_read_with(cache, cache_key, ttl.into(), sub, auth_role, f)
             .await
     }
 
+    /// [`cached_caller_read`] with a full
+    /// [`CachePolicy`](crate::state::kv::CachePolicy) — soft TTL with
+    /// background refresh, negative caching of `NotFound`, jitter.
+    pub async fn cached_caller_read_with<T, F>(
+        self: &Arc<Self>,
+        cache: &Arc<crate::state::kv::KvCache>,
+        cache_key: &str,
+        policy: crate::state::kv::CachePolicy,
+        sub: Uuid,
+        auth_role: Option<&str>,
+        f: F,
+    ) -> Result<T, JediError>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+        F: for<'tx> FnOnce(
+                &'tx tokio_postgres::Transaction<'_>,
+            ) -> BoxFuture<'tx, Result<T, JediError>>
+            + Send
+            + 'static,
+    {
+        self.cached_caller_read_tagged_with(
+            cache,
+            cache_key,
+            policy,
+            sub,
+            auth_role,
+            f,
+            |_: &T| Vec::new(),
+        )
+        .await
+    }
+
     /// [`cached_caller_read`] variant that also registers per-value
     /// tags so a later [`crate::state::kv::KvCache::invalidate_tag`]
     /// can purge every key carrying the tag. Tags are derived from
@@ -521,6 +553,40 @@ impl PgCluster {
         f: F,
         tag_fn: TagFn,
     ) -> Result<T, JediError>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+        F: for<'tx> FnOnce(
+                &'tx tokio_postgres::Transaction<'_>,
+            ) -> BoxFuture<'tx, Result<T, JediError>>
+            + Send
+            + 'static,
+        TagFn: FnOnce(&T) -> Vec<String> + Send + 'static,
+    {
+        self.cached_caller_read_tagged_with(
+            cache,
+            cache_key,
+            ttl.into(),
+            sub,
+            auth_role,
+            f,
+            tag_fn,
+        )
+        .await
+    }
+
+    /// [`cached_caller_read_tagged`] with a full
+    /// [`CachePolicy`](crate::state::kv::CachePolicy).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn cached_caller_read_tagged_with<T, F, TagFn>(
+        self: &Arc<Self>,
+        cache: &Arc<crate::state::kv::KvCache>,
+        cache_key: &str,
+        policy: crate::state::kv::CachePolicy,
+        sub: Uuid,
+        auth_role: Option<&str>,
+        f: F,
+        tag_fn: TagFn,
+    ) -> Result<T, JediError>
     where
         T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
         F: for<'tx> FnOnce(
@@ -533,9 +599,9 @@ impl PgCluster {
         let cluster = Arc::clone(self);
         let owned_role = auth_role.map(|s| s.to_string());
         cache
-            .get_or_fetch_json_tagged(
+            .get_or_fetch_json_tagged_with(
                 cache_key,
-                ttl,
+                policy,
                 move || async move {
                     cluster
                         .with_caller_read(sub, owned_role.as_deref(), f)
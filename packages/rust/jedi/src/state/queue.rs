@@ -0,0 +1,782 @@
+//! Durable job queue on Valkey Streams.
+//!
+//! Unlike the in-memory envelope pipeline, jobs here survive a pod
+//! restart: they live in a stream until a consumer acks them.
+//!
+//!   - producers `XADD` a JSON envelope (`job` field) to `{stream}`; the
+//!     name is wrapped in a hash tag unless it already carries one, so the
+//!     stream, its delayed set and its dead-letter stream share a cluster
+//!     slot and the promote script can touch two of them in one `EVAL`
+//!   - workers read through a consumer group (`XREADGROUP ... >`), so each
+//!     job goes to one consumer of the group
+//!   - a handler error re-queues the job with `attempts + 1` after an
+//!     exponential backoff; once `max_attempts` is reached it moves to the
+//!     dead-letter stream `{stream}:dead` together with the last error
+//!   - entries left pending longer than `claim_idle` (their consumer died
+//!     mid-job) are reclaimed with `XAUTOCLAIM` and re-queued the same way,
+//!     so a job that keeps crashing its worker still ends up dead-lettered
+//!   - delayed jobs wait in the sorted set `{stream}:delayed` (score = due
+//!     unix-ms) and are moved onto the stream by a Lua script, atomically
+//!   - the stream is trimmed by `MINID` below the oldest entry any consumer
+//!     group still needs (pending, or not yet delivered), never by length,
+//!     so a backlog is never dropped; only `{stream}:dead` is length-capped
+//!
+//! Delivery is at-least-once: a job re-queued or reclaimed around a crash
+//! can run twice, so handlers should be idempotent.
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use fred::clients::Pool as ValkeyPool;
+use fred::prelude::*;
+use fred::types::streams::{XCap, XCapKind, XCapTrim, XReadResponse, XReadValue};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::entity::error::JediError;
+
+/// Moves due members of the delayed set onto the stream.
+/// KEYS: delayed zset, stream. ARGV: now_ms, batch.
+const PROMOTE_SCRIPT: &str = r#"
+local due = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, ARGV[2])
+for _, job in ipairs(due) do
+    redis.call('ZREM', KEYS[1], job)
+    redis.call('XADD', KEYS[2], '*', 'job', job)
+end
+return #due
+"#;
+
+/// Trims stream entries every consumer group has both delivered and
+/// acked: the floor is each group's oldest pending entry, or its
+/// last-delivered id when nothing is pending. KEYS: stream.
+const TRIM_SCRIPT: &str = r#"
+local function before(a, b)
+    local am, as = string.match(a, '(%d+)-(%d+)')
+    local bm, bs = string.match(b, '(%d+)-(%d+)')
+    am, as, bm, bs = tonumber(am), tonumber(as), tonumber(bm), tonumber(bs)
+    return am < bm or (am == bm and as < bs)
+end
+local floor = nil
+for _, group in ipairs(redis.call('XINFO', 'GROUPS', KEYS[1])) do
+    local name, low
+    for i = 1, #group, 2 do
+        if group[i] == 'name' then name = group[i + 1] end
+        if group[i] == 'last-delivered-id' then low = group[i + 1] end
+    end
+    local pending = redis.call('XPENDING', KEYS[1], name)
+    if pending[1] > 0 then low = pending[2] end
+    if floor == nil or before(low, floor) then floor = low end
+end
+if floor == nil then return 0 end
+return redis.call('XTRIM', KEYS[1], 'MINID', '~', floor)
+"#;
+
+#[derive(Clone, Debug)]
+pub struct JobQueueConfig {
+    /// Stream key. [`JobQueueConfig::new`] wraps it in a `{...}` hash tag
+    /// when it has none; set it directly only to a name that already has
+    /// one, or the delayed set lands on another slot and promotion fails
+    /// with `CROSSSLOT` on a cluster.
+    pub stream: String,
+    pub group: String,
+    /// Unique per worker process; defaults to `$HOSTNAME` (the pod name).
+    pub consumer: String,
+    /// Approximate `MAXLEN` cap on the dead-letter stream. The work stream
+    /// isn't length-capped; see [`JobQueue::trim_acked`].
+    pub max_len: u64,
+    /// Deliveries before a job is dead-lettered.
+    pub max_attempts: u32,
+    /// Pending entries idle longer than this are reclaimed.
+    pub claim_idle: Duration,
+    /// Jobs fetched per `XREADGROUP` / `XAUTOCLAIM` / promotion round.
+    pub batch: u64,
+    /// `XREADGROUP BLOCK` timeout. Keep it below the fred command timeout.
+    pub block: Duration,
+    /// How often a worker promotes delayed jobs and reclaims stale ones.
+    pub maintenance_interval: Duration,
+    pub retry_backoff: Duration,
+    pub retry_backoff_max: Duration,
+}
+
+impl JobQueueConfig {
+    pub fn new(stream: impl Into<String>, group: impl Into<String>) -> Self {
+        let consumer = std::env::var("HOSTNAME")
+            .ok()
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| format!("consumer-{}", ulid::Ulid::new()));
+        Self {
+            stream: hash_tagged(stream.into()),
+            group: group.into(),
+            consumer,
+            max_len: 100_000,
+            max_attempts: 5,
+            claim_idle: Duration::from_secs(60),
+            batch: 16,
+            block: Duration::from_secs(2),
+            maintenance_interval: Duration::from_secs(5),
+            retry_backoff: Duration::from_secs(1),
+            retry_backoff_max: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_consumer(mut self, consumer: impl Into<String>) -> Self {
+        self.consumer = consumer.into();
+        self
+    }
+
+    pub fn dead_letter_stream(&self) -> String {
+        format!("{}:dead", self.stream)
+    }
+
+    pub fn delayed_key(&self) -> String {
+        format!("{}:delayed", self.stream)
+    }
+
+    /// Backoff before retry number `attempts` (1-based): `retry_backoff`
+    /// doubled per prior attempt, capped at `retry_backoff_max`.
+    fn backoff_for(&self, attempts: u32) -> Duration {
+        let factor = 1u32 << attempts.saturating_sub(1).min(20);
+        self.retry_backoff
+            .saturating_mul(factor)
+            .min(self.retry_backoff_max)
+    }
+}
+
+/// Wire format of the `job` field, shared by the stream and delayed set.
+#[derive(Debug, Serialize, Deserialize)]
+struct JobEnvelope<P> {
+    id: String,
+    attempts: u32,
+    enqueued_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+    payload: P,
+}
+
+/// A job handed to a worker. `entry_id` is the stream entry to ack;
+/// `id` stays stable across retries.
+#[derive(Debug, Clone)]
+pub struct Job<T> {
+    pub id: String,
+    pub entry_id: String,
+    /// Failed deliveries so far (0 on the first run).
+    pub attempts: u32,
+    pub enqueued_at: u64,
+    pub last_error: Option<String>,
+    pub payload: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NackOutcome {
+    Retried { delay: Duration },
+    DeadLettered,
+}
+
+/// Typed handle on one stream + consumer group. Cheap to share via `Arc`.
+pub struct JobQueue<T> {
+    pool: ValkeyPool,
+    /// Own connection for `XREADGROUP BLOCK`, so a parked read never holds
+    /// up the pool's other commands. Closed when the queue drops.
+    blocking: Client,
+    cfg: JobQueueConfig,
+    _payload: PhantomData<fn() -> T>,
+}
+
+impl<T> JobQueue<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Create the consumer group (and stream) if missing, reading from
+    /// the start so jobs enqueued before the first worker aren't skipped.
+    pub async fn new(pool: ValkeyPool, cfg: JobQueueConfig) -> Result<Arc<Self>, JediError> {
+        let created: Result<(), _> = pool
+            .xgroup_create(&*cfg.stream, &*cfg.group, "0", true)
+            .await;
+        if let Err(e) = created
+            && !e.details().starts_with("BUSYGROUP")
+        {
+            return Err(internal("xgroup create", e));
+        }
+        let blocking = pool.next().clone_new();
+        blocking
+            .init()
+            .await
+            .map_err(|e| internal("blocking client init", e))?;
+        Ok(Arc::new(Self {
+            pool,
+            blocking,
+            cfg,
+            _payload: PhantomData,
+        }))
+    }
+
+    pub fn config(&self) -> &JobQueueConfig {
+        &self.cfg
+    }
+
+    /// Append a job; returns its job id.
+    pub async fn enqueue(&self, payload: &T) -> Result<String, JediError> {
+        let envelope = JobEnvelope {
+            id: ulid::Ulid::new().to_string(),
+            attempts: 0,
+            enqueued_at: now_ms(),
+            last_error: None,
+            payload,
+        };
+        let id = envelope.id.clone();
+        self.xadd(&self.cfg.stream, false, vec![("job", encode(&envelope)?)])
+            .await?;
+        metrics_inc("kbve_queue_enqueued");
+        Ok(id)
+    }
+
+    /// Enqueue `payload` to become visible to workers after `delay`.
+    pub async fn enqueue_in(&self, payload: &T, delay: Duration) -> Result<String, JediError> {
+        self.enqueue_at(payload, SystemTime::now() + delay).await
+    }
+
+    pub async fn enqueue_at(&self, payload: &T, at: SystemTime) -> Result<String, JediError> {
+        let envelope = JobEnvelope {
+            id: ulid::Ulid::new().to_string(),
+            attempts: 0,
+            enqueued_at: now_ms(),
+            last_error: None,
+            payload,
+        };
+        let id = envelope.id.clone();
+        self.schedule(encode(&envelope)?, unix_ms(at)).await?;
+        metrics_inc("kbve_queue_enqueued_delayed");
+        Ok(id)
+    }
+
+    /// Move due delayed jobs onto the stream. Returns how many moved.
+    pub async fn promote_delayed(&self) -> Result<usize, JediError> {
+        let moved: i64 = self
+            .pool
+            .eval(
+                PROMOTE_SCRIPT,
+                vec![self.cfg.delayed_key(), self.cfg.stream.clone()],
+                vec![now_ms().to_string(), self.cfg.batch.to_string()],
+            )
+            .await
+            .map_err(|e| internal("promote delayed", e))?;
+        Ok(moved.max(0) as usize)
+    }
+
+    /// Drop stream entries that every consumer group has already been
+    /// handed and acked. Pending and undelivered entries are kept however
+    /// long the backlog grows. Returns how many entries were removed
+    /// (approximate trim, so whole stream nodes at a time).
+    pub async fn trim_acked(&self) -> Result<usize, JediError> {
+        let trimmed: i64 = self
+            .pool
+            .eval(
+                TRIM_SCRIPT,
+                vec![self.cfg.stream.clone()],
+                Vec::<String>::new(),
+            )
+            .await
+            .map_err(|e| internal("trim acked", e))?;
+        Ok(trimmed.max(0) as usize)
+    }
+
+    /// Read up to `batch` new jobs for this consumer, blocking up to
+    /// `block`. Undecodable entries are dead-lettered, not returned.
+    pub async fn fetch(&self) -> Result<Vec<Job<T>>, JediError> {
+        let mut response: XReadResponse<String, String, String, String> = self
+            .blocking
+            .xreadgroup_map(
+                &*self.cfg.group,
+                &*self.cfg.consumer,
+                Some(self.cfg.batch),
+                Some(self.cfg.block.as_millis() as u64),
+                false,
+                &*self.cfg.stream,
+                ">",
+            )
+            .await
+            .map_err(|e| internal("xreadgroup", e))?;
+        let entries = response.remove(&self.cfg.stream).unwrap_or_default();
+
+        let mut jobs = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if let Some(job) = self.decode_entry(entry).await? {
+                jobs.push(job);
+            }
+        }
+        Ok(jobs)
+    }
+
+    pub async fn ack(&self, job: &Job<T>) -> Result<(), JediError> {
+        self.ack_entry(&job.entry_id).await?;
+        metrics_inc("kbve_queue_acked");
+        Ok(())
+    }
+
+    /// Record a failed delivery: re-queue after backoff, or dead-letter
+    /// once `max_attempts` is reached.
+    pub async fn nack(&self, job: Job<T>, error: &str) -> Result<NackOutcome, JediError> {
+        let attempts = job.attempts + 1;
+        let envelope = JobEnvelope {
+            id: job.id,
+            attempts,
+            enqueued_at: job.enqueued_at,
+            last_error: Some(error.to_string()),
+            payload: &job.payload,
+        };
+        let raw = encode(&envelope)?;
+        self.retry_or_dead_letter(&job.entry_id, raw, attempts, error)
+            .await
+    }
+
+    /// Reclaim up to `batch` entries pending longer than `claim_idle` and
+    /// re-queue them as failed deliveries. Returns how many were handled.
+    pub async fn reclaim_stale(&self) -> Result<usize, JediError> {
+        let (_cursor, entries): (String, Vec<XReadValue<String, String, String>>) = self
+            .pool
+            .xautoclaim_values(
+                &*self.cfg.stream,
+                &*self.cfg.group,
+                &*self.cfg.consumer,
+                self.cfg.claim_idle.as_millis() as u64,
+                "0-0",
+                Some(self.cfg.batch),
+                false,
+            )
+            .await
+            .map_err(|e| internal("xautoclaim", e))?;
+
+        let count = entries.len();
+        for (entry_id, mut fields) in entries {
+            let Some(raw) = fields.remove("job") else {
+                self.ack_entry(&entry_id).await?;
+                continue;
+            };
+            let reason = "reclaimed: consumer idle past claim_idle";
+            match serde_json::from_str::<JobEnvelope<serde_json::Value>>(&raw) {
+                Ok(mut envelope) => {
+                    envelope.attempts += 1;
+                    envelope.last_error = Some(reason.to_string());
+                    let attempts = envelope.attempts;
+                    self.retry_or_dead_letter(&entry_id, encode(&envelope)?, attempts, reason)
+                        .await?;
+                }
+                Err(e) => {
+                    self.dead_letter(&entry_id, raw, &format!("undecodable job: {e}"))
+                        .await?;
+                }
+            }
+            metrics_inc("kbve_queue_reclaimed");
+        }
+        Ok(count)
+    }
+
+    /// Run `handler` over the queue until the queue is dropped: fetch,
+    /// handle, ack on `Ok` / nack on `Err`, with periodic delayed-job
+    /// promotion and stale reclaim. Jobs are handled one at a time; run
+    /// more workers (with distinct consumer names) for parallelism.
+    pub fn spawn_worker<F, Fut>(self: &Arc<Self>, handler: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Job<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), JediError>> + Send,
+        T: Clone,
+    {
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut last_maintenance: Option<Instant> = None;
+            loop {
+                let Some(queue) = weak.upgrade() else {
+                    break;
+                };
+                if last_maintenance.is_none_or(|at| at.elapsed() >= queue.cfg.maintenance_interval)
+                {
+                    queue.maintenance().await;
+                    last_maintenance = Some(Instant::now());
+                }
+
+                let jobs = match queue.fetch().await {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        tracing::warn!(error = %e, stream = %queue.cfg.stream, "[JobQueue] fetch failed");
+                        tokio::time::sleep(queue.cfg.block).await;
+                        continue;
+                    }
+                };
+                for job in jobs {
+                    let result = handler(job.clone()).await;
+                    let settled = match result {
+                        Ok(()) => queue.ack(&job).await.map(|_| ()),
+                        Err(e) => queue.nack(job, &e.to_string()).await.map(|_| ()),
+                    };
+                    if let Err(e) = settled {
+                        tracing::warn!(error = %e, stream = %queue.cfg.stream, "[JobQueue] ack/nack failed");
+                    }
+                }
+            }
+        })
+    }
+
+    async fn maintenance(&self) {
+        if let Err(e) = self.promote_delayed().await {
+            tracing::warn!(error = %e, stream = %self.cfg.stream, "[JobQueue] promote failed");
+        }
+        if let Err(e) = self.reclaim_stale().await {
+            tracing::warn!(error = %e, stream = %self.cfg.stream, "[JobQueue] reclaim failed");
+        }
+        if let Err(e) = self.trim_acked().await {
+            tracing::warn!(error = %e, stream = %self.cfg.stream, "[JobQueue] trim failed");
+        }
+    }
+
+    async fn decode_entry(
+        &self,
+        (entry_id, mut fields): XReadValue<String, String, String>,
+    ) -> Result<Option<Job<T>>, JediError> {
+        let Some(raw) = fields.remove("job") else {
+            self.ack_entry(&entry_id).await?;
+            return Ok(None);
+        };
+        match serde_json::from_str::<JobEnvelope<T>>(&raw) {
+            Ok(envelope) => Ok(Some(Job {
+                id: envelope.id,
+                entry_id,
+                attempts: envelope.attempts,
+                enqueued_at: envelope.enqueued_at,
+                last_error: envelope.last_error,
+                payload: envelope.payload,
+            })),
+            Err(e) => {
+                self.dead_letter(&entry_id, raw, &format!("undecodable job: {e}"))
+                    .await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn retry_or_dead_letter(
+        &self,
+        entry_id: &str,
+        raw: String,
+        attempts: u32,
+        error: &str,
+    ) -> Result<NackOutcome, JediError> {
+        if attempts >= self.cfg.max_attempts {
+            self.dead_letter(entry_id, raw, error).await?;
+            return Ok(NackOutcome::DeadLettered);
+        }
+        let delay = self.cfg.backoff_for(attempts);
+        if delay.is_zero() {
+            self.xadd(&self.cfg.stream, false, vec![("job", raw)])
+                .await?;
+        } else {
+            self.schedule(raw, unix_ms(SystemTime::now() + delay))
+                .await?;
+        }
+        self.ack_entry(entry_id).await?;
+        metrics_inc("kbve_queue_retried");
+        Ok(NackOutcome::Retried { delay })
+    }
+
+    async fn dead_letter(&self, entry_id: &str, raw: String, error: &str) -> Result<(), JediError> {
+        let dead = self.cfg.dead_letter_stream();
+        self.xadd(
+            &dead,
+            true,
+            vec![
+                ("job", raw),
+                ("error", error.to_string()),
+                ("entry_id", entry_id.to_string()),
+                ("failed_at", now_ms().to_string()),
+            ],
+        )
+        .await?;
+        self.ack_entry(entry_id).await?;
+        metrics_inc("kbve_queue_dead_lettered");
+        tracing::warn!(stream = %self.cfg.stream, entry_id, error, "[JobQueue] job dead-lettered");
+        Ok(())
+    }
+
+    /// `capped` applies the `max_len` cap; only the dead-letter stream
+    /// uses it, since a length cap on the work stream drops unacked jobs.
+    async fn xadd(
+        &self,
+        stream: &str,
+        capped: bool,
+        fields: Vec<(&str, String)>,
+    ) -> Result<(), JediError> {
+        let cap = if capped {
+            XCap::try_from((
+                XCapKind::MaxLen,
+                XCapTrim::AlmostExact,
+                self.cfg.max_len as i64,
+            ))
+            .map_err(|e| internal("xadd cap", e))?
+        } else {
+            XCap::from(None)
+        };
+        let _: String = self
+            .pool
+            .xadd(stream, false, cap, "*", fields)
+            .await
+            .map_err(|e| internal("xadd", e))?;
+        Ok(())
+    }
+
+    async fn schedule(&self, raw: String, due_ms: u64) -> Result<(), JediError> {
+        let _: i64 = self
+            .pool
+            .zadd(
+                self.cfg.delayed_key(),
+                None,
+                None,
+                false,
+                false,
+                (due_ms as f64, raw),
+            )
+            .await
+            .map_err(|e| internal("zadd", e))?;
+        Ok(())
+    }
+
+    async fn ack_entry(&self, entry_id: &str) -> Result<(), JediError> {
+        let _: i64 = self
+            .pool
+            .xack(&*self.cfg.stream, &*self.cfg.group, entry_id)
+            .await
+            .map_err(|e| internal("xack", e))?;
+        Ok(())
+    }
+}
+
+fn encode<P: Serialize>(envelope: &JobEnvelope<P>) -> Result<String, JediError> {
+    serde_json::to_string(envelope)
+        .map_err(|e| JediError::Internal(format!("queue encode: {e}").into()))
+}
+
+impl<T> Drop for JobQueue<T> {
+    fn drop(&mut self) {
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let blocking = self.blocking.clone();
+            runtime.spawn(async move {
+                let _ = blocking.quit().await;
+            });
+        }
+    }
+}
+
+/// `name` as is when it already has a non-empty `{tag}` (the part the
+/// cluster hashes), otherwise `{name}`.
+fn hash_tagged(name: String) -> String {
+    let tagged = name
+        .find('{')
+        .and_then(|open| name[open + 1..].find('}'))
+        .is_some_and(|len| len > 0);
+    if tagged { name } else { format!("{{{name}}}") }
+}
+
+fn internal(op: &str, e: impl std::fmt::Display) -> JediError {
+    JediError::Internal(format!("queue {op}: {e}").into())
+}
+
+fn unix_ms(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn now_ms() -> u64 {
+    unix_ms(SystemTime::now())
+}
+
+#[cfg(feature = "prometheus")]
+fn metrics_inc(name: &'static str) {
+    metrics::counter!(name).increment(1);
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn metrics_inc(_: &'static str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Email {
+        to: String,
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let mut cfg = JobQueueConfig::new("jobs", "workers");
+        cfg.retry_backoff = Duration::from_secs(1);
+        cfg.retry_backoff_max = Duration::from_secs(10);
+        assert_eq!(cfg.backoff_for(1), Duration::from_secs(1));
+        assert_eq!(cfg.backoff_for(2), Duration::from_secs(2));
+        assert_eq!(cfg.backoff_for(4), Duration::from_secs(8));
+        assert_eq!(cfg.backoff_for(5), Duration::from_secs(10));
+        assert_eq!(cfg.backoff_for(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn derived_keys_hang_off_the_stream() {
+        let cfg = JobQueueConfig::new("rows:jobs", "rows").with_consumer("pod-a");
+        assert_eq!(cfg.stream, "{rows:jobs}");
+        assert_eq!(cfg.dead_letter_stream(), "{rows:jobs}:dead");
+        assert_eq!(cfg.delayed_key(), "{rows:jobs}:delayed");
+        assert_eq!(cfg.consumer, "pod-a");
+    }
+
+    #[test]
+    fn existing_hash_tags_are_kept() {
+        let tagged = JobQueueConfig::new("app:{rows}:jobs", "rows");
+        assert_eq!(tagged.stream, "app:{rows}:jobs");
+        assert_eq!(tagged.delayed_key(), "app:{rows}:jobs:delayed");
+
+        // An empty `{}` hashes the whole key, so it doesn't count.
+        assert_eq!(JobQueueConfig::new("{}jobs", "g").stream, "{{}jobs}");
+        assert_eq!(JobQueueConfig::new("jobs}{", "g").stream, "{jobs}{}");
+    }
+
+    #[test]
+    fn envelope_roundtrips_through_untyped_retry() {
+        let envelope = JobEnvelope {
+            id: "01J".into(),
+            attempts: 0,
+            enqueued_at: 42,
+            last_error: None,
+            payload: Email {
+                to: "a@kbve.com".into(),
+            },
+        };
+        let raw = encode(&envelope).unwrap();
+        assert!(!raw.contains("last_error"));
+
+        // Reclaim bumps attempts without knowing `T`.
+        let mut untyped: JobEnvelope<serde_json::Value> = serde_json::from_str(&raw).unwrap();
+        untyped.attempts += 1;
+        untyped.last_error = Some("boom".into());
+        let raw = encode(&untyped).unwrap();
+
+        let typed: JobEnvelope<Email> = serde_json::from_str(&raw).unwrap();
+        assert_eq!(typed.attempts, 1);
+        assert_eq!(typed.last_error.as_deref(), Some("boom"));
+        assert_eq!(typed.payload, envelope.payload);
+    }
+
+    const TEST_URL_ENV: &str = "JEDI_TEST_VALKEY_URL";
+
+    /// A queue on a fresh stream: retries are immediate, two deliveries
+    /// dead-letter, and anything pending counts as stale.
+    async fn test_queue() -> Arc<JobQueue<Email>> {
+        let url = std::env::var(TEST_URL_ENV)
+            .unwrap_or_else(|_| panic!("{TEST_URL_ENV} must point at a Valkey server"));
+        let pool = Builder::from_config(Config::from_url(&url).expect("valkey url"))
+            .build_pool(1)
+            .expect("valkey pool");
+        pool.init().await.expect("valkey init");
+        let mut cfg = JobQueueConfig::new(format!("test:jobs:{}", ulid::Ulid::new()), "workers")
+            .with_consumer("c1");
+        cfg.max_attempts = 2;
+        cfg.retry_backoff = Duration::ZERO;
+        cfg.claim_idle = Duration::ZERO;
+        cfg.block = Duration::from_millis(100);
+        cfg.batch = 500;
+        JobQueue::new(pool, cfg).await.expect("queue")
+    }
+
+    async fn drop_keys(queue: &JobQueue<Email>) {
+        let cfg = queue.config();
+        let keys = vec![
+            cfg.stream.clone(),
+            cfg.dead_letter_stream(),
+            cfg.delayed_key(),
+        ];
+        let _: i64 = queue.pool.del(keys).await.unwrap();
+    }
+
+    fn email(to: &str) -> Email {
+        Email { to: to.into() }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Valkey"]
+    async fn jobs_flow_through_ack_retry_and_dead_letter() {
+        let queue = test_queue().await;
+        queue.enqueue(&email("a")).await.unwrap();
+        let b_id = queue.enqueue(&email("b")).await.unwrap();
+        queue.enqueue_in(&email("c"), Duration::ZERO).await.unwrap();
+        assert_eq!(queue.promote_delayed().await.unwrap(), 1);
+
+        let jobs = queue.fetch().await.unwrap();
+        let to: Vec<&str> = jobs.iter().map(|j| j.payload.to.as_str()).collect();
+        assert_eq!(to, ["a", "b", "c"]);
+        let mut jobs = jobs.into_iter();
+        queue.ack(&jobs.next().unwrap()).await.unwrap();
+        let b = jobs.next().unwrap();
+        assert_eq!(
+            queue.nack(b, "boom").await.unwrap(),
+            NackOutcome::Retried {
+                delay: Duration::ZERO
+            }
+        );
+        queue.ack(&jobs.next().unwrap()).await.unwrap();
+
+        let retried = queue.fetch().await.unwrap();
+        assert_eq!(retried.len(), 1);
+        assert_eq!(retried[0].id, b_id, "job id survives the retry");
+        assert_eq!(retried[0].attempts, 1);
+        assert_eq!(retried[0].last_error.as_deref(), Some("boom"));
+
+        // The consumer "dies" holding it; reclaim is its second failed
+        // delivery, which dead-letters it.
+        assert_eq!(queue.reclaim_stale().await.unwrap(), 1);
+        assert!(queue.fetch().await.unwrap().is_empty());
+        let dead: Vec<XReadValue<String, String, String>> = queue
+            .pool
+            .xrange_values(queue.config().dead_letter_stream(), "-", "+", None)
+            .await
+            .unwrap();
+        assert_eq!(dead.len(), 1);
+        let envelope: JobEnvelope<Email> = serde_json::from_str(&dead[0].1["job"]).unwrap();
+        assert_eq!(
+            (envelope.id.as_str(), envelope.attempts),
+            (b_id.as_str(), 2)
+        );
+        assert!(dead[0].1["error"].starts_with("reclaimed"));
+
+        drop_keys(&queue).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Valkey"]
+    async fn trim_keeps_pending_and_undelivered_jobs() {
+        let queue = test_queue().await;
+        for i in 0..300 {
+            queue.enqueue(&email(&format!("{i}"))).await.unwrap();
+        }
+        let jobs = queue.fetch().await.unwrap();
+        assert_eq!(jobs.len(), 300);
+        for job in &jobs[..250] {
+            queue.ack(job).await.unwrap();
+        }
+        for i in 0..10 {
+            queue.enqueue(&email(&format!("late-{i}"))).await.unwrap();
+        }
+
+        assert!(
+            queue.trim_acked().await.unwrap() > 0,
+            "acked prefix is trimmed"
+        );
+        let fresh = queue.fetch().await.unwrap();
+        assert_eq!(fresh.len(), 10, "undelivered jobs survive the trim");
+        let mut fresh = fresh.into_iter();
+        queue.ack(&fresh.next().unwrap()).await.unwrap();
+        // 50 from the first batch plus 9 late ones are still pending.
+        assert_eq!(queue.reclaim_stale().await.unwrap(), 59);
+
+        drop_keys(&queue).await;
+    }
+}
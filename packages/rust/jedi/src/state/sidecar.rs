@@ -196,6 +196,18 @@ impl ClickHouseConfig {
     }
 
     pub async fn execute_select(&self, query: &str) -> Result<Vec<serde_json::Value>, JediError> {
+        self.execute_select_with_params(query, &[]).await
+    }
+
+    /// Same as `execute_select`, but binds `{name:Type}` placeholders in the
+    /// query through the HTTP interface's `param_<name>` URL arguments so
+    /// values never get spliced into the SQL text. Values must already be in
+    /// ClickHouse's escaped text form (see `pipe_clickhouse::query`).
+    pub async fn execute_select_with_params(
+        &self,
+        query: &str,
+        params: &[(String, String)],
+    ) -> Result<Vec<serde_json::Value>, JediError> {
         let http = Self::shared_http_client();
         let full_query = format!("{} FORMAT JSONEachRow", query);
 
@@ -204,6 +216,10 @@ impl ClickHouseConfig {
             .query(&[("database", &self.database)])
             .body(full_query);
 
+        for (name, value) in params {
+            req = req.query(&[(format!("param_{}", name), value)]);
+        }
+
         if !self.user.is_empty() {
             req = req.header("X-ClickHouse-User", &self.user);
         }
@@ -259,6 +275,15 @@ impl ClickHouseConfig {
     /// Insert a pre-serialized JSONEachRow body (newline-delimited JSON objects).
     /// Lets hot callers serialize once on their own threads and hand the flusher
     /// a ready body, avoiding a second pass over `serde_json::Value`.
+    ///
+    /// A 4xx other than 408/429 (malformed rows, unknown table) comes back as
+    /// [`JediError::BadRequest`]: resending the same body cannot succeed.
+    /// Auth failures (401/403, or a ClickHouse auth exception code) stay
+    /// [`JediError::Database`] like any other outage, since the body is
+    /// fine and the credentials or grants get fixed out of band.
+    ///
+    /// Callers that treated every failure as `Database` must now handle
+    /// `BadRequest` too; [`Self::execute_insert`] behaves the same way.
     pub async fn execute_insert_raw(&self, table: &str, body: &str) -> Result<(), JediError> {
         if body.is_empty() {
             return Ok(());
@@ -283,8 +308,20 @@ impl ClickHouseConfig {
             JediError::Database(Cow::Owned(format!("ClickHouse HTTP error: {}", e)))
         })?;
 
-        if !resp.status().is_success() {
+        let status = resp.status();
+        if !status.is_success() {
+            let code = resp
+                .headers()
+                .get("X-ClickHouse-Exception-Code")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u32>().ok());
             let err_body = resp.text().await.unwrap_or_default();
+            if insert_rejected(status.as_u16(), code) {
+                return Err(JediError::BadRequest(format!(
+                    "ClickHouse rejected insert ({}): {}",
+                    status, err_body
+                )));
+            }
             return Err(JediError::Database(Cow::Owned(format!(
                 "ClickHouse insert failed: {}",
                 err_body
@@ -295,6 +332,20 @@ impl ClickHouseConfig {
     }
 }
 
+/// ClickHouse auth exception codes: UNKNOWN_USER, WRONG_PASSWORD,
+/// REQUIRED_PASSWORD, IP_ADDRESS_NOT_ALLOWED, ACCESS_DENIED,
+/// AUTHENTICATION_FAILED.
+#[cfg(feature = "clickhouse")]
+const CH_AUTH_CODES: [u32; 6] = [192, 193, 194, 195, 497, 516];
+
+/// Whether a failed insert can never succeed as sent. Timeouts, rate
+/// limits and auth failures are retried; other 4xx are not.
+#[cfg(feature = "clickhouse")]
+fn insert_rejected(status: u16, code: Option<u32>) -> bool {
+    let auth = matches!(status, 401 | 403) || code.is_some_and(|c| CH_AUTH_CODES.contains(&c));
+    (400..500).contains(&status) && !matches!(status, 408 | 429) && !auth
+}
+
 pub struct TwitchAuth {
     pub client_id: String,
     pub client_secret: String,
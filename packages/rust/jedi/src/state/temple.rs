@@ -1,7 +1,8 @@
 use std::sync::Arc;
 // use bb8_redis::{ RedisConnectionManager, bb8::Pool };
-use super::watchmaster::WatchManager;
-use fred::{clients::Pool, prelude::*};
+use super::watchmaster::{ConnId, WatchDelta, WatchManager, WatchResume};
+use fred::{clients::Pool, prelude::*, types::scan::ScanType};
+use futures_util::StreamExt;
 use tokio::{
     sync::{broadcast, mpsc::Sender, oneshot},
     task::JoinHandle,
@@ -10,13 +11,20 @@ use tokio::{
 use crate::{
     entity::envelope::EnvelopeWorkItem,
     entity::pipe_redis::faucet_redis::{
-        create_pubsub_connection_fred, spawn_pubsub_listener_task, spawn_redis_worker,
-        spawn_watch_event_listener,
+        create_pubsub_connection_fred, spawn_redis_worker, spawn_watch_event_listener,
+        spawn_watched_pubsub_listener_task,
     },
+    entity::pipe_redis::redis_types::WatchResumeOutput,
     error::JediError,
     proto::jedi::JediEnvelope,
 };
 
+/// Keys taken per watched pattern when a resume falls back to a snapshot.
+pub const SNAPSHOT_PATTERN_LIMIT: usize = 1000;
+
+/// Keys per `MGET` when reading a resume snapshot.
+const SNAPSHOT_MGET_BATCH: usize = 100;
+
 pub struct TempleState {
     pub redis_pool: Pool,
     pub envelope_tx: Sender<EnvelopeWorkItem>,
@@ -54,7 +62,8 @@ impl TempleState {
 
         let (conn, push_rx) = create_pubsub_connection_fred(config.clone()).await?;
         let watch_listener_task = spawn_watch_event_listener(watch_event_rx, conn.clone());
-        let pubsub_task = spawn_pubsub_listener_task(push_rx, event_tx.clone());
+        let pubsub_task =
+            spawn_watched_pubsub_listener_task(push_rx, event_tx.clone(), watch_manager.clone());
 
         let temple = Arc::new(Self {
             redis_pool,
@@ -103,7 +112,8 @@ impl TempleState {
 
         let (conn, push_rx) = create_pubsub_connection_fred(config.clone()).await?;
         let watch_listener_task = spawn_watch_event_listener(watch_event_rx, conn.clone());
-        let pubsub_task = spawn_pubsub_listener_task(push_rx, event_tx.clone());
+        let pubsub_task =
+            spawn_watched_pubsub_listener_task(push_rx, event_tx.clone(), watch_manager.clone());
 
         let ch_client = ch_config.build_client();
 
@@ -141,6 +151,70 @@ impl TempleState {
             .map_err(|_| JediError::Internal("Failed to receive response".into()))
     }
 
+    /// Catch a reconnected client up on its current watches: the missed
+    /// deltas when `token` is still in the watch log, otherwise a snapshot
+    /// of each watched key (patterns are expanded with `SCAN ... TYPE
+    /// string`, capped at [`SNAPSHOT_PATTERN_LIMIT`] keys each). Values are
+    /// read with batched `MGET`s; an exact watch on a non-string key
+    /// snapshots as `None` rather than failing the resume.
+    pub async fn resume_watches(
+        &self,
+        conn_id: &ConnId,
+        token: Option<&str>,
+    ) -> Result<WatchResumeOutput, JediError> {
+        let (token, seq, mut keys, patterns) = match self.watch_manager.resume(conn_id, token) {
+            WatchResume::Deltas { token, deltas } => {
+                return Ok(WatchResumeOutput {
+                    token,
+                    snapshot: false,
+                    deltas,
+                });
+            }
+            WatchResume::Snapshot {
+                token,
+                seq,
+                keys,
+                patterns,
+            } => (token, seq, keys, patterns),
+        };
+
+        let client = self.redis_pool.next().clone();
+        for pattern in &patterns {
+            let mut scan = std::pin::pin!(
+                client
+                    .scan_buffered(&**pattern, Some(100), Some(ScanType::String))
+                    .take(SNAPSHOT_PATTERN_LIMIT)
+            );
+            while let Some(key) = scan.next().await {
+                if let Some(key) = key?.as_str() {
+                    keys.push(Arc::from(key));
+                }
+            }
+        }
+        keys.sort_unstable();
+        keys.dedup();
+
+        let mut deltas = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(SNAPSHOT_MGET_BATCH) {
+            let names: Vec<&str> = chunk.iter().map(|k| &**k).collect();
+            let values: Vec<Option<String>> = client.mget(names).await?;
+            for (key, value) in chunk.iter().zip(values) {
+                deltas.push(WatchDelta {
+                    seq,
+                    version: self.watch_manager.log.version(key),
+                    key: Arc::clone(key),
+                    value: value.map(Arc::from),
+                });
+            }
+        }
+
+        Ok(WatchResumeOutput {
+            token,
+            snapshot: true,
+            deltas,
+        })
+    }
+
     pub fn subscribe_events(&self) -> broadcast::Receiver<JediEnvelope> {
         self.event_tx.subscribe()
     }
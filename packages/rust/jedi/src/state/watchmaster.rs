@@ -1,7 +1,9 @@
 use bytes::Bytes;
 use dashmap::DashSet;
 use papaya::HashMap;
-use std::sync::Arc;
+use serde::Serialize;
+use std::collections::{HashMap as StdHashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
@@ -16,18 +18,43 @@ pub type ConnId = [u8; 16];
 pub type WatchedKeys = Arc<DashSet<Arc<str>>>;
 pub type WatchedConns = Arc<DashSet<ConnId>>;
 
+/// Deltas kept for resume before a reconnecting client falls back to a
+/// snapshot.
+pub const DEFAULT_WATCH_LOG_CAPACITY: usize = 4096;
+
+/// Cheap to clone; clones share all watch state.
+#[derive(Clone)]
 pub struct WatchManager {
     pub key_to_conns: Arc<HashMap<Arc<str>, WatchedConns>>,
     pub conn_to_keys: Arc<HashMap<ConnId, WatchedKeys>>,
+    /// Glob watches (`mc:player:*`), Redis `PSUBSCRIBE` syntax.
+    pub pattern_to_conns: Arc<HashMap<Arc<str>, WatchedConns>>,
+    pub conn_to_patterns: Arc<HashMap<ConnId, WatchedKeys>>,
+    pub log: Arc<WatchLog>,
     pub event_tx: UnboundedSender<JediEnvelope>,
+    /// Pattern copies of an update still expected from Redis, per key.
+    pattern_echoes: Arc<Mutex<StdHashMap<Arc<str>, PatternEcho>>>,
+}
+
+struct PatternEcho {
+    value: Option<Arc<str>>,
+    remaining: usize,
 }
 
 impl WatchManager {
     pub fn new(event_tx: UnboundedSender<JediEnvelope>) -> Self {
+        Self::with_log_capacity(event_tx, DEFAULT_WATCH_LOG_CAPACITY)
+    }
+
+    pub fn with_log_capacity(event_tx: UnboundedSender<JediEnvelope>, capacity: usize) -> Self {
         Self {
             key_to_conns: Arc::new(HashMap::default()),
             conn_to_keys: Arc::new(HashMap::default()),
+            pattern_to_conns: Arc::new(HashMap::default()),
+            conn_to_patterns: Arc::new(HashMap::default()),
+            log: Arc::new(WatchLog::new(capacity)),
             event_tx,
+            pattern_echoes: Arc::new(Mutex::new(StdHashMap::new())),
         }
     }
 
@@ -144,24 +171,216 @@ impl WatchManager {
                 .unwrap_or_default()
         };
 
-        removed_keys
-            .into_iter()
-            .filter_map(
-                |key| match self.unwatch(conn_id, Arc::clone(&key), format) {
-                    Ok(true) => Some(key),
-                    Ok(false) => None,
-                    Err(e) => {
-                        tracing::warn!(
-                            "[WatchManager] Failed to unwatch: key={key}, conn={:?}, err={e}",
-                            conn_id
-                        );
-                        None
-                    }
-                },
-            )
+        let removed_patterns: Vec<Arc<str>> = {
+            let conn_guard = self.conn_to_patterns.guard();
+            self.conn_to_patterns
+                .get(conn_id, &conn_guard)
+                .map(|set| set.iter().map(|p| Arc::clone(p.key())).collect())
+                .unwrap_or_default()
+        };
+
+        let keys = removed_keys.into_iter().map(|key| {
+            let result = self.unwatch(conn_id, Arc::clone(&key), format);
+            (key, result)
+        });
+        let patterns = removed_patterns.into_iter().map(|pattern| {
+            let result = self.unwatch_pattern(conn_id, Arc::clone(&pattern), format);
+            (pattern, result)
+        });
+
+        keys.chain(patterns)
+            .filter_map(|(key, result)| match result {
+                Ok(true) => Some(key),
+                Ok(false) => None,
+                Err(e) => {
+                    tracing::warn!(
+                        "[WatchManager] Failed to unwatch: key={key}, conn={:?}, err={e}",
+                        conn_id
+                    );
+                    None
+                }
+            })
             .collect()
     }
 
+    /// Watch every key matching `pattern` (`*`, `?`, `[a-z]`, `\` escapes).
+    /// The first watcher of a pattern emits a `PWATCH` event so the
+    /// listener `PSUBSCRIBE`s `key:<pattern>`.
+    pub fn watch_pattern<K: Into<Arc<str>>>(
+        &self,
+        conn_id: ConnId,
+        pattern: K,
+        format: PayloadFormat,
+    ) -> Result<(), JediError> {
+        let pattern = pattern.into();
+        if pattern.is_empty() {
+            return Err(JediError::BadRequest("Empty watch pattern".into()));
+        }
+
+        let pattern_guard = self.pattern_to_conns.guard();
+        let conns = self
+            .pattern_to_conns
+            .get(&pattern, &pattern_guard)
+            .cloned()
+            .unwrap_or_else(|| {
+                let new = Arc::new(DashSet::new());
+                self.pattern_to_conns
+                    .insert(Arc::clone(&pattern), new.clone(), &pattern_guard);
+                new
+            });
+
+        if !conns.insert(conn_id) {
+            return Err(JediError::BadRequest(
+                "Already watching this pattern".into(),
+            ));
+        }
+
+        let conn_guard = self.conn_to_patterns.guard();
+        let patterns = self
+            .conn_to_patterns
+            .get(&conn_id, &conn_guard)
+            .cloned()
+            .unwrap_or_else(|| {
+                let new = Arc::new(DashSet::new());
+                self.conn_to_patterns
+                    .insert(conn_id, new.clone(), &conn_guard);
+                new
+            });
+
+        patterns.insert(Arc::clone(&pattern));
+
+        if conns.len() == 1 {
+            self.forget_pattern_echoes(&pattern);
+            let payload = KeyValueInput {
+                key: Arc::clone(&pattern),
+                value: None,
+                ttl: None,
+            };
+
+            let metadata = Some(Bytes::copy_from_slice(&conn_id));
+            let envelope = wrap_hybrid(MessageKind::PWATCH, format, &payload, metadata);
+            if let Err(e) = self.event_tx.send(envelope) {
+                tracing::warn!("[WatchManager] Failed to emit PWATCH event: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn unwatch_pattern<K: Into<Arc<str>>>(
+        &self,
+        conn_id: &ConnId,
+        pattern: K,
+        format: PayloadFormat,
+    ) -> Result<bool, JediError> {
+        let pattern = pattern.into();
+        let mut last = false;
+
+        let pattern_guard = self.pattern_to_conns.guard();
+        let Some(conns) = self
+            .pattern_to_conns
+            .get(&pattern, &pattern_guard)
+            .filter(|conns| conns.contains(conn_id))
+        else {
+            return Err(JediError::BadRequest(
+                "This pattern is not watched by the connection".into(),
+            ));
+        };
+
+        conns.remove(conn_id);
+        if conns.is_empty() {
+            self.pattern_to_conns.remove(&pattern, &pattern_guard);
+            self.forget_pattern_echoes(&pattern);
+            last = true;
+
+            let payload = KeyValueInput {
+                key: Arc::clone(&pattern),
+                value: None,
+                ttl: None,
+            };
+
+            let envelope = wrap_hybrid(MessageKind::PUNWATCH, format, &payload, None);
+            let _ = self.event_tx.send(envelope);
+        }
+
+        let conn_guard = self.conn_to_patterns.guard();
+        if let Some(patterns) = self.conn_to_patterns.get(conn_id, &conn_guard) {
+            patterns.remove(&pattern);
+            if patterns.is_empty() {
+                self.conn_to_patterns.remove(conn_id, &conn_guard);
+            }
+        }
+
+        Ok(last)
+    }
+
+    pub fn is_watching_pattern<K: Into<Arc<str>>>(&self, conn_id: &ConnId, pattern: K) -> bool {
+        let pattern = pattern.into();
+        let guard = self.pattern_to_conns.guard();
+
+        self.pattern_to_conns
+            .get(&pattern, &guard)
+            .map(|set| set.contains(conn_id))
+            .unwrap_or(false)
+    }
+
+    /// Whether updates to `key` reach `conn_id`, through an exact watch
+    /// or a matching pattern.
+    pub fn is_subscribed(&self, conn_id: &ConnId, key: &str) -> bool {
+        if self.is_watching(conn_id, key) {
+            return true;
+        }
+        let guard = self.conn_to_patterns.guard();
+        self.conn_to_patterns
+            .get(conn_id, &guard)
+            .is_some_and(|patterns| patterns.iter().any(|p| glob_match(p.key(), key)))
+    }
+
+    /// Resume a reconnecting client from the token of the last update it
+    /// saw. Yields the missed deltas for what `conn_id` watches now (watch
+    /// first, then resume), or a snapshot plan when the token is from
+    /// another process or older than the retained log.
+    pub fn resume(&self, conn_id: &ConnId, token: Option<&str>) -> WatchResume {
+        match token.and_then(|t| self.log.since(t)) {
+            Some((deltas, token)) => WatchResume::Deltas {
+                token,
+                deltas: deltas
+                    .into_iter()
+                    .filter(|d| self.is_subscribed(conn_id, &d.key))
+                    .collect(),
+            },
+            None => {
+                let mut keys = Vec::new();
+                self.for_each_key(conn_id, |k| keys.push(Arc::clone(k)));
+                let patterns = {
+                    let guard = self.conn_to_patterns.guard();
+                    self.conn_to_patterns
+                        .get(conn_id, &guard)
+                        .map(|set| set.iter().map(|p| Arc::clone(p.key())).collect())
+                        .unwrap_or_default()
+                };
+                let seq = self.log.seq();
+                WatchResume::Snapshot {
+                    token: self.log.token_at(seq),
+                    seq,
+                    keys,
+                    patterns,
+                }
+            }
+        }
+    }
+
+    fn for_each_pattern_watcher<F: FnMut(&ConnId)>(&self, key: &str, mut f: F) {
+        let guard = self.pattern_to_conns.guard();
+        for (pattern, conns) in self.pattern_to_conns.iter(&guard) {
+            if glob_match(pattern, key) {
+                for conn in conns.iter() {
+                    f(conn.key());
+                }
+            }
+        }
+    }
+
     pub fn is_watching<K: Into<Arc<str>>>(&self, conn_id: &ConnId, key: K) -> bool {
         let key_arc = key.into();
         let guard = self.key_to_conns.guard();
@@ -172,26 +391,42 @@ impl WatchManager {
             .unwrap_or(false)
     }
 
+    /// Visits exact and pattern watchers of `key`, each connection once.
     pub fn for_each_watcher_pin<K: Into<Arc<str>>, F: FnMut(&ConnId)>(&self, key: K, mut f: F) {
         let pinned = self.key_to_conns.pin_owned();
         let key_arc = key.into();
+        let mut seen = HashSet::new();
 
         if let Some(set) = pinned.get(&key_arc) {
             for conn in set.iter() {
+                seen.insert(*conn.key());
                 f(conn.key());
             }
         }
+        self.for_each_pattern_watcher(&key_arc, |conn| {
+            if seen.insert(*conn) {
+                f(conn);
+            }
+        });
     }
 
+    /// Visits exact and pattern watchers of `key`, each connection once.
     pub fn for_each_watcher<K: Into<Arc<str>>, F: FnMut(&ConnId)>(&self, key: K, mut f: F) {
         let key_arc = key.into();
         let guard = self.key_to_conns.guard();
+        let mut seen = HashSet::new();
 
         if let Some(set) = self.key_to_conns.get(&key_arc, &guard) {
             for conn in set.iter() {
+                seen.insert(*conn.key());
                 f(conn.key());
             }
         }
+        self.for_each_pattern_watcher(&key_arc, |conn| {
+            if seen.insert(*conn) {
+                f(conn);
+            }
+        });
     }
 
     pub fn for_each_key_pin<F: FnMut(&Arc<str>)>(&self, conn_id: &ConnId, mut f: F) {
@@ -214,13 +449,492 @@ impl WatchManager {
         }
     }
 
+    /// Exact or pattern watchers exist for `key`.
     pub fn has_watchers<K: Into<Arc<str>>>(&self, key: K) -> bool {
         let key_arc = key.into();
+        if self.has_exact_watchers(&key_arc) {
+            return true;
+        }
+        let guard = self.pattern_to_conns.guard();
+        self.pattern_to_conns
+            .iter(&guard)
+            .any(|(pattern, conns)| !conns.is_empty() && glob_match(pattern, &key_arc))
+    }
+
+    pub fn has_exact_watchers(&self, key: &Arc<str>) -> bool {
         let guard = self.key_to_conns.guard();
 
         self.key_to_conns
-            .get(&key_arc, &guard)
+            .get(key, &guard)
             .map(|set| !set.is_empty())
             .unwrap_or(false)
     }
+
+    /// Watched patterns `key` matches, i.e. how many `pmessage` copies
+    /// Redis sends for one update to it.
+    pub fn matching_patterns(&self, key: &str) -> usize {
+        let guard = self.pattern_to_conns.guard();
+        self.pattern_to_conns
+            .iter(&guard)
+            .filter(|(pattern, conns)| !conns.is_empty() && glob_match(pattern, key))
+            .count()
+    }
+
+    /// Drop the expected copies for keys `pattern` matches. Once it is
+    /// (un)subscribed they no longer arrive as counted, and a stale echo
+    /// would swallow the next real update carrying the same value.
+    fn forget_pattern_echoes(&self, pattern: &str) {
+        self.pattern_echoes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|key, _| !glob_match(pattern, key));
+    }
+
+    /// Record an update Redis delivered for `key`, or return `None` if it
+    /// is a duplicate. Redis sends one copy per matching subscription, back
+    /// to back: the exact `SUBSCRIBE` first, then each overlapping
+    /// `PSUBSCRIBE` pattern. The exact copy wins when there is one;
+    /// otherwise the first pattern copy is recorded and the rest dropped.
+    pub fn observe(
+        &self,
+        key: Arc<str>,
+        value: Option<Arc<str>>,
+        via_pattern: bool,
+    ) -> Option<WatchDelta> {
+        if via_pattern {
+            if self.has_exact_watchers(&key) {
+                return None;
+            }
+            let mut echoes = self.pattern_echoes.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(echo) = echoes.get_mut(&key)
+                && echo.value == value
+            {
+                echo.remaining -= 1;
+                if echo.remaining == 0 {
+                    echoes.remove(&key);
+                }
+                return None;
+            }
+            match self.matching_patterns(&key) {
+                0 | 1 => {
+                    echoes.remove(&key);
+                }
+                n => {
+                    echoes.insert(
+                        Arc::clone(&key),
+                        PatternEcho {
+                            value: value.clone(),
+                            remaining: n - 1,
+                        },
+                    );
+                }
+            }
+        }
+        Some(self.log.record(key, value))
+    }
+}
+
+/// One observed key update. `version` increases per key and `seq` across
+/// all keys, both scoped to this process (see [`WatchLog`]). Neither is
+/// contiguous per key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchDelta {
+    pub seq: u64,
+    #[serde(with = "crate::entity::serde_arc_str")]
+    pub key: Arc<str>,
+    pub version: u64,
+    #[serde(with = "crate::entity::serde_arc_str::option")]
+    pub value: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchResume {
+    /// Replay these, then continue from `token`.
+    Deltas {
+        token: String,
+        deltas: Vec<WatchDelta>,
+    },
+    /// The gap can't be replayed: re-read these keys and patterns. `token`
+    /// was taken before the read, so later updates are not lost.
+    Snapshot {
+        token: String,
+        seq: u64,
+        keys: Vec<Arc<str>>,
+        patterns: Vec<Arc<str>>,
+    },
+}
+
+/// Per-key versions plus a bounded ring of recent deltas.
+///
+/// A key's version is only kept while one of its deltas is in the ring.
+/// Once the last one is evicted the key is forgotten, and its next update
+/// starts above the highest evicted `seq` — every version is at most the
+/// `seq` it was recorded at, so the key's versions still only increase.
+///
+/// Resume tokens are `<epoch>:<seq>`; the epoch is fresh per process, so
+/// a token minted by another pod or before a restart always falls back
+/// to a snapshot instead of replaying a foreign sequence.
+pub struct WatchLog {
+    epoch: String,
+    capacity: usize,
+    inner: Mutex<WatchLogInner>,
+}
+
+#[derive(Default)]
+struct WatchLogInner {
+    seq: u64,
+    /// `seq` of the newest delta evicted from the ring.
+    evicted_seq: u64,
+    versions: StdHashMap<Arc<str>, KeyVersion>,
+    deltas: VecDeque<WatchDelta>,
+}
+
+struct KeyVersion {
+    version: u64,
+    /// Deltas for the key still in the ring.
+    retained: usize,
+}
+
+impl WatchLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            epoch: ulid::Ulid::new().to_string(),
+            capacity: capacity.max(1),
+            inner: Mutex::new(WatchLogInner::default()),
+        }
+    }
+
+    /// Bump `key`'s version and append the delta.
+    pub fn record(&self, key: Arc<str>, value: Option<Arc<str>>) -> WatchDelta {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.deltas.len() == self.capacity
+            && let Some(old) = inner.deltas.pop_front()
+        {
+            inner.evicted_seq = old.seq;
+            if let Some(v) = inner.versions.get_mut(&old.key) {
+                v.retained -= 1;
+                if v.retained == 0 {
+                    inner.versions.remove(&old.key);
+                }
+            }
+        }
+        inner.seq += 1;
+        let seq = inner.seq;
+        let floor = inner.evicted_seq;
+        let version = {
+            let v = inner
+                .versions
+                .entry(Arc::clone(&key))
+                .or_insert(KeyVersion {
+                    version: floor,
+                    retained: 0,
+                });
+            v.version += 1;
+            v.retained += 1;
+            v.version
+        };
+        let delta = WatchDelta {
+            seq,
+            key,
+            version,
+            value,
+        };
+        inner.deltas.push_back(delta.clone());
+        delta
+    }
+
+    /// Current version of `key`; 0 if none of its updates is retained.
+    pub fn version(&self, key: &str) -> u64 {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.versions.get(key).map_or(0, |v| v.version)
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).seq
+    }
+
+    pub fn token(&self) -> String {
+        self.token_at(self.seq())
+    }
+
+    pub fn token_at(&self, seq: u64) -> String {
+        format!("{}:{seq}", self.epoch)
+    }
+
+    /// Deltas after `token` and the token to continue from, or `None`
+    /// if the token is foreign, malformed or already evicted.
+    pub fn since(&self, token: &str) -> Option<(Vec<WatchDelta>, String)> {
+        let (epoch, seq) = token.rsplit_once(':')?;
+        let seq: u64 = seq.parse().ok()?;
+        if epoch != self.epoch {
+            return None;
+        }
+
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if seq > inner.seq {
+            return None;
+        }
+        let oldest = inner.deltas.front().map_or(inner.seq + 1, |d| d.seq);
+        if seq + 1 < oldest {
+            return None;
+        }
+        let deltas = inner
+            .deltas
+            .iter()
+            .filter(|d| d.seq > seq)
+            .cloned()
+            .collect();
+        Some((deltas, self.token_at(inner.seq)))
+    }
+}
+
+/// Redis glob semantics (`*`, `?`, `[abc]`, `[^a]`, `[a-z]`, `\x`), the
+/// same matching `PSUBSCRIBE` applies to channels.
+pub fn glob_match(pattern: &str, key: &str) -> bool {
+    glob_match_bytes(pattern.as_bytes(), key.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], key: &[u8]) -> bool {
+    let (mut p, mut k) = (0, 0);
+    // Backtrack point for the most recent `*`: (pattern after it, key pos).
+    let mut star: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        let step = match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p + 1, k));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => Some(p + 1),
+            Some(b'[') => match_class(pattern, p, key[k]),
+            Some(b'\\') if p + 1 < pattern.len() => (pattern[p + 1] == key[k]).then_some(p + 2),
+            Some(&c) => (c == key[k]).then_some(p + 1),
+            None => None,
+        };
+        match (step, star) {
+            (Some(next), _) => {
+                p = next;
+                k += 1;
+            }
+            (None, Some((star_p, star_k))) => {
+                p = star_p;
+                k = star_k + 1;
+                star = Some((star_p, star_k + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Match `c` against the class opening at `pattern[start]`; returns the
+/// index after the closing `]` on a match.
+fn match_class(pattern: &[u8], start: usize, c: u8) -> Option<usize> {
+    let mut i = start + 1;
+    let negate = pattern.get(i) == Some(&b'^');
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            matched |= pattern[i + 1] == c;
+            i += 2;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            let (lo, hi) = (
+                pattern[i].min(pattern[i + 2]),
+                pattern[i].max(pattern[i + 2]),
+            );
+            matched |= (lo..=hi).contains(&c);
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    // An unterminated class matches like Redis: up to the end of pattern.
+    (matched != negate).then_some((i + 1).min(pattern.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+    fn manager(capacity: usize) -> (WatchManager, UnboundedReceiver<JediEnvelope>) {
+        let (tx, rx) = unbounded_channel();
+        (WatchManager::with_log_capacity(tx, capacity), rx)
+    }
+
+    #[test]
+    fn glob_match_follows_redis_rules() {
+        assert!(glob_match("mc:player:*", "mc:player:42"));
+        assert!(glob_match("mc:player:*", "mc:player:"));
+        assert!(!glob_match("mc:player:*", "mc:guild:1"));
+        assert!(glob_match("mc:*:hp", "mc:player:7:hp"));
+        assert!(glob_match("h?llo", "hello"));
+        assert!(!glob_match("h?llo", "hllo"));
+        assert!(glob_match("h[ae]llo", "hallo"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("room:[0-9]", "room:5"));
+        assert!(!glob_match("room:[0-9]", "room:a"));
+        assert!(glob_match(r"lit\*", "lit*"));
+        assert!(!glob_match(r"lit\*", "lit!"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn pattern_watch_reaches_matching_keys_once() {
+        let (wm, mut rx) = manager(16);
+        let conn = [1u8; 16];
+        wm.watch_pattern(conn, "mc:player:*", PayloadFormat::Json)
+            .unwrap();
+        wm.watch(conn, "mc:player:1", PayloadFormat::Json).unwrap();
+
+        assert_eq!(rx.try_recv().unwrap().kind, MessageKind::PWATCH);
+        assert!(wm.is_subscribed(&conn, "mc:player:2"));
+        assert!(!wm.is_subscribed(&conn, "mc:guild:2"));
+        assert!(wm.has_watchers("mc:player:9"));
+
+        let mut seen = Vec::new();
+        wm.for_each_watcher("mc:player:1", |c| seen.push(*c));
+        assert_eq!(seen, vec![conn]);
+
+        let removed = wm.remove_connection(&conn, PayloadFormat::Json);
+        assert_eq!(removed.len(), 2);
+        assert!(!wm.has_watchers("mc:player:9"));
+    }
+
+    #[test]
+    fn log_versions_keys_and_replays_from_token() {
+        let log = WatchLog::new(8);
+        let start = log.token();
+        assert_eq!(log.record("a".into(), Some("1".into())).version, 1);
+        assert_eq!(log.record("b".into(), None).version, 1);
+        let mid = log.token();
+        assert_eq!(log.record("a".into(), Some("2".into())).version, 2);
+        assert_eq!(log.version("a"), 2);
+
+        let (deltas, token) = log.since(&start).unwrap();
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(token, log.token());
+
+        let (deltas, _) = log.since(&mid).unwrap();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].value.as_deref(), Some("2"));
+
+        assert!(log.since("someone-else:1").is_none());
+        assert!(log.since("garbage").is_none());
+    }
+
+    #[test]
+    fn evicted_keys_are_forgotten_but_versions_keep_rising() {
+        let log = WatchLog::new(2);
+        assert_eq!(log.record("a".into(), None).version, 1);
+        log.record("b".into(), None);
+        log.record("c".into(), None);
+        assert_eq!(log.version("a"), 0, "a has no delta left in the ring");
+
+        let a = log.record("a".into(), None);
+        assert!(a.version > 1);
+        assert_eq!(log.version("b"), 0);
+        assert_eq!(log.inner.lock().unwrap().versions.len(), 2);
+    }
+
+    #[test]
+    fn overlapping_patterns_record_one_update_once() {
+        let (wm, _rx) = manager(16);
+        let conn = [4u8; 16];
+        wm.watch_pattern(conn, "mc:*", PayloadFormat::Json).unwrap();
+        wm.watch_pattern(conn, "mc:player:*", PayloadFormat::Json)
+            .unwrap();
+        assert_eq!(wm.matching_patterns("mc:player:1"), 2);
+
+        let first = wm.observe("mc:player:1".into(), Some("a".into()), true);
+        assert_eq!(first.map(|d| d.version), Some(1));
+        assert!(wm.observe("mc:player:1".into(), Some("a".into()), true).is_none());
+        let second = wm.observe("mc:player:1".into(), Some("b".into()), true);
+        assert_eq!(second.map(|d| d.version), Some(2));
+        assert!(wm.observe("mc:player:1".into(), Some("b".into()), true).is_none());
+
+        // One matching pattern: every copy is a distinct update.
+        assert!(wm.observe("mc:guild:1".into(), Some("x".into()), true).is_some());
+        assert!(wm.observe("mc:guild:1".into(), Some("x".into()), true).is_some());
+
+        // An exact watch takes precedence over its pattern copies.
+        wm.watch(conn, "mc:player:1", PayloadFormat::Json).unwrap();
+        assert!(wm.observe("mc:player:1".into(), Some("c".into()), false).is_some());
+        assert!(wm.observe("mc:player:1".into(), Some("c".into()), true).is_none());
+        assert_eq!(wm.log.version("mc:player:1"), 3);
+    }
+
+    #[test]
+    fn unwatching_a_pattern_forgets_its_pending_copies() {
+        let (wm, _rx) = manager(16);
+        let conn = [5u8; 16];
+        wm.watch_pattern(conn, "mc:*", PayloadFormat::Json).unwrap();
+        wm.watch_pattern(conn, "mc:player:*", PayloadFormat::Json)
+            .unwrap();
+
+        assert!(wm.observe("mc:player:1".into(), Some("a".into()), true).is_some());
+        // The second copy never arrives: its pattern went away first.
+        wm.unwatch_pattern(&conn, "mc:player:*", PayloadFormat::Json)
+            .unwrap();
+        let next = wm.observe("mc:player:1".into(), Some("a".into()), true);
+        assert_eq!(next.map(|d| d.version), Some(2));
+    }
+
+    #[test]
+    fn evicted_token_falls_back_to_snapshot() {
+        let (wm, _rx) = manager(2);
+        let conn = [2u8; 16];
+        wm.watch(conn, "k", PayloadFormat::Json).unwrap();
+        wm.watch_pattern(conn, "p:*", PayloadFormat::Json).unwrap();
+
+        let token = wm.log.token();
+        for i in 0..3 {
+            wm.log.record("k".into(), Some(i.to_string().into()));
+        }
+
+        match wm.resume(&conn, Some(&token)) {
+            WatchResume::Snapshot {
+                seq,
+                keys,
+                patterns,
+                ..
+            } => {
+                assert_eq!(seq, 3);
+                assert_eq!(keys, vec![Arc::<str>::from("k")]);
+                assert_eq!(patterns, vec![Arc::<str>::from("p:*")]);
+            }
+            other => panic!("expected snapshot, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resume_replays_only_subscribed_keys() {
+        let (wm, _rx) = manager(16);
+        let conn = [3u8; 16];
+        wm.watch_pattern(conn, "mc:player:*", PayloadFormat::Json)
+            .unwrap();
+
+        let token = wm.log.token();
+        wm.log.record("mc:player:1".into(), Some("x".into()));
+        wm.log.record("mc:guild:1".into(), Some("y".into()));
+
+        match wm.resume(&conn, Some(&token)) {
+            WatchResume::Deltas { deltas, token } => {
+                assert_eq!(deltas.len(), 1);
+                assert_eq!(&*deltas[0].key, "mc:player:1");
+                assert_eq!(token, wm.log.token());
+            }
+            other => panic!("expected deltas, got {other:?}"),
+        }
+        assert!(matches!(
+            wm.resume(&conn, None),
+            WatchResume::Snapshot { .. }
+        ));
+    }
 }
@@ -518,9 +518,9 @@ impl From<&RedisEnvelope> for RedisCommand {
 
 // ** Redis Cluster Connection
 
-pub async fn create_pubsub_connection_fred(
-    config: Config,
-) -> Result<(SubscriberClient, UnboundedReceiver<RedisEventEnvelope>), JediError> {
+/// Build and connect a fred subscriber that re-subscribes to its channels
+/// after reconnects. Callers subscribe and read `message_rx()` themselves.
+pub async fn create_subscriber_fred(config: Config) -> Result<SubscriberClient, JediError> {
     let subscriber = Builder::from_config(config)
         .build_subscriber_client()
         .map_err(|e| JediError::Internal(format!("SubscriberClient build failed: {e}").into()))?;
@@ -536,6 +536,14 @@ pub async fn create_pubsub_connection_fred(
 
     let _resub = subscriber.manage_subscriptions();
 
+    Ok(subscriber)
+}
+
+pub async fn create_pubsub_connection_fred(
+    config: Config,
+) -> Result<(SubscriberClient, UnboundedReceiver<RedisEventEnvelope>), JediError> {
+    let subscriber = create_subscriber_fred(config).await?;
+
     let mut rx = subscriber.message_rx();
     let (tx, internal_rx) = unbounded_channel::<RedisEventEnvelope>();
 
@@ -716,13 +724,7 @@ pub fn redis_key_update_from_command(cmd: &RedisCommand) -> Option<RedisKeyUpdat
 }
 
 pub fn should_emit_update(key_update: &RedisKeyUpdate, watch_manager: &WatchManager) -> bool {
-    let key_arc = Arc::<str>::from(key_update.key.as_str());
-    let guard = watch_manager.key_to_conns.guard();
-
-    watch_manager
-        .key_to_conns
-        .get(&key_arc, &guard)
-        .is_some_and(|set| !set.is_empty())
+    watch_manager.has_watchers(key_update.key.as_str())
 }
 
 pub fn create_ws_update_if_watched(
@@ -924,16 +926,9 @@ pub fn filter_updates_for_active_keys<'a>(
     updates: impl Iterator<Item = &'a RedisKeyUpdate>,
     watch_manager: &WatchManager,
 ) -> Vec<RedisWsMessage> {
-    let guard = watch_manager.key_to_conns.guard();
-
     updates
         .filter_map(|upd| {
-            let key_arc = Arc::<str>::from(upd.key.as_str());
-            if watch_manager
-                .key_to_conns
-                .get(&key_arc, &guard)
-                .is_some_and(|set| !set.is_empty())
-            {
+            if watch_manager.has_watchers(upd.key.as_str()) {
                 Some(redis_ws_update_msg(upd.clone()))
             } else {
                 None
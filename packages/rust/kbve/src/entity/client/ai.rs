@@ -1,8 +1,11 @@
 use axum::{Json, extract::Extension, response::IntoResponse};
-use jedi::groq::{GroqClient, GroqMessage, GroqRequestBody};
+use jedi::ai::{
+    GroqClient, LlmFinishReason, LlmMessage, LlmProvider, LlmRequest, LlmResponse,
+    LlmResponseFormat,
+};
 use serde::Deserialize;
+use serde_json::{Value, json};
 use std::sync::Arc;
-use tokio::task;
 use tokio::time::Duration;
 use tracing::error;
 
@@ -30,57 +33,85 @@ pub async fn groq_handler(
     let mut messages = vec![];
 
     if let Some(system_message) = payload.system {
-        messages.push(GroqMessage {
-            role: "system".to_string(),
-            content: system_message,
-        });
+        messages.push(LlmMessage::system(system_message));
     }
 
-    messages.push(GroqMessage {
-        role: "user".to_string(),
-        content: payload.message,
-    });
-
-    let body = GroqRequestBody {
-        messages,
-        model: payload.model,
-        response_format: payload.response_format,
-    };
+    messages.push(LlmMessage::user(payload.message));
 
-    let client_clone = Arc::clone(&client);
+    let mut request = LlmRequest::new(messages).model(payload.model);
+    request.response_format = payload.response_format.as_ref().and_then(response_format);
 
-    let task1 = task::spawn(async move {
-        match client_clone.test_request(&body).await {
-            Ok(response) => Ok(response),
-            Err(e) => {
-                error!("Error: {:?}", e);
-                Err("Error occurred".to_string())
-            }
-        }
-    });
-
-    match task1.await {
-        Ok(Ok(response)) => Json(response).into_response(),
-        Ok(Err(e)) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    match client.chat(&request).await {
+        Ok(response) => Json(completion_json(&response)).into_response(),
         Err(e) => {
-            error!("Task error: {:?}", e);
+            error!("Error: {:?}", e);
             (
                 axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                "Task error occurred".to_string(),
+                "Error occurred".to_string(),
             )
                 .into_response()
         }
     }
 }
 
+/// Map an OpenAI-style `response_format` object onto the provider type.
+/// Unknown types fall back to the provider default.
+fn response_format(value: &Value) -> Option<LlmResponseFormat> {
+    match value.get("type")?.as_str()? {
+        "text" => Some(LlmResponseFormat::Text),
+        "json_object" => Some(LlmResponseFormat::JsonObject),
+        "json_schema" => {
+            let spec = value.get("json_schema")?;
+            Some(LlmResponseFormat::JsonSchema {
+                name: spec.get("name")?.as_str()?.to_string(),
+                schema: spec.get("schema").cloned().unwrap_or_else(|| json!({})),
+                strict: spec.get("strict").and_then(Value::as_bool).unwrap_or(false),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Render a response in the chat-completions shape this endpoint has
+/// always returned. Groq-only fields the provider-agnostic response doesn't
+/// carry (`created`, `system_fingerprint`, `x_groq` and the usage timings)
+/// are kept as `null` so clients reading them still find the keys.
+fn completion_json(response: &LlmResponse) -> Value {
+    let finish_reason = response.finish_reason.as_ref().map(|r| match r {
+        LlmFinishReason::Stop => "stop",
+        LlmFinishReason::Length => "length",
+        LlmFinishReason::ToolCalls => "tool_calls",
+        LlmFinishReason::ContentFilter => "content_filter",
+        LlmFinishReason::Other(other) => other.as_str(),
+    });
+    let usage = response.usage.as_ref().map(|u| {
+        json!({
+            "prompt_tokens": u.prompt_tokens,
+            "prompt_time": null,
+            "completion_tokens": u.completion_tokens,
+            "completion_time": null,
+            "total_tokens": u.total_tokens,
+            "total_time": null,
+        })
+    });
+    json!({
+        "id": response.id,
+        "object": "chat.completion",
+        "created": null,
+        "model": response.model,
+        "choices": [{
+            "index": 0,
+            "message": response.message,
+            "finish_reason": finish_reason,
+        }],
+        "usage": usage,
+        "system_fingerprint": null,
+        "x_groq": null,
+    })
+}
+
 pub async fn setup_groqclient(api_key: String) -> Arc<GroqClient> {
     let rate_limit_delay = Duration::from_secs(1);
     let max_retries = 3;
-    let client_pool = 5;
-    Arc::new(GroqClient::new(
-        api_key,
-        client_pool,
-        rate_limit_delay,
-        max_retries,
-    ))
+    Arc::new(GroqClient::new(api_key).with_retries(max_retries, rate_limit_delay))
 }